@@ -0,0 +1,247 @@
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    path::Path,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use axum::{
+    extract::State as AxumState,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::{stream, Stream};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Emitter,
+};
+use tokio::sync::{broadcast, mpsc};
+use tokio_util::sync::CancellationToken;
+
+use super::{file_meta, scan_share_entry, HttpState, ServerFile, SharedFileMeta, SharedFiles};
+
+pub(super) const FILES_CHANGED_EVENT: &str = "file-share-files-changed";
+const RESCAN_DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Serialize)]
+pub struct ShareFilesEvent {
+    pub session_id: String,
+    pub files: Vec<SharedFileMeta>,
+}
+
+/// 推送给分享页的变更通知；停止分享时关闭，避免 SSE 长连接拖住服务退出。
+#[derive(Clone)]
+pub(super) struct ShareEvents {
+    sender: broadcast::Sender<usize>,
+    closed: CancellationToken,
+}
+
+pub(super) struct LiveContext {
+    pub session_id: String,
+    pub files: SharedFiles,
+    pub events: ShareEvents,
+    pub app: Option<AppHandle>,
+}
+
+/// 实时分享：监听所选文件夹，变化后重新扫描并更新文件表。
+pub(super) struct LiveShare {
+    watcher: StdMutex<RecommendedWatcher>,
+    sources: Arc<StdMutex<ShareSources>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct ShareSources {
+    roots: Vec<String>,
+    excluded: HashSet<String>,
+}
+
+impl ShareEvents {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(16);
+        Self {
+            sender,
+            closed: CancellationToken::new(),
+        }
+    }
+
+    pub fn files_changed(&self, file_count: usize) {
+        let _ = self.sender.send(file_count);
+    }
+
+    pub fn close(&self) {
+        self.closed.cancel();
+    }
+}
+
+impl LiveShare {
+    pub fn start(roots: &[String], context: LiveContext) -> Result<Self, String> {
+        let (rescan_tx, mut rescan_rx) = mpsc::unbounded_channel();
+        let watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if !event.kind.is_access() => {
+                    let _ = rescan_tx.send(());
+                }
+                Ok(_) => {}
+                Err(err) => eprintln!("监听分享文件夹失败: {err}"),
+            })
+            .map_err(|err| format!("无法监听分享文件夹: {err}"))?;
+
+        let sources = Arc::new(StdMutex::new(ShareSources::default()));
+        let task_sources = Arc::clone(&sources);
+        let task = async_runtime::spawn(async move {
+            while rescan_rx.recv().await.is_some() {
+                tokio::time::sleep(RESCAN_DEBOUNCE).await;
+                while rescan_rx.try_recv().is_ok() {}
+                rescan(&context, &task_sources).await;
+            }
+        });
+
+        let live = Self {
+            watcher: StdMutex::new(watcher),
+            sources,
+            task,
+        };
+        live.add_roots(roots, &[])?;
+        Ok(live)
+    }
+
+    /// 追加监听的路径；重新加入的文件不再被排除。
+    pub fn add_roots(&self, roots: &[String], added_ids: &[String]) -> Result<(), String> {
+        let mut watcher = self.watcher.lock().unwrap();
+        let mut sources = self.sources.lock().unwrap();
+        for id in added_ids {
+            sources.excluded.remove(id);
+        }
+        for root in roots {
+            if sources.roots.contains(root) {
+                continue;
+            }
+            let path = Path::new(root)
+                .canonicalize()
+                .map_err(|err| format!("无法读取路径 {root}: {err}"))?;
+            let result = if path.is_dir() {
+                watcher.watch(&path, RecursiveMode::Recursive)
+            } else {
+                watcher.watch(path.parent().unwrap_or(&path), RecursiveMode::NonRecursive)
+            };
+            result.map_err(|err| format!("无法监听 {root}: {err}"))?;
+            sources.roots.push(root.clone());
+        }
+        Ok(())
+    }
+
+    /// 手动移除的文件在之后的扫描中保持隐藏。
+    pub fn exclude(&self, file_ids: &[String]) {
+        let mut sources = self.sources.lock().unwrap();
+        sources.excluded.extend(file_ids.iter().cloned());
+    }
+
+    pub fn shutdown(self) {
+        self.task.abort();
+    }
+}
+
+pub(super) async fn serve_events(
+    AxumState(state): AxumState<HttpState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.events.sender.subscribe();
+    let closed = state.events.closed.clone();
+    let events = stream::unfold((receiver, closed), |(mut receiver, closed)| async move {
+        loop {
+            let received = tokio::select! {
+                _ = closed.cancelled() => return None,
+                received = receiver.recv() => received,
+            };
+            match received {
+                Ok(file_count) => {
+                    let event = Event::default().event("files").data(file_count.to_string());
+                    return Some((Ok(event), (receiver, closed)));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn rescan(context: &LiveContext, sources: &StdMutex<ShareSources>) {
+    let (roots, excluded) = {
+        let sources = sources.lock().unwrap();
+        (sources.roots.clone(), sources.excluded.clone())
+    };
+    let scanned = match async_runtime::spawn_blocking(move || scan_roots(&roots)).await {
+        Ok(files) => files,
+        Err(err) => {
+            eprintln!("重新扫描分享文件夹失败: {err}");
+            return;
+        }
+    };
+
+    let mut table = context.files.write().await;
+    let next = merge_scanned(&table, scanned, &excluded);
+    if same_files(&table, &next) {
+        return;
+    }
+    *table = next;
+    let files = table.iter().map(file_meta).collect::<Vec<_>>();
+    drop(table);
+
+    context.events.files_changed(files.len());
+    if let Some(app) = context.app.as_ref() {
+        let event = ShareFilesEvent {
+            session_id: context.session_id.clone(),
+            files,
+        };
+        if let Err(err) = app.emit(FILES_CHANGED_EVENT, event) {
+            eprintln!("推送文件变更事件失败: {err}");
+        }
+    }
+}
+
+fn scan_roots(roots: &[String]) -> Vec<ServerFile> {
+    roots
+        .iter()
+        .flat_map(|root| {
+            scan_share_entry(root).unwrap_or_else(|err| {
+                eprintln!("{err}");
+                Vec::new()
+            })
+        })
+        .collect()
+}
+
+/// 未变化的文件沿用旧条目，保留已经计算好的校验和。
+fn merge_scanned(
+    current: &[ServerFile],
+    scanned: Vec<ServerFile>,
+    excluded: &HashSet<String>,
+) -> Vec<ServerFile> {
+    let mut seen = HashSet::new();
+    scanned
+        .into_iter()
+        .filter(|file| !excluded.contains(&file.id) && seen.insert(file.id.clone()))
+        .map(|file| {
+            current
+                .iter()
+                .find(|existing| {
+                    existing.id == file.id
+                        && existing.size == file.size
+                        && existing.modified == file.modified
+                })
+                .cloned()
+                .unwrap_or(file)
+        })
+        .collect()
+}
+
+fn same_files(current: &[ServerFile], next: &[ServerFile]) -> bool {
+    current.len() == next.len()
+        && current
+            .iter()
+            .zip(next)
+            .all(|(a, b)| a.id == b.id && a.size == b.size && a.modified == b.modified)
+}
@@ -0,0 +1,310 @@
+use std::{
+    fs::File as StdFile,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use axum::{
+    extract::State as AxumState,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::async_runtime;
+
+use super::{HttpState, ServerFile};
+use crate::utils::current_timestamp_millis;
+
+#[derive(Clone, Serialize)]
+pub struct ShareManifest {
+    pub name: String,
+    pub generated_at: u64,
+    pub file_count: usize,
+    pub total_size: u64,
+    pub script_url: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ManifestEntry {
+    pub id: String,
+    pub path: String,
+    pub size: u64,
+    pub sha256: Option<String>,
+    pub url: String,
+}
+
+pub(super) async fn serve_manifest(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Json<ShareManifest> {
+    let base_url = request_base_url(&state, &headers);
+    let entries = build_entries(&state, &base_url, true).await;
+    Json(ShareManifest {
+        name: state.session_name.clone(),
+        generated_at: current_timestamp_millis() as u64,
+        file_count: entries.len(),
+        total_size: entries.iter().map(|entry| entry.size).sum(),
+        script_url: format!("{base_url}/fetch.sh"),
+        files: entries,
+    })
+}
+
+pub(super) async fn serve_url_list(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Response {
+    let base_url = request_base_url(&state, &headers);
+    let entries = build_entries(&state, &base_url, false).await;
+    let body = entries
+        .iter()
+        .map(|entry| format!("{}\n", entry.url))
+        .collect::<String>();
+    plain_text(body)
+}
+
+pub(super) async fn serve_fetch_script(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Response {
+    let base_url = request_base_url(&state, &headers);
+    let entries = build_entries(&state, &base_url, true).await;
+    let flags = curl_flags(state.insecure_tls, state.public_key_pin.as_deref());
+    plain_text(render_fetch_script(
+        &state.session_name,
+        &base_url,
+        &flags,
+        &entries,
+    ))
+}
+
+/// 生成在终端中下载整个分享的一行命令。
+pub(super) fn fetch_one_liner(
+    primary_url: &str,
+    insecure_tls: bool,
+    public_key_pin: Option<&str>,
+) -> String {
+    let flags = curl_flags(insecure_tls, public_key_pin).replace("-fL", "-fsSL");
+    format!("curl {flags} {primary_url}/fetch.sh | sh")
+}
+
+/// 自签名证书无法通过 CA 校验，用 `-k` 跳过后必须靠公钥固定防止中间人替换脚本。
+fn curl_flags(insecure_tls: bool, public_key_pin: Option<&str>) -> String {
+    let mut flags = "-fL".to_string();
+    if let Some(pin) = public_key_pin {
+        if insecure_tls {
+            flags.push_str(" -k");
+        }
+        flags.push_str(&format!(" --pinnedpubkey {}", shell_quote(pin)));
+    }
+    flags
+}
+
+/// 读取缓存的 SHA-256，首次请求时才计算。
+pub(super) async fn file_sha256(file: &ServerFile) -> Result<String, String> {
+    file.sha256
+        .get_or_try_init(|| async {
+            let path = file.path.clone();
+            async_runtime::spawn_blocking(move || hash_file(&path))
+                .await
+                .map_err(|err| format!("计算校验和失败: {err}"))?
+        })
+        .await
+        .cloned()
+}
+
+async fn build_entries(state: &HttpState, base_url: &str, with_hash: bool) -> Vec<ManifestEntry> {
+    let files = state.files.read().await.clone();
+    let mut entries = Vec::with_capacity(files.len());
+    for file in &files {
+        let sha256 = if with_hash {
+            match file_sha256(file).await {
+                Ok(hash) => Some(hash),
+                Err(err) => {
+                    eprintln!("{err}");
+                    None
+                }
+            }
+        } else {
+            file.sha256.get().cloned()
+        };
+        entries.push(ManifestEntry {
+            id: file.id.clone(),
+            path: safe_relative_path(&file.display_name),
+            size: file.size,
+            sha256,
+            url: format!("{base_url}/files/{}", file.id),
+        });
+    }
+    entries
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let file =
+        StdFile::open(path).map_err(|err| format!("无法读取文件 {}: {err}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|err| format!("无法读取文件 {}: {err}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Host 头只用来在已知地址中选择与请求一致的那个，匹配不上时使用主地址。
+pub(super) fn request_base_url(state: &HttpState, headers: &HeaderMap) -> String {
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    state
+        .known_urls
+        .iter()
+        .find(|url| url.split_once("://").map(|(_, authority)| authority) == Some(host))
+        .unwrap_or(&state.primary_url)
+        .clone()
+}
+
+fn render_fetch_script(
+    name: &str,
+    base_url: &str,
+    curl_flags: &str,
+    entries: &[ManifestEntry],
+) -> String {
+    let mut script = String::new();
+    script.push_str("#!/bin/sh\n");
+    script.push_str(&format!("# Chef 局域网分享：{}\n", name.replace('\n', " ")));
+    script.push_str("set -e\n\n");
+    script.push_str(&format!(
+        "BASE_URL=\"${{CHEF_SHARE_URL:-{}}}\"\n",
+        base_url.replace(['"', '$', '`', '\\'], "")
+    ));
+    script.push_str("TARGET_DIR=\"${CHEF_SHARE_DIR:-.}\"\n\n");
+    script.push_str(
+        "if command -v sha256sum >/dev/null 2>&1; then\n  SHA256=\"sha256sum\"\nelse\n  SHA256=\"shasum -a 256\"\nfi\n\n",
+    );
+    script.push_str("fetch() {\n");
+    script.push_str("  dest=\"$TARGET_DIR/$2\"\n");
+    script.push_str("  mkdir -p \"$(dirname \"$dest\")\"\n");
+    script.push_str("  echo \"下载 $2\"\n");
+    script.push_str(&format!(
+        "  curl {curl_flags} -o \"$dest\" \"$BASE_URL/files/$1\"\n"
+    ));
+    script.push_str("  if [ \"$3\" != \"-\" ]; then\n");
+    script.push_str(
+        "    (cd \"$(dirname \"$dest\")\" && echo \"$3  $(basename \"$dest\")\" | $SHA256 -c -)\n",
+    );
+    script.push_str("  fi\n");
+    script.push_str("}\n\n");
+    for entry in entries {
+        script.push_str(&format!(
+            "fetch {} {} {}\n",
+            shell_quote(&entry.id),
+            shell_quote(&entry.path),
+            shell_quote(entry.sha256.as_deref().unwrap_or("-"))
+        ));
+    }
+    script.push_str(&format!("\necho \"完成，共 {} 个文件。\"\n", entries.len()));
+    script
+}
+
+fn safe_relative_path(raw: &str) -> String {
+    let parts = raw
+        .split(['/', '\\'])
+        .map(|part| part.trim())
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        "chef-share".into()
+    } else {
+        parts.join("/")
+    }
+}
+
+fn shell_quote(input: &str) -> String {
+    format!("'{}'", input.replace('\'', "'\\''"))
+}
+
+fn plain_text(body: String) -> Response {
+    let mut response = body.into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_traversal_from_relative_paths() {
+        assert_eq!(safe_relative_path("../../etc/passwd"), "etc/passwd");
+        assert_eq!(safe_relative_path("docs\\sub/./a.txt"), "docs/sub/a.txt");
+        assert_eq!(safe_relative_path("/"), "chef-share");
+    }
+
+    #[test]
+    fn renders_fetch_script_with_checksums_and_quoting() {
+        let entries = vec![
+            ManifestEntry {
+                id: "id-1".into(),
+                path: "folder/it's.txt".into(),
+                size: 3,
+                sha256: Some("abc123".into()),
+                url: "http://10.0.0.2:8000/files/id-1".into(),
+            },
+            ManifestEntry {
+                id: "id-2".into(),
+                path: "folder/b.bin".into(),
+                size: 1,
+                sha256: None,
+                url: "http://10.0.0.2:8000/files/id-2".into(),
+            },
+        ];
+        let flags = curl_flags(true, Some("sha256//AbC+/="));
+        let script = render_fetch_script("folder", "https://10.0.0.2:8000", &flags, &entries);
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("BASE_URL=\"${CHEF_SHARE_URL:-https://10.0.0.2:8000}\""));
+        assert!(script.contains("curl -fL -k --pinnedpubkey 'sha256//AbC+/=' -o"));
+        assert!(script.contains("fetch 'id-1' 'folder/it'\\''s.txt' 'abc123'\n"));
+        assert!(script.contains("fetch 'id-2' 'folder/b.bin' '-'\n"));
+    }
+
+    #[test]
+    fn pins_public_key_in_fetch_commands() {
+        assert_eq!(
+            fetch_one_liner("https://10.0.0.2:8000", true, Some("sha256//AbC=")),
+            "curl -fsSL -k --pinnedpubkey 'sha256//AbC=' https://10.0.0.2:8000/fetch.sh | sh"
+        );
+        assert_eq!(
+            fetch_one_liner("http://10.0.0.2:8000", false, None),
+            "curl -fsSL http://10.0.0.2:8000/fetch.sh | sh"
+        );
+        assert_eq!(curl_flags(false, None), "-fL");
+    }
+
+    #[test]
+    fn hashes_file_contents() {
+        let path = std::env::temp_dir().join(format!("chef_hash_{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"chef").expect("failed to write test file");
+        assert_eq!(
+            hash_file(&path).unwrap(),
+            "f59ac0828b9a32293b348e398a0efd342b1e4377a687f3a9055ee2871dff35e4"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,315 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+
+pub(super) const SHARE_SERVICE_TYPE: &str = "_chef-share._tcp.local.";
+
+pub(super) struct ShareAdvertiser {
+    daemon: ServiceDaemon,
+    fullname: String,
+    hostname: String,
+}
+
+#[derive(Clone)]
+pub(super) struct ShareAnnouncement {
+    pub fullname: String,
+    pub instance_name: String,
+    pub hostname: String,
+    pub port: u16,
+    pub addresses: Vec<IpAddr>,
+    pub scheme: String,
+    pub fingerprint: Option<String>,
+    pub session_name: Option<String>,
+}
+
+impl ShareAdvertiser {
+    pub fn start(
+        session_name: &str,
+        port: u16,
+        scheme: &str,
+        fingerprint: Option<&str>,
+        bind_ip: IpAddr,
+        include_loopback: bool,
+    ) -> Result<Self, String> {
+        let daemon = ServiceDaemon::new().map_err(|err| format!("启动 mDNS 服务失败: {err}"))?;
+        if include_loopback {
+            daemon
+                .enable_interface(IfKind::LoopbackV4)
+                .map_err(|err| format!("启用回环接口广播失败: {err}"))?;
+        }
+
+        let host_label = local_host_label();
+        let hostname = format!("chef-{host_label}.local.");
+        let instance_name = format!("Chef {host_label} {port}");
+
+        let addresses = advertised_addresses(bind_ip, include_loopback);
+        let mut service = share_service_info(
+            &instance_name,
+            &hostname,
+            &addresses,
+            port,
+            share_properties(session_name, scheme, fingerprint),
+        )?;
+        if bind_ip.is_unspecified() {
+            service = service.enable_addr_auto();
+        }
+        let fullname = service.get_fullname().to_string();
+        daemon
+            .register(service)
+            .map_err(|err| format!("注册 mDNS 服务失败: {err}"))?;
+
+        Ok(Self {
+            daemon,
+            fullname,
+            hostname,
+        })
+    }
+
+    pub fn fullname(&self) -> &str {
+        &self.fullname
+    }
+
+    /// `.local` 主机名，去掉 mDNS 末尾的点，便于直接拼接到 URL 中。
+    pub fn hostname(&self) -> &str {
+        self.hostname.trim_end_matches('.')
+    }
+
+    pub fn shutdown(self) {
+        if let Ok(receiver) = self.daemon.unregister(&self.fullname) {
+            let _ = receiver.recv_timeout(Duration::from_secs(1));
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// 在局域网内浏览 `_chef-share._tcp` 服务，直到超时为止，返回解析成功的实例。
+pub(super) fn browse_shares(
+    timeout: Duration,
+    include_loopback: bool,
+) -> Result<Vec<ShareAnnouncement>, String> {
+    let daemon = ServiceDaemon::new().map_err(|err| format!("启动 mDNS 服务失败: {err}"))?;
+    if include_loopback {
+        daemon
+            .enable_interface(IfKind::LoopbackV4)
+            .map_err(|err| format!("启用回环接口广播失败: {err}"))?;
+    }
+    let receiver = daemon
+        .browse(SHARE_SERVICE_TYPE)
+        .map_err(|err| format!("搜索局域网分享失败: {err}"))?;
+
+    let deadline = Instant::now() + timeout;
+    let mut found: HashMap<String, ShareAnnouncement> = HashMap::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match receiver.recv_timeout(remaining) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                let announcement = announcement_from_info(&info);
+                found.insert(announcement.fullname.clone(), announcement);
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+                found.remove(&fullname);
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    let _ = daemon.stop_browse(SHARE_SERVICE_TYPE);
+    let _ = daemon.shutdown();
+
+    let mut shares = found.into_values().collect::<Vec<_>>();
+    shares.sort_by(|a, b| a.instance_name.cmp(&b.instance_name));
+    Ok(shares)
+}
+
+/// TXT 记录：访问协议、分享名称和 https 证书指纹。
+fn share_properties(
+    session_name: &str,
+    scheme: &str,
+    fingerprint: Option<&str>,
+) -> HashMap<String, String> {
+    let mut properties = HashMap::new();
+    properties.insert("scheme".to_string(), scheme.to_string());
+    properties.insert("path".to_string(), "/".to_string());
+    properties.insert("name".to_string(), session_name.to_string());
+    if let Some(fingerprint) = fingerprint {
+        properties.insert("fp".to_string(), fingerprint.to_string());
+    }
+    properties
+}
+
+fn share_service_info(
+    instance_name: &str,
+    hostname: &str,
+    addresses: &[IpAddr],
+    port: u16,
+    properties: HashMap<String, String>,
+) -> Result<ServiceInfo, String> {
+    ServiceInfo::new(
+        SHARE_SERVICE_TYPE,
+        instance_name,
+        hostname,
+        addresses,
+        port,
+        properties,
+    )
+    .map_err(|err| format!("构建 mDNS 服务信息失败: {err}"))
+}
+
+fn announcement_from_info(info: &ServiceInfo) -> ShareAnnouncement {
+    let fullname = info.get_fullname().to_string();
+    let instance_name = fullname
+        .strip_suffix(SHARE_SERVICE_TYPE)
+        .map(|name| name.trim_end_matches('.').to_string())
+        .unwrap_or_else(|| fullname.clone());
+    let mut addresses = info.get_addresses().iter().copied().collect::<Vec<_>>();
+    addresses.sort_by_key(|addr| (addr.is_ipv6(), *addr));
+
+    ShareAnnouncement {
+        fullname,
+        instance_name,
+        hostname: info.get_hostname().trim_end_matches('.').to_string(),
+        port: info.get_port(),
+        addresses,
+        scheme: info
+            .get_property_val_str("scheme")
+            .filter(|scheme| *scheme == "https")
+            .unwrap_or("http")
+            .to_string(),
+        fingerprint: info
+            .get_property_val_str("fp")
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string()),
+        session_name: info
+            .get_property_val_str("name")
+            .filter(|value| !value.is_empty())
+            .map(|value| value.to_string()),
+    }
+}
+
+fn advertised_addresses(bind_ip: IpAddr, include_loopback: bool) -> Vec<IpAddr> {
+    if !bind_ip.is_unspecified() {
+        return vec![bind_ip];
+    }
+    let mut addresses = if_addrs::get_if_addrs()
+        .map(|ifaces| {
+            ifaces
+                .into_iter()
+                .map(|iface| iface.ip())
+                .filter(|ip| !ip.is_loopback())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if include_loopback {
+        addresses.push(IpAddr::from([127, 0, 0, 1]));
+    }
+    addresses
+}
+
+fn local_host_label() -> String {
+    let raw = gethostname::gethostname().to_string_lossy().to_string();
+    let base = raw.split('.').next().unwrap_or_default();
+    let label = base
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string();
+    if label.is_empty() {
+        "share".into()
+    } else {
+        label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_and_parses_share_txt_record() {
+        let addresses = [
+            IpAddr::from([192, 168, 1, 20]),
+            IpAddr::from([127, 0, 0, 1]),
+        ];
+        let info = share_service_info(
+            "Chef laptop 8080",
+            "chef-laptop.local.",
+            &addresses,
+            8080,
+            share_properties("测试分享", "https", Some("AA:BB")),
+        )
+        .unwrap();
+        let share = announcement_from_info(&info);
+        assert_eq!(share.instance_name, "Chef laptop 8080");
+        assert_eq!(share.hostname, "chef-laptop.local");
+        assert_eq!(share.port, 8080);
+        assert_eq!(share.scheme, "https");
+        assert_eq!(share.fingerprint.as_deref(), Some("AA:BB"));
+        assert_eq!(share.session_name.as_deref(), Some("测试分享"));
+        assert_eq!(
+            share.addresses,
+            vec![
+                IpAddr::from([127, 0, 0, 1]),
+                IpAddr::from([192, 168, 1, 20])
+            ]
+        );
+
+        // 未知协议按 http 处理，空指纹视为没有指纹
+        let info = share_service_info(
+            "Chef laptop 8081",
+            "chef-laptop.local.",
+            &addresses,
+            8081,
+            share_properties("", "ftp", Some("")),
+        )
+        .unwrap();
+        let share = announcement_from_info(&info);
+        assert_eq!(share.scheme, "http");
+        assert_eq!(share.fingerprint, None);
+        assert_eq!(share.session_name, None);
+    }
+
+    /// 依赖真实的组播网络，沙箱和 CI 中通常不可用，需要时用 `cargo test -- --ignored` 手动运行。
+    #[test]
+    #[ignore = "需要可用的组播网络"]
+    fn advertises_and_discovers_share_on_loopback() {
+        let port = 40000 + (std::process::id() % 20000) as u16;
+        let advertiser = ShareAdvertiser::start(
+            "测试分享",
+            port,
+            "https",
+            Some("AA:BB"),
+            IpAddr::from([0, 0, 0, 0]),
+            true,
+        )
+        .expect("advertiser should start");
+
+        let shares = browse_shares(Duration::from_secs(3), true).expect("browse should succeed");
+        let share = shares
+            .iter()
+            .find(|share| share.fullname == advertiser.fullname())
+            .expect("own share should be discovered on loopback");
+        assert_eq!(share.port, port);
+        assert_eq!(share.scheme, "https");
+        assert_eq!(share.fingerprint.as_deref(), Some("AA:BB"));
+        assert_eq!(share.session_name.as_deref(), Some("测试分享"));
+        assert_eq!(share.hostname, advertiser.hostname());
+        assert!(share.addresses.contains(&IpAddr::from([127, 0, 0, 1])));
+
+        advertiser.shutdown();
+    }
+}
@@ -0,0 +1,1672 @@
+mod live;
+mod manifest;
+mod mdns;
+mod page;
+mod preview;
+mod qr;
+mod texts;
+mod throttle;
+mod tls;
+mod webdav;
+
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    body::Body,
+    extract::{Path as AxumPath, State as AxumState},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{any, get},
+    Json, Router,
+};
+use mime_guess::MimeGuess;
+use once_cell::sync::Lazy;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{
+    async_runtime::{self, JoinHandle},
+    AppHandle, Manager,
+};
+use tokio::{
+    fs::File,
+    io::AsyncReadExt,
+    net::TcpListener,
+    sync::{Mutex, OnceCell, RwLock},
+};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+use crate::utils::current_timestamp_millis;
+
+use live::{LiveContext, LiveShare, ShareEvents};
+use mdns::ShareAdvertiser;
+pub use page::FileSharePageOptions;
+use page::SharePage;
+pub use preview::PreviewKind;
+use preview::ThumbnailCache;
+pub use qr::ShareQrCode;
+pub use texts::SharedTextItem;
+use texts::{SharedTexts, TextOrigin};
+use throttle::TransferControl;
+pub use throttle::{FileShareLimits, TransferStats};
+pub use tls::FileShareTlsOptions;
+use webdav::DavShare;
+pub use webdav::FileShareWebDavOptions;
+
+static LAST_SHARE_DIR: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+static SHARE_PAGE_TEMPLATE: &str = include_str!("../../../../share/share-template.html");
+
+#[derive(Default)]
+pub struct FileShareManager {
+    inner: Mutex<HashMap<String, ActiveShare>>,
+}
+
+#[derive(Clone)]
+struct ServerFile {
+    id: String,
+    display_name: String,
+    download_name: String,
+    size: u64,
+    modified: u64,
+    extension: Option<String>,
+    path: PathBuf,
+    mime: MimeGuess,
+    sha256: Arc<OnceCell<String>>,
+}
+
+type SharedFiles = Arc<RwLock<Vec<ServerFile>>>;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SharedFileMeta {
+    pub id: String,
+    pub display_name: String,
+    pub download_name: String,
+    pub size: u64,
+    pub extension: Option<String>,
+    pub sha256: Option<String>,
+    #[serde(default)]
+    pub preview: Option<PreviewKind>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileShareStartOptions {
+    pub name: Option<String>,
+    pub port: Option<u16>,
+    pub bind_address: Option<String>,
+    pub tls: Option<FileShareTlsOptions>,
+    pub limits: Option<FileShareLimits>,
+    pub webdav: Option<FileShareWebDavOptions>,
+    pub page: Option<FileSharePageOptions>,
+    #[serde(default)]
+    pub live: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FileShareSession {
+    pub id: String,
+    pub name: String,
+    pub port: u16,
+    pub bind_address: String,
+    pub addresses: Vec<String>,
+    pub primary_url: String,
+    pub files: Vec<SharedFileMeta>,
+    pub texts: Vec<SharedTextItem>,
+    pub transfer: TransferStats,
+    pub tls: Option<FileShareTlsInfo>,
+    pub webdav: Option<FileShareWebDavInfo>,
+    pub local_url: Option<String>,
+    pub qr_code: Option<ShareQrCode>,
+    pub fetch_command: String,
+    pub live: bool,
+    pub created_at: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FileShareTlsInfo {
+    pub fingerprint_sha256: String,
+    pub self_signed: bool,
+}
+
+#[derive(Clone, Serialize)]
+pub struct FileShareWebDavInfo {
+    pub url: String,
+    pub writable: bool,
+    /// 写入口令，客户端以任意用户名、该口令作为 Basic 认证密码后才能修改文件。
+    pub write_token: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct DiscoveredShare {
+    pub instance_name: String,
+    pub session_name: Option<String>,
+    pub hostname: String,
+    pub port: u16,
+    pub addresses: Vec<String>,
+    pub primary_url: String,
+    pub tls_fingerprint: Option<String>,
+    pub files: Vec<SharedFileMeta>,
+    pub error: Option<String>,
+}
+
+struct ActiveShare {
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+    handle: JoinHandle<()>,
+    advertiser: Option<ShareAdvertiser>,
+    live: Option<LiveShare>,
+    webdav: Option<DavShare>,
+    events: ShareEvents,
+    files: SharedFiles,
+    texts: SharedTexts,
+    transfers: Arc<TransferControl>,
+    session: FileShareSession,
+}
+
+impl ActiveShare {
+    async fn shutdown(self) {
+        if let Some(live) = self.live {
+            live.shutdown();
+        }
+        self.events.close();
+        if let Some(advertiser) = self.advertiser {
+            let _ = async_runtime::spawn_blocking(move || advertiser.shutdown()).await;
+        }
+        if let Some(tx) = self.shutdown {
+            let _ = tx.send(());
+        }
+        let _ = self.handle.await;
+    }
+
+    async fn snapshot(&self) -> FileShareSession {
+        let files = self.files.read().await;
+        let texts = self.texts.read().await;
+        FileShareSession {
+            files: files.iter().map(file_meta).collect(),
+            texts: texts.clone(),
+            transfer: self.transfers.stats(),
+            ..self.session.clone()
+        }
+    }
+}
+
+#[derive(Clone)]
+struct HttpState {
+    session_id: String,
+    files: SharedFiles,
+    texts: SharedTexts,
+    transfers: Arc<TransferControl>,
+    thumbnails: ThumbnailCache,
+    events: ShareEvents,
+    webdav: Option<DavShare>,
+    app: Option<AppHandle>,
+    page: Arc<SharePage>,
+    session_name: String,
+    /// 本机可访问的地址，生成链接时只从中挑选，不直接采用请求的 Host 头。
+    known_urls: Vec<String>,
+    primary_url: String,
+    insecure_tls: bool,
+    public_key_pin: Option<String>,
+}
+
+#[tauri::command]
+pub async fn start_file_share(
+    app: AppHandle,
+    state: tauri::State<'_, FileShareManager>,
+    files: Vec<String>,
+    options: Option<FileShareStartOptions>,
+) -> Result<FileShareSession, String> {
+    state.start(files, options.unwrap_or_default(), Some(app)).await
+}
+
+#[tauri::command]
+pub async fn stop_file_share(
+    state: tauri::State<'_, FileShareManager>,
+    id: Option<String>,
+) -> Result<(), String> {
+    match id {
+        Some(id) => state.stop(&id).await,
+        None => {
+            state.stop_all().await;
+            Ok(())
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn get_file_share_status(
+    state: tauri::State<'_, FileShareManager>,
+) -> Result<Option<FileShareSession>, String> {
+    Ok(state.list().await.pop())
+}
+
+#[tauri::command]
+pub async fn list_file_shares(
+    state: tauri::State<'_, FileShareManager>,
+) -> Result<Vec<FileShareSession>, String> {
+    Ok(state.list().await)
+}
+
+#[tauri::command]
+pub async fn add_file_share_items(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    files: Vec<String>,
+) -> Result<FileShareSession, String> {
+    state.add_files(&id, files).await
+}
+
+#[tauri::command]
+pub async fn remove_file_share_items(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    file_ids: Vec<String>,
+) -> Result<FileShareSession, String> {
+    state.remove_files(&id, file_ids).await
+}
+
+#[tauri::command]
+pub async fn update_file_share_limits(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    limits: FileShareLimits,
+) -> Result<FileShareSession, String> {
+    state.update_limits(&id, limits).await
+}
+
+#[tauri::command]
+pub async fn push_share_text(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    content: String,
+    label: Option<String>,
+) -> Result<FileShareSession, String> {
+    let item = texts::new_text_item(content, label, TextOrigin::Desktop, None)?;
+    state.push_text(&id, item).await
+}
+
+#[tauri::command]
+pub async fn push_share_clipboard(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+) -> Result<FileShareSession, String> {
+    let content = async_runtime::spawn_blocking(texts::read_clipboard_text)
+        .await
+        .map_err(|err| format!("读取剪贴板失败: {err}"))??;
+    let item = texts::new_text_item(content, Some("剪贴板".into()), TextOrigin::Desktop, None)?;
+    state.push_text(&id, item).await
+}
+
+#[tauri::command]
+pub async fn remove_share_text(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    text_id: String,
+) -> Result<FileShareSession, String> {
+    state.remove_text(&id, &text_id).await
+}
+
+#[tauri::command]
+pub async fn get_file_share_qr_codes(
+    state: tauri::State<'_, FileShareManager>,
+    id: String,
+    url: Option<String>,
+) -> Result<Vec<ShareQrCode>, String> {
+    let session = state
+        .list()
+        .await
+        .into_iter()
+        .find(|session| session.id == id)
+        .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+    let addresses = session_addresses(&session);
+    let targets = match url {
+        Some(url) if addresses.contains(&url) => vec![url],
+        Some(url) => return Err(format!("{url} 不属于该分享会话。")),
+        None => {
+            let lan = addresses
+                .iter()
+                .filter(|url| !qr::is_loopback_url(url))
+                .cloned()
+                .collect::<Vec<_>>();
+            if lan.is_empty() {
+                addresses
+            } else {
+                lan
+            }
+        }
+    };
+    targets.iter().map(|url| qr::render_qr_code(url)).collect()
+}
+
+#[tauri::command]
+pub async fn list_share_templates(app: AppHandle) -> Result<Vec<String>, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("无法定位应用数据目录: {err}"))?
+        .join(page::SHARE_TEMPLATE_DIR);
+    Ok(page::list_templates(&dir))
+}
+
+#[tauri::command]
+pub async fn list_share_bind_addresses() -> Result<Vec<String>, String> {
+    let mut addresses = vec!["0.0.0.0".to_string(), "127.0.0.1".to_string()];
+    for ip in collect_interface_ips() {
+        let ip = ip.to_string();
+        if !addresses.contains(&ip) {
+            addresses.push(ip);
+        }
+    }
+    Ok(addresses)
+}
+
+#[tauri::command]
+pub async fn discover_file_shares(
+    state: tauri::State<'_, FileShareManager>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<DiscoveredShare>, String> {
+    let own_fullnames = state.advertised_fullnames().await;
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(3000).clamp(500, 15000));
+    let announcements = async_runtime::spawn_blocking(move || mdns::browse_shares(timeout, false))
+        .await
+        .map_err(|err| format!("搜索局域网分享失败: {err}"))??;
+    let others = announcements
+        .into_iter()
+        .filter(|announcement| !own_fullnames.contains(&announcement.fullname))
+        .collect::<Vec<_>>();
+    Ok(resolve_discovered_shares(others).await)
+}
+
+#[tauri::command]
+pub async fn pick_share_files() -> Result<Vec<String>, String> {
+    let selection = async_runtime::spawn_blocking(|| {
+        FileDialog::new().set_title("选择要分享的文件").pick_files()
+    })
+    .await
+    .map_err(|err| format!("打开文件对话框失败: {err}"))?;
+
+    Ok(selection
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
+}
+
+#[tauri::command]
+pub async fn pick_share_directories() -> Result<Vec<String>, String> {
+    let last_dir = {
+        let guard = LAST_SHARE_DIR.lock().await;
+        guard.clone()
+    };
+
+    let last_dir_clone = last_dir.clone();
+    let selection = async_runtime::spawn_blocking(move || {
+        let mut dialog = FileDialog::new().set_title("选择要分享的文件夹");
+        if let Some(path) = last_dir_clone.as_ref() {
+            dialog = dialog.set_directory(path);
+        }
+        dialog.pick_folders()
+    })
+    .await
+    .map_err(|err| format!("打开文件夹对话框失败: {err}"))?;
+
+    let folders = selection
+        .unwrap_or_default()
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+
+    if let Some(first) = folders.first() {
+        let mut guard = LAST_SHARE_DIR.lock().await;
+        *guard = Some(PathBuf::from(first));
+    }
+
+    Ok(folders)
+}
+
+impl FileShareManager {
+    async fn start(
+        &self,
+        files: Vec<String>,
+        options: FileShareStartOptions,
+        app: Option<AppHandle>,
+    ) -> Result<FileShareSession, String> {
+        if files.is_empty() {
+            return Err("请选择至少一个需要分享的文件。".into());
+        }
+
+        let server_files = build_server_files(&files)?;
+        let templates_dir = app
+            .as_ref()
+            .and_then(|app| app.path().app_data_dir().ok())
+            .map(|dir| dir.join(page::SHARE_TEMPLATE_DIR));
+        let page = Arc::new(SharePage::new(
+            &options.page.clone().unwrap_or_default(),
+            templates_dir.as_deref(),
+        )?);
+        let shared_files: SharedFiles = Arc::new(RwLock::new(server_files));
+        let shared_texts: SharedTexts = Arc::new(RwLock::new(Vec::new()));
+        let session_id = Uuid::new_v4().to_string();
+        let events = ShareEvents::new();
+        let webdav = options
+            .webdav
+            .as_ref()
+            .filter(|webdav| webdav.enabled)
+            .map(|webdav| DavShare::new(webdav, &files));
+        let live = if options.live {
+            Some(LiveShare::start(
+                &files,
+                LiveContext {
+                    session_id: session_id.clone(),
+                    files: Arc::clone(&shared_files),
+                    events: events.clone(),
+                    app: app.clone(),
+                },
+            )?)
+        } else {
+            None
+        };
+        let transfers = TransferControl::new(options.limits.unwrap_or_default());
+        let name = options
+            .name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| default_session_name(&files));
+
+        let bind_ip = parse_bind_address(options.bind_address.as_deref())?;
+        let listener = TcpListener::bind(SocketAddr::new(bind_ip, options.port.unwrap_or(0)))
+            .await
+            .map_err(|err| match options.port {
+                Some(port) => format!("无法在端口 {port} 上启动服务: {err}"),
+                None => format!("无法启动服务: {err}"),
+            })?;
+        let port = listener
+            .local_addr()
+            .map_err(|err| format!("无法获取端口: {err}"))?
+            .port();
+
+        let tls_material = match options.tls.as_ref().filter(|tls| tls.enabled) {
+            Some(tls_options) => Some(tls::prepare_tls(tls_options, &collect_local_hosts())?),
+            None => None,
+        };
+        let scheme = if tls_material.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+
+        let addresses = collect_accessible_urls(scheme, bind_ip, port);
+        let preferred_url = addresses
+            .iter()
+            .find(|url| url.starts_with(&format!("{scheme}://192.")))
+            .cloned();
+        let primary_url = preferred_url
+            .or_else(|| {
+                addresses
+                    .iter()
+                    .find(|url| !url.contains("127.0.0.1") && !url.contains("localhost"))
+                    .cloned()
+            })
+            .or_else(|| addresses.first().cloned())
+            .unwrap_or_else(|| format!("{scheme}://127.0.0.1:{port}"));
+
+        let insecure_tls = tls_material
+            .as_ref()
+            .map(|material| material.self_signed)
+            .unwrap_or(false);
+        let public_key_pin = tls_material
+            .as_ref()
+            .map(|material| material.public_key_pin.clone());
+        let http_state = HttpState {
+            session_id: session_id.clone(),
+            files: Arc::clone(&shared_files),
+            texts: Arc::clone(&shared_texts),
+            transfers: Arc::clone(&transfers),
+            thumbnails: ThumbnailCache::default(),
+            events: events.clone(),
+            webdav: webdav.clone(),
+            app,
+            page: Arc::clone(&page),
+            session_name: name.clone(),
+            known_urls: addresses,
+            primary_url: primary_url.clone(),
+            insecure_tls,
+            public_key_pin: public_key_pin.clone(),
+        };
+        let mut router = Router::new()
+            .route("/", get(page::serve_index))
+            .route("/files/:id", get(download_file))
+            .route("/preview/:id", get(preview::serve_preview))
+            .route("/thumbnails/:id", get(preview::serve_thumbnail))
+            .route("/api/files", get(list_files))
+            .route("/api/events", get(live::serve_events))
+            .route("/manifest.json", get(manifest::serve_manifest))
+            .route("/files.txt", get(manifest::serve_url_list))
+            .route("/fetch.sh", get(manifest::serve_fetch_script))
+            .route("/qr.svg", get(qr::serve_qr_svg))
+            .route("/qr.png", get(qr::serve_qr_png))
+            .route(
+                "/api/texts",
+                get(texts::list_texts).post(texts::receive_text),
+            );
+        if webdav.is_some() {
+            router = router
+                .route(webdav::DAV_PREFIX, any(webdav::handle_dav))
+                .route("/dav/", any(webdav::handle_dav))
+                .route("/dav/*path", any(webdav::handle_dav));
+        }
+        let router = router.with_state(http_state);
+
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        let tls_info = tls_material.as_ref().map(|material| FileShareTlsInfo {
+            fingerprint_sha256: material.fingerprint.clone(),
+            self_signed: material.self_signed,
+        });
+        let handle = match tls_material {
+            Some(material) => {
+                let std_listener = listener
+                    .into_std()
+                    .map_err(|err| format!("无法启动服务: {err}"))?;
+                let server_handle = axum_server::Handle::new();
+                let shutdown_handle = server_handle.clone();
+                async_runtime::spawn(async move {
+                    let _ = shutdown_rx.await;
+                    shutdown_handle.graceful_shutdown(Some(Duration::from_secs(3)));
+                });
+                async_runtime::spawn(async move {
+                    let server = axum_server::from_tcp_rustls(std_listener, material.config)
+                        .handle(server_handle)
+                        .serve(router.into_make_service_with_connect_info::<SocketAddr>());
+                    if let Err(err) = server.await {
+                        eprintln!("文件分享服务异常: {err}");
+                    }
+                })
+            }
+            None => async_runtime::spawn(async move {
+                let server = axum::serve(
+                    listener,
+                    router.into_make_service_with_connect_info::<SocketAddr>(),
+                )
+                .with_graceful_shutdown(async move {
+                    let _ = shutdown_rx.await;
+                });
+                if let Err(err) = server.await {
+                    eprintln!("文件分享服务异常: {err}");
+                }
+            }),
+        };
+
+        let fingerprint = tls_info
+            .as_ref()
+            .map(|info| info.fingerprint_sha256.clone());
+        let advertised_name = name.clone();
+        let advertiser = async_runtime::spawn_blocking(move || {
+            ShareAdvertiser::start(
+                &advertised_name,
+                port,
+                scheme,
+                fingerprint.as_deref(),
+                bind_ip,
+                false,
+            )
+        })
+        .await
+        .map_err(|err| format!("启动 mDNS 广播失败: {err}"))
+        .and_then(|result| result);
+        let advertiser = match advertiser {
+            Ok(advertiser) => Some(advertiser),
+            Err(err) => {
+                eprintln!("局域网广播不可用: {err}");
+                None
+            }
+        };
+        let local_url = advertiser
+            .as_ref()
+            .map(|advertiser| format!("{scheme}://{}:{port}", advertiser.hostname()));
+
+        let session = FileShareSession {
+            id: session_id,
+            name,
+            port,
+            bind_address: bind_ip.to_string(),
+            addresses: vec![primary_url.clone()],
+            primary_url: primary_url.clone(),
+            files: Vec::new(),
+            texts: Vec::new(),
+            transfer: transfers.stats(),
+            tls: tls_info,
+            webdav: webdav.as_ref().map(|dav| FileShareWebDavInfo {
+                url: format!("{primary_url}{}/", webdav::DAV_PREFIX),
+                writable: dav.writable,
+                write_token: dav.write_token.clone(),
+            }),
+            local_url,
+            qr_code: match qr::render_qr_code(&primary_url) {
+                Ok(code) => Some(code),
+                Err(err) => {
+                    eprintln!("{err}");
+                    None
+                }
+            },
+            fetch_command: manifest::fetch_one_liner(
+                &primary_url,
+                insecure_tls,
+                public_key_pin.as_deref(),
+            ),
+            live: live.is_some(),
+            created_at: current_timestamp_millis() as u64,
+        };
+        let active = ActiveShare {
+            shutdown: Some(shutdown_tx),
+            handle,
+            advertiser,
+            live,
+            webdav,
+            events,
+            files: shared_files,
+            texts: shared_texts,
+            transfers,
+            session,
+        };
+        let snapshot = active.snapshot().await;
+
+        let mut guard = self.inner.lock().await;
+        guard.insert(snapshot.id.clone(), active);
+        drop(guard);
+
+        Ok(snapshot)
+    }
+
+    async fn stop(&self, id: &str) -> Result<(), String> {
+        let active = {
+            let mut guard = self.inner.lock().await;
+            guard.remove(id)
+        };
+        match active {
+            Some(active) => {
+                active.shutdown().await;
+                Ok(())
+            }
+            None => Err("分享会话不存在或已停止。".into()),
+        }
+    }
+
+    async fn stop_all(&self) {
+        let actives = {
+            let mut guard = self.inner.lock().await;
+            guard.drain().map(|(_, active)| active).collect::<Vec<_>>()
+        };
+        for active in actives {
+            active.shutdown().await;
+        }
+    }
+
+    async fn list(&self) -> Vec<FileShareSession> {
+        let guard = self.inner.lock().await;
+        let mut sessions = Vec::with_capacity(guard.len());
+        for active in guard.values() {
+            sessions.push(active.snapshot().await);
+        }
+        sessions.sort_by_key(|session| session.created_at);
+        sessions
+    }
+
+    async fn add_files(&self, id: &str, files: Vec<String>) -> Result<FileShareSession, String> {
+        if files.is_empty() {
+            return Err("请选择至少一个需要分享的文件。".into());
+        }
+        let additions = build_server_files(&files)?;
+
+        let guard = self.inner.lock().await;
+        let active = guard
+            .get(id)
+            .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+        if let Some(live) = active.live.as_ref() {
+            let added_ids = additions
+                .iter()
+                .map(|file| file.id.clone())
+                .collect::<Vec<_>>();
+            live.add_roots(&files, &added_ids)?;
+        }
+        if let Some(dav) = active.webdav.as_ref() {
+            dav.add_entries(&files);
+        }
+        let file_count = {
+            let mut table = active.files.write().await;
+            for file in additions {
+                if !table.iter().any(|existing| existing.id == file.id) {
+                    table.push(file);
+                }
+            }
+            table.len()
+        };
+        active.events.files_changed(file_count);
+        Ok(active.snapshot().await)
+    }
+
+    async fn remove_files(
+        &self,
+        id: &str,
+        file_ids: Vec<String>,
+    ) -> Result<FileShareSession, String> {
+        let guard = self.inner.lock().await;
+        let active = guard
+            .get(id)
+            .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+        if let Some(live) = active.live.as_ref() {
+            live.exclude(&file_ids);
+        }
+        let file_count = {
+            let mut table = active.files.write().await;
+            table.retain(|file| !file_ids.contains(&file.id));
+            table.len()
+        };
+        active.events.files_changed(file_count);
+        Ok(active.snapshot().await)
+    }
+
+    async fn update_limits(
+        &self,
+        id: &str,
+        limits: FileShareLimits,
+    ) -> Result<FileShareSession, String> {
+        let guard = self.inner.lock().await;
+        let active = guard
+            .get(id)
+            .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+        active.transfers.update_limits(limits);
+        Ok(active.snapshot().await)
+    }
+
+    async fn push_text(&self, id: &str, item: SharedTextItem) -> Result<FileShareSession, String> {
+        let guard = self.inner.lock().await;
+        let active = guard
+            .get(id)
+            .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+        texts::push_text(&active.texts, item).await;
+        Ok(active.snapshot().await)
+    }
+
+    async fn remove_text(&self, id: &str, text_id: &str) -> Result<FileShareSession, String> {
+        let guard = self.inner.lock().await;
+        let active = guard
+            .get(id)
+            .ok_or_else(|| "分享会话不存在或已停止。".to_string())?;
+        active.texts.write().await.retain(|item| item.id != text_id);
+        Ok(active.snapshot().await)
+    }
+
+    async fn advertised_fullnames(&self) -> Vec<String> {
+        let guard = self.inner.lock().await;
+        guard
+            .values()
+            .filter_map(|share| share.advertiser.as_ref())
+            .map(|advertiser| advertiser.fullname().to_string())
+            .collect()
+    }
+}
+
+fn session_addresses(session: &FileShareSession) -> Vec<String> {
+    let scheme = if session.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let bind_ip = session
+        .bind_address
+        .parse::<IpAddr>()
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    let mut addresses = vec![session.primary_url.clone()];
+    addresses.extend(session.local_url.clone());
+    for url in collect_accessible_urls(scheme, bind_ip, session.port) {
+        if !addresses.contains(&url) {
+            addresses.push(url);
+        }
+    }
+    addresses
+}
+
+fn default_session_name(entries: &[String]) -> String {
+    let first = entries
+        .first()
+        .and_then(|entry| Path::new(entry).file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "局域网分享".into());
+    if entries.len() > 1 {
+        format!("{first} 等 {} 项", entries.len())
+    } else {
+        first
+    }
+}
+
+fn parse_bind_address(raw: Option<&str>) -> Result<IpAddr, String> {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    };
+    if let Ok(ip) = raw
+        .trim_matches(|ch| ch == '[' || ch == ']')
+        .parse::<IpAddr>()
+    {
+        return Ok(ip);
+    }
+    let host = reqwest::Url::parse(raw)
+        .ok()
+        .and_then(|url| url.host_str().map(|host| host.to_string()))
+        .ok_or_else(|| format!("无法识别的绑定地址：{raw}"))?;
+    if host == "localhost" {
+        return Ok(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    }
+    host.trim_matches(|ch| ch == '[' || ch == ']')
+        .parse::<IpAddr>()
+        .map_err(|_| format!("无法识别的绑定地址：{raw}"))
+}
+
+async fn resolve_discovered_shares(
+    announcements: Vec<mdns::ShareAnnouncement>,
+) -> Vec<DiscoveredShare> {
+    let tasks = announcements
+        .into_iter()
+        .map(|announcement| async_runtime::spawn(resolve_discovered_share(announcement)))
+        .collect::<Vec<_>>();
+
+    let mut shares = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        if let Ok(share) = task.await {
+            shares.push(share);
+        }
+    }
+    shares
+}
+
+/// https 分享只信任广播中指纹对应的证书，没有广播指纹时无法确认对方身份，不去获取文件列表。
+fn discovery_client(announcement: &mdns::ShareAnnouncement) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().timeout(Duration::from_secs(3));
+    if announcement.scheme == "https" {
+        let fingerprint = announcement
+            .fingerprint
+            .as_deref()
+            .ok_or_else(|| "对方未广播证书指纹，无法确认身份，已跳过获取文件列表。".to_string())?;
+        builder = builder.use_preconfigured_tls(tls::pinned_client_config(fingerprint)?);
+    }
+    builder
+        .build()
+        .map_err(|err| format!("创建 HTTP 客户端失败: {err}"))
+}
+
+async fn resolve_discovered_share(announcement: mdns::ShareAnnouncement) -> DiscoveredShare {
+    let scheme = announcement.scheme.as_str();
+    let port = announcement.port;
+    let addresses = announcement
+        .addresses
+        .iter()
+        .map(|ip| format_url(scheme, *ip, port))
+        .collect::<Vec<_>>();
+
+    let mut files = Vec::new();
+    let mut error = None;
+    let mut primary_url = None;
+    match discovery_client(&announcement) {
+        Ok(client) => {
+            for base in &addresses {
+                match fetch_remote_files(&client, base).await {
+                    Ok(list) => {
+                        files = list;
+                        primary_url = Some(base.clone());
+                        error = None;
+                        break;
+                    }
+                    Err(err) => error = Some(err),
+                }
+            }
+        }
+        Err(err) => error = Some(err),
+    }
+    if addresses.is_empty() {
+        error = Some("未解析到可用地址。".into());
+    }
+
+    DiscoveredShare {
+        primary_url: primary_url
+            .or_else(|| addresses.first().cloned())
+            .unwrap_or_else(|| format!("{scheme}://{}:{port}", announcement.hostname)),
+        instance_name: announcement.instance_name,
+        session_name: announcement.session_name,
+        hostname: announcement.hostname,
+        port,
+        addresses,
+        tls_fingerprint: announcement.fingerprint,
+        files,
+        error,
+    }
+}
+
+async fn fetch_remote_files(
+    client: &reqwest::Client,
+    base_url: &str,
+) -> Result<Vec<SharedFileMeta>, String> {
+    client
+        .get(format!("{base_url}/api/files"))
+        .send()
+        .await
+        .map_err(|err| format!("访问 {base_url} 失败: {err}"))?
+        .error_for_status()
+        .map_err(|err| format!("访问 {base_url} 失败: {err}"))?
+        .json::<Vec<SharedFileMeta>>()
+        .await
+        .map_err(|err| format!("解析 {base_url} 的文件列表失败: {err}"))
+}
+
+fn file_meta(file: &ServerFile) -> SharedFileMeta {
+    SharedFileMeta {
+        id: file.id.clone(),
+        display_name: file.display_name.clone(),
+        download_name: file.download_name.clone(),
+        size: file.size,
+        extension: file.extension.clone(),
+        sha256: file.sha256.get().cloned(),
+        preview: preview::preview_kind(&file.path, file.mime.first_or_octet_stream().essence_str()),
+    }
+}
+
+fn build_server_files(entries: &[String]) -> Result<Vec<ServerFile>, String> {
+    let mut result = Vec::new();
+    for raw_path in entries {
+        result.extend(scan_share_entry(raw_path)?);
+    }
+
+    if result.is_empty() {
+        return Err("所选项目中没有可分享的文件。".into());
+    }
+    Ok(result)
+}
+
+fn scan_share_entry(raw_path: &str) -> Result<Vec<ServerFile>, String> {
+    let mut result = Vec::new();
+    let canonical = Path::new(raw_path)
+        .canonicalize()
+        .map_err(|err| format!("无法读取路径 {raw_path}: {err}"))?;
+    let metadata = canonical
+        .metadata()
+        .map_err(|err| format!("无法获取文件信息 {raw_path}: {err}"))?;
+
+    if metadata.is_file() {
+        let display = canonical
+            .file_name()
+            .and_then(|os| os.to_str())
+            .ok_or_else(|| format!("无法解析文件名：{raw_path}"))?
+            .to_string();
+        let root = canonical.parent().unwrap_or(&canonical);
+        let id = stable_file_id(root, &display);
+        push_file_entry(&mut result, &canonical, id, display)?;
+        return Ok(result);
+    }
+
+    if metadata.is_dir() {
+        let folder_label = folder_label(&canonical);
+
+        for entry in WalkDir::new(&canonical).into_iter() {
+            let entry = entry.map_err(|err| format!("读取文件夹 {raw_path} 时出错: {err}"))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry
+                .path()
+                .strip_prefix(&canonical)
+                .map_err(|err| format!("解析文件夹结构失败: {err}"))?;
+            let relative_str = relative.to_string_lossy().replace('\\', "/");
+            let display = format!("{}/{}", folder_label, relative_str);
+            let id = stable_file_id(&canonical, &relative_str);
+            push_file_entry(&mut result, entry.path(), id, display)?;
+        }
+    }
+    Ok(result)
+}
+
+fn folder_label(canonical: &Path) -> String {
+    canonical
+        .file_name()
+        .and_then(|os| os.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| canonical.to_string_lossy().to_string())
+}
+
+/// 由分享根目录与相对路径推导文件 id，重新扫描时保持不变。
+fn stable_file_id(root: &Path, relative: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{relative}", root.to_string_lossy()).as_bytes());
+    digest[..12]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn push_file_entry(
+    result: &mut Vec<ServerFile>,
+    path: &Path,
+    id: String,
+    display_name: String,
+) -> Result<(), String> {
+    let metadata = path
+        .metadata()
+        .map_err(|err| format!("无法获取文件信息 {}: {err}", path.display()))?;
+    if !metadata.is_file() {
+        return Ok(());
+    }
+    let download_name = path
+        .file_name()
+        .and_then(|os| os.to_str())
+        .ok_or_else(|| format!("无法解析文件名：{}", path.display()))?
+        .to_string();
+    let modified = system_time_millis(metadata.modified().ok());
+
+    result.push(ServerFile {
+        id,
+        display_name,
+        download_name,
+        size: metadata.len(),
+        modified,
+        extension: path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|s| s.to_string()),
+        path: path.to_path_buf(),
+        mime: mime_guess::from_path(path),
+        sha256: Arc::new(OnceCell::new()),
+    });
+
+    Ok(())
+}
+
+fn system_time_millis(time: Option<SystemTime>) -> u64 {
+    time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+fn collect_accessible_urls(scheme: &str, bind_ip: IpAddr, port: u16) -> Vec<String> {
+    if !bind_ip.is_unspecified() && !bind_ip.is_loopback() {
+        return vec![format_url(scheme, bind_ip, port)];
+    }
+    let mut urls = vec![
+        format!("{scheme}://localhost:{port}"),
+        format!("{scheme}://127.0.0.1:{port}"),
+    ];
+    if bind_ip.is_unspecified() {
+        for ip in collect_interface_ips() {
+            urls.push(format_url(scheme, ip, port));
+        }
+    }
+    urls.sort();
+    urls.dedup();
+    urls
+}
+
+fn format_url(scheme: &str, ip: IpAddr, port: u16) -> String {
+    match ip {
+        IpAddr::V4(v4) => format!("{scheme}://{v4}:{port}"),
+        IpAddr::V6(v6) => format!("{scheme}://[{}]:{port}", format_ipv6(v6)),
+    }
+}
+
+fn collect_interface_ips() -> Vec<IpAddr> {
+    if_addrs::get_if_addrs()
+        .map(|ifaces| {
+            ifaces
+                .into_iter()
+                .map(|iface| iface.ip())
+                .filter(|ip| !ip.is_loopback())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn collect_local_hosts() -> Vec<String> {
+    let mut hosts = vec!["127.0.0.1".to_string(), "::1".to_string()];
+    hosts.extend(collect_interface_ips().into_iter().map(|ip| ip.to_string()));
+    hosts
+}
+
+fn format_ipv6(addr: Ipv6Addr) -> String {
+    if addr.segments().iter().all(|segment| *segment == 0) {
+        return "::".into();
+    }
+    addr.to_string()
+}
+
+async fn list_files(AxumState(state): AxumState<HttpState>) -> Json<Vec<SharedFileMeta>> {
+    Json(state.files.read().await.iter().map(file_meta).collect())
+}
+
+async fn download_file(
+    AxumPath(file_id): AxumPath<String>,
+    AxumState(state): AxumState<HttpState>,
+) -> Response {
+    let file = state
+        .files
+        .read()
+        .await
+        .iter()
+        .find(|file| file.id == file_id)
+        .cloned();
+    match file {
+        Some(file) => stream_file(&state, &file).await,
+        None => (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response(),
+    }
+}
+
+/// 以附件形式限速输出文件内容，网页下载与 WebDAV 共用。
+async fn stream_file(state: &HttpState, file: &ServerFile) -> Response {
+    let permit = state.transfers.acquire().await;
+    let opened = match File::open(&file.path).await {
+        Ok(f) => f.metadata().await.map(|meta| (f, meta.len())),
+        Err(err) => Err(err),
+    };
+    match opened {
+        Ok((f, len)) => {
+            // 文件可能在分享期间被修改，长度以当前元数据为准，并截断到该长度。
+            let stream = ReaderStream::with_capacity(f.take(len), 64 * 1024);
+            let body = Body::from_stream(throttle::throttle_stream(stream, permit, len));
+            let mut response = Response::new(body);
+            response
+                .headers_mut()
+                .insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+            let mime = file.mime.first_raw().unwrap_or("application/octet-stream");
+            if let Ok(value) = HeaderValue::from_str(mime) {
+                response.headers_mut().insert(header::CONTENT_TYPE, value);
+            }
+            let disposition = format!(
+                "attachment; filename=\"{}\"",
+                sanitize_filename(&file.download_name)
+            );
+            if let Ok(value) = HeaderValue::from_str(&disposition) {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_DISPOSITION, value);
+            }
+            response
+        }
+        Err(_) => (StatusCode::NOT_FOUND, "文件不再可用，请在桌面端重新选择。").into_response(),
+    }
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .chars()
+        .map(|ch| match ch {
+            '<' => "&lt;".into(),
+            '>' => "&gt;".into(),
+            '"' => "&quot;".into(),
+            '\'' => "&#39;".into(),
+            '&' => "&amp;".into(),
+            _ => ch.to_string(),
+        })
+        .collect()
+}
+
+fn sanitize_filename(input: &str) -> String {
+    input
+        .chars()
+        .map(|ch| match ch {
+            '"' | '\\' | '/' | ':' | '*' | '?' | '<' | '>' | '|' => '_',
+            other => other,
+        })
+        .collect()
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    if size == 0 {
+        return "0 B".into();
+    }
+    let mut value = size as f64;
+    let mut unit_index = 0;
+    while value >= 1024.0 && unit_index < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit_index += 1;
+    }
+    if unit_index == 0 {
+        format!("{size} {}", UNITS[unit_index])
+    } else {
+        format!("{value:.1} {}", UNITS[unit_index])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn create_temp_files(names: &[&str]) -> (PathBuf, Vec<String>) {
+        let dir = std::env::temp_dir().join(format!("chef_share_test_{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("failed to create temp dir");
+        let paths = names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                fs::write(&path, name.as_bytes()).expect("failed to write test file");
+                path.display().to_string()
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    #[test]
+    fn parses_bind_address_from_ip_or_url() {
+        assert_eq!(
+            parse_bind_address(None).unwrap(),
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        );
+        assert_eq!(
+            parse_bind_address(Some("192.168.1.20")).unwrap(),
+            IpAddr::from([192, 168, 1, 20])
+        );
+        assert_eq!(
+            parse_bind_address(Some("http://[fe80::1]:54321")).unwrap(),
+            "fe80::1".parse::<IpAddr>().unwrap()
+        );
+        assert!(parse_bind_address(Some("not an address")).is_err());
+    }
+
+    async fn start_loopback(manager: &FileShareManager, paths: &[String]) -> FileShareSession {
+        manager
+            .start(
+                paths.to_vec(),
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("session should start")
+    }
+
+    async fn start_tls_loopback(manager: &FileShareManager, paths: &[String]) -> FileShareSession {
+        manager
+            .start(
+                paths.to_vec(),
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    tls: Some(FileShareTlsOptions {
+                        enabled: true,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("tls session should start")
+    }
+
+    async fn post_text(session: &FileShareSession, content: &str) {
+        let posted = reqwest::Client::new()
+            .post(format!("{}/api/texts", session.primary_url))
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+            .await
+            .expect("text should be accepted");
+        assert_eq!(posted.status(), reqwest::StatusCode::CREATED);
+    }
+
+    async fn get_text(url: String) -> String {
+        reqwest::get(url)
+            .await
+            .expect("share should be reachable")
+            .text()
+            .await
+            .expect("response should be text")
+    }
+
+    /// 每收到一条 SSE 推送就检查一次文件列表，直到满足条件；超时则测试失败。
+    async fn wait_for_files(
+        manager: &FileShareManager,
+        events: &mut reqwest::Response,
+        done: impl Fn(&[SharedFileMeta]) -> bool,
+    ) -> Vec<SharedFileMeta> {
+        let wait = async {
+            loop {
+                let files = manager.list().await.remove(0).files;
+                if done(&files) {
+                    return files;
+                }
+                let chunk = events
+                    .chunk()
+                    .await
+                    .unwrap()
+                    .expect("event stream should stay open");
+                assert!(String::from_utf8_lossy(&chunk).contains("event: files"));
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), wait)
+            .await
+            .expect("change should be pushed")
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_share_over_https_with_user_certificate() {
+        let (dir, paths) = create_temp_files(&["a.txt"]);
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["127.0.0.1".to_string()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        fs::write(&cert_path, cert.pem()).unwrap();
+        fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+
+        let manager = FileShareManager::default();
+        let session = manager
+            .start(
+                paths,
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    tls: Some(FileShareTlsOptions {
+                        enabled: true,
+                        cert_path: Some(cert_path.display().to_string()),
+                        key_path: Some(key_path.display().to_string()),
+                    }),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("tls session should start");
+        assert!(session.primary_url.starts_with("https://"));
+        assert!(session
+            .addresses
+            .iter()
+            .all(|url| url.starts_with("https://")));
+        assert!(!session.tls.as_ref().unwrap().self_signed);
+
+        // 只信任这张证书，不带系统根证书
+        let client = reqwest::Client::builder()
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(reqwest::Certificate::from_pem(cert.pem().as_bytes()).unwrap())
+            .build()
+            .unwrap();
+        let files = client
+            .get(format!("{}/api/files", session.primary_url))
+            .send()
+            .await
+            .expect("share should be reachable over https")
+            .json::<Vec<SharedFileMeta>>()
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(reqwest::Client::new()
+            .get(format!("{}/api/files", session.primary_url))
+            .send()
+            .await
+            .is_err());
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn discovery_trusts_only_the_announced_certificate() {
+        let (dir, paths) = create_temp_files(&["a.txt"]);
+        let manager = FileShareManager::default();
+        let session = start_tls_loopback(&manager, &paths).await;
+        let announcement = |fingerprint: Option<&str>| mdns::ShareAnnouncement {
+            fullname: "Chef test._chef-share._tcp.local.".into(),
+            instance_name: "Chef test".into(),
+            hostname: "chef-test.local".into(),
+            port: session.port,
+            addresses: vec![IpAddr::from([127, 0, 0, 1])],
+            scheme: "https".into(),
+            fingerprint: fingerprint.map(str::to_string),
+            session_name: None,
+        };
+
+        let fingerprint = session.tls.as_ref().unwrap().fingerprint_sha256.clone();
+        let trusted = resolve_discovered_share(announcement(Some(&fingerprint))).await;
+        assert_eq!(trusted.error, None);
+        assert_eq!(trusted.files.len(), 1);
+
+        let forged = resolve_discovered_share(announcement(Some("AA:BB"))).await;
+        assert!(forged.error.is_some());
+        assert!(forged.files.is_empty());
+
+        let unannounced = resolve_discovered_share(announcement(None)).await;
+        assert!(unannounced.error.unwrap().contains("指纹"));
+        assert!(unannounced.files.is_empty());
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn runs_concurrent_sessions_and_edits_files_in_place() {
+        let (dir, paths) = create_temp_files(&["a.txt", "b.txt", "c.txt"]);
+        let manager = FileShareManager::default();
+        let first = start_loopback(&manager, &paths[..1]).await;
+        let second = start_loopback(&manager, &paths[1..2]).await;
+        assert_ne!(first.id, second.id);
+        assert_eq!(manager.list().await.len(), 2);
+
+        let updated = manager
+            .add_files(&first.id, vec![paths[2].clone(), paths[0].clone()])
+            .await
+            .expect("files should be added");
+        assert_eq!(updated.primary_url, first.primary_url);
+        assert_eq!(updated.files.len(), 2);
+
+        let body = reqwest::get(format!("{}/api/files", first.primary_url))
+            .await
+            .expect("share should be reachable")
+            .json::<Vec<SharedFileMeta>>()
+            .await
+            .expect("file list should be json");
+        assert_eq!(body.len(), 2);
+
+        let updated = manager
+            .remove_files(&first.id, vec![first.files[0].id.clone()])
+            .await
+            .expect("file should be removed");
+        assert_eq!(updated.files.len(), 1);
+        assert_eq!(updated.files[0].download_name, "c.txt");
+
+        manager.stop(&first.id).await.expect("stop by id");
+        let remaining = manager.list().await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, second.id);
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_manifest_with_checksums() {
+        let (dir, paths) = create_temp_files(&["a.txt", "b.txt"]);
+        let manager = FileShareManager::default();
+        let session = start_loopback(&manager, &paths).await;
+
+        let manifest = reqwest::get(format!("{}/manifest.json", session.primary_url))
+            .await
+            .expect("manifest should be reachable")
+            .json::<serde_json::Value>()
+            .await
+            .expect("manifest should be json");
+        assert_eq!(manifest["file_count"], 2);
+        assert_eq!(
+            manifest["files"][0]["sha256"].as_str().map(str::len),
+            Some(64)
+        );
+        let listed = manager.list().await.remove(0);
+        assert!(listed.files.iter().all(|file| file.sha256.is_some()));
+        assert!(listed.fetch_command.ends_with("/fetch.sh | sh"));
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exchanges_text_snippets_with_remote_clients() {
+        let (dir, paths) = create_temp_files(&["a.txt"]);
+        let manager = FileShareManager::default();
+        let session = start_loopback(&manager, &paths).await;
+
+        post_text(&session, "token-123").await;
+        let pushed = manager
+            .push_text(
+                &session.id,
+                texts::new_text_item("from desktop".into(), None, TextOrigin::Desktop, None)
+                    .unwrap(),
+            )
+            .await
+            .expect("desktop text should be pushed");
+        assert_eq!(pushed.texts.len(), 2);
+        assert_eq!(pushed.texts[0].origin, TextOrigin::Remote);
+        assert_eq!(pushed.texts[0].remote_addr.as_deref(), Some("127.0.0.1"));
+        let page = get_text(session.primary_url.clone()).await;
+        assert!(page.contains("token-123"));
+        assert!(page.contains("来自 127.0.0.1"));
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_qr_codes_for_share_urls() {
+        let (dir, paths) = create_temp_files(&["a.txt"]);
+        let manager = FileShareManager::default();
+        let session = start_loopback(&manager, &paths).await;
+
+        let qr = get_text(format!("{}/qr.svg", session.primary_url)).await;
+        assert!(qr.contains("<svg"));
+        assert!(qr::is_loopback_url("http://127.0.0.1:8000"));
+        assert!(qr::is_loopback_url("https://[::1]:8000"));
+        assert!(!qr::is_loopback_url("http://192.168.1.5:8000"));
+        assert!(session
+            .qr_code
+            .as_ref()
+            .is_some_and(|code| code.png_data_url.starts_with("data:image/png;base64,")));
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn previews_text_files_inline() {
+        let (dir, paths) = create_temp_files(&["a.txt", "b.svg"]);
+        let manager = FileShareManager::default();
+        let session = start_loopback(&manager, &paths).await;
+        assert_eq!(session.files[0].preview, Some(PreviewKind::Text));
+
+        let preview = reqwest::get(format!(
+            "{}/preview/{}",
+            session.primary_url, session.files[0].id
+        ))
+        .await
+        .expect("preview should be reachable");
+        assert_eq!(preview.status(), reqwest::StatusCode::OK);
+        assert_eq!(preview.headers()["x-content-type-options"], "nosniff");
+        assert!(preview.headers()["content-security-policy"]
+            .to_str()
+            .unwrap()
+            .starts_with("sandbox"));
+        assert!(preview.text().await.unwrap().contains("<pre"));
+
+        let svg = reqwest::get(format!(
+            "{}/preview/{}",
+            session.primary_url, session.files[1].id
+        ))
+        .await
+        .expect("svg preview should be reachable");
+        assert!(svg.headers()["content-disposition"]
+            .to_str()
+            .unwrap()
+            .starts_with("attachment"));
+        assert!(manager.list().await[0].transfer.total_bytes_sent > 0);
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn localizes_share_page_from_accept_language() {
+        let (dir, paths) = create_temp_files(&["a.txt"]);
+        let manager = FileShareManager::default();
+        let session = start_loopback(&manager, &paths).await;
+        post_text(&session, "hello").await;
+
+        let english = reqwest::Client::new()
+            .get(&session.primary_url)
+            .header(header::ACCEPT_LANGUAGE, "en-US,en;q=0.9")
+            .send()
+            .await
+            .expect("share page should be reachable")
+            .text()
+            .await
+            .expect("share page should be html");
+        assert!(english.contains(r#"<html lang="en">"#));
+        assert!(english.contains("From 127.0.0.1"));
+
+        manager.stop_all().await;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn live_share_tracks_folder_changes_with_stable_ids() {
+        let (dir, _) = create_temp_files(&["a.txt", "b.txt"]);
+        let manager = FileShareManager::default();
+        let session = manager
+            .start(
+                vec![dir.display().to_string()],
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    live: true,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("live session should start");
+        assert!(session.live);
+        let id_of = |files: &[SharedFileMeta], name: &str| {
+            files
+                .iter()
+                .find(|file| file.download_name == name)
+                .map(|file| file.id.clone())
+        };
+        let a_id = id_of(&session.files, "a.txt").expect("a.txt should be shared");
+
+        let mut events = reqwest::get(format!("{}/api/events", session.primary_url))
+            .await
+            .expect("event stream should open");
+        fs::write(dir.join("c.txt"), b"c").unwrap();
+        fs::remove_file(dir.join("b.txt")).unwrap();
+
+        let files = wait_for_files(&manager, &mut events, |files| {
+            id_of(files, "c.txt").is_some() && id_of(files, "b.txt").is_none()
+        })
+        .await;
+        assert_eq!(files.len(), 2);
+        assert_eq!(id_of(&files, "a.txt"), Some(a_id.clone()));
+
+        manager
+            .remove_files(&session.id, vec![a_id])
+            .await
+            .expect("file should be removed");
+        fs::write(dir.join("d.txt"), b"d").unwrap();
+        let files = wait_for_files(&manager, &mut events, |files| {
+            id_of(files, "d.txt").is_some()
+        })
+        .await;
+        assert!(id_of(&files, "a.txt").is_none());
+
+        tokio::time::timeout(Duration::from_secs(5), manager.stop(&session.id))
+            .await
+            .expect("open event streams should not block shutdown")
+            .unwrap();
+        let _ = fs::remove_dir_all(dir);
+    }
+}
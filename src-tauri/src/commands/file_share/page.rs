@@ -0,0 +1,406 @@
+use std::path::Path;
+
+use axum::{
+    extract::State as AxumState,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use minijinja::Environment;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    format_size,
+    preview::{preview_kind, PreviewKind},
+    texts::{SharedTextItem, TextOrigin},
+    HttpState, SHARE_PAGE_TEMPLATE,
+};
+
+pub(super) const SHARE_TEMPLATE_DIR: &str = "share-templates";
+const TEMPLATE_NAME: &str = "share.html";
+const DEFAULT_ACCENT: &str = "#7055ff";
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSharePageOptions {
+    pub accent_color: Option<String>,
+    pub title: Option<String>,
+    pub message: Option<String>,
+    /// 应用数据目录下 `share-templates` 中的模板文件名。
+    pub template: Option<String>,
+}
+
+/// 会话的分享页：编译好的模板与展示设置。
+pub(super) struct SharePage {
+    env: Environment<'static>,
+    accent: String,
+    title: Option<String>,
+    message: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(super) enum Locale {
+    Zh,
+    En,
+}
+
+#[derive(Serialize)]
+struct Labels {
+    brand: &'static str,
+    tag: &'static str,
+    headline: &'static str,
+    description: &'static str,
+    files: &'static str,
+    size: &'static str,
+    network: &'static str,
+    lan_only: &'static str,
+    qr_alt: &'static str,
+    download: &'static str,
+    preview: &'static str,
+    no_files: &'static str,
+    texts_title: &'static str,
+    text_empty: &'static str,
+    text_placeholder: &'static str,
+    send: &'static str,
+    send_failed: &'static str,
+    copy: &'static str,
+    copied: &'static str,
+    footer: &'static str,
+    from_desktop: &'static str,
+    from_remote: &'static str,
+    lan_device: &'static str,
+}
+
+const ZH_LABELS: Labels = Labels {
+    brand: "Chef 局域网分享",
+    tag: "CHEF LAN SHARE",
+    headline: "链接只在网络内部可见",
+    description: "由桌面端即时托管的分享页，关闭分享后即刻失效，可放心给同网段用户下载。",
+    files: "文件",
+    size: "大小",
+    network: "网络",
+    lan_only: "仅局域网",
+    qr_alt: "扫码在其他设备打开",
+    download: "下载",
+    preview: "预览",
+    no_files: "暂时没有分享的文件。",
+    texts_title: "文本片段",
+    text_empty: "暂时没有分享的文本。",
+    text_placeholder: "输入要发送到桌面端的文本、链接或日志片段",
+    send: "发送到桌面",
+    send_failed: "发送失败",
+    copy: "复制",
+    copied: "已复制",
+    footer: "下载完成即可关闭页面，重新分享请回到桌面端点击“结束分享”后重新生成。",
+    from_desktop: "来自桌面",
+    from_remote: "来自",
+    lan_device: "局域网设备",
+};
+
+const EN_LABELS: Labels = Labels {
+    brand: "Chef LAN Share",
+    tag: "CHEF LAN SHARE",
+    headline: "This link is only visible on your network",
+    description: "Hosted live by the desktop app. The page stops working as soon as sharing ends, so it is safe to hand out on the same network.",
+    files: "FILES",
+    size: "SIZE",
+    network: "NETWORK",
+    lan_only: "LAN ONLY",
+    qr_alt: "Scan to open on another device",
+    download: "Download",
+    preview: "Preview",
+    no_files: "No files are shared right now.",
+    texts_title: "Text snippets",
+    text_empty: "No text has been shared yet.",
+    text_placeholder: "Type text, a link or a log snippet to send to the desktop",
+    send: "Send to desktop",
+    send_failed: "Failed to send",
+    copy: "Copy",
+    copied: "Copied",
+    footer: "You can close this page once your downloads finish. To share again, stop sharing in the desktop app and start a new share.",
+    from_desktop: "From desktop",
+    from_remote: "From",
+    lan_device: "a LAN device",
+};
+
+#[derive(Serialize)]
+struct PageContext<'a> {
+    lang: &'static str,
+    t: &'static Labels,
+    title: Option<&'a str>,
+    message: Option<&'a str>,
+    accent: &'a str,
+    session_name: &'a str,
+    file_count: usize,
+    total_size: String,
+    files: Vec<PageFile>,
+    texts: Vec<PageText>,
+}
+
+#[derive(Serialize)]
+struct PageFile {
+    id: String,
+    name: String,
+    description: String,
+    size: String,
+    thumbnail: bool,
+    preview: bool,
+}
+
+#[derive(Serialize)]
+struct PageText {
+    id: String,
+    title: String,
+    content: String,
+}
+
+impl Locale {
+    fn tag(self) -> &'static str {
+        match self {
+            Locale::Zh => "zh-CN",
+            Locale::En => "en",
+        }
+    }
+
+    fn labels(self) -> &'static Labels {
+        match self {
+            Locale::Zh => &ZH_LABELS,
+            Locale::En => &EN_LABELS,
+        }
+    }
+}
+
+impl SharePage {
+    /// 加载模板并校验展示设置；`templates_dir` 为空时只能使用内置模板。
+    pub fn new(
+        options: &FileSharePageOptions,
+        templates_dir: Option<&Path>,
+    ) -> Result<Self, String> {
+        let source = match options.template.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => {
+                let dir = templates_dir.ok_or_else(|| "无法定位自定义模板目录。".to_string())?;
+                read_user_template(dir, name)?
+            }
+            _ => SHARE_PAGE_TEMPLATE.to_string(),
+        };
+        let mut env = Environment::new();
+        env.add_template_owned(TEMPLATE_NAME, source)
+            .map_err(|err| format!("分享页模板有误: {err}"))?;
+
+        Ok(Self {
+            env,
+            accent: match options.accent_color.as_deref() {
+                Some(accent) => normalize_accent(accent)?,
+                None => DEFAULT_ACCENT.to_string(),
+            },
+            title: clean_text(options.title.as_deref()),
+            message: clean_text(options.message.as_deref()),
+        })
+    }
+}
+
+/// 列出自定义模板目录中的 `.html` 文件。
+pub(super) fn list_templates(dir: &Path) -> Vec<String> {
+    let mut names = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".html"))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+pub(super) async fn serve_index(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Response {
+    let locale = negotiate_locale(
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let labels = locale.labels();
+    let files = state.files.read().await;
+    let texts = state.texts.read().await;
+
+    let context = PageContext {
+        lang: locale.tag(),
+        t: labels,
+        title: state.page.title.as_deref(),
+        message: state.page.message.as_deref(),
+        accent: &state.page.accent,
+        session_name: &state.session_name,
+        file_count: files.len(),
+        total_size: format_size(files.iter().map(|file| file.size).sum()),
+        files: files
+            .iter()
+            .map(|file| {
+                let kind =
+                    preview_kind(&file.path, file.mime.first_or_octet_stream().essence_str());
+                PageFile {
+                    id: file.id.clone(),
+                    name: file.display_name.clone(),
+                    description: match &file.extension {
+                        Some(ext) => format!("{} · {}", ext.to_uppercase(), format_size(file.size)),
+                        None => format_size(file.size),
+                    },
+                    size: format_size(file.size),
+                    thumbnail: kind == Some(PreviewKind::Image),
+                    preview: kind.is_some(),
+                }
+            })
+            .collect(),
+        texts: texts
+            .iter()
+            .rev()
+            .map(|item| PageText {
+                id: item.id.clone(),
+                title: text_title(item, labels),
+                content: item.content.clone(),
+            })
+            .collect(),
+    };
+    drop(files);
+    drop(texts);
+
+    let rendered = state
+        .page
+        .env
+        .get_template(TEMPLATE_NAME)
+        .and_then(|template| template.render(&context));
+    match rendered {
+        Ok(html) => {
+            let mut response = Html(html).into_response();
+            response
+                .headers_mut()
+                .insert(header::VARY, HeaderValue::from_static("Accept-Language"));
+            response
+        }
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("渲染分享页失败: {err}"),
+        )
+            .into_response(),
+    }
+}
+
+/// 按 `Accept-Language` 的权重选择中文或英文，都不匹配时使用中文。
+pub(super) fn negotiate_locale(header: Option<&str>) -> Locale {
+    let mut candidates = header
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|part| {
+            let mut pieces = part.split(';');
+            let tag = pieces.next()?.trim().to_ascii_lowercase();
+            let quality = pieces
+                .find_map(|piece| piece.trim().strip_prefix("q="))
+                .and_then(|value| value.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((tag, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates
+        .iter()
+        .find_map(|(tag, _)| match tag.split('-').next() {
+            Some("zh") => Some(Locale::Zh),
+            Some("en") => Some(Locale::En),
+            _ => None,
+        })
+        .unwrap_or(Locale::Zh)
+}
+
+fn text_title(item: &SharedTextItem, labels: &Labels) -> String {
+    item.label.clone().unwrap_or_else(|| match item.origin {
+        TextOrigin::Desktop => labels.from_desktop.to_string(),
+        TextOrigin::Remote => format!(
+            "{} {}",
+            labels.from_remote,
+            item.remote_addr.as_deref().unwrap_or(labels.lan_device)
+        ),
+    })
+}
+
+fn read_user_template(dir: &Path, name: &str) -> Result<String, String> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("无效的模板名称：{name}"));
+    }
+    let file_name = if name.ends_with(".html") {
+        name.to_string()
+    } else {
+        format!("{name}.html")
+    };
+    let path = dir.join(file_name);
+    std::fs::read_to_string(&path).map_err(|err| format!("无法读取模板 {}: {err}", path.display()))
+}
+
+fn normalize_accent(raw: &str) -> Result<String, String> {
+    let value = raw.trim();
+    let valid = value
+        .strip_prefix('#')
+        .map(|hex| matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false);
+    if valid {
+        Ok(value.to_ascii_lowercase())
+    } else {
+        Err(format!("无效的主题色：{raw}，请使用 #RRGGBB 格式。"))
+    }
+}
+
+fn clean_text(value: Option<&str>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_locale_from_accept_language() {
+        assert_eq!(negotiate_locale(None), Locale::Zh);
+        assert_eq!(negotiate_locale(Some("en-US,en;q=0.9")), Locale::En);
+        assert_eq!(
+            negotiate_locale(Some("fr-FR, en;q=0.5, zh-CN;q=0.8")),
+            Locale::Zh
+        );
+        assert_eq!(
+            negotiate_locale(Some("zh;q=0, de, en-GB;q=0.3")),
+            Locale::En
+        );
+    }
+
+    #[test]
+    fn validates_page_options_and_user_templates() {
+        assert_eq!(normalize_accent(" #22C55E ").unwrap(), "#22c55e");
+        assert!(normalize_accent("red; background: url(x)").is_err());
+
+        let dir = std::env::temp_dir().join(format!("chef_templates_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("plain.html"), "<h1>{{ title }}</h1>").unwrap();
+        std::fs::write(dir.join("broken.html"), "{% for %}").unwrap();
+        assert_eq!(list_templates(&dir), vec!["broken.html", "plain.html"]);
+
+        let options = |template: &str| FileSharePageOptions {
+            title: Some("<b>团队周报</b>".into()),
+            template: Some(template.into()),
+            ..Default::default()
+        };
+        let page = SharePage::new(&options("plain"), Some(&dir)).unwrap();
+        let html = page
+            .env
+            .get_template(TEMPLATE_NAME)
+            .unwrap()
+            .render(minijinja::context! { title => page.title })
+            .unwrap();
+        assert_eq!(html, "<h1>&lt;b&gt;团队周报&lt;&#x2f;b&gt;</h1>");
+        assert!(SharePage::new(&options("broken"), Some(&dir)).is_err());
+        assert!(SharePage::new(&options("../plain"), Some(&dir)).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    collections::HashMap,
+    io::{self, Cursor},
+    path::Path,
+    sync::Arc,
+};
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Path as AxumPath, Request, State as AxumState},
+    http::{header, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use image::{imageops::FilterType, ImageFormat};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use syntect::{highlighting::ThemeSet, html::highlighted_html_for_string, parsing::SyntaxSet};
+use tauri::async_runtime;
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+use super::{
+    escape_html, format_size,
+    throttle::{self, DownloadPermit},
+    HttpState, ServerFile,
+};
+
+const THUMBNAIL_SIZE: u32 = 320;
+const MAX_THUMBNAIL_SOURCE_BYTES: u64 = 64 * 1024 * 1024;
+const MAX_HIGHLIGHT_BYTES: u64 = 1024 * 1024;
+const MAX_CACHED_THUMBNAILS: usize = 512;
+
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+pub(super) type ThumbnailCache = Arc<Mutex<ThumbnailLru>>;
+
+/// 缩略图缓存，满了之后淘汰最久未被访问的一项。
+#[derive(Default)]
+pub(super) struct ThumbnailLru {
+    entries: HashMap<String, (Bytes, u64)>,
+    tick: u64,
+}
+
+impl ThumbnailLru {
+    fn get(&mut self, key: &str) -> Option<Bytes> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(bytes, used)| {
+            *used = tick;
+            bytes.clone()
+        })
+    }
+
+    fn insert(&mut self, key: String, bytes: Bytes) {
+        if self.entries.len() >= MAX_CACHED_THUMBNAILS && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, (bytes, self.tick));
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewKind {
+    Image,
+    Video,
+    Audio,
+    Pdf,
+    Text,
+}
+
+pub(super) fn preview_kind(path: &Path, mime: &str) -> Option<PreviewKind> {
+    match mime.split('/').next().unwrap_or_default() {
+        "image" => return Some(PreviewKind::Image),
+        "video" => return Some(PreviewKind::Video),
+        "audio" => return Some(PreviewKind::Audio),
+        "text" => return Some(PreviewKind::Text),
+        _ => {}
+    }
+    if mime == "application/pdf" {
+        return Some(PreviewKind::Pdf);
+    }
+    if matches!(
+        mime,
+        "application/json" | "application/xml" | "application/javascript" | "application/toml"
+    ) {
+        return Some(PreviewKind::Text);
+    }
+    let extension = path.extension().and_then(|ext| ext.to_str())?;
+    SYNTAX_SET
+        .find_syntax_by_extension(extension)
+        .map(|_| PreviewKind::Text)
+}
+
+/// 预览内容来自分享者的文件，一律禁止浏览器嗅探类型并放进沙箱，避免 HTML/SVG 在本站执行脚本。
+pub(super) async fn serve_preview(
+    AxumPath(file_id): AxumPath<String>,
+    AxumState(state): AxumState<HttpState>,
+    request: Request,
+) -> Response {
+    let mut response = preview_response(&state, &file_id, request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("sandbox allow-downloads"),
+    );
+    response
+}
+
+async fn preview_response(state: &HttpState, file_id: &str, request: Request) -> Response {
+    let Some(file) = find_file(state, file_id).await else {
+        return (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response();
+    };
+    let mime = file.mime.first_or_octet_stream();
+    let kind = preview_kind(&file.path, mime.essence_str());
+    if kind.is_none() {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "该文件类型暂不支持预览。",
+        )
+            .into_response();
+    }
+
+    let permit = state.transfers.acquire().await;
+    let response = if kind == Some(PreviewKind::Text) {
+        render_text_preview(file).await
+    } else {
+        let mut response = match ServeFile::new_with_mime(&file.path, &mime)
+            .oneshot(request)
+            .await
+        {
+            Ok(response) => response.map(Body::new),
+            Err(_) => {
+                return (StatusCode::NOT_FOUND, "文件不再可用，请在桌面端重新选择。")
+                    .into_response()
+            }
+        };
+        let disposition = if is_active_mime(mime.essence_str()) {
+            "attachment"
+        } else {
+            "inline"
+        };
+        if let Ok(value) = HeaderValue::from_str(&format!(
+            "{disposition}; filename=\"{}\"",
+            super::sanitize_filename(&file.download_name)
+        )) {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_DISPOSITION, value);
+        }
+        response
+    };
+    throttle_response(response, permit)
+}
+
+/// 浏览器直接打开时可能执行脚本的类型，只允许以附件形式下载。
+fn is_active_mime(mime: &str) -> bool {
+    matches!(
+        mime,
+        "image/svg+xml" | "text/html" | "application/xhtml+xml" | "text/xml" | "application/xml"
+    )
+}
+
+/// 与 `/files/:id` 一样占用下载名额并限速输出响应体。
+fn throttle_response(response: Response, permit: DownloadPermit) -> Response {
+    let len = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let (parts, body) = response.into_parts();
+    let len = len.or(body.size_hint().exact()).unwrap_or(u64::MAX);
+    let stream = body
+        .into_data_stream()
+        .map(|chunk| chunk.map_err(io::Error::other));
+    Response::from_parts(
+        parts,
+        Body::from_stream(throttle::throttle_stream(stream, permit, len)),
+    )
+}
+
+pub(super) async fn serve_thumbnail(
+    AxumPath(file_id): AxumPath<String>,
+    AxumState(state): AxumState<HttpState>,
+) -> Response {
+    let Some(file) = find_file(&state, &file_id).await else {
+        return (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response();
+    };
+    if preview_kind(&file.path, file.mime.first_or_octet_stream().essence_str())
+        != Some(PreviewKind::Image)
+        || file.size > MAX_THUMBNAIL_SOURCE_BYTES
+    {
+        return (StatusCode::UNSUPPORTED_MEDIA_TYPE, "该文件无法生成缩略图。").into_response();
+    }
+
+    let cache_key = format!("{}:{}:{}", file.id, file.size, file.modified);
+    if let Some(cached) = state.thumbnails.lock().await.get(&cache_key) {
+        return thumbnail_response(cached);
+    }
+
+    let path = file.path.clone();
+    let rendered = async_runtime::spawn_blocking(move || render_thumbnail(&path))
+        .await
+        .map_err(|err| format!("生成缩略图失败: {err}"))
+        .and_then(|result| result);
+    match rendered {
+        Ok(bytes) => {
+            let bytes = Bytes::from(bytes);
+            state
+                .thumbnails
+                .lock()
+                .await
+                .insert(cache_key, bytes.clone());
+            thumbnail_response(bytes)
+        }
+        Err(err) => (StatusCode::UNPROCESSABLE_ENTITY, err).into_response(),
+    }
+}
+
+async fn find_file(state: &HttpState, file_id: &str) -> Option<ServerFile> {
+    state
+        .files
+        .read()
+        .await
+        .iter()
+        .find(|file| file.id == file_id)
+        .cloned()
+}
+
+fn render_thumbnail(path: &Path) -> Result<Vec<u8>, String> {
+    let image = image::ImageReader::open(path)
+        .map_err(|err| format!("无法读取图片: {err}"))?
+        .with_guessed_format()
+        .map_err(|err| format!("无法识别图片格式: {err}"))?
+        .decode()
+        .map_err(|err| format!("无法解码图片: {err}"))?;
+    let thumbnail = image
+        .resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle)
+        .to_rgb8();
+    let mut buffer = Cursor::new(Vec::new());
+    thumbnail
+        .write_to(&mut buffer, ImageFormat::Jpeg)
+        .map_err(|err| format!("编码缩略图失败: {err}"))?;
+    Ok(buffer.into_inner())
+}
+
+fn thumbnail_response(bytes: Bytes) -> Response {
+    let mut response = Response::new(Body::from(bytes));
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static("image/jpeg"));
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, max-age=3600"),
+    );
+    response
+}
+
+async fn render_text_preview(file: ServerFile) -> Response {
+    if file.size > MAX_HIGHLIGHT_BYTES {
+        return (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "文件大小 {}，超过 {} 的预览上限，请直接下载。",
+                format_size(file.size),
+                format_size(MAX_HIGHLIGHT_BYTES)
+            ),
+        )
+            .into_response();
+    }
+    let rendered = async_runtime::spawn_blocking(move || highlight_file(&file))
+        .await
+        .map_err(|err| format!("渲染预览失败: {err}"))
+        .and_then(|result| result);
+    match rendered {
+        Ok(html) => Html(html).into_response(),
+        Err(err) => (StatusCode::UNPROCESSABLE_ENTITY, err).into_response(),
+    }
+}
+
+fn highlight_file(file: &ServerFile) -> Result<String, String> {
+    let bytes = std::fs::read(&file.path).map_err(|err| format!("无法读取文件: {err}"))?;
+    let text = String::from_utf8_lossy(&bytes);
+    let syntax = file
+        .path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| SYNTAX_SET.find_syntax_by_extension(ext))
+        .or_else(|| SYNTAX_SET.find_syntax_by_first_line(&text))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
+    let theme = &THEME_SET.themes["InspiredGitHub"];
+    let body = highlighted_html_for_string(&text, &SYNTAX_SET, syntax, theme)
+        .map_err(|err| format!("语法高亮失败: {err}"))?;
+    Ok(format!(
+        r#"<!doctype html><html lang="zh-CN"><head><meta charset="utf-8" /><meta name="viewport" content="width=device-width, initial-scale=1" /><title>{title}</title><style>body{{margin:0;font-family:"SF Mono",Menlo,Consolas,monospace;font-size:13px;background:#fff}}header{{position:sticky;top:0;padding:12px 20px;background:#f6f6f8;border-bottom:1px solid rgba(0,0,0,.08);font-family:-apple-system,"Segoe UI",sans-serif;display:flex;justify-content:space-between;gap:12px}}header a{{color:#111;font-weight:600}}pre{{margin:0;padding:16px 20px;overflow:auto}}</style></head><body><header><span>{title} · {syntax}</span><a href="/files/{id}" download>下载</a></header>{body}</body></html>"#,
+        title = escape_html(&file.display_name),
+        syntax = escape_html(&syntax.name),
+        id = file.id,
+        body = body
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_preview_kinds() {
+        let kind = |name: &str| {
+            let path = Path::new(name);
+            preview_kind(
+                path,
+                mime_guess::from_path(path)
+                    .first_or_octet_stream()
+                    .essence_str(),
+            )
+        };
+        assert_eq!(kind("photo.JPG"), Some(PreviewKind::Image));
+        assert_eq!(kind("clip.mp4"), Some(PreviewKind::Video));
+        assert_eq!(kind("song.mp3"), Some(PreviewKind::Audio));
+        assert_eq!(kind("manual.pdf"), Some(PreviewKind::Pdf));
+        assert_eq!(kind("main.rs"), Some(PreviewKind::Text));
+        assert_eq!(kind("config.json"), Some(PreviewKind::Text));
+        assert_eq!(kind("archive.zip"), None);
+        assert!(is_active_mime("image/svg+xml"));
+        assert!(!is_active_mime("image/png"));
+    }
+
+    #[test]
+    fn evicts_least_recently_used_thumbnails() {
+        let mut cache = ThumbnailLru::default();
+        for index in 0..MAX_CACHED_THUMBNAILS {
+            cache.insert(index.to_string(), Bytes::from_static(b"jpg"));
+        }
+        assert!(cache.get("0").is_some());
+        cache.insert("new".into(), Bytes::from_static(b"jpg"));
+        assert_eq!(cache.entries.len(), MAX_CACHED_THUMBNAILS);
+        assert!(cache.get("0").is_some());
+        assert!(cache.get("1").is_none());
+        assert!(cache.get("new").is_some());
+    }
+}
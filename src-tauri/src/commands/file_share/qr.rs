@@ -0,0 +1,109 @@
+use std::io::Cursor;
+
+use axum::{
+    extract::State as AxumState,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{DynamicImage, ImageFormat, Luma};
+use qrcode::{render::svg, QrCode};
+use serde::Serialize;
+
+use super::{manifest::request_base_url, HttpState};
+
+const QR_MIN_SIZE: u32 = 240;
+
+#[derive(Clone, Serialize)]
+pub struct ShareQrCode {
+    pub url: String,
+    pub svg: String,
+    pub png_data_url: String,
+}
+
+pub(super) fn render_qr_code(url: &str) -> Result<ShareQrCode, String> {
+    let code = QrCode::new(url.as_bytes()).map_err(|err| format!("生成二维码失败: {err}"))?;
+    let png = encode_png(&code)?;
+    Ok(ShareQrCode {
+        url: url.to_string(),
+        svg: encode_svg(&code),
+        png_data_url: format!("data:image/png;base64,{}", STANDARD.encode(png)),
+    })
+}
+
+pub(super) async fn serve_qr_svg(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Response {
+    let url = lan_base_url(&state, &headers);
+    match QrCode::new(url.as_bytes()) {
+        Ok(code) => image_response(encode_svg(&code).into_bytes(), "image/svg+xml"),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+    }
+}
+
+pub(super) async fn serve_qr_png(
+    AxumState(state): AxumState<HttpState>,
+    headers: HeaderMap,
+) -> Response {
+    let url = lan_base_url(&state, &headers);
+    let png = QrCode::new(url.as_bytes())
+        .map_err(|err| err.to_string())
+        .and_then(|code| encode_png(&code));
+    match png {
+        Ok(png) => image_response(png, "image/png"),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err).into_response(),
+    }
+}
+
+/// 通过 localhost 打开页面时，二维码改用局域网地址，否则手机扫码无法访问。
+fn lan_base_url(state: &HttpState, headers: &HeaderMap) -> String {
+    let url = request_base_url(state, headers);
+    if !is_loopback_url(&url) {
+        return url;
+    }
+    state
+        .known_urls
+        .iter()
+        .find(|url| !is_loopback_url(url))
+        .unwrap_or(&url)
+        .clone()
+}
+
+pub(super) fn is_loopback_url(url: &str) -> bool {
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    ["localhost", "127.", "[::1]"]
+        .iter()
+        .any(|prefix| authority.starts_with(prefix))
+}
+
+fn encode_svg(code: &QrCode) -> String {
+    code.render::<svg::Color>()
+        .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+        .quiet_zone(true)
+        .build()
+}
+
+fn encode_png(code: &QrCode) -> Result<Vec<u8>, String> {
+    let image = code
+        .render::<Luma<u8>>()
+        .min_dimensions(QR_MIN_SIZE, QR_MIN_SIZE)
+        .quiet_zone(true)
+        .build();
+    let mut buffer = Cursor::new(Vec::new());
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut buffer, ImageFormat::Png)
+        .map_err(|err| format!("编码二维码图片失败: {err}"))?;
+    Ok(buffer.into_inner())
+}
+
+fn image_response(body: Vec<u8>, content_type: &'static str) -> Response {
+    let mut response = body.into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
@@ -0,0 +1,126 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{ConnectInfo, State as AxumState},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::HttpState;
+use crate::utils::current_timestamp_millis;
+
+pub(super) const TEXT_RECEIVED_EVENT: &str = "file-share-text-received";
+const MAX_TEXT_BYTES: usize = 64 * 1024;
+const MAX_TEXT_ITEMS: usize = 200;
+
+pub(super) type SharedTexts = Arc<RwLock<Vec<SharedTextItem>>>;
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TextOrigin {
+    Desktop,
+    Remote,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SharedTextItem {
+    pub id: String,
+    pub content: String,
+    pub label: Option<String>,
+    pub origin: TextOrigin,
+    pub remote_addr: Option<String>,
+    pub created_at: u64,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ShareTextEvent {
+    pub session_id: String,
+    pub item: SharedTextItem,
+}
+
+#[derive(Deserialize)]
+pub(super) struct PostTextPayload {
+    content: String,
+    label: Option<String>,
+}
+
+pub(super) fn new_text_item(
+    content: String,
+    label: Option<String>,
+    origin: TextOrigin,
+    remote_addr: Option<String>,
+) -> Result<SharedTextItem, String> {
+    if content.trim().is_empty() {
+        return Err("分享的文本不能为空。".into());
+    }
+    if content.len() > MAX_TEXT_BYTES {
+        return Err(format!("文本过长，最多支持 {} KB。", MAX_TEXT_BYTES / 1024));
+    }
+    Ok(SharedTextItem {
+        id: Uuid::new_v4().to_string(),
+        content,
+        label: label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty()),
+        origin,
+        remote_addr,
+        created_at: current_timestamp_millis() as u64,
+    })
+}
+
+pub(super) async fn push_text(texts: &SharedTexts, item: SharedTextItem) {
+    let mut guard = texts.write().await;
+    guard.push(item);
+    if guard.len() > MAX_TEXT_ITEMS {
+        let overflow = guard.len() - MAX_TEXT_ITEMS;
+        guard.drain(..overflow);
+    }
+}
+
+pub(super) fn read_clipboard_text() -> Result<String, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|err| format!("无法访问剪贴板: {err}"))?;
+    clipboard
+        .get_text()
+        .map_err(|err| format!("剪贴板中没有可分享的文本: {err}"))
+}
+
+pub(super) async fn list_texts(
+    AxumState(state): AxumState<HttpState>,
+) -> Json<Vec<SharedTextItem>> {
+    Json(state.texts.read().await.clone())
+}
+
+pub(super) async fn receive_text(
+    AxumState(state): AxumState<HttpState>,
+    ConnectInfo(remote): ConnectInfo<SocketAddr>,
+    Json(payload): Json<PostTextPayload>,
+) -> Response {
+    let item = match new_text_item(
+        payload.content,
+        payload.label,
+        TextOrigin::Remote,
+        Some(remote.ip().to_string()),
+    ) {
+        Ok(item) => item,
+        Err(err) => return (StatusCode::BAD_REQUEST, err).into_response(),
+    };
+    push_text(&state.texts, item.clone()).await;
+
+    if let Some(app) = state.app.as_ref() {
+        let event = ShareTextEvent {
+            session_id: state.session_id.clone(),
+            item: item.clone(),
+        };
+        if let Err(err) = app.emit(TEXT_RECEIVED_EVENT, event) {
+            eprintln!("推送文本事件失败: {err}");
+        }
+    }
+
+    (StatusCode::CREATED, Json(item)).into_response()
+}
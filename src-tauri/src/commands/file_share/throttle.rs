@@ -0,0 +1,302 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex as StdMutex,
+    },
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+/// 单个分享会话的限速配置；`global_bps` 指该会话所有连接合计的上限。
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileShareLimits {
+    pub per_connection_bps: Option<u64>,
+    pub global_bps: Option<u64>,
+    pub max_concurrent_downloads: Option<usize>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TransferStats {
+    pub active_downloads: usize,
+    pub queued_downloads: usize,
+    pub completed_downloads: u64,
+    pub total_bytes_sent: u64,
+    pub current_bps: u64,
+    pub limits: FileShareLimits,
+}
+
+pub(super) struct TransferControl {
+    limits: StdMutex<FileShareLimits>,
+    global_bucket: StdMutex<TokenBucket>,
+    active: AtomicUsize,
+    queued: AtomicUsize,
+    completed: AtomicU64,
+    total_sent: AtomicU64,
+    meter: StdMutex<ThroughputMeter>,
+    slot_released: Notify,
+}
+
+pub(super) struct DownloadPermit {
+    control: Arc<TransferControl>,
+    /// 只有响应体完整发送后才计入已完成，中途断开或读取失败的不算。
+    finished: bool,
+}
+
+/// 排队期间持有；等待中的下载被取消时同样会把排队计数减回去。
+struct QueueGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct TokenBucket {
+    allowance: f64,
+    last: Instant,
+}
+
+struct ThroughputMeter {
+    window_start: Instant,
+    window_bytes: u64,
+    last_rate: u64,
+}
+
+const METER_WINDOW: Duration = Duration::from_secs(1);
+
+impl TransferControl {
+    pub fn new(limits: FileShareLimits) -> Arc<Self> {
+        Arc::new(Self {
+            limits: StdMutex::new(limits),
+            global_bucket: StdMutex::new(TokenBucket::new()),
+            active: AtomicUsize::new(0),
+            queued: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            total_sent: AtomicU64::new(0),
+            meter: StdMutex::new(ThroughputMeter {
+                window_start: Instant::now(),
+                window_bytes: 0,
+                last_rate: 0,
+            }),
+            slot_released: Notify::new(),
+        })
+    }
+
+    pub fn limits(&self) -> FileShareLimits {
+        *self.limits.lock().unwrap()
+    }
+
+    pub fn update_limits(&self, limits: FileShareLimits) {
+        *self.limits.lock().unwrap() = limits;
+        self.slot_released.notify_waiters();
+    }
+
+    /// 等待一个下载名额；超过并发上限时排队，直到有下载结束。
+    pub async fn acquire(self: &Arc<Self>) -> DownloadPermit {
+        let mut queued: Option<QueueGuard> = None;
+        loop {
+            let notified = self.slot_released.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let max = self.limits().max_concurrent_downloads.unwrap_or(usize::MAX);
+            let claimed = self
+                .active
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                    (active < max.max(1)).then_some(active + 1)
+                })
+                .is_ok();
+            if claimed {
+                return DownloadPermit {
+                    control: Arc::clone(self),
+                    finished: false,
+                };
+            }
+            if queued.is_none() {
+                self.queued.fetch_add(1, Ordering::SeqCst);
+                queued = Some(QueueGuard(&self.queued));
+            }
+            notified.await;
+        }
+    }
+
+    pub fn stats(&self) -> TransferStats {
+        let current_bps = {
+            let meter = self.meter.lock().unwrap();
+            let elapsed = meter.window_start.elapsed();
+            if elapsed > METER_WINDOW * 2 {
+                0
+            } else {
+                meter.last_rate
+            }
+        };
+        TransferStats {
+            active_downloads: self.active.load(Ordering::SeqCst),
+            queued_downloads: self.queued.load(Ordering::SeqCst),
+            completed_downloads: self.completed.load(Ordering::SeqCst),
+            total_bytes_sent: self.total_sent.load(Ordering::SeqCst),
+            current_bps,
+            limits: self.limits(),
+        }
+    }
+
+    fn record_sent(&self, bytes: u64) {
+        self.total_sent.fetch_add(bytes, Ordering::SeqCst);
+        let mut meter = self.meter.lock().unwrap();
+        meter.window_bytes += bytes;
+        let elapsed = meter.window_start.elapsed();
+        if elapsed >= METER_WINDOW {
+            meter.last_rate = (meter.window_bytes as f64 / elapsed.as_secs_f64()) as u64;
+            meter.window_bytes = 0;
+            meter.window_start = Instant::now();
+        }
+    }
+
+    fn global_delay(&self, bytes: usize) -> Duration {
+        match self.limits().global_bps {
+            Some(rate) if rate > 0 => self.global_bucket.lock().unwrap().reserve(rate, bytes),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+impl Drop for DownloadPermit {
+    fn drop(&mut self) {
+        self.control.active.fetch_sub(1, Ordering::SeqCst);
+        if self.finished {
+            self.control.completed.fetch_add(1, Ordering::SeqCst);
+        }
+        self.control.slot_released.notify_waiters();
+    }
+}
+
+impl TokenBucket {
+    fn new() -> Self {
+        Self {
+            allowance: 0.0,
+            last: Instant::now(),
+        }
+    }
+
+    /// 预留 `bytes` 个字节的额度，返回需要等待的时长。允许最多一秒的突发。
+    fn reserve(&mut self, rate: u64, bytes: usize) -> Duration {
+        let rate = rate as f64;
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.allowance = (self.allowance + elapsed * rate).min(rate);
+        self.allowance -= bytes as f64;
+        if self.allowance >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.allowance / rate)
+        }
+    }
+}
+
+/// 在原始文件流外包一层限速与统计，下载结束（或连接断开）时释放名额。
+/// `len` 是响应体的总长度，发满后才算完成一次下载。
+pub(super) fn throttle_stream<S>(
+    inner: S,
+    permit: DownloadPermit,
+    len: u64,
+) -> impl Stream<Item = std::io::Result<Bytes>> + Send
+where
+    S: Stream<Item = std::io::Result<Bytes>> + Send + Unpin + 'static,
+{
+    let connection_bucket = TokenBucket::new();
+    stream::unfold(
+        (inner, permit, connection_bucket, 0u64),
+        move |(mut inner, mut permit, mut bucket, mut sent)| async move {
+            let chunk = inner.next().await?;
+            if let Ok(bytes) = &chunk {
+                let control = &permit.control;
+                let connection_delay = match control.limits().per_connection_bps {
+                    Some(rate) if rate > 0 => bucket.reserve(rate, bytes.len()),
+                    _ => Duration::ZERO,
+                };
+                let delay = connection_delay.max(control.global_delay(bytes.len()));
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                control.record_sent(bytes.len() as u64);
+                sent += bytes.len() as u64;
+                // 带 Content-Length 的响应发满后 hyper 不会再轮询到流结束，这里直接记完成。
+                permit.finished = sent >= len;
+            }
+            Some((chunk, (inner, permit, bucket, sent)))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn queues_downloads_beyond_concurrency_limit() {
+        let control = TransferControl::new(FileShareLimits {
+            max_concurrent_downloads: Some(1),
+            ..Default::default()
+        });
+        let first = control.acquire().await;
+
+        let waiter = {
+            let control = Arc::clone(&control);
+            tokio::spawn(async move { control.acquire().await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let stats = control.stats();
+        assert_eq!(stats.active_downloads, 1);
+        assert_eq!(stats.queued_downloads, 1);
+
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("queued download should start")
+            .unwrap();
+        let stats = control.stats();
+        assert_eq!(stats.active_downloads, 1);
+        assert_eq!(stats.queued_downloads, 0);
+        // 没有发送任何内容就释放的名额不算完成。
+        assert_eq!(stats.completed_downloads, 0);
+
+        let cancelled = {
+            let control = Arc::clone(&control);
+            tokio::spawn(async move { control.acquire().await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(control.stats().queued_downloads, 1);
+        cancelled.abort();
+        let _ = cancelled.await;
+        assert_eq!(control.stats().queued_downloads, 0);
+        drop(second);
+    }
+
+    #[tokio::test]
+    async fn throttles_stream_to_configured_rate() {
+        let control = TransferControl::new(FileShareLimits {
+            per_connection_bps: Some(64 * 1024),
+            ..Default::default()
+        });
+        let permit = control.acquire().await;
+        let chunks = (0..8)
+            .map(|_| Ok(Bytes::from(vec![0u8; 16 * 1024])))
+            .collect::<Vec<_>>();
+        let started = Instant::now();
+        let received = throttle_stream(stream::iter(chunks), permit, 128 * 1024)
+            .map(|chunk| chunk.map(|bytes| bytes.len()).unwrap_or(0))
+            .fold(0, |total, len| async move { total + len })
+            .await;
+        assert_eq!(received, 128 * 1024);
+        assert!(started.elapsed() >= Duration::from_millis(1800));
+        let stats = control.stats();
+        assert_eq!(stats.total_bytes_sent, 128 * 1024);
+        assert_eq!(stats.completed_downloads, 1);
+    }
+}
@@ -0,0 +1,323 @@
+use std::{fs, io::BufReader, sync::Arc};
+
+use axum_server::tls_rustls::RustlsConfig;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rcgen::{CertificateParams, DistinguishedName, DnType, KeyPair};
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, WebPkiSupportedAlgorithms},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, DigitallySignedStruct, ServerConfig, SignatureScheme,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use x509_parser::parse_x509_certificate;
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileShareTlsOptions {
+    #[serde(default)]
+    pub enabled: bool,
+    pub cert_path: Option<String>,
+    pub key_path: Option<String>,
+}
+
+pub(super) struct TlsMaterial {
+    pub config: RustlsConfig,
+    pub fingerprint: String,
+    /// curl `--pinnedpubkey` 使用的公钥指纹，形如 `sha256//<base64>`。
+    pub public_key_pin: String,
+    pub self_signed: bool,
+}
+
+pub(super) fn prepare_tls(
+    options: &FileShareTlsOptions,
+    hosts: &[String],
+) -> Result<TlsMaterial, String> {
+    let (certs, key, self_signed) = match (&options.cert_path, &options.key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            let (certs, key) = load_pem_pair(cert_path, key_path)?;
+            (certs, key, false)
+        }
+        (None, None) => {
+            let (certs, key) = generate_self_signed(hosts)?;
+            (certs, key, true)
+        }
+        _ => return Err("使用自定义证书时需要同时提供证书和私钥文件。".into()),
+    };
+
+    let leaf = certs
+        .first()
+        .ok_or_else(|| "证书文件中没有找到证书。".to_string())?;
+    let fingerprint = fingerprint_sha256(leaf.as_ref());
+    let public_key_pin = public_key_pin(leaf.as_ref())?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("初始化 TLS 失败: {err}"))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| format!("证书与私钥不匹配: {err}"))?;
+
+    Ok(TlsMaterial {
+        config: RustlsConfig::from_config(Arc::new(config)),
+        fingerprint,
+        public_key_pin,
+        self_signed,
+    })
+}
+
+/// 只信任指纹与 mDNS 广播一致的证书，用于访问局域网内其他设备（通常是自签名）的分享。
+pub(super) fn pinned_client_config(fingerprint: &str) -> Result<ClientConfig, String> {
+    let provider = Arc::new(ring::default_provider());
+    let verifier = Arc::new(PinnedCertVerifier {
+        fingerprint: fingerprint.trim().to_ascii_uppercase(),
+        algorithms: provider.signature_verification_algorithms,
+    });
+    Ok(ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("初始化 TLS 失败: {err}"))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier)
+        .with_no_client_auth())
+}
+
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: String,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        // 自签名证书没有可信的签发者，主机名也常常对不上，只认指纹
+        if fingerprint_sha256(end_entity.as_ref()) == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+fn generate_self_signed(
+    hosts: &[String],
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), String> {
+    let mut names = vec!["localhost".to_string()];
+    names.extend(hosts.iter().cloned());
+    names.sort();
+    names.dedup();
+
+    let mut params =
+        CertificateParams::new(names).map_err(|err| format!("生成证书参数失败: {err}"))?;
+    let mut subject = DistinguishedName::new();
+    subject.push(DnType::CommonName, "Chef LAN Share");
+    subject.push(DnType::OrganizationName, "Chef");
+    params.distinguished_name = subject;
+
+    let key_pair = KeyPair::generate().map_err(|err| format!("生成私钥失败: {err}"))?;
+    let cert = params
+        .self_signed(&key_pair)
+        .map_err(|err| format!("生成自签名证书失败: {err}"))?;
+    let key = PrivateKeyDer::try_from(key_pair.serialize_der())
+        .map_err(|err| format!("解析私钥失败: {err}"))?;
+    Ok((vec![cert.der().clone()], key))
+}
+
+fn load_pem_pair(
+    cert_path: &str,
+    key_path: &str,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), String> {
+    let cert_file =
+        fs::File::open(cert_path).map_err(|err| format!("无法读取证书 {cert_path}: {err}"))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("解析证书 {cert_path} 失败: {err}"))?;
+    if certs.is_empty() {
+        return Err(format!("证书文件 {cert_path} 中没有找到证书。"));
+    }
+
+    let key_file =
+        fs::File::open(key_path).map_err(|err| format!("无法读取私钥 {key_path}: {err}"))?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .map_err(|err| format!("解析私钥 {key_path} 失败: {err}"))?
+        .ok_or_else(|| format!("私钥文件 {key_path} 中没有找到私钥。"))?;
+
+    Ok((certs, key))
+}
+
+fn public_key_pin(der: &[u8]) -> Result<String, String> {
+    let (_, cert) =
+        parse_x509_certificate(der).map_err(|err| format!("解析证书公钥失败: {err}"))?;
+    Ok(format!(
+        "sha256//{}",
+        STANDARD.encode(Sha256::digest(cert.public_key().raw))
+    ))
+}
+
+fn fingerprint_sha256(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use x509_parser::extensions::GeneralName;
+
+    use super::*;
+
+    /// 用 rcgen 生成一对 PEM 写到临时目录，返回 (目录, 证书路径, 私钥路径, 证书 DER)。
+    fn write_pem_pair(names: &[&str]) -> (PathBuf, String, String, Vec<u8>) {
+        let dir = std::env::temp_dir().join(format!("chef_tls_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let key_pair = KeyPair::generate().unwrap();
+        let names = names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        let cert = CertificateParams::new(names)
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        fs::write(&cert_path, cert.pem()).unwrap();
+        fs::write(&key_path, key_pair.serialize_pem()).unwrap();
+        (
+            dir,
+            cert_path.display().to_string(),
+            key_path.display().to_string(),
+            cert.der().to_vec(),
+        )
+    }
+
+    #[test]
+    fn generates_certificate_for_local_hosts() {
+        let (certs, _) =
+            generate_self_signed(&["192.168.1.20".into(), "chef.local".into()]).unwrap();
+        let (_, cert) = parse_x509_certificate(certs[0].as_ref()).unwrap();
+        let names = cert
+            .subject_alternative_name()
+            .unwrap()
+            .unwrap()
+            .value
+            .general_names
+            .iter()
+            .map(|name| match name {
+                GeneralName::DNSName(name) => name.to_string(),
+                GeneralName::IPAddress(bytes) => <[u8; 4]>::try_from(*bytes)
+                    .map(|octets| std::net::IpAddr::from(octets).to_string())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            })
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"localhost".to_string()));
+        assert!(names.contains(&"chef.local".to_string()));
+        assert!(names.contains(&"192.168.1.20".to_string()));
+
+        let fingerprint = fingerprint_sha256(certs[0].as_ref());
+        assert_eq!(fingerprint.len(), 32 * 3 - 1);
+        assert!(fingerprint
+            .split(':')
+            .all(|byte| byte.len() == 2 && byte.chars().all(|ch| ch.is_ascii_hexdigit())));
+
+        let options = FileShareTlsOptions {
+            enabled: true,
+            ..Default::default()
+        };
+        let material = prepare_tls(&options, &["127.0.0.1".into()]).unwrap();
+        assert!(material.self_signed);
+        assert!(material.public_key_pin.starts_with("sha256//"));
+    }
+
+    #[test]
+    fn loads_user_supplied_pem_pair() {
+        let (dir, cert_path, key_path, der) = write_pem_pair(&["127.0.0.1"]);
+        let material = prepare_tls(
+            &FileShareTlsOptions {
+                enabled: true,
+                cert_path: Some(cert_path.clone()),
+                key_path: Some(key_path),
+            },
+            &[],
+        )
+        .unwrap();
+        assert!(!material.self_signed);
+        let expected = Sha256::digest(&der)
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(":");
+        assert_eq!(material.fingerprint, expected);
+
+        // 证书和另一把私钥不匹配
+        let (other_dir, _, other_key, _) = write_pem_pair(&["127.0.0.1"]);
+        let mismatch = |key_path: &str| {
+            prepare_tls(
+                &FileShareTlsOptions {
+                    enabled: true,
+                    cert_path: Some(cert_path.clone()),
+                    key_path: Some(key_path.to_string()),
+                },
+                &[],
+            )
+        };
+        let Err(err) = mismatch(&other_key) else {
+            panic!("mismatched key should be rejected");
+        };
+        assert!(err.contains("不匹配"), "{err}");
+        let Err(err) = mismatch(&dir.join("missing.pem").display().to_string()) else {
+            panic!("missing key file should be rejected");
+        };
+        assert!(err.contains("无法读取私钥"), "{err}");
+        let Err(err) = prepare_tls(
+            &FileShareTlsOptions {
+                enabled: true,
+                cert_path: Some(cert_path),
+                key_path: None,
+            },
+            &[],
+        ) else {
+            panic!("a lone certificate should be rejected");
+        };
+        assert!(err.contains("同时提供"), "{err}");
+
+        fs::remove_dir_all(dir).unwrap();
+        fs::remove_dir_all(other_dir).unwrap();
+    }
+}
@@ -0,0 +1,759 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex as StdMutex},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use axum::{
+    body::Body,
+    extract::{Request, State as AxumState},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use futures_util::StreamExt;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::{fs, io::AsyncWriteExt};
+use uuid::Uuid;
+
+use super::{
+    escape_html, folder_label, push_file_entry, stable_file_id, stream_file, system_time_millis,
+    HttpState, ServerFile,
+};
+
+pub(super) const DAV_PREFIX: &str = "/dav";
+const READ_METHODS: &str = "OPTIONS, GET, HEAD, PROPFIND";
+const WRITE_METHODS: &str = "OPTIONS, GET, HEAD, PROPFIND, PUT, MKCOL, DELETE";
+const HREF_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}')
+    .add(b'/');
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileShareWebDavOptions {
+    pub enabled: bool,
+    pub writable: bool,
+}
+
+/// 会话的 WebDAV 配置；可写时只允许修改所分享文件夹内部的内容，
+/// 且写操作需要以 `write_token` 作为 Basic 认证密码。
+#[derive(Clone)]
+pub(super) struct DavShare {
+    pub writable: bool,
+    pub write_token: Option<String>,
+    folders: Arc<StdMutex<Vec<DavFolder>>>,
+}
+
+#[derive(Clone)]
+struct DavFolder {
+    label: String,
+    path: PathBuf,
+}
+
+enum DavResource {
+    Collection { path: String, modified: u64 },
+    File { path: String, file: ServerFile },
+}
+
+impl DavShare {
+    pub fn new(options: &FileShareWebDavOptions, entries: &[String]) -> Self {
+        let share = Self {
+            writable: options.writable,
+            write_token: options
+                .writable
+                .then(|| Uuid::new_v4().simple().to_string()),
+            folders: Arc::new(StdMutex::new(Vec::new())),
+        };
+        share.add_entries(entries);
+        share
+    }
+
+    pub fn add_entries(&self, entries: &[String]) {
+        let mut folders = self.folders.lock().unwrap();
+        for entry in entries {
+            let Ok(canonical) = Path::new(entry).canonicalize() else {
+                continue;
+            };
+            if !canonical.is_dir() || folders.iter().any(|folder| folder.path == canonical) {
+                continue;
+            }
+            let label = unique_label(&folders, folder_label(&canonical));
+            folders.push(DavFolder {
+                label,
+                path: canonical,
+            });
+        }
+    }
+
+    /// 把 `文件夹名/相对路径` 映射到磁盘上的真实路径；经符号链接等逃出分享文件夹的路径返回 `None`。
+    fn real_path(&self, path: &str) -> Option<(DavFolder, String, PathBuf)> {
+        let (label, relative) = path.split_once('/').unwrap_or((path, ""));
+        let folder = {
+            let folders = self.folders.lock().unwrap();
+            folders.iter().find(|folder| folder.label == label)?.clone()
+        };
+        let real = relative
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .fold(folder.path.clone(), |real, segment| real.join(segment));
+        let real = contained_path(&folder.path, &real)?;
+        Some((folder, relative.to_string(), real))
+    }
+
+    fn folders(&self) -> Vec<DavFolder> {
+        self.folders.lock().unwrap().clone()
+    }
+
+    fn authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(token) = self.write_token.as_deref() else {
+            return false;
+        };
+        headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|credentials| {
+                credentials
+                    .split_once(':')
+                    .map(|(_, password)| same_secret(password, token))
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// 比较两者的 SHA-256 摘要并累积差异，耗时与哪一位不同无关，避免通过计时逐位猜出令牌。
+fn same_secret(given: &str, expected: &str) -> bool {
+    let (given, expected) = (Sha256::digest(given), Sha256::digest(expected));
+    given
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+/// 同名文件夹依次编号为 `名称 (2)`、`名称 (3)`……
+fn unique_label(folders: &[DavFolder], base: String) -> String {
+    let taken = |label: &str| folders.iter().any(|folder| folder.label == label);
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|index| format!("{base} ({index})"))
+        .find(|label| !taken(label))
+        .unwrap_or(base)
+}
+
+/// 以最近一级已存在的上级目录的真实路径为准，确认 `real` 仍位于 `root` 之内。
+/// 目标本身是符号链接时，同样要求其指向的位置在 `root` 之内。
+fn contained_path(root: &Path, real: &Path) -> Option<PathBuf> {
+    if real == root {
+        return Some(root.to_path_buf());
+    }
+    let name = real.file_name()?;
+    let mut existing = real.parent()?;
+    let mut missing = Vec::new();
+    let canonical = loop {
+        match existing.canonicalize() {
+            Ok(canonical) => break canonical,
+            Err(_) => {
+                missing.push(existing.file_name()?);
+                existing = existing.parent()?;
+            }
+        }
+    };
+    if !canonical.starts_with(root) {
+        return None;
+    }
+    let resolved = missing
+        .into_iter()
+        .rev()
+        .fold(canonical, |path, segment| path.join(segment))
+        .join(name);
+    let is_link = std::fs::symlink_metadata(&resolved)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_link
+        && !resolved
+            .canonicalize()
+            .is_ok_and(|target| target.starts_with(root))
+    {
+        return None;
+    }
+    Some(resolved)
+}
+
+pub(super) async fn handle_dav(
+    AxumState(state): AxumState<HttpState>,
+    request: Request,
+) -> Response {
+    let Some(dav) = state.webdav.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Some(path) = request_path(request.uri().path()) else {
+        return (StatusCode::BAD_REQUEST, "无效的路径。").into_response();
+    };
+    let allow = if dav.writable {
+        WRITE_METHODS
+    } else {
+        READ_METHODS
+    };
+
+    match request.method().as_str() {
+        "OPTIONS" => {
+            let mut response = StatusCode::OK.into_response();
+            let headers = response.headers_mut();
+            headers.insert("DAV", HeaderValue::from_static("1"));
+            headers.insert("MS-Author-Via", HeaderValue::from_static("DAV"));
+            headers.insert(header::ALLOW, HeaderValue::from_static(allow));
+            response
+        }
+        "PROPFIND" => propfind(&state, &dav, &path, request.headers()).await,
+        "GET" | "HEAD" => match resolve(&state, &dav, &path).await {
+            Some(DavResource::File { file, .. }) => {
+                let mut response = if request.method() == Method::HEAD {
+                    let len = tokio::fs::metadata(&file.path)
+                        .await
+                        .map(|meta| meta.len())
+                        .unwrap_or(file.size);
+                    let mut response = Response::new(Body::empty());
+                    response
+                        .headers_mut()
+                        .insert(header::CONTENT_LENGTH, HeaderValue::from(len));
+                    if let Ok(value) = HeaderValue::from_str(&content_type(&file)) {
+                        response.headers_mut().insert(header::CONTENT_TYPE, value);
+                    }
+                    response
+                } else {
+                    stream_file(&state, &file).await
+                };
+                if let Ok(value) = HeaderValue::from_str(&http_date(file.modified)) {
+                    response.headers_mut().insert(header::LAST_MODIFIED, value);
+                }
+                if let Ok(value) = HeaderValue::from_str(&etag(&file)) {
+                    response.headers_mut().insert(header::ETAG, value);
+                }
+                response
+            }
+            Some(DavResource::Collection { .. }) => (
+                StatusCode::METHOD_NOT_ALLOWED,
+                "请使用 WebDAV 客户端浏览该目录。",
+            )
+                .into_response(),
+            None => (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response(),
+        },
+        "PUT" | "MKCOL" | "DELETE" if !dav.writable => {
+            let mut response = (StatusCode::METHOD_NOT_ALLOWED, "该分享为只读。").into_response();
+            response
+                .headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static(allow));
+            response
+        }
+        "PUT" | "MKCOL" | "DELETE" if !dav.authorized(request.headers()) => {
+            let mut response =
+                (StatusCode::UNAUTHORIZED, "写入需要提供分享的写入口令。").into_response();
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                HeaderValue::from_static("Basic realm=\"Chef WebDAV\""),
+            );
+            response
+        }
+        "PUT" => put_file(&state, &dav, &path, request).await,
+        "MKCOL" => make_collection(&dav, &path).await,
+        "DELETE" => delete_resource(&state, &dav, &path).await,
+        _ => {
+            let mut response = StatusCode::METHOD_NOT_ALLOWED.into_response();
+            response
+                .headers_mut()
+                .insert(header::ALLOW, HeaderValue::from_static(allow));
+            response
+        }
+    }
+}
+
+/// 去掉 `/dav` 前缀并解码，拒绝包含 `..` 的路径。
+fn request_path(raw: &str) -> Option<String> {
+    let stripped = raw.strip_prefix(DAV_PREFIX).unwrap_or(raw);
+    let decoded = percent_decode_str(stripped).decode_utf8().ok()?;
+    let segments = decoded
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<_>>();
+    if segments
+        .iter()
+        .any(|segment| *segment == ".." || segment.contains('\\'))
+    {
+        return None;
+    }
+    Some(segments.join("/"))
+}
+
+async fn resolve(state: &HttpState, dav: &DavShare, path: &str) -> Option<DavResource> {
+    if path.is_empty() {
+        return Some(DavResource::Collection {
+            path: String::new(),
+            modified: latest_modified(&state.files.read().await),
+        });
+    }
+    let Some((_, _, real)) = dav.real_path(path) else {
+        // 不在文件夹中的文件以文件名直接挂在根目录下。
+        let files = state.files.read().await;
+        let file = files
+            .iter()
+            .find(|file| !file.display_name.contains('/') && file.display_name == path)?;
+        return Some(DavResource::File {
+            path: path.to_string(),
+            file: file.clone(),
+        });
+    };
+    let metadata = fs::metadata(&real).await.ok()?;
+    if metadata.is_dir() {
+        return Some(DavResource::Collection {
+            path: path.to_string(),
+            modified: system_time_millis(metadata.modified().ok()),
+        });
+    }
+    let files = state.files.read().await;
+    let file = files.iter().find(|file| file.path == real)?;
+    Some(DavResource::File {
+        path: path.to_string(),
+        file: file.clone(),
+    })
+}
+
+async fn children(state: &HttpState, dav: &DavShare, path: &str) -> Vec<DavResource> {
+    let mut collections = BTreeMap::new();
+    let mut entries = Vec::new();
+    if path.is_empty() {
+        for folder in dav.folders() {
+            let modified = fs::metadata(&folder.path)
+                .await
+                .ok()
+                .and_then(|metadata| metadata.modified().ok());
+            collections.insert(folder.label, system_time_millis(modified));
+        }
+        let files = state.files.read().await;
+        entries.extend(
+            files
+                .iter()
+                .filter(|file| !file.display_name.contains('/'))
+                .map(|file| DavResource::File {
+                    path: file.display_name.clone(),
+                    file: file.clone(),
+                }),
+        );
+    } else if let Some((_, _, real)) = dav.real_path(path) {
+        if let Ok(mut dir) = fs::read_dir(&real).await {
+            let files = state.files.read().await;
+            while let Ok(Some(entry)) = dir.next_entry().await {
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                let child_path = format!("{path}/{}", entry.file_name().to_string_lossy());
+                if metadata.is_dir() {
+                    collections.insert(child_path, system_time_millis(metadata.modified().ok()));
+                } else if let Some(file) = files.iter().find(|file| file.path == entry.path()) {
+                    entries.push(DavResource::File {
+                        path: child_path,
+                        file: file.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    collections
+        .into_iter()
+        .map(|(path, modified)| DavResource::Collection { path, modified })
+        .chain(entries)
+        .collect()
+}
+
+async fn propfind(state: &HttpState, dav: &DavShare, path: &str, headers: &HeaderMap) -> Response {
+    let Some(resource) = resolve(state, dav, path).await else {
+        return (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response();
+    };
+    let depth_zero = headers
+        .get("Depth")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim() == "0")
+        .unwrap_or(false);
+
+    let mut body =
+        String::from(r#"<?xml version="1.0" encoding="utf-8"?><D:multistatus xmlns:D="DAV:">"#);
+    let is_collection = matches!(resource, DavResource::Collection { .. });
+    body.push_str(&render_response(&resource));
+    if is_collection && !depth_zero {
+        for child in children(state, dav, path).await {
+            body.push_str(&render_response(&child));
+        }
+    }
+    body.push_str("</D:multistatus>");
+
+    let mut response = (StatusCode::MULTI_STATUS, body).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml; charset=utf-8"),
+    );
+    response
+}
+
+fn render_response(resource: &DavResource) -> String {
+    let props = match resource {
+        DavResource::Collection { path, modified } => format!(
+            "<D:displayname>{}</D:displayname><D:resourcetype><D:collection/></D:resourcetype><D:getlastmodified>{}</D:getlastmodified>",
+            escape_html(path.rsplit('/').next().unwrap_or_default()),
+            http_date(*modified)
+        ),
+        DavResource::File { file, .. } => format!(
+            "<D:displayname>{}</D:displayname><D:resourcetype/><D:getcontentlength>{}</D:getcontentlength><D:getcontenttype>{}</D:getcontenttype><D:getetag>{}</D:getetag><D:getlastmodified>{}</D:getlastmodified>",
+            escape_html(&file.download_name),
+            file.size,
+            escape_html(&content_type(file)),
+            escape_html(&etag(file)),
+            http_date(file.modified)
+        ),
+    };
+    let href = match resource {
+        DavResource::Collection { path, .. } => href(path, true),
+        DavResource::File { path, .. } => href(path, false),
+    };
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{props}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape_html(&href)
+    )
+}
+
+fn href(path: &str, collection: bool) -> String {
+    let mut href = String::from(DAV_PREFIX);
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        href.push('/');
+        href.extend(utf8_percent_encode(segment, HREF_SEGMENT));
+    }
+    if collection {
+        href.push('/');
+    }
+    href
+}
+
+async fn put_file(state: &HttpState, dav: &DavShare, path: &str, request: Request) -> Response {
+    let Some((folder, relative, real)) = dav.real_path(path).filter(|(_, rel, _)| !rel.is_empty())
+    else {
+        return (StatusCode::FORBIDDEN, "只能写入已分享的文件夹。").into_response();
+    };
+    if real.is_dir() {
+        return (StatusCode::METHOD_NOT_ALLOWED, "目标是一个文件夹。").into_response();
+    }
+    let Some(parent) = real.parent().filter(|parent| parent.is_dir()) else {
+        return (StatusCode::CONFLICT, "上级文件夹不存在。").into_response();
+    };
+    let file_name = real
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let existed = real.exists();
+    let temp = parent.join(format!(
+        ".{file_name}.{}.chef-upload",
+        Uuid::new_v4().simple()
+    ));
+
+    if let Err(err) = write_body(&temp, request.into_body()).await {
+        let _ = fs::remove_file(&temp).await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, err).into_response();
+    }
+    if let Err(err) = fs::rename(&temp, &real).await {
+        let _ = fs::remove_file(&temp).await;
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("保存文件失败: {err}"),
+        )
+            .into_response();
+    }
+
+    let mut entry = Vec::new();
+    let id = stable_file_id(&folder.path, &relative);
+    if let Err(err) = push_file_entry(
+        &mut entry,
+        &real,
+        id,
+        format!("{}/{relative}", folder_label(&folder.path)),
+    ) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, err).into_response();
+    }
+    let file_count = {
+        let mut table = state.files.write().await;
+        for file in entry {
+            match table.iter_mut().find(|existing| existing.id == file.id) {
+                Some(existing) => *existing = file,
+                None => table.push(file),
+            }
+        }
+        table.len()
+    };
+    state.events.files_changed(file_count);
+
+    if existed {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        StatusCode::CREATED.into_response()
+    }
+}
+
+async fn write_body(path: &Path, body: Body) -> Result<(), String> {
+    let mut file = fs::File::create(path)
+        .await
+        .map_err(|err| format!("无法创建文件: {err}"))?;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| format!("接收上传内容失败: {err}"))?;
+        file.write_all(&chunk)
+            .await
+            .map_err(|err| format!("写入文件失败: {err}"))?;
+    }
+    file.flush()
+        .await
+        .map_err(|err| format!("写入文件失败: {err}"))
+}
+
+async fn make_collection(dav: &DavShare, path: &str) -> Response {
+    let Some((_, _, real)) = dav.real_path(path).filter(|(_, rel, _)| !rel.is_empty()) else {
+        return (StatusCode::FORBIDDEN, "只能在已分享的文件夹中创建目录。").into_response();
+    };
+    if real.exists() {
+        return (StatusCode::METHOD_NOT_ALLOWED, "该路径已存在。").into_response();
+    }
+    if !real.parent().map(Path::is_dir).unwrap_or(false) {
+        return (StatusCode::CONFLICT, "上级文件夹不存在。").into_response();
+    }
+    match fs::create_dir(&real).await {
+        Ok(()) => StatusCode::CREATED.into_response(),
+        Err(err) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("创建文件夹失败: {err}"),
+        )
+            .into_response(),
+    }
+}
+
+async fn delete_resource(state: &HttpState, dav: &DavShare, path: &str) -> Response {
+    let Some((_, _, real)) = dav.real_path(path).filter(|(_, rel, _)| !rel.is_empty()) else {
+        return (StatusCode::FORBIDDEN, "只能删除已分享文件夹中的内容。").into_response();
+    };
+    // 不跟随符号链接：链接本身被删除，而不是它指向的内容。
+    let Ok(metadata) = fs::symlink_metadata(&real).await else {
+        return (StatusCode::NOT_FOUND, "你要找的文件不存在。").into_response();
+    };
+    let removed = if metadata.is_dir() {
+        fs::remove_dir_all(&real).await
+    } else {
+        fs::remove_file(&real).await
+    };
+    if let Err(err) = removed {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("删除失败: {err}"),
+        )
+            .into_response();
+    }
+
+    let file_count = {
+        let mut table = state.files.write().await;
+        table.retain(|file| !file.path.starts_with(&real));
+        table.len()
+    };
+    state.events.files_changed(file_count);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+fn latest_modified(files: &[ServerFile]) -> u64 {
+    files
+        .iter()
+        .map(|file| file.modified)
+        .max()
+        .unwrap_or_default()
+}
+
+fn content_type(file: &ServerFile) -> String {
+    file.mime.first_or_octet_stream().essence_str().to_string()
+}
+
+fn etag(file: &ServerFile) -> String {
+    format!("\"{}-{:x}-{:x}\"", file.id, file.size, file.modified)
+}
+
+fn http_date(millis: u64) -> String {
+    httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{FileShareManager, FileShareStartOptions};
+    use super::*;
+    use reqwest_dav::{list_cmd::ListEntity, Auth, ClientBuilder, Depth};
+
+    #[test]
+    fn rejects_traversal_in_request_paths() {
+        assert_eq!(request_path("/dav/").as_deref(), Some(""));
+        assert_eq!(
+            request_path("/dav/docs/a%20b.txt").as_deref(),
+            Some("docs/a b.txt")
+        );
+        assert_eq!(request_path("/dav/docs/%2E%2E/secret"), None);
+        assert_eq!(href("docs/a b#1.txt", false), "/dav/docs/a%20b%231.txt");
+
+        let folders = vec![DavFolder {
+            label: "docs".into(),
+            path: PathBuf::from("/a/docs"),
+        }];
+        assert_eq!(unique_label(&folders, "docs".into()), "docs (2)");
+        assert_eq!(unique_label(&folders, "photos".into()), "photos");
+
+        assert!(same_secret("5f1c-token", "5f1c-token"));
+        assert!(!same_secret("5f1c-tokem", "5f1c-token"));
+        assert!(!same_secret("", "5f1c-token"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_share_to_webdav_client() {
+        let dir = std::env::temp_dir().join(format!("chef_dav_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("docs/sub")).unwrap();
+        std::fs::write(dir.join("docs/readme.txt"), b"hello").unwrap();
+        std::fs::write(dir.join("docs/sub/note.md"), b"# note").unwrap();
+        std::fs::create_dir_all(dir.join("other/docs")).unwrap();
+        std::fs::write(dir.join("other/docs/todo.txt"), b"todo").unwrap();
+        std::fs::create_dir_all(dir.join("outside")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("docs/escape")).unwrap();
+        let root = dir.join("docs").display().to_string();
+        let other = dir.join("other/docs").display().to_string();
+
+        let manager = FileShareManager::default();
+        let session = manager
+            .start(
+                vec![root.clone(), other],
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    webdav: Some(FileShareWebDavOptions {
+                        enabled: true,
+                        writable: true,
+                    }),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("share should start");
+        let info = session.webdav.clone().expect("webdav should be enabled");
+        assert!(info.writable);
+        let anonymous = ClientBuilder::new()
+            .set_host(info.url.clone())
+            .set_auth(Auth::Anonymous)
+            .build()
+            .unwrap();
+        assert!(anonymous.put("docs/anonymous.txt", "no").await.is_err());
+        assert!(!dir.join("docs/anonymous.txt").exists());
+        let client = ClientBuilder::new()
+            .set_host(info.url.clone())
+            .set_auth(Auth::Basic(
+                "chef".into(),
+                info.write_token
+                    .clone()
+                    .expect("writable share needs a token"),
+            ))
+            .build()
+            .unwrap();
+
+        let names = |entries: Vec<ListEntity>| {
+            let mut names = entries
+                .into_iter()
+                .map(|entry| match entry {
+                    ListEntity::File(file) => file.href,
+                    ListEntity::Folder(folder) => folder.href,
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(client.list("", Depth::Number(1)).await.unwrap()),
+            vec!["/dav/", "/dav/docs%20(2)/", "/dav/docs/"]
+        );
+        assert_eq!(
+            names(client.list("docs/", Depth::Number(1)).await.unwrap()),
+            vec!["/dav/docs/", "/dav/docs/readme.txt", "/dav/docs/sub/"]
+        );
+        let body = client.get("docs/readme.txt").await.unwrap().text().await;
+        assert_eq!(body.unwrap(), "hello");
+        let body = client.get("docs (2)/todo.txt").await.unwrap().text().await;
+        assert_eq!(body.unwrap(), "todo");
+
+        if cfg!(unix) {
+            assert!(client.put("docs/escape/leak.txt", "no").await.is_err());
+            assert!(client.delete("docs/escape").await.is_err());
+            assert!(!dir.join("outside/leak.txt").exists());
+            assert!(dir.join("outside").exists());
+        }
+
+        client.mkcol("docs/new").await.unwrap();
+        client.put("docs/new/upload.txt", "uploaded").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("docs/new/upload.txt")).unwrap(),
+            "uploaded"
+        );
+        let files = manager.list().await.remove(0).files;
+        assert!(files
+            .iter()
+            .any(|file| file.display_name == "docs/new/upload.txt"));
+
+        client.delete("docs/sub").await.unwrap();
+        assert!(!dir.join("docs/sub").exists());
+        let files = manager.list().await.remove(0).files;
+        assert!(!files
+            .iter()
+            .any(|file| file.display_name.starts_with("docs/sub/")));
+        manager.stop_all().await;
+
+        let read_only = manager
+            .start(
+                vec![root],
+                FileShareStartOptions {
+                    bind_address: Some("127.0.0.1".into()),
+                    webdav: Some(FileShareWebDavOptions {
+                        enabled: true,
+                        writable: false,
+                    }),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .expect("share should start");
+        let client = ClientBuilder::new()
+            .set_host(read_only.webdav.unwrap().url)
+            .set_auth(Auth::Anonymous)
+            .build()
+            .unwrap();
+        assert!(client.put("docs/blocked.txt", "no").await.is_err());
+        assert!(!dir.join("docs/blocked.txt").exists());
+
+        manager.stop_all().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
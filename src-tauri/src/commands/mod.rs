@@ -14,10 +14,22 @@ pub use env_reader::read_environment_sources;
 pub use hosts::read_hosts_file;
 pub use file_search::{pick_search_directories, search_files};
 pub use file_share::{
-    get_file_share_status, pick_share_directories, pick_share_files, start_file_share,
-    stop_file_share, FileShareManager,
+    add_file_share_items, discover_file_shares, get_file_share_qr_codes, get_file_share_status,
+    list_file_shares, list_share_bind_addresses, list_share_templates, pick_share_directories,
+    pick_share_files, push_share_clipboard, push_share_text, remove_file_share_items,
+    remove_share_text, start_file_share, stop_file_share, update_file_share_limits,
+    FileShareManager,
+};
+pub use network::{
+    check_proxies, diagnose_network_connectivity, get_ip_lookup_settings,
+    get_network_monitor_status, get_network_overview, get_system_proxy_config,
+    inspect_port_process, kill_port_process, list_dns_resolvers, list_listening_sockets,
+    list_network_fix_snapshots, load_ip_lookup_settings, lookup_geoip, probe_network_targets,
+    query_dns, query_network_history, resolve_proxy_for_url, revert_network_fix,
+    run_network_fix_action, save_ip_lookup_settings, scan_ports, send_http_request,
+    start_network_monitor, start_network_trace, stop_network_monitor, stop_network_trace,
+    NetworkMonitor, TraceManager,
 };
-pub use network::{diagnose_network_connectivity, get_network_overview, run_network_fix_action};
 pub use region_capture::{
     cancel_region_capture, capture_region, finalize_region_capture, show_region_capture_overlay,
 };
@@ -0,0 +1,1027 @@
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures_util::future::join_all;
+use hickory_proto::{
+    op::{Edns, Message, MessageType, OpCode, Query},
+    rr::{Name, Record, RecordType},
+};
+use reqwest::{
+    header::{ACCEPT, CONTENT_TYPE},
+    Client, Url,
+};
+use rustls::{crypto::ring, pki_types::ServerName, ClientConfig, RootCertStore};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{lookup_host, TcpStream, UdpSocket},
+    time::timeout,
+};
+use tokio_rustls::TlsConnector;
+use uuid::Uuid;
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use super::interfaces::{collect_routes, detect_vpns};
+use super::probe::{elapsed_ms, split_host_port};
+
+const DEFAULT_TIMEOUT_MS: u64 = 3000;
+const REVERSE_LOOKUP_TIMEOUT_MS: u64 = 1500;
+const UDP_PAYLOAD_SIZE: u16 = 1232;
+const DNS_MESSAGE_TYPE: &str = "application/dns-message";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DnsRecordType {
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Txt,
+    Ns,
+    Srv,
+    Soa,
+    Ptr,
+}
+
+impl DnsRecordType {
+    fn record_type(self) -> RecordType {
+        match self {
+            Self::A => RecordType::A,
+            Self::Aaaa => RecordType::AAAA,
+            Self::Cname => RecordType::CNAME,
+            Self::Mx => RecordType::MX,
+            Self::Txt => RecordType::TXT,
+            Self::Ns => RecordType::NS,
+            Self::Srv => RecordType::SRV,
+            Self::Soa => RecordType::SOA,
+            Self::Ptr => RecordType::PTR,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsQueryRequest {
+    pub name: String,
+    pub record_type: DnsRecordType,
+    /// 支持 system、vpn、IP[:端口]、udp://、tcp://、tls://（DoT，可用 #名称 指定 SNI）和 https://（DoH）。
+    pub resolvers: Vec<String>,
+    pub timeout_ms: Option<u64>,
+    pub recursion_desired: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum DnsTransport {
+    System,
+    Udp,
+    Tcp,
+    Dot,
+    Doh,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsQueryResult {
+    resolver: String,
+    transport: Option<DnsTransport>,
+    server: Option<String>,
+    duration_ms: f64,
+    response_code: Option<String>,
+    flags: Vec<String>,
+    retried_over_tcp: bool,
+    answers: Vec<DnsRecordEntry>,
+    authority: Vec<DnsRecordEntry>,
+    additional: Vec<DnsRecordEntry>,
+    response_size: usize,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DnsRecordEntry {
+    name: String,
+    record_type: String,
+    /// 通过系统 getaddrinfo 查询时拿不到 TTL。
+    ttl: Option<u32>,
+    data: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsResolverPreset {
+    label: String,
+    resolver: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ResolverSpec {
+    System,
+    Vpn,
+    Udp(String, u16),
+    Tcp(String, u16),
+    Tls {
+        host: String,
+        port: u16,
+        server_name: String,
+    },
+    Https(Url),
+}
+
+struct PreparedQuery {
+    name: Name,
+    record_type: RecordType,
+    recursion_desired: bool,
+}
+
+struct Exchange {
+    transport: DnsTransport,
+    server: String,
+    response: Vec<u8>,
+    retried_over_tcp: bool,
+}
+
+const PUBLIC_RESOLVERS: &[(&str, &str)] = &[
+    ("阿里 DNS", "223.5.5.5"),
+    ("DNSPod", "119.29.29.29"),
+    ("114 DNS", "114.114.114.114"),
+    ("Google", "8.8.8.8"),
+    ("Cloudflare", "1.1.1.1"),
+    ("Quad9", "9.9.9.9"),
+    ("阿里 DoH", "https://dns.alidns.com/dns-query"),
+    ("Google DoH", "https://dns.google/dns-query"),
+    ("Cloudflare DoH", "https://cloudflare-dns.com/dns-query"),
+    ("Cloudflare DoT", "tls://1.1.1.1#cloudflare-dns.com"),
+];
+
+/// 向多个解析器并发发送同一查询，便于对比各自的结果。
+#[tauri::command]
+pub async fn query_dns(request: DnsQueryRequest) -> Result<Vec<DnsQueryResult>, String> {
+    if request.resolvers.is_empty() {
+        return Err("请至少选择一个 DNS 解析器。".into());
+    }
+    let query = prepare_query(
+        &request.name,
+        request.record_type,
+        request.recursion_desired.unwrap_or(true),
+    )?;
+    let specs = request
+        .resolvers
+        .iter()
+        .map(|raw| parse_resolver(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+    let limit = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let client = Client::builder()
+        .timeout(limit)
+        .user_agent("Chef Network Doctor/0.1")
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    let queries = request
+        .resolvers
+        .iter()
+        .zip(specs)
+        .map(|(raw, spec)| run_query(raw.trim(), spec, &query, &client, limit));
+    Ok(join_all(queries).await)
+}
+
+/// 列出可供对比的解析器：系统、当前 VPN 下发的 DNS 以及常用公共 DNS。
+#[tauri::command]
+pub async fn list_dns_resolvers() -> Result<Vec<DnsResolverPreset>, String> {
+    let mut presets = vec![DnsResolverPreset {
+        label: "系统 DNS".into(),
+        resolver: "system".into(),
+    }];
+    let vpn = spawn_blocking(vpn_nameservers)
+        .await
+        .map_err(|err| err.to_string())?;
+    presets.extend(vpn.into_iter().map(|(interface, ip)| DnsResolverPreset {
+        label: format!("VPN DNS ({interface})"),
+        resolver: udp_spec(ip),
+    }));
+    presets.extend(
+        PUBLIC_RESOLVERS
+            .iter()
+            .map(|(label, resolver)| DnsResolverPreset {
+                label: (*label).into(),
+                resolver: (*resolver).into(),
+            }),
+    );
+    Ok(presets)
+}
+
+/// 连通性诊断用：通过系统 DNS 查询 A 记录，返回可读的结论。
+pub(super) async fn check_system_dns(name: &str) -> Result<String, String> {
+    let query = prepare_query(name, DnsRecordType::A, true)?;
+    let client = Client::new();
+    let limit = Duration::from_millis(DEFAULT_TIMEOUT_MS);
+    let result = run_query("system", ResolverSpec::System, &query, &client, limit).await;
+    if let Some(err) = result.error {
+        return Err(format!("DNS 查询 {name} 失败: {err}"));
+    }
+    let code = result.response_code.unwrap_or_default();
+    if code != "NOERROR" || result.answers.is_empty() {
+        return Err(format!("DNS 响应状态 {code}，未获得有效记录。"));
+    }
+    Ok(format!(
+        "DNS 解析 {name} 成功（{}，耗时 {:.0} ms）。",
+        result.server.unwrap_or_else(|| "系统解析".into()),
+        result.duration_ms
+    ))
+}
+
+/// 路由追踪用：通过系统 DNS 做 PTR 反查，失败或超时返回 None。
+pub(super) async fn reverse_lookup(ip: IpAddr) -> Option<String> {
+    let query = prepare_query(&ip.to_string(), DnsRecordType::Ptr, true).ok()?;
+    let client = Client::new();
+    let limit = Duration::from_millis(REVERSE_LOOKUP_TIMEOUT_MS);
+    let result = run_query("system", ResolverSpec::System, &query, &client, limit).await;
+    result
+        .answers
+        .into_iter()
+        .find(|entry| entry.record_type == "PTR")
+        .map(|entry| entry.data.trim_end_matches('.').to_string())
+}
+
+fn prepare_query(
+    name: &str,
+    record_type: DnsRecordType,
+    recursion_desired: bool,
+) -> Result<PreparedQuery, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("请输入要查询的域名。".into());
+    }
+    // PTR 查询可以直接填写 IP，自动转换为 in-addr.arpa / ip6.arpa 名称
+    let name = match (record_type, name.parse::<IpAddr>()) {
+        (DnsRecordType::Ptr, Ok(ip)) => Name::from(ip),
+        _ => Name::from_ascii(name).map_err(|err| format!("域名无效 {name}: {err}"))?,
+    };
+    Ok(PreparedQuery {
+        name,
+        record_type: record_type.record_type(),
+        recursion_desired,
+    })
+}
+
+impl PreparedQuery {
+    fn encode(&self, id: u16) -> Result<Vec<u8>, String> {
+        let mut edns = Edns::new();
+        edns.set_max_payload(UDP_PAYLOAD_SIZE);
+        let mut message = Message::new();
+        message
+            .set_id(id)
+            .set_message_type(MessageType::Query)
+            .set_op_code(OpCode::Query)
+            .set_recursion_desired(self.recursion_desired)
+            .add_query(Query::query(self.name.clone(), self.record_type))
+            .set_edns(edns);
+        message
+            .to_vec()
+            .map_err(|err| format!("构造 DNS 查询失败: {err}"))
+    }
+}
+
+fn parse_resolver(raw: &str) -> Result<ResolverSpec, String> {
+    let raw = raw.trim();
+    if raw.eq_ignore_ascii_case("system") {
+        return Ok(ResolverSpec::System);
+    }
+    if raw.eq_ignore_ascii_case("vpn") {
+        return Ok(ResolverSpec::Vpn);
+    }
+    if raw.starts_with("https://") || raw.starts_with("http://") {
+        let url = Url::parse(raw).map_err(|err| format!("DoH 地址无效 {raw}: {err}"))?;
+        return Ok(ResolverSpec::Https(url));
+    }
+
+    let server = |rest: &str, default_port: u16| -> Result<(String, u16), String> {
+        let (host, port) = split_host_port(rest)?;
+        Ok((host, port.unwrap_or(default_port)))
+    };
+    if let Some(rest) = raw.strip_prefix("udp://") {
+        let (host, port) = server(rest, 53)?;
+        return Ok(ResolverSpec::Udp(host, port));
+    }
+    if let Some(rest) = raw.strip_prefix("tcp://") {
+        let (host, port) = server(rest, 53)?;
+        return Ok(ResolverSpec::Tcp(host, port));
+    }
+    if let Some(rest) = raw.strip_prefix("tls://") {
+        let (address, server_name) = match rest.split_once('#') {
+            Some((address, name)) => (address, Some(name.to_string())),
+            None => (rest, None),
+        };
+        let (host, port) = server(address, 853)?;
+        return Ok(ResolverSpec::Tls {
+            server_name: server_name.unwrap_or_else(|| host.clone()),
+            host,
+            port,
+        });
+    }
+    if raw.contains("://") {
+        return Err(format!("不支持的解析器地址 {raw}"));
+    }
+    let (host, port) = server(raw, 53)?;
+    Ok(ResolverSpec::Udp(host, port))
+}
+
+async fn run_query(
+    raw: &str,
+    spec: ResolverSpec,
+    query: &PreparedQuery,
+    client: &Client,
+    limit: Duration,
+) -> DnsQueryResult {
+    let mut result = DnsQueryResult {
+        resolver: raw.to_string(),
+        transport: None,
+        server: None,
+        duration_ms: 0.0,
+        response_code: None,
+        flags: Vec::new(),
+        retried_over_tcp: false,
+        answers: Vec::new(),
+        authority: Vec::new(),
+        additional: Vec::new(),
+        response_size: 0,
+        error: None,
+    };
+
+    let started = Instant::now();
+    let exchange = match spec {
+        ResolverSpec::System => {
+            let nameservers = system_nameservers();
+            if nameservers.is_empty() {
+                // 没有 resolv.conf 的系统只能走 getaddrinfo，仅支持 A/AAAA
+                lookup_system(query, limit, &mut result).await;
+                result.duration_ms = elapsed_ms(started);
+                return result;
+            }
+            let server = SocketAddr::new(nameservers[0], 53);
+            exchange_udp_with_fallback(server, query, limit)
+                .await
+                .map(|exchange| Exchange {
+                    transport: DnsTransport::System,
+                    ..exchange
+                })
+        }
+        ResolverSpec::Vpn => match spawn_blocking(vpn_nameservers).await {
+            Ok(nameservers) => match nameservers.first() {
+                Some((_, ip)) => {
+                    exchange_udp_with_fallback(SocketAddr::new(*ip, 53), query, limit).await
+                }
+                None => Err("未检测到 VPN 下发的 DNS 服务器".into()),
+            },
+            Err(err) => Err(err.to_string()),
+        },
+        ResolverSpec::Udp(host, port) => match resolve_server(&host, port).await {
+            Ok(server) => exchange_udp_with_fallback(server, query, limit).await,
+            Err(err) => Err(err),
+        },
+        ResolverSpec::Tcp(host, port) => match resolve_server(&host, port).await {
+            Ok(server) => exchange_tcp(server, query, limit).await,
+            Err(err) => Err(err),
+        },
+        ResolverSpec::Tls {
+            host,
+            port,
+            server_name,
+        } => match resolve_server(&host, port).await {
+            Ok(server) => exchange_tls(server, &server_name, query, limit).await,
+            Err(err) => Err(err),
+        },
+        ResolverSpec::Https(url) => exchange_https(url, query, client).await,
+    };
+    result.duration_ms = elapsed_ms(started);
+
+    let exchange = match exchange {
+        Ok(exchange) => exchange,
+        Err(err) => {
+            result.error = Some(err);
+            return result;
+        }
+    };
+    result.transport = Some(exchange.transport);
+    result.server = Some(exchange.server);
+    result.retried_over_tcp = exchange.retried_over_tcp;
+    result.response_size = exchange.response.len();
+    match Message::from_vec(&exchange.response) {
+        Ok(message) => {
+            result.response_code =
+                Some(format!("{:?}", message.response_code()).to_ascii_uppercase());
+            result.flags = header_flags(&message);
+            result.answers = message.answers().iter().map(record_entry).collect();
+            result.authority = message.name_servers().iter().map(record_entry).collect();
+            result.additional = message.additionals().iter().map(record_entry).collect();
+        }
+        Err(err) => result.error = Some(format!("无法解析 DNS 响应: {err}")),
+    }
+    result
+}
+
+async fn resolve_server(host: &str, port: u16) -> Result<SocketAddr, String> {
+    lookup_host((host, port))
+        .await
+        .map_err(|err| format!("无法解析 DNS 服务器 {host}: {err}"))?
+        .next()
+        .ok_or_else(|| format!("DNS 服务器 {host} 没有可用地址"))
+}
+
+/// UDP 响应被截断时自动改用 TCP 重试。
+async fn exchange_udp_with_fallback(
+    server: SocketAddr,
+    query: &PreparedQuery,
+    limit: Duration,
+) -> Result<Exchange, String> {
+    let id = random_id();
+    let request = query.encode(id)?;
+    let response = timeout(limit, exchange_udp(server, &request, id))
+        .await
+        .map_err(|_| format!("{server} 响应超时"))?
+        .map_err(|err| format!("查询 {server} 失败: {err}"))?;
+
+    let truncated = Message::from_vec(&response)
+        .map(|message| message.truncated())
+        .unwrap_or(false);
+    if truncated {
+        let exchange = exchange_tcp(server, query, limit).await?;
+        return Ok(Exchange {
+            retried_over_tcp: true,
+            ..exchange
+        });
+    }
+    Ok(Exchange {
+        transport: DnsTransport::Udp,
+        server: server.to_string(),
+        response,
+        retried_over_tcp: false,
+    })
+}
+
+async fn exchange_udp(server: SocketAddr, request: &[u8], id: u16) -> io::Result<Vec<u8>> {
+    let local: SocketAddr = if server.is_ipv4() {
+        (Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    socket.send(request).await?;
+    let mut buf = vec![0u8; u16::MAX as usize];
+    loop {
+        let len = socket.recv(&mut buf).await?;
+        // 忽略 ID 不匹配的迟到响应
+        if len >= 2 && u16::from_be_bytes([buf[0], buf[1]]) == id {
+            buf.truncate(len);
+            return Ok(buf);
+        }
+    }
+}
+
+async fn exchange_tcp(
+    server: SocketAddr,
+    query: &PreparedQuery,
+    limit: Duration,
+) -> Result<Exchange, String> {
+    let request = query.encode(random_id())?;
+    let exchange = async {
+        let mut stream = TcpStream::connect(server).await?;
+        exchange_stream(&mut stream, &request).await
+    };
+    let response = timeout(limit, exchange)
+        .await
+        .map_err(|_| format!("{server} 响应超时"))?
+        .map_err(|err| format!("查询 {server} 失败: {err}"))?;
+    Ok(Exchange {
+        transport: DnsTransport::Tcp,
+        server: server.to_string(),
+        response,
+        retried_over_tcp: false,
+    })
+}
+
+async fn exchange_tls(
+    server: SocketAddr,
+    server_name: &str,
+    query: &PreparedQuery,
+    limit: Duration,
+) -> Result<Exchange, String> {
+    let request = query.encode(random_id())?;
+    let name = ServerName::try_from(server_name.to_string())
+        .map_err(|err| format!("无效的 TLS 主机名 {server_name}: {err}"))?;
+    let connector = TlsConnector::from(dot_client_config()?);
+    let exchange = async {
+        let stream = TcpStream::connect(server).await?;
+        let mut stream = connector.connect(name, stream).await?;
+        exchange_stream(&mut stream, &request).await
+    };
+    let response = timeout(limit, exchange)
+        .await
+        .map_err(|_| format!("{server} 响应超时"))?
+        .map_err(|err| format!("查询 {server} 失败: {err}"))?;
+    Ok(Exchange {
+        transport: DnsTransport::Dot,
+        server: format!("{server} ({server_name})"),
+        response,
+        retried_over_tcp: false,
+    })
+}
+
+async fn exchange_https(
+    url: Url,
+    query: &PreparedQuery,
+    client: &Client,
+) -> Result<Exchange, String> {
+    // RFC 8484 建议 DoH 请求使用 0 作为 ID，便于缓存
+    let request = query.encode(0)?;
+    let server = url.to_string();
+    let response = client
+        .post(url)
+        .header(CONTENT_TYPE, DNS_MESSAGE_TYPE)
+        .header(ACCEPT, DNS_MESSAGE_TYPE)
+        .body(request)
+        .send()
+        .await
+        .map_err(|err| format!("请求 {server} 失败: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!("{server} 返回状态码 {}", response.status()));
+    }
+    let response = response
+        .bytes()
+        .await
+        .map_err(|err| format!("读取 {server} 响应失败: {err}"))?;
+    Ok(Exchange {
+        transport: DnsTransport::Doh,
+        server,
+        response: response.to_vec(),
+        retried_over_tcp: false,
+    })
+}
+
+/// TCP 和 DoT 使用两字节长度前缀的报文格式。
+async fn exchange_stream<S>(stream: &mut S, request: &[u8]) -> io::Result<Vec<u8>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut framed = Vec::with_capacity(request.len() + 2);
+    framed.extend_from_slice(&(request.len() as u16).to_be_bytes());
+    framed.extend_from_slice(request);
+    stream.write_all(&framed).await?;
+    stream.flush().await?;
+
+    let mut len = [0u8; 2];
+    stream.read_exact(&mut len).await?;
+    let mut response = vec![0u8; u16::from_be_bytes(len) as usize];
+    stream.read_exact(&mut response).await?;
+    Ok(response)
+}
+
+fn dot_client_config() -> Result<Arc<ClientConfig>, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("初始化 TLS 失败: {err}"))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"dot".to_vec()];
+    Ok(Arc::new(config))
+}
+
+async fn lookup_system(query: &PreparedQuery, limit: Duration, result: &mut DnsQueryResult) {
+    result.transport = Some(DnsTransport::System);
+    let want_v4 = match query.record_type {
+        RecordType::A => true,
+        RecordType::AAAA => false,
+        _ => {
+            result.error = Some("系统解析只支持 A 和 AAAA 记录".into());
+            return;
+        }
+    };
+    let host = query.name.to_string();
+    let host = host.trim_end_matches('.').to_string();
+    let lookup = timeout(limit, lookup_host((host.as_str(), 0))).await;
+    match lookup {
+        Ok(Ok(addrs)) => {
+            result.response_code = Some("NOERROR".into());
+            for addr in addrs.filter(|addr| addr.is_ipv4() == want_v4) {
+                let data = addr.ip().to_string();
+                if result.answers.iter().any(|entry| entry.data == data) {
+                    continue;
+                }
+                result.answers.push(DnsRecordEntry {
+                    name: host.clone(),
+                    record_type: query.record_type.to_string(),
+                    ttl: None,
+                    data,
+                });
+            }
+        }
+        Ok(Err(err)) => result.error = Some(format!("解析 {host} 失败: {err}")),
+        Err(_) => result.error = Some(format!("解析 {host} 超时")),
+    }
+}
+
+fn header_flags(message: &Message) -> Vec<String> {
+    let header = message.header();
+    [
+        ("qr", header.message_type() == MessageType::Response),
+        ("aa", header.authoritative()),
+        ("tc", header.truncated()),
+        ("rd", header.recursion_desired()),
+        ("ra", header.recursion_available()),
+        ("ad", header.authentic_data()),
+        ("cd", header.checking_disabled()),
+    ]
+    .into_iter()
+    .filter(|(_, set)| *set)
+    .map(|(flag, _)| flag.to_string())
+    .collect()
+}
+
+fn record_entry(record: &Record) -> DnsRecordEntry {
+    DnsRecordEntry {
+        name: record.name().to_string(),
+        record_type: record.record_type().to_string(),
+        ttl: Some(record.ttl()),
+        data: record
+            .data()
+            .map(|data| data.to_string())
+            .unwrap_or_default(),
+    }
+}
+
+fn random_id() -> u16 {
+    Uuid::new_v4().as_u128() as u16
+}
+
+fn udp_spec(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("udp://{ip}"),
+        IpAddr::V6(ip) => format!("udp://[{ip}]"),
+    }
+}
+
+fn system_nameservers() -> Vec<IpAddr> {
+    #[cfg(unix)]
+    {
+        std::fs::read_to_string("/etc/resolv.conf")
+            .map(|content| parse_resolv_conf(&content))
+            .unwrap_or_default()
+    }
+    #[cfg(not(unix))]
+    {
+        Vec::new()
+    }
+}
+
+pub(super) fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            if parts.next()? != "nameserver" {
+                return None;
+            }
+            // 去掉 fe80::1%eth0 这类链路本地地址的作用域后缀
+            let value = parts.next()?;
+            value.split('%').next()?.parse().ok()
+        })
+        .collect()
+}
+
+/// 找出 VPN 网卡上配置的 DNS 服务器，返回 (网卡名, 地址)。
+fn vpn_nameservers() -> Vec<(String, IpAddr)> {
+    #[cfg(target_os = "linux")]
+    {
+        let vpns = vpn_names();
+        Command::new("resolvectl")
+            .arg("dns")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| {
+                parse_resolvectl_dns(&String::from_utf8_lossy(&output.stdout), |name| {
+                    vpns.iter().any(|vpn| vpn == name)
+                })
+            })
+            .unwrap_or_default()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let vpns = vpn_names();
+        Command::new("scutil")
+            .arg("--dns")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| {
+                parse_scutil_dns(&String::from_utf8_lossy(&output.stdout), |name| {
+                    vpns.iter().any(|vpn| vpn == name)
+                })
+            })
+            .unwrap_or_default()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        Vec::new()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn vpn_names() -> Vec<String> {
+    detect_vpns(&collect_routes())
+        .into_iter()
+        .map(|link| link.name)
+        .collect()
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_resolvectl_dns(output: &str, is_vpn: impl Fn(&str) -> bool) -> Vec<(String, IpAddr)> {
+    let mut servers = Vec::new();
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("Link ") else {
+            continue;
+        };
+        let Some((interface, addresses)) = rest
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split_once("):"))
+        else {
+            continue;
+        };
+        if !is_vpn(interface) {
+            continue;
+        }
+        for address in addresses.split_whitespace() {
+            if let Ok(ip) = address.split('%').next().unwrap_or(address).parse() {
+                push_server(&mut servers, interface, ip);
+            }
+        }
+    }
+    servers
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn parse_scutil_dns(output: &str, is_vpn: impl Fn(&str) -> bool) -> Vec<(String, IpAddr)> {
+    let mut servers = Vec::new();
+    let mut flush = |interface: &Option<String>, nameservers: &mut Vec<IpAddr>| {
+        if let Some(interface) = interface.as_deref().filter(|name| is_vpn(name)) {
+            for ip in nameservers.iter() {
+                push_server(&mut servers, interface, *ip);
+            }
+        }
+        nameservers.clear();
+    };
+
+    let mut interface = None;
+    let mut nameservers = Vec::new();
+    for line in output.lines() {
+        let line = line.trim();
+        if line.starts_with("resolver #") {
+            flush(&interface, &mut nameservers);
+            interface = None;
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.starts_with("nameserver[") {
+            if let Ok(ip) = value.parse() {
+                nameservers.push(ip);
+            }
+        } else if key == "if_index" {
+            interface = value
+                .split_once('(')
+                .map(|(_, name)| name.trim_end_matches(')').to_string());
+        }
+    }
+    flush(&interface, &mut nameservers);
+    servers
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", test))]
+fn push_server(servers: &mut Vec<(String, IpAddr)>, interface: &str, ip: IpAddr) {
+    if !servers.iter().any(|(_, existing)| *existing == ip) {
+        servers.push((interface.to_string(), ip));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, routing::post, Router};
+    use hickory_proto::rr::{
+        rdata::{A, MX, PTR},
+        RData,
+    };
+    use tokio::net::TcpListener;
+
+    /// 模拟权威服务器：按查询类型返回固定记录，`truncated` 时只回带截断标记的空响应。
+    fn answer(request: &[u8], truncated: bool) -> Vec<u8> {
+        let request = Message::from_vec(request).unwrap();
+        let query = request.queries()[0].clone();
+        let mut response = Message::new();
+        response
+            .set_id(request.id())
+            .set_message_type(MessageType::Response)
+            .set_recursion_desired(request.recursion_desired())
+            .set_recursion_available(true)
+            .set_authoritative(true)
+            .set_truncated(truncated)
+            .add_query(query.clone());
+        if !truncated {
+            let name = query.name().clone();
+            let rdata = match query.query_type() {
+                RecordType::A => RData::A(A::new(93, 184, 216, 34)),
+                RecordType::MX => {
+                    RData::MX(MX::new(10, Name::from_ascii("mail.example.com.").unwrap()))
+                }
+                RecordType::PTR => RData::PTR(PTR(Name::from_ascii("host.example.").unwrap())),
+                _ => return response.to_vec().unwrap(),
+            };
+            response.add_answer(Record::from_rdata(name, 300, rdata));
+        }
+        response.to_vec().unwrap()
+    }
+
+    async fn spawn_dns_stand_in(truncate_udp: bool) -> SocketAddr {
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(addr).await.unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = udp.recv_from(&mut buf).await {
+                let _ = udp.send_to(&answer(&buf[..len], truncate_udp), peer).await;
+            }
+        });
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = tcp.accept().await {
+                let mut len = [0u8; 2];
+                stream.read_exact(&mut len).await.unwrap();
+                let mut request = vec![0u8; u16::from_be_bytes(len) as usize];
+                stream.read_exact(&mut request).await.unwrap();
+                let response = answer(&request, false);
+                stream
+                    .write_all(&(response.len() as u16).to_be_bytes())
+                    .await
+                    .unwrap();
+                stream.write_all(&response).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    fn request(name: &str, record_type: DnsRecordType, resolvers: Vec<String>) -> DnsQueryRequest {
+        DnsQueryRequest {
+            name: name.into(),
+            record_type,
+            resolvers,
+            timeout_ms: Some(2000),
+            recursion_desired: None,
+        }
+    }
+
+    #[test]
+    fn parses_resolver_specs() {
+        assert_eq!(parse_resolver("system").unwrap(), ResolverSpec::System);
+        assert_eq!(parse_resolver("VPN").unwrap(), ResolverSpec::Vpn);
+        assert_eq!(
+            parse_resolver("8.8.8.8").unwrap(),
+            ResolverSpec::Udp("8.8.8.8".into(), 53)
+        );
+        assert_eq!(
+            parse_resolver("udp://[2001:4860:4860::8888]:5353").unwrap(),
+            ResolverSpec::Udp("2001:4860:4860::8888".into(), 5353)
+        );
+        assert_eq!(
+            parse_resolver("tcp://dns.example").unwrap(),
+            ResolverSpec::Tcp("dns.example".into(), 53)
+        );
+        assert_eq!(
+            parse_resolver("tls://1.1.1.1#cloudflare-dns.com").unwrap(),
+            ResolverSpec::Tls {
+                host: "1.1.1.1".into(),
+                port: 853,
+                server_name: "cloudflare-dns.com".into(),
+            }
+        );
+        assert!(matches!(
+            parse_resolver("https://dns.google/dns-query").unwrap(),
+            ResolverSpec::Https(_)
+        ));
+        assert!(parse_resolver("quic://dns.example").is_err());
+        assert!(parse_resolver("1.1.1.1:99999").is_err());
+    }
+
+    #[test]
+    fn converts_ip_to_reverse_name_for_ptr() {
+        let query = prepare_query("192.0.2.10", DnsRecordType::Ptr, true).unwrap();
+        assert_eq!(query.name.to_string(), "10.2.0.192.in-addr.arpa.");
+        assert!(prepare_query("", DnsRecordType::A, true).is_err());
+    }
+
+    #[test]
+    fn parses_system_and_vpn_nameservers() {
+        let resolv = "# generated\nnameserver 127.0.0.53\nnameserver fe80::1%eth0\nsearch lan\n";
+        assert_eq!(
+            parse_resolv_conf(resolv),
+            vec![
+                "127.0.0.53".parse::<IpAddr>().unwrap(),
+                "fe80::1".parse().unwrap()
+            ]
+        );
+
+        let is_vpn = |name: &str| ["tun0", "wg0", "utun3"].contains(&name);
+        let resolvectl = "Global:\nLink 2 (eth0): 192.168.1.1\nLink 5 (tun0): 10.8.0.1 10.8.0.2\nLink 7 (wg0):\n";
+        assert_eq!(
+            parse_resolvectl_dns(resolvectl, is_vpn),
+            vec![
+                ("tun0".to_string(), "10.8.0.1".parse().unwrap()),
+                ("tun0".to_string(), "10.8.0.2".parse().unwrap())
+            ]
+        );
+
+        let scutil = "DNS configuration\n\nresolver #1\n  nameserver[0] : 192.168.1.1\n  if_index : 6 (en0)\n\nresolver #2\n  search domain[0] : corp.example\n  nameserver[0] : 10.20.0.53\n  if_index : 18 (utun3)\n  flags    : Supplemental, Request A records\n";
+        assert_eq!(
+            parse_scutil_dns(scutil, is_vpn),
+            vec![("utun3".to_string(), "10.20.0.53".parse().unwrap())]
+        );
+    }
+
+    #[tokio::test]
+    async fn queries_udp_and_tcp_resolvers() {
+        let server = spawn_dns_stand_in(false).await;
+        let results = query_dns(request(
+            "example.com",
+            DnsRecordType::Mx,
+            vec![format!("{server}"), format!("tcp://{server}")],
+        ))
+        .await
+        .unwrap();
+
+        for (result, transport) in results.iter().zip([DnsTransport::Udp, DnsTransport::Tcp]) {
+            assert!(result.error.is_none(), "{:?}", result.error);
+            assert_eq!(result.transport, Some(transport));
+            assert_eq!(result.response_code.as_deref(), Some("NOERROR"));
+            assert_eq!(result.flags, vec!["qr", "aa", "rd", "ra"]);
+            assert_eq!(result.answers.len(), 1);
+            let answer = &result.answers[0];
+            assert_eq!(answer.record_type, "MX");
+            assert_eq!(answer.ttl, Some(300));
+            assert_eq!(answer.data, "10 mail.example.com.");
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_truncated_udp_over_tcp() {
+        let server = spawn_dns_stand_in(true).await;
+        let results = query_dns(request(
+            "192.0.2.10",
+            DnsRecordType::Ptr,
+            vec![format!("udp://{server}")],
+        ))
+        .await
+        .unwrap();
+        let result = &results[0];
+        assert!(result.retried_over_tcp);
+        assert_eq!(result.transport, Some(DnsTransport::Tcp));
+        assert_eq!(result.answers[0].name, "10.2.0.192.in-addr.arpa.");
+        assert_eq!(result.answers[0].data, "host.example.");
+    }
+
+    #[tokio::test]
+    async fn queries_doh_resolver() {
+        let app = Router::new().route(
+            "/dns-query",
+            post(|body: Bytes| async move {
+                ([(CONTENT_TYPE, DNS_MESSAGE_TYPE)], answer(&body, false))
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let results = query_dns(request(
+            "example.com",
+            DnsRecordType::A,
+            vec![
+                format!("http://{addr}/dns-query"),
+                "udp://127.0.0.1:1".into(),
+            ],
+        ))
+        .await
+        .unwrap();
+        assert_eq!(results[0].transport, Some(DnsTransport::Doh));
+        assert_eq!(results[0].answers[0].data, "93.184.216.34");
+        assert!(results[1].error.is_some());
+    }
+}
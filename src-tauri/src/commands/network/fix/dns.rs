@@ -0,0 +1,657 @@
+use std::net::IpAddr;
+
+use super::{
+    interface::{mac_service, network_manager_running, target_interface},
+    runner::{read_output, CommandRunner},
+    FixPlan, FixStep, NetworkFixOptions, Platform, StateValue,
+};
+
+const GAI_CONF: &str = "/etc/gai.conf";
+const IPV4_MAPPED_PREFIX: &str = "::ffff:0:0/96";
+const GAI_PREFER_IPV4: &str = "precedence ::ffff:0:0/96  100";
+
+pub(super) fn set_dns_plan(
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    let servers = match parse_servers(&options.dns_servers) {
+        Ok(servers) => servers,
+        Err(err) => return FixPlan::unsupported(&err),
+    };
+    let interface = match target_interface(options, platform, runner) {
+        Ok(interface) => interface,
+        Err(err) => return FixPlan::unsupported(&err),
+    };
+    match platform {
+        Platform::Linux if network_manager_running(runner) => {
+            network_manager_dns_plan(&interface, &servers, runner)
+        }
+        Platform::Linux => resolved_dns_plan(&interface, &servers, runner),
+        Platform::MacOs => macos_dns_plan(&interface, &servers, runner),
+        Platform::Windows => windows_dns_plan(&interface, &servers, runner),
+        Platform::Other => FixPlan::unsupported("当前系统暂不支持自动设置 DNS 服务器。"),
+    }
+}
+
+pub(super) fn prefer_ipv4_plan(
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    match platform {
+        Platform::Linux => gai_conf_plan(runner),
+        Platform::MacOs => match target_interface(options, platform, runner) {
+            Ok(interface) => macos_ipv6_plan(&interface, runner),
+            Err(err) => FixPlan::unsupported(&err),
+        },
+        Platform::Windows => windows_prefix_policy_plan(runner),
+        Platform::Other => FixPlan::unsupported("当前系统暂不支持调整 IPv4/IPv6 优先级。"),
+    }
+}
+
+fn parse_servers(values: &[String]) -> Result<Vec<String>, String> {
+    let servers = values
+        .iter()
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            value
+                .parse::<IpAddr>()
+                .map(|ip| ip.to_string())
+                .map_err(|_| format!("DNS 服务器地址无效: {value}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if servers.is_empty() {
+        return Err("请至少指定一个 DNS 服务器。".into());
+    }
+    Ok(servers)
+}
+
+fn split_families(servers: &[String]) -> [(&'static str, Vec<&str>); 2] {
+    let family = |v6: bool| {
+        servers
+            .iter()
+            .filter(|server| server.contains(':') == v6)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+    };
+    [("ipv4", family(false)), ("ipv6", family(true))]
+}
+
+/// `nmcli device modify` 只改当前激活的连接，不写回配置文件，`reapply` 即可还原。
+fn network_manager_dns_plan(
+    interface: &str,
+    servers: &[String],
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    let current = read_output(
+        runner,
+        "nmcli",
+        &["-g", "IP4.DNS,IP6.DNS", "device", "show", interface],
+    )
+    .map(|output| {
+        output
+            .lines()
+            .flat_map(|line| line.split(" | "))
+            .map(str::trim)
+            .filter(|server| !server.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    });
+
+    let mut args = vec!["device".to_string(), "modify".into(), interface.into()];
+    for (family, list) in split_families(servers) {
+        if !list.is_empty() {
+            args.extend([
+                format!("{family}.dns"),
+                list.join(" "),
+                format!("{family}.ignore-auto-dns"),
+                "yes".into(),
+            ]);
+        }
+    }
+    FixPlan {
+        steps: vec![FixStep::Command {
+            program: "nmcli".into(),
+            args,
+            label: format!("已临时将 {interface} 的 DNS 设置为 {}", servers.join(", ")),
+            privileged: false,
+        }],
+        current_values: vec![StateValue::new(
+            format!("{interface} DNS"),
+            current.filter(|servers| !servers.is_empty()),
+        )],
+        revert: vec![FixStep::command(
+            "nmcli",
+            &["device", "reapply", interface],
+            format!("已恢复 {interface} 的 DNS 配置"),
+        )],
+        notes: vec!["只修改当前连接，重新连接后会恢复原配置。".into()],
+        ..FixPlan::default()
+    }
+}
+
+fn resolved_dns_plan(interface: &str, servers: &[String], runner: &dyn CommandRunner) -> FixPlan {
+    let current = read_output(runner, "resolvectl", &["dns", interface])
+        .and_then(|output| {
+            output
+                .split_once(':')
+                .map(|(_, servers)| servers.trim().to_string())
+        })
+        .filter(|servers| !servers.is_empty());
+    let args = [
+        &["dns", interface][..],
+        &servers.iter().map(String::as_str).collect::<Vec<_>>(),
+    ]
+    .concat();
+    FixPlan {
+        steps: vec![FixStep::command(
+            "resolvectl",
+            &args,
+            format!("已临时将 {interface} 的 DNS 设置为 {}", servers.join(", ")),
+        )
+        .privileged()],
+        current_values: vec![StateValue::new(format!("{interface} DNS"), current)],
+        revert: vec![FixStep::command(
+            "resolvectl",
+            &["revert", interface],
+            format!("已恢复 {interface} 的 DNS 配置"),
+        )
+        .privileged()],
+        notes: vec!["修改只在 systemd-resolved 运行期间有效，重启后恢复原配置。".into()],
+        ..FixPlan::default()
+    }
+}
+
+fn macos_dns_plan(interface: &str, servers: &[String], runner: &dyn CommandRunner) -> FixPlan {
+    let Some(service) = mac_service(runner, interface) else {
+        return FixPlan::unsupported(&format!("未找到 {interface} 对应的网络服务。"));
+    };
+    let mut plan = FixPlan::default();
+    let current =
+        read_output(runner, "networksetup", &["-getdnsservers", &service]).map(|output| {
+            // 没有手动设置时输出 "There aren't any DNS Servers set on ..."
+            output
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.parse::<IpAddr>().is_ok())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        });
+    let set_servers = |list: &[&str], label: String| {
+        let args = [&["-setdnsservers", service.as_str()][..], list].concat();
+        FixStep::command("networksetup", &args, label).privileged()
+    };
+    plan.steps.push(set_servers(
+        &servers.iter().map(String::as_str).collect::<Vec<_>>(),
+        format!("已将 {service} 的 DNS 设置为 {}", servers.join(", ")),
+    ));
+    match current {
+        Some(current) => {
+            plan.current_values.push(StateValue::new(
+                format!("{service} DNS"),
+                (!current.is_empty()).then(|| current.join(" ")),
+            ));
+            let previous = if current.is_empty() {
+                vec!["Empty"]
+            } else {
+                current.iter().map(String::as_str).collect()
+            };
+            plan.revert.push(set_servers(
+                &previous,
+                format!("已恢复 {service} 的 DNS 配置"),
+            ));
+        }
+        None => plan.notes.push(format!(
+            "无法读取 {service} 的 DNS 配置，执行后将无法撤销。"
+        )),
+    }
+    plan
+}
+
+fn windows_dns_plan(interface: &str, servers: &[String], runner: &dyn CommandRunner) -> FixPlan {
+    let mut plan = FixPlan::default();
+    for (family, wanted) in split_families(servers) {
+        if wanted.is_empty() {
+            continue;
+        }
+        let name = format!("name={interface}");
+        plan.steps.extend(windows_dns_steps(
+            family,
+            &name,
+            &wanted,
+            format!(
+                "已将 {interface} 的 {family} DNS 设置为 {}",
+                wanted.join(", ")
+            ),
+        ));
+
+        let Some(output) = read_output(
+            runner,
+            "netsh",
+            &["interface", family, "show", "dnsservers", &name],
+        ) else {
+            plan.notes.push(format!(
+                "无法读取 {interface} 的 {family} DNS 配置，执行后将无法撤销。"
+            ));
+            continue;
+        };
+        let dhcp = output.contains("DHCP");
+        let current = output
+            .split_whitespace()
+            .filter(|token| token.parse::<IpAddr>().is_ok())
+            .collect::<Vec<_>>();
+        plan.current_values.push(StateValue::new(
+            format!("{interface} {family} DNS"),
+            Some(match (dhcp, current.is_empty()) {
+                (true, _) => format!("DHCP {}", current.join(" ")).trim_end().to_string(),
+                (false, true) => "无".into(),
+                (false, false) => current.join(" "),
+            }),
+        ));
+        let label = format!("已恢复 {interface} 的 {family} DNS 配置");
+        let set_args = ["interface", family, "set", "dnsservers", name.as_str()];
+        if dhcp {
+            let args = [&set_args[..], &["source=dhcp"]].concat();
+            plan.revert
+                .push(FixStep::command("netsh", &args, label).privileged());
+        } else if current.is_empty() {
+            let args = [&set_args[..], &["source=static", "address=none"]].concat();
+            plan.revert
+                .push(FixStep::command("netsh", &args, label).privileged());
+        } else {
+            plan.revert
+                .extend(windows_dns_steps(family, &name, &current, label));
+        }
+    }
+    plan
+}
+
+/// 第一个地址用 set 替换原有列表，其余的按顺序追加。
+fn windows_dns_steps(family: &str, name: &str, servers: &[&str], label: String) -> Vec<FixStep> {
+    servers
+        .iter()
+        .enumerate()
+        .map(|(index, server)| {
+            let address = format!("address={server}");
+            if index == 0 {
+                FixStep::command(
+                    "netsh",
+                    &[
+                        "interface",
+                        family,
+                        "set",
+                        "dnsservers",
+                        name,
+                        "source=static",
+                        &address,
+                        "register=primary",
+                        "validate=no",
+                    ],
+                    label.clone(),
+                )
+            } else {
+                FixStep::command(
+                    "netsh",
+                    &[
+                        "interface",
+                        family,
+                        "add",
+                        "dnsservers",
+                        name,
+                        &address,
+                        &format!("index={}", index + 1),
+                        "validate=no",
+                    ],
+                    format!("已添加 DNS 服务器 {server}"),
+                )
+            }
+            .privileged()
+        })
+        .collect()
+}
+
+/// glibc 按 gai.conf 的 precedence 排序解析结果，提高 IPv4 映射地址的优先级即可优先使用 IPv4。
+fn gai_conf_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let current = read_output(runner, "cat", &[GAI_CONF]);
+    let is_rule = |line: &&str| {
+        let mut tokens = line.split_whitespace();
+        tokens.next() == Some("precedence") && tokens.next() == Some(IPV4_MAPPED_PREFIX)
+    };
+    let active = current
+        .as_deref()
+        .and_then(|content| content.lines().map(str::trim).find(is_rule));
+    let mut plan = FixPlan {
+        current_values: vec![StateValue::new(
+            format!("{GAI_CONF} {IPV4_MAPPED_PREFIX}"),
+            active.map(str::to_string),
+        )],
+        ..FixPlan::default()
+    };
+    if active.is_some_and(|line| line.split_whitespace().nth(2) == Some("100")) {
+        plan.notes.push("系统已优先使用 IPv4，无需修改。".into());
+        return plan;
+    }
+
+    let content = match &current {
+        Some(content) if active.is_some() => {
+            content
+                .lines()
+                .map(|line| {
+                    if is_rule(&line.trim()) {
+                        GAI_PREFER_IPV4
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+                + "\n"
+        }
+        Some(content) => format!(
+            "{}\n# 优先使用 IPv4（网络诊断添加）\n{GAI_PREFER_IPV4}\n",
+            content.trim_end()
+        ),
+        None => format!("# 优先使用 IPv4（网络诊断添加）\n{GAI_PREFER_IPV4}\n"),
+    };
+    plan.steps.push(write_file_step(
+        GAI_CONF,
+        &content,
+        "已在 /etc/gai.conf 中设置优先使用 IPv4",
+    ));
+    plan.revert.push(match &current {
+        Some(content) => write_file_step(GAI_CONF, content, "已恢复 /etc/gai.conf"),
+        None => {
+            FixStep::command("rm", &["-f", GAI_CONF], "已删除新建的 /etc/gai.conf").privileged()
+        }
+    });
+    plan.notes
+        .push("对新建立的连接生效，已运行的程序可能需要重启。".into());
+    plan
+}
+
+/// 内容作为位置参数传给 sh，不经过 shell 解析。
+fn write_file_step(path: &str, content: &str, label: &str) -> FixStep {
+    FixStep::command(
+        "sh",
+        &["-c", "printf '%s' \"$1\" > \"$2\"", "sh", content, path],
+        label,
+    )
+    .privileged()
+}
+
+/// macOS 没有全局的地址优先级配置，只能关闭该网络服务的 IPv6。
+fn macos_ipv6_plan(interface: &str, runner: &dyn CommandRunner) -> FixPlan {
+    let Some(service) = mac_service(runner, interface) else {
+        return FixPlan::unsupported(&format!("未找到 {interface} 对应的网络服务。"));
+    };
+    let mode = read_output(runner, "networksetup", &["-getinfo", &service]).and_then(|output| {
+        output.lines().find_map(|line| {
+            line.strip_prefix("IPv6:")
+                .map(|mode| mode.trim().to_string())
+        })
+    });
+    let restore = match mode.as_deref() {
+        Some("Off") => {
+            return FixPlan {
+                current_values: vec![StateValue::new(format!("{service} IPv6"), mode)],
+                notes: vec![format!("{service} 已关闭 IPv6，无需修改。")],
+                ..FixPlan::default()
+            }
+        }
+        Some("Automatic") => "-setv6automatic",
+        Some(mode) if mode.starts_with("Link") => "-setv6LinkLocal",
+        _ => {
+            return FixPlan::unsupported(&format!(
+                "{service} 的 IPv6 为手动配置或无法读取，请手动调整。"
+            ))
+        }
+    };
+    FixPlan {
+        steps: vec![FixStep::command(
+            "networksetup",
+            &["-setv6off", &service],
+            format!("已关闭 {service} 的 IPv6"),
+        )
+        .privileged()],
+        current_values: vec![StateValue::new(format!("{service} IPv6"), mode)],
+        revert: vec![FixStep::command(
+            "networksetup",
+            &[restore, &service],
+            format!("已恢复 {service} 的 IPv6 配置"),
+        )
+        .privileged()],
+        notes: vec!["macOS 无法单独调整地址优先级，将关闭该网络服务的 IPv6。".into()],
+        ..FixPlan::default()
+    }
+}
+
+fn windows_prefix_policy_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let output = read_output(
+        runner,
+        "netsh",
+        &["interface", "ipv6", "show", "prefixpolicies"],
+    );
+    // 表格列依次为 Precedence、Label、Prefix
+    let row = output.as_deref().and_then(|output| {
+        output.lines().find_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            (columns.len() == 3 && columns[2] == IPV4_MAPPED_PREFIX)
+                .then(|| (columns[0].to_string(), columns[1].to_string()))
+        })
+    });
+    let mut plan = FixPlan {
+        current_values: vec![StateValue::new(
+            format!("IPv6 前缀策略 {IPV4_MAPPED_PREFIX}"),
+            row.as_ref()
+                .map(|(precedence, label)| format!("precedence={precedence} label={label}")),
+        )],
+        ..FixPlan::default()
+    };
+    if row
+        .as_ref()
+        .is_some_and(|(precedence, _)| precedence == "100")
+    {
+        plan.notes.push("系统已优先使用 IPv4，无需修改。".into());
+        return plan;
+    }
+    plan.steps.push(
+        FixStep::command(
+            "netsh",
+            &[
+                "interface",
+                "ipv6",
+                "set",
+                "prefixpolicy",
+                IPV4_MAPPED_PREFIX,
+                "100",
+                "4",
+            ],
+            "已提高 IPv4 地址的优先级",
+        )
+        .privileged(),
+    );
+    let label = "已恢复 IPv6 前缀策略";
+    match (&output, row) {
+        (_, Some((precedence, prefix_label))) => plan.revert.push(
+            FixStep::command(
+                "netsh",
+                &[
+                    "interface",
+                    "ipv6",
+                    "set",
+                    "prefixpolicy",
+                    IPV4_MAPPED_PREFIX,
+                    &precedence,
+                    &prefix_label,
+                ],
+                label,
+            )
+            .privileged(),
+        ),
+        (Some(_), None) => plan.revert.push(
+            FixStep::command(
+                "netsh",
+                &[
+                    "interface",
+                    "ipv6",
+                    "delete",
+                    "prefixpolicy",
+                    IPV4_MAPPED_PREFIX,
+                ],
+                label,
+            )
+            .privileged(),
+        ),
+        (None, None) => plan
+            .notes
+            .push("无法读取 IPv6 前缀策略，执行后将无法撤销。".into()),
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::runner::fake::FakeRunner;
+    use super::*;
+
+    fn options(servers: &[&str], interface: &str) -> NetworkFixOptions {
+        NetworkFixOptions {
+            dns_servers: servers.iter().map(|server| server.to_string()).collect(),
+            interface: Some(interface.into()),
+        }
+    }
+
+    #[test]
+    fn plans_dns_override_per_platform() {
+        let nm = FakeRunner::default()
+            .respond("nmcli -t -f RUNNING general", true, "running\n")
+            .respond(
+                "nmcli -g IP4.DNS,IP6.DNS device show wlan0",
+                true,
+                "192.168.1.1 | 192.168.1.2\n\n",
+            );
+        let opts = options(&["1.1.1.1", " 2606:4700:4700::1111", "8.8.8.8"], "wlan0");
+        let plan = set_dns_plan(&opts, Platform::Linux, &nm);
+        assert_eq!(
+            plan.steps[0].display(),
+            "nmcli device modify wlan0 ipv4.dns '1.1.1.1 8.8.8.8' ipv4.ignore-auto-dns yes ipv6.dns 2606:4700:4700::1111 ipv6.ignore-auto-dns yes"
+        );
+        assert_eq!(
+            plan.current_values[0].value.as_deref(),
+            Some("192.168.1.1 192.168.1.2")
+        );
+        assert_eq!(plan.revert[0].display(), "nmcli device reapply wlan0");
+
+        let resolved = FakeRunner::default().respond(
+            "resolvectl dns wlan0",
+            true,
+            "Link 3 (wlan0): 10.0.0.53\n",
+        );
+        let plan = set_dns_plan(&opts, Platform::Linux, &resolved);
+        assert_eq!(plan.current_values[0].value.as_deref(), Some("10.0.0.53"));
+        assert_eq!(plan.revert[0].display(), "resolvectl revert wlan0");
+
+        let windows = FakeRunner::default().respond(
+            "netsh interface ipv4 show dnsservers name=Wi-Fi",
+            true,
+            "Configuration for interface \"Wi-Fi\"\n    DNS servers configured through DHCP:  192.168.1.1\n    Register with which suffix:           Primary only\n",
+        );
+        let plan = set_dns_plan(
+            &options(&["1.1.1.1", "8.8.8.8"], "Wi-Fi"),
+            Platform::Windows,
+            &windows,
+        );
+        assert_eq!(
+            plan.steps[1].display(),
+            "netsh interface ipv4 add dnsservers name=Wi-Fi address=8.8.8.8 index=2 validate=no"
+        );
+        assert_eq!(
+            plan.revert[0].display(),
+            "netsh interface ipv4 set dnsservers name=Wi-Fi source=dhcp"
+        );
+
+        assert!(
+            set_dns_plan(&options(&["dns.example"], "eth0"), Platform::Linux, &nm)
+                .failure_hint
+                .is_some()
+        );
+        assert!(set_dns_plan(&options(&[], "eth0"), Platform::Linux, &nm)
+            .failure_hint
+            .is_some());
+    }
+
+    #[test]
+    fn plans_macos_dns_override_with_revert_to_empty() {
+        let runner = FakeRunner::default()
+            .respond(
+                "networksetup -listnetworkserviceorder",
+                true,
+                "(1) Wi-Fi\n(Hardware Port: Wi-Fi, Device: en0)\n",
+            )
+            .respond(
+                "networksetup -getdnsservers Wi-Fi",
+                true,
+                "There aren't any DNS Servers set on Wi-Fi.\n",
+            );
+        let plan = set_dns_plan(&options(&["9.9.9.9"], "en0"), Platform::MacOs, &runner);
+        assert_eq!(
+            plan.steps[0].display(),
+            "networksetup -setdnsservers Wi-Fi 9.9.9.9"
+        );
+        assert_eq!(plan.current_values[0].value, None);
+        assert_eq!(
+            plan.revert[0].display(),
+            "networksetup -setdnsservers Wi-Fi Empty"
+        );
+    }
+
+    #[test]
+    fn plans_prefer_ipv4() {
+        let runner = FakeRunner::default().respond(
+            "cat /etc/gai.conf",
+            true,
+            "# Configuration for getaddrinfo(3).\n#precedence ::ffff:0:0/96  100\n",
+        );
+        let plan = prefer_ipv4_plan(&NetworkFixOptions::default(), Platform::Linux, &runner);
+        let FixStep::Command { args, .. } = &plan.steps[0] else {
+            panic!("expected a command step");
+        };
+        assert_eq!(
+            args[3],
+            "# Configuration for getaddrinfo(3).\n#precedence ::ffff:0:0/96  100\n# 优先使用 IPv4（网络诊断添加）\nprecedence ::ffff:0:0/96  100\n"
+        );
+        assert_eq!(args[4], GAI_CONF);
+        let FixStep::Command { args, .. } = &plan.revert[0] else {
+            panic!("expected a command step");
+        };
+        assert!(args[3].starts_with("# Configuration"));
+
+        let missing = prefer_ipv4_plan(
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            &FakeRunner::default(),
+        );
+        assert_eq!(missing.revert[0].display(), "rm -f /etc/gai.conf");
+
+        let done = FakeRunner::default().respond(
+            "cat /etc/gai.conf",
+            true,
+            "precedence ::ffff:0:0/96 100\n",
+        );
+        let plan = prefer_ipv4_plan(&NetworkFixOptions::default(), Platform::Linux, &done);
+        assert!(plan.steps.is_empty() && plan.failure_hint.is_none());
+
+        let windows = FakeRunner::default().respond(
+            "netsh interface ipv6 show prefixpolicies",
+            true,
+            "Precedence  Label  Prefix\n----------  -----  --------------------------------\n        50      0  ::1/128\n        40      1  ::/0\n        35      4  ::ffff:0:0/96\n",
+        );
+        let plan = prefer_ipv4_plan(&NetworkFixOptions::default(), Platform::Windows, &windows);
+        assert_eq!(
+            plan.revert[0].display(),
+            "netsh interface ipv6 set prefixpolicy ::ffff:0:0/96 35 4"
+        );
+    }
+}
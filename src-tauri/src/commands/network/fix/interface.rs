@@ -0,0 +1,355 @@
+use super::{
+    privilege::chain_privileged,
+    runner::{read_output, CommandRunner},
+    FixPlan, FixStep, NetworkFixOptions, Platform, StepMode,
+};
+
+/// 参数里指定的网卡，名称会原样传给系统命令，需要先校验。
+fn requested_interface(options: &NetworkFixOptions) -> Result<Option<String>, String> {
+    let Some(name) = options
+        .interface
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(None);
+    };
+    if name.starts_with('-') || name.chars().any(char::is_control) {
+        return Err(format!("网卡名称无效: {name}"));
+    }
+    Ok(Some(name.to_string()))
+}
+
+/// 要操作的网卡：优先使用参数，否则取默认路由所在的网卡。
+pub(super) fn target_interface(
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> Result<String, String> {
+    if let Some(name) = requested_interface(options)? {
+        return Ok(name);
+    }
+    default_interface(platform, runner).ok_or_else(|| "未找到默认网卡，请指定网卡名称。".into())
+}
+
+fn default_interface(platform: Platform, runner: &dyn CommandRunner) -> Option<String> {
+    match platform {
+        Platform::Linux => read_output(runner, "ip", &["route", "show", "default"])?
+            .lines()
+            .find_map(|line| {
+                let mut tokens = line.split_whitespace();
+                tokens.find(|token| *token == "dev")?;
+                tokens.next().map(str::to_string)
+            }),
+        Platform::MacOs => read_output(runner, "route", &["-n", "get", "default"])?
+            .lines()
+            .find_map(|line| {
+                line.trim()
+                    .strip_prefix("interface:")
+                    .map(|name| name.trim().to_string())
+            }),
+        Platform::Windows | Platform::Other => None,
+    }
+}
+
+pub(super) fn network_manager_running(runner: &dyn CommandRunner) -> bool {
+    read_output(runner, "nmcli", &["-t", "-f", "RUNNING", "general"])
+        .is_some_and(|state| state.trim() == "running")
+}
+
+/// networksetup 按网络服务名称操作，把设备名（如 en0）换成服务名；本身就是服务名时原样返回。
+pub(super) fn mac_service(runner: &dyn CommandRunner, name: &str) -> Option<String> {
+    let output = read_output(runner, "networksetup", &["-listnetworkserviceorder"])?;
+    let mut service = None;
+    for line in output.lines().map(str::trim) {
+        let Some(rest) = line.strip_prefix('(') else {
+            continue;
+        };
+        if let Some(port) = rest.strip_prefix("Hardware Port:") {
+            let device = port
+                .rsplit_once("Device:")
+                .map(|(_, device)| device.trim_end_matches(')').trim());
+            if device == Some(name) {
+                return service;
+            }
+        } else if let Some((_, current)) = rest.split_once(") ") {
+            // 带星号的是已停用的服务
+            let current = current.trim();
+            if current == name {
+                return Some(current.to_string());
+            }
+            service = Some(current.to_string());
+        }
+    }
+    None
+}
+
+pub(super) fn reset_interface_plan(
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    let interface = match target_interface(options, platform, runner) {
+        Ok(interface) => interface,
+        Err(err) => return FixPlan::unsupported(&err),
+    };
+    let label = format!("已重启 {interface}");
+    let steps = match platform {
+        // 停用和启用放在同一次提权里，避免只停用成功后网卡一直处于关闭状态；
+        // nmcli disconnect 还会关闭自动连接，单独授权的 connect 被拒绝时不会自己恢复
+        Platform::Linux if network_manager_running(runner) => vec![chain_privileged(
+            &[
+                FixStep::command(
+                    "nmcli",
+                    &["device", "disconnect", &interface],
+                    format!("已断开 {interface}"),
+                ),
+                FixStep::command(
+                    "nmcli",
+                    &["device", "connect", &interface],
+                    format!("已重新连接 {interface}"),
+                ),
+            ],
+            label,
+            platform,
+        )],
+        Platform::Linux => vec![chain_privileged(
+            &[
+                FixStep::command(
+                    "ip",
+                    &["link", "set", "dev", &interface, "down"],
+                    format!("已停用 {interface}"),
+                ),
+                FixStep::command(
+                    "ip",
+                    &["link", "set", "dev", &interface, "up"],
+                    format!("已启用 {interface}"),
+                ),
+            ],
+            label,
+            platform,
+        )],
+        Platform::MacOs => vec![chain_privileged(
+            &[
+                FixStep::command(
+                    "ifconfig",
+                    &[&interface, "down"],
+                    format!("已停用 {interface}"),
+                ),
+                FixStep::command(
+                    "ifconfig",
+                    &[&interface, "up"],
+                    format!("已启用 {interface}"),
+                ),
+            ],
+            label,
+            platform,
+        )],
+        Platform::Windows => {
+            let name = format!("name={interface}");
+            vec![chain_privileged(
+                &[
+                    FixStep::command(
+                        "netsh",
+                        &["interface", "set", "interface", &name, "admin=disabled"],
+                        format!("已禁用 {interface}"),
+                    ),
+                    FixStep::command(
+                        "netsh",
+                        &["interface", "set", "interface", &name, "admin=enabled"],
+                        format!("已启用 {interface}"),
+                    ),
+                ],
+                label,
+                platform,
+            )]
+        }
+        Platform::Other => return FixPlan::unsupported("当前系统暂不支持自动重启网卡。"),
+    };
+    FixPlan {
+        steps,
+        mode: StepMode::Sequential,
+        notes: vec!["网卡重启期间会短暂断网，该操作无需撤销。".into()],
+        ..FixPlan::default()
+    }
+}
+
+pub(super) fn renew_dhcp_plan(
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    // Windows 不指定网卡时续租所有网卡
+    let interface = match (platform, requested_interface(options)) {
+        (_, Err(err)) => return FixPlan::unsupported(&err),
+        (Platform::Windows, Ok(interface)) => interface,
+        _ => match target_interface(options, platform, runner) {
+            Ok(interface) => Some(interface),
+            Err(err) => return FixPlan::unsupported(&err),
+        },
+    };
+    let name = interface.clone().unwrap_or_default();
+    let mut plan = match platform {
+        Platform::Linux if network_manager_running(runner) => FixPlan {
+            steps: vec![FixStep::command(
+                "nmcli",
+                &["device", "connect", &name],
+                format!("已重新激活 {name} 并重新获取地址"),
+            )],
+            ..FixPlan::default()
+        },
+        Platform::Linux => FixPlan {
+            steps: vec![
+                FixStep::command(
+                    "networkctl",
+                    &["renew", &name],
+                    format!("已通过 networkctl 续租 {name}"),
+                ),
+                FixStep::command("dhclient", &[&name], format!("已通过 dhclient 续租 {name}"))
+                    .privileged(),
+            ],
+            mode: StepMode::FirstSuccess,
+            failure_hint: Some("未能自动续租 DHCP，请手动重新连接网络。".into()),
+            ..FixPlan::default()
+        },
+        Platform::MacOs => FixPlan {
+            steps: vec![FixStep::command(
+                "ipconfig",
+                &["set", &name, "DHCP"],
+                format!("已为 {name} 重新获取 DHCP 地址"),
+            )
+            .privileged()],
+            ..FixPlan::default()
+        },
+        Platform::Windows => {
+            let target = interface
+                .as_deref()
+                .map(|name| vec![name])
+                .unwrap_or_default();
+            FixPlan {
+                steps: vec![chain_privileged(
+                    &[
+                        FixStep::command(
+                            "ipconfig",
+                            &[&["/release"], target.as_slice()].concat(),
+                            "已释放 DHCP 地址",
+                        ),
+                        FixStep::command(
+                            "ipconfig",
+                            &[&["/renew"], target.as_slice()].concat(),
+                            "已重新获取 DHCP 地址",
+                        ),
+                    ],
+                    "已释放并重新获取 DHCP 地址".into(),
+                    platform,
+                )],
+                mode: StepMode::Sequential,
+                ..FixPlan::default()
+            }
+        }
+        Platform::Other => return FixPlan::unsupported("当前系统暂不支持自动续租 DHCP。"),
+    };
+    plan.notes
+        .push("续租 DHCP 不涉及配置修改，无需撤销。".into());
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::runner::fake::FakeRunner;
+    use super::*;
+
+    fn options(interface: Option<&str>) -> NetworkFixOptions {
+        NetworkFixOptions {
+            interface: interface.map(str::to_string),
+            ..NetworkFixOptions::default()
+        }
+    }
+
+    #[test]
+    fn resolves_default_interface_and_mac_service() {
+        let runner = FakeRunner::default()
+            .respond(
+                "ip route show default",
+                true,
+                "default via 192.168.1.1 dev wlp2s0 proto dhcp metric 600\n",
+            )
+            .respond(
+                "route -n get default",
+                true,
+                "   route to: default\n  gateway: 10.0.0.1\n  interface: en0\n",
+            )
+            .respond(
+                "networksetup -listnetworkserviceorder",
+                true,
+                "An asterisk (*) denotes that a network service is disabled.\n(1) USB LAN\n(Hardware Port: USB 10/100/1000 LAN, Device: en7)\n\n(2) Wi-Fi\n(Hardware Port: Wi-Fi, Device: en0)\n\n(*) Bluetooth PAN\n(Hardware Port: Bluetooth PAN, Device: en5)\n",
+            );
+        let none = options(None);
+        assert_eq!(
+            target_interface(&none, Platform::Linux, &runner).unwrap(),
+            "wlp2s0"
+        );
+        assert_eq!(
+            target_interface(&none, Platform::MacOs, &runner).unwrap(),
+            "en0"
+        );
+        assert!(target_interface(&none, Platform::Windows, &runner).is_err());
+        assert!(target_interface(&options(Some("-rf")), Platform::Linux, &runner).is_err());
+
+        assert_eq!(mac_service(&runner, "en0").as_deref(), Some("Wi-Fi"));
+        assert_eq!(
+            mac_service(&runner, "en5").as_deref(),
+            Some("Bluetooth PAN")
+        );
+        assert_eq!(mac_service(&runner, "USB LAN").as_deref(), Some("USB LAN"));
+        assert_eq!(mac_service(&runner, "en9"), None);
+    }
+
+    #[test]
+    fn plans_interface_reset_and_dhcp_renew() {
+        let nm = FakeRunner::default().respond("nmcli -t -f RUNNING general", true, "running\n");
+        let plan = reset_interface_plan(&options(Some("eth0")), Platform::Linux, &nm);
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(
+            plan.steps[0].display(),
+            "sh -c 'nmcli device disconnect eth0 && nmcli device connect eth0'"
+        );
+        assert!(plan.revert.is_empty());
+        let plan = renew_dhcp_plan(&options(Some("eth0")), Platform::Linux, &nm);
+        assert_eq!(plan.steps[0].display(), "nmcli device connect eth0");
+
+        let plain = FakeRunner::default();
+        let plan = reset_interface_plan(&options(Some("eth0")), Platform::Linux, &plain);
+        assert_eq!(plan.mode, StepMode::Sequential);
+        assert_eq!(
+            plan.steps[0].display(),
+            "sh -c 'ip link set dev eth0 down && ip link set dev eth0 up'"
+        );
+        let plan = renew_dhcp_plan(&options(Some("eth0")), Platform::Linux, &plain);
+        assert_eq!(plan.mode, StepMode::FirstSuccess);
+        assert_eq!(plan.steps[1].display(), "dhclient eth0");
+
+        let plan = renew_dhcp_plan(&options(None), Platform::Windows, &plain);
+        let FixStep::Command { program, args, .. } = &plan.steps[0] else {
+            panic!("expected a command step");
+        };
+        assert_eq!(program, "powershell");
+        assert_eq!(
+            args[2],
+            "ipconfig /release; if ($LASTEXITCODE) { exit $LASTEXITCODE }; ipconfig /renew; exit $LASTEXITCODE"
+        );
+        let plan = reset_interface_plan(&options(Some("Ethernet 2")), Platform::Windows, &plain);
+        let FixStep::Command { args, .. } = &plan.steps[0] else {
+            panic!("expected a command step");
+        };
+        assert_eq!(
+            args[2],
+            "netsh interface set interface 'name=Ethernet 2' admin=disabled; if ($LASTEXITCODE) { exit $LASTEXITCODE }; netsh interface set interface 'name=Ethernet 2' admin=enabled; exit $LASTEXITCODE"
+        );
+        assert!(
+            reset_interface_plan(&options(None), Platform::Windows, &plain)
+                .failure_hint
+                .is_some()
+        );
+    }
+}
@@ -0,0 +1,709 @@
+mod dns;
+mod interface;
+mod plans;
+mod privilege;
+mod runner;
+mod snapshots;
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime::spawn_blocking, AppHandle, Manager};
+
+use plans::build_plan;
+use runner::{CommandRunner, SystemRunner};
+pub use snapshots::FixSnapshot;
+use snapshots::SnapshotStore;
+
+const SNAPSHOT_FILE: &str = "network-fix-snapshots.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NetworkFixAction {
+    ClearProxyEnv,
+    ResetSystemProxy,
+    FlushDnsCache,
+    SetDnsServers,
+    PreferIpv4,
+    ResetInterface,
+    RenewDhcp,
+}
+
+/// 部分修复动作需要的参数。
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NetworkFixOptions {
+    /// `set-dns-servers` 使用的 DNS 服务器。
+    dns_servers: Vec<String>,
+    /// 目标网卡（Windows 上为连接名称），为空时使用默认路由所在的网卡。
+    interface: Option<String>,
+}
+
+/// 修复动作中的一步：执行一条命令或修改一个环境变量。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FixStep {
+    #[serde(rename_all = "camelCase")]
+    Command {
+        program: String,
+        args: Vec<String>,
+        label: String,
+        /// 需要管理员权限，生成计划时已按平台加上提权方式。
+        #[serde(default)]
+        privileged: bool,
+    },
+    Env {
+        key: String,
+        /// 为空表示删除该变量。
+        value: Option<String>,
+        label: String,
+    },
+}
+
+/// 修复前读取到的一项系统配置。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateValue {
+    key: String,
+    value: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFixResult {
+    action: NetworkFixAction,
+    success: bool,
+    messages: Vec<String>,
+    /// 为 true 时只列出将要执行的步骤，没有修改系统。
+    dry_run: bool,
+    steps: Vec<FixStep>,
+    current_values: Vec<StateValue>,
+    /// 执行前保存的快照，可交给 `revert_network_fix` 撤销。
+    snapshot_id: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Platform {
+    Linux,
+    MacOs,
+    Windows,
+    Other,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "linux") {
+            Self::Linux
+        } else if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(target_os = "windows") {
+            Self::Windows
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// 计划中多个步骤的执行方式。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum StepMode {
+    /// 依次执行全部步骤，有一条成功即视为成功。
+    #[default]
+    All,
+    /// 多个候选命令，只要有一条成功就停止。
+    FirstSuccess,
+    /// 步骤之间有先后依赖，遇到失败立即停止，全部成功才算成功。
+    Sequential,
+}
+
+/// 某个修复动作在当前系统上的完整计划。
+#[derive(Default)]
+pub(super) struct FixPlan {
+    steps: Vec<FixStep>,
+    mode: StepMode,
+    current_values: Vec<StateValue>,
+    revert: Vec<FixStep>,
+    notes: Vec<String>,
+    /// 所有步骤都失败（或没有可执行的步骤）时的提示。
+    failure_hint: Option<String>,
+}
+
+impl FixPlan {
+    fn unsupported(message: &str) -> Self {
+        Self {
+            failure_hint: Some(message.into()),
+            ..Self::default()
+        }
+    }
+}
+
+impl FixStep {
+    fn command(program: &str, args: &[&str], label: impl Into<String>) -> Self {
+        Self::Command {
+            program: program.into(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            label: label.into(),
+            privileged: false,
+        }
+    }
+
+    fn privileged(mut self) -> Self {
+        if let Self::Command { privileged, .. } = &mut self {
+            *privileged = true;
+        }
+        self
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Self::Command { label, .. } | Self::Env { label, .. } => label,
+        }
+    }
+
+    /// 可直接在终端执行的形式。
+    fn display(&self) -> String {
+        match self {
+            Self::Command { program, args, .. } => std::iter::once(program.as_str())
+                .chain(args.iter().map(String::as_str))
+                .map(posix_quote)
+                .collect::<Vec<_>>()
+                .join(" "),
+            Self::Env {
+                key,
+                value: Some(value),
+                ..
+            } => format!("export {key}={}", posix_quote(value)),
+            Self::Env {
+                key, value: None, ..
+            } => format!("unset {key}"),
+        }
+    }
+
+    fn apply(&self, runner: &dyn CommandRunner) -> Result<(), String> {
+        match self {
+            Self::Command { program, args, .. } => match runner.run(program, args) {
+                Ok(output) if output.success => Ok(()),
+                Ok(output) => Err(output.failure_reason()),
+                Err(err) => Err(format!("无法执行 {program}: {err}")),
+            },
+            Self::Env { key, value, .. } => {
+                runner.set_env_var(key, value.as_deref());
+                Ok(())
+            }
+        }
+    }
+}
+
+impl StateValue {
+    fn new(key: String, value: Option<String>) -> Self {
+        Self { key, value }
+    }
+}
+
+/// 按 POSIX shell 规则加单引号，单引号内的 `$`、反引号和 `\` 都不会被解释。
+fn posix_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_./:=,@%+".contains(ch));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+#[tauri::command]
+pub async fn run_network_fix_action(
+    app: AppHandle,
+    action: NetworkFixAction,
+    dry_run: Option<bool>,
+    options: Option<NetworkFixOptions>,
+) -> Result<NetworkFixResult, String> {
+    let store = SnapshotStore::new(snapshot_path(&app)?);
+    let dry_run = dry_run.unwrap_or(false);
+    let options = options.unwrap_or_default();
+    spawn_blocking(move || {
+        run_fix(
+            &SystemRunner,
+            &store,
+            action,
+            &options,
+            Platform::current(),
+            dry_run,
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// 按快照恢复修复前的配置，不指定快照时撤销最近一次修复。
+#[tauri::command]
+pub async fn revert_network_fix(
+    app: AppHandle,
+    snapshot_id: Option<String>,
+) -> Result<NetworkFixResult, String> {
+    let store = SnapshotStore::new(snapshot_path(&app)?);
+    spawn_blocking(move || revert_fix(&SystemRunner, &store, snapshot_id.as_deref()))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_network_fix_snapshots(app: AppHandle) -> Result<Vec<FixSnapshot>, String> {
+    let store = SnapshotStore::new(snapshot_path(&app)?);
+    spawn_blocking(move || store.list())
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+fn run_fix(
+    runner: &dyn CommandRunner,
+    store: &SnapshotStore,
+    action: NetworkFixAction,
+    options: &NetworkFixOptions,
+    platform: Platform,
+    dry_run: bool,
+) -> Result<NetworkFixResult, String> {
+    let plan = build_plan(action, options, platform, runner);
+    let mut result = NetworkFixResult {
+        action,
+        success: true,
+        messages: plan.notes.clone(),
+        dry_run,
+        steps: plan.steps.clone(),
+        current_values: plan.current_values.clone(),
+        snapshot_id: None,
+    };
+    if dry_run {
+        result.messages.extend(
+            plan.steps
+                .iter()
+                .map(|step| format!("将执行：{}", step.display())),
+        );
+        return Ok(result);
+    }
+
+    // 先落盘快照再修改系统，中途崩溃也能撤销
+    if !plan.revert.is_empty() {
+        let id = uuid::Uuid::new_v4().to_string();
+        store.push(FixSnapshot {
+            id: id.clone(),
+            action,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            values: plan.current_values.clone(),
+            revert: plan.revert.clone(),
+        })?;
+        result.snapshot_id = Some(id);
+    }
+
+    let outcome = execute_steps(runner, &plan.steps, plan.mode, plan.failure_hint.as_deref());
+    result.success = outcome.success;
+    result.messages.extend(outcome.messages);
+    if !outcome.success {
+        // 什么都没改动，快照没有意义
+        if let Some(id) = result.snapshot_id.take() {
+            store.take(Some(&id))?;
+        }
+    }
+    Ok(result)
+}
+
+fn revert_fix(
+    runner: &dyn CommandRunner,
+    store: &SnapshotStore,
+    snapshot_id: Option<&str>,
+) -> Result<NetworkFixResult, String> {
+    let snapshot = store.take(snapshot_id)?.ok_or_else(|| match snapshot_id {
+        Some(id) => format!("未找到修复快照 {id}"),
+        None => "没有可撤销的修复记录。".to_string(),
+    })?;
+    let outcome = execute_steps(runner, &snapshot.revert, StepMode::All, None);
+    let mut result = NetworkFixResult {
+        action: snapshot.action,
+        success: outcome.failed.is_empty(),
+        messages: outcome.messages,
+        dry_run: false,
+        steps: snapshot.revert.clone(),
+        current_values: snapshot.values.clone(),
+        snapshot_id: Some(snapshot.id.clone()),
+    };
+    if !outcome.failed.is_empty() {
+        // 只保留没能恢复的步骤，方便重试
+        store.push(FixSnapshot {
+            revert: outcome.failed,
+            ..snapshot
+        })?;
+        result
+            .messages
+            .push("未恢复的步骤已保留在快照中，可稍后重试。".into());
+    }
+    Ok(result)
+}
+
+struct StepsOutcome {
+    success: bool,
+    messages: Vec<String>,
+    failed: Vec<FixStep>,
+}
+
+fn execute_steps(
+    runner: &dyn CommandRunner,
+    steps: &[FixStep],
+    mode: StepMode,
+    failure_hint: Option<&str>,
+) -> StepsOutcome {
+    let mut success = false;
+    let mut messages = Vec::new();
+    let mut failed = Vec::new();
+    for step in steps {
+        match step.apply(runner) {
+            Ok(()) => {
+                success = true;
+                messages.push(step.label().to_string());
+                if mode == StepMode::FirstSuccess {
+                    break;
+                }
+            }
+            Err(err) => {
+                messages.push(format!("{} 失败：{err}", step.label()));
+                failed.push(step.clone());
+                if mode == StepMode::Sequential {
+                    success = false;
+                    break;
+                }
+            }
+        }
+    }
+    if steps.is_empty() {
+        success = failure_hint.is_none();
+    }
+    if !success {
+        messages.extend(failure_hint.map(str::to_string));
+    }
+    StepsOutcome {
+        success,
+        messages,
+        failed,
+    }
+}
+
+fn snapshot_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("无法定位应用数据目录: {err}"))?
+        .join(SNAPSHOT_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::runner::fake::FakeRunner;
+    use super::*;
+
+    const GNOME_PROXY: &str = "org.gnome.system.proxy mode 'manual'\norg.gnome.system.proxy ignore-hosts ['localhost']\norg.gnome.system.proxy.http host 'proxy.corp'\norg.gnome.system.proxy.http port 3128\n";
+
+    fn temp_store() -> (PathBuf, SnapshotStore) {
+        let dir = std::env::temp_dir().join(format!("chef_fix_{}", uuid::Uuid::new_v4()));
+        let store = SnapshotStore::new(dir.join(SNAPSHOT_FILE));
+        (dir, store)
+    }
+
+    #[test]
+    fn dry_run_reports_commands_without_changing_anything() {
+        let (dir, store) = temp_store();
+        let runner = FakeRunner::default().respond(
+            "gsettings list-recursively org.gnome.system.proxy",
+            true,
+            GNOME_PROXY,
+        );
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            true,
+        )
+        .unwrap();
+        assert!(result.dry_run && result.success && result.snapshot_id.is_none());
+        assert_eq!(
+            result.messages,
+            vec![
+                "将执行：gsettings set org.gnome.system.proxy mode none",
+                "将执行：gsettings reset-recursively org.gnome.system.proxy",
+            ]
+        );
+        assert_eq!(result.current_values.len(), 4);
+        assert_eq!(result.current_values[0].key, "org.gnome.system.proxy mode");
+        assert_eq!(result.current_values[0].value.as_deref(), Some("'manual'"));
+        assert_eq!(
+            runner.calls(),
+            vec!["gsettings list-recursively org.gnome.system.proxy"]
+        );
+        assert!(store.list().unwrap().is_empty());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn resets_gnome_proxy_and_reverts_from_snapshot() {
+        let (dir, store) = temp_store();
+        let runner = FakeRunner::default()
+            .respond(
+                "gsettings list-recursively org.gnome.system.proxy",
+                true,
+                GNOME_PROXY,
+            )
+            .respond("gsettings set org.gnome.system.proxy mode none", true, "")
+            .respond(
+                "gsettings reset-recursively org.gnome.system.proxy",
+                true,
+                "",
+            )
+            .respond(
+                "gsettings set org.gnome.system.proxy mode 'manual'",
+                true,
+                "",
+            )
+            .respond(
+                "gsettings set org.gnome.system.proxy ignore-hosts ['localhost']",
+                true,
+                "",
+            )
+            .respond(
+                "gsettings set org.gnome.system.proxy.http host 'proxy.corp'",
+                true,
+                "",
+            )
+            .respond(
+                "gsettings set org.gnome.system.proxy.http port 3128",
+                true,
+                "",
+            );
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(
+            result.messages,
+            vec!["已关闭 GNOME 代理模式", "已重置 GNOME 代理配置"]
+        );
+        let snapshot_id = result.snapshot_id.unwrap();
+        assert_eq!(store.list().unwrap()[0].id, snapshot_id);
+
+        let reverted = revert_fix(&runner, &store, None).unwrap();
+        assert!(reverted.success);
+        assert_eq!(reverted.snapshot_id.as_deref(), Some(snapshot_id.as_str()));
+        // 代理模式最后恢复
+        assert_eq!(
+            runner.calls()[3..],
+            [
+                "gsettings set org.gnome.system.proxy ignore-hosts ['localhost']",
+                "gsettings set org.gnome.system.proxy.http host 'proxy.corp'",
+                "gsettings set org.gnome.system.proxy.http port 3128",
+                "gsettings set org.gnome.system.proxy mode 'manual'",
+            ]
+        );
+        assert!(store.list().unwrap().is_empty());
+        let Err(err) = revert_fix(&runner, &store, None) else {
+            panic!("nothing left to revert");
+        };
+        assert!(err.contains("没有可撤销"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_unrestored_steps_in_snapshot() {
+        let (dir, store) = temp_store();
+        let runner = FakeRunner::default()
+            .respond(
+                "gsettings list-recursively org.gnome.system.proxy",
+                true,
+                GNOME_PROXY,
+            )
+            .respond("gsettings set org.gnome.system.proxy mode none", true, "")
+            .respond(
+                "gsettings reset-recursively org.gnome.system.proxy",
+                true,
+                "",
+            )
+            .respond(
+                "gsettings set org.gnome.system.proxy mode 'manual'",
+                true,
+                "",
+            );
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        let snapshot_id = result.snapshot_id.unwrap();
+
+        let reverted = revert_fix(&runner, &store, None).unwrap();
+        assert!(!reverted.success);
+        let remaining = store.list().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, snapshot_id);
+        assert_eq!(remaining[0].revert.len(), 3);
+        // 单引号包裹，`$`、反引号等不会被 shell 展开
+        assert_eq!(
+            remaining[0].revert[0].display(),
+            r#"gsettings set org.gnome.system.proxy ignore-hosts '['\''localhost'\'']'"#
+        );
+        assert_eq!(
+            FixStep::command("echo", &["$HOME `id` \\"], "echo").display(),
+            r#"echo '$HOME `id` \'"#
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sequential_steps_stop_at_first_failure() {
+        let runner = FakeRunner::default().respond("nmcli device connect eth0", true, "");
+        let steps = [
+            FixStep::command("nmcli", &["device", "disconnect", "eth0"], "disconnect"),
+            FixStep::command("nmcli", &["device", "connect", "eth0"], "connect"),
+        ];
+        let outcome = execute_steps(&runner, &steps, StepMode::Sequential, None);
+        assert!(!outcome.success);
+        assert_eq!(runner.calls(), vec!["nmcli device disconnect eth0"]);
+    }
+
+    #[test]
+    fn network_manager_reset_runs_under_one_authorization() {
+        let (dir, store) = temp_store();
+        // 提权后的 connect 失败，整条命令失败
+        let runner = FakeRunner::default()
+            .respond("nmcli -t -f RUNNING general", true, "running\n")
+            .respond("id -u", true, "1000\n")
+            .respond(
+                "pkexec sh -c nmcli device disconnect eth0 && nmcli device connect eth0",
+                false,
+                "",
+            );
+        let options = NetworkFixOptions {
+            interface: Some("eth0".into()),
+            ..NetworkFixOptions::default()
+        };
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::ResetInterface,
+            &options,
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        assert!(!result.success);
+        let calls = runner.calls();
+        assert_eq!(
+            calls.last().map(String::as_str),
+            Some("pkexec sh -c nmcli device disconnect eth0 && nmcli device connect eth0")
+        );
+        assert!(!calls.iter().any(|call| call.starts_with("nmcli device")));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn clears_and_restores_proxy_env() {
+        let (dir, store) = temp_store();
+        let runner = FakeRunner::default()
+            .with_env("HTTP_PROXY", "http://127.0.0.1:7890")
+            .with_env("all_proxy", "socks5://127.0.0.1:1080");
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::ClearProxyEnv,
+            &NetworkFixOptions::default(),
+            Platform::Other,
+            false,
+        )
+        .unwrap();
+        assert!(result.success);
+        assert!(runner.env.lock().unwrap().is_empty());
+        assert!(runner.calls().is_empty());
+
+        revert_fix(&runner, &store, result.snapshot_id.as_deref()).unwrap();
+        assert_eq!(
+            runner.env_var("HTTP_PROXY").as_deref(),
+            Some("http://127.0.0.1:7890")
+        );
+        assert_eq!(
+            runner.env_var("all_proxy").as_deref(),
+            Some("socks5://127.0.0.1:1080")
+        );
+
+        let empty = run_fix(
+            &FakeRunner::default(),
+            &store,
+            NetworkFixAction::ClearProxyEnv,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        assert!(empty.success && empty.snapshot_id.is_none());
+        assert_eq!(empty.messages, vec!["未检测到需要清理的代理环境变量。"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn flushes_dns_with_first_working_command() {
+        let (dir, store) = temp_store();
+        let runner = FakeRunner::default()
+            .respond("resolvectl flush-caches", false, "Failed to flush caches")
+            .respond("systemd-resolve --flush-caches", true, "");
+        let result = run_fix(
+            &runner,
+            &store,
+            NetworkFixAction::FlushDnsCache,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        assert!(result.success && result.snapshot_id.is_none());
+        assert_eq!(
+            result.messages[1..],
+            [
+                "resolvectl 已刷新缓存 失败：Failed to flush caches",
+                "systemd-resolve 已刷新缓存",
+            ]
+        );
+        assert_eq!(runner.calls().len(), 2);
+
+        let failing = run_fix(
+            &FakeRunner::default(),
+            &store,
+            NetworkFixAction::FlushDnsCache,
+            &NetworkFixOptions::default(),
+            Platform::Linux,
+            false,
+        )
+        .unwrap();
+        assert!(!failing.success);
+        assert_eq!(
+            failing.messages.last().map(String::as_str),
+            Some("未能自动刷新 DNS 缓存，请手动执行对应命令。")
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,407 @@
+use super::{
+    super::PROXY_ENV_KEYS,
+    dns::{prefer_ipv4_plan, set_dns_plan},
+    interface::{renew_dhcp_plan, reset_interface_plan},
+    privilege::elevate_plan,
+    runner::{read_output, CommandRunner},
+    FixPlan, FixStep, NetworkFixAction, NetworkFixOptions, Platform, StateValue, StepMode,
+};
+
+const GNOME_PROXY_SCHEMA: &str = "org.gnome.system.proxy";
+
+/// networksetup 读取和关闭三类代理的参数。
+const MAC_PROXY_KINDS: [(&str, &str, &str); 3] = [
+    ("-getwebproxy", "-setwebproxystate", "HTTP 代理"),
+    (
+        "-getsecurewebproxy",
+        "-setsecurewebproxystate",
+        "HTTPS 代理",
+    ),
+    (
+        "-getsocksfirewallproxy",
+        "-setsocksfirewallproxystate",
+        "SOCKS 代理",
+    ),
+];
+
+/// 生成修复计划：只做读取，真正的修改留给执行阶段。
+pub(super) fn build_plan(
+    action: NetworkFixAction,
+    options: &NetworkFixOptions,
+    platform: Platform,
+    runner: &dyn CommandRunner,
+) -> FixPlan {
+    let mut plan = match (action, platform) {
+        (NetworkFixAction::ClearProxyEnv, _) => clear_proxy_env_plan(runner),
+        (NetworkFixAction::ResetSystemProxy, Platform::Linux) => linux_proxy_plan(runner),
+        (NetworkFixAction::ResetSystemProxy, Platform::MacOs) => macos_proxy_plan(runner),
+        (NetworkFixAction::ResetSystemProxy, Platform::Windows) => windows_proxy_plan(runner),
+        (NetworkFixAction::ResetSystemProxy, Platform::Other) => {
+            FixPlan::unsupported("当前系统暂不支持自动重置代理。")
+        }
+        (NetworkFixAction::FlushDnsCache, platform) => flush_dns_plan(platform),
+        (NetworkFixAction::SetDnsServers, platform) => set_dns_plan(options, platform, runner),
+        (NetworkFixAction::PreferIpv4, platform) => prefer_ipv4_plan(options, platform, runner),
+        (NetworkFixAction::ResetInterface, platform) => {
+            reset_interface_plan(options, platform, runner)
+        }
+        (NetworkFixAction::RenewDhcp, platform) => renew_dhcp_plan(options, platform, runner),
+    };
+    elevate_plan(&mut plan, platform, runner);
+    plan
+}
+
+fn clear_proxy_env_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let mut plan = FixPlan::default();
+    for key in PROXY_ENV_KEYS {
+        let Some(value) = runner.env_var(key) else {
+            continue;
+        };
+        plan.current_values
+            .push(StateValue::new(format!("env:{key}"), Some(value.clone())));
+        plan.steps.push(FixStep::Env {
+            key: key.to_string(),
+            value: None,
+            label: format!("已清理环境变量 {key}"),
+        });
+        plan.revert.push(FixStep::Env {
+            key: key.to_string(),
+            value: Some(value),
+            label: format!("已恢复环境变量 {key}"),
+        });
+    }
+    if plan.steps.is_empty() {
+        plan.notes.push("未检测到需要清理的代理环境变量。".into());
+    }
+    plan
+}
+
+fn linux_proxy_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let mut plan = FixPlan {
+        steps: vec![
+            FixStep::command(
+                "gsettings",
+                &["set", GNOME_PROXY_SCHEMA, "mode", "none"],
+                "已关闭 GNOME 代理模式",
+            ),
+            FixStep::command(
+                "gsettings",
+                &["reset-recursively", GNOME_PROXY_SCHEMA],
+                "已重置 GNOME 代理配置",
+            ),
+        ],
+        failure_hint: Some("未检测到可自动重置的桌面代理设置，请手动检查。".into()),
+        ..FixPlan::default()
+    };
+    let Some(output) = read_output(
+        runner,
+        "gsettings",
+        &["list-recursively", GNOME_PROXY_SCHEMA],
+    ) else {
+        plan.notes
+            .push("无法读取 GNOME 代理配置，执行后将无法撤销。".into());
+        return plan;
+    };
+
+    let mut mode = None;
+    for line in output.lines() {
+        let mut parts = line.trim().splitn(3, ' ');
+        let (Some(schema), Some(key), Some(value)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        plan.current_values.push(StateValue::new(
+            format!("{schema} {key}"),
+            Some(value.to_string()),
+        ));
+        let step = FixStep::command(
+            "gsettings",
+            &["set", schema, key, value],
+            format!("已恢复 {schema} {key}"),
+        );
+        // 最后恢复代理模式，避免中途按不完整的配置生效
+        if schema == GNOME_PROXY_SCHEMA && key == "mode" {
+            mode = Some(step);
+        } else {
+            plan.revert.push(step);
+        }
+    }
+    plan.revert.extend(mode);
+    plan
+}
+
+fn macos_proxy_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let services = read_output(runner, "networksetup", &["-listallnetworkservices"])
+        .map(|output| parse_mac_services(&output))
+        .unwrap_or_default();
+    if services.is_empty() {
+        return FixPlan {
+            failure_hint: Some("未找到可操作的网络服务，已跳过代理重置。".into()),
+            ..FixPlan::default()
+        };
+    }
+
+    let mut plan = FixPlan::default();
+    for service in &services {
+        for (get_flag, set_flag, label) in MAC_PROXY_KINDS {
+            if let Some(output) = read_output(runner, "networksetup", &[get_flag, service]) {
+                let (enabled, address) = parse_mac_proxy(&output);
+                plan.current_values.push(StateValue::new(
+                    format!("{service} {label}"),
+                    Some(format!(
+                        "{} {address}",
+                        if enabled { "已开启" } else { "已关闭" }
+                    )),
+                ));
+                if enabled {
+                    plan.revert.push(FixStep::command(
+                        "networksetup",
+                        &[set_flag, service, "on"],
+                        format!("{service}: {label} 已重新开启"),
+                    ));
+                }
+            }
+            plan.steps.push(FixStep::command(
+                "networksetup",
+                &[set_flag, service, "off"],
+                format!("{service}: {label} 已关闭"),
+            ));
+        }
+    }
+    plan
+}
+
+fn parse_mac_services(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("An asterisk"))
+        // 带星号的是已停用的服务，networksetup 需要去掉星号的名称
+        .map(|line| line.trim_start_matches('*').trim().to_string())
+        .collect()
+}
+
+/// 解析 `networksetup -getwebproxy` 的 Enabled/Server/Port。
+fn parse_mac_proxy(output: &str) -> (bool, String) {
+    let value = |name: &str| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim().to_string())
+        })
+    };
+    let enabled = value("Enabled").is_some_and(|enabled| enabled.eq_ignore_ascii_case("yes"));
+    let address = match (value("Server"), value("Port")) {
+        (Some(server), Some(port)) if !server.is_empty() => format!("{server}:{port}"),
+        _ => String::new(),
+    };
+    (enabled, address)
+}
+
+fn windows_proxy_plan(runner: &dyn CommandRunner) -> FixPlan {
+    let mut plan = FixPlan {
+        steps: vec![
+            FixStep::command(
+                "netsh",
+                &["winhttp", "reset", "proxy"],
+                "WinHTTP 代理已重置",
+            ),
+            FixStep::command(
+                "netsh",
+                &["winhttp", "import", "proxy", "source=ie"],
+                "已同步系统代理配置",
+            ),
+        ],
+        ..FixPlan::default()
+    };
+    let Some(output) = read_output(runner, "netsh", &["winhttp", "show", "proxy"]) else {
+        plan.notes
+            .push("无法读取 WinHTTP 代理配置，执行后将无法撤销。".into());
+        return plan;
+    };
+
+    let field = |names: &[&str]| {
+        output.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            names
+                .iter()
+                .any(|name| key.contains(name))
+                .then(|| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        })
+    };
+    let server = field(&["Proxy Server", "代理服务器"]);
+    let bypass = field(&["Bypass List", "绕过列表"]).filter(|bypass| bypass != "(none)");
+    plan.current_values.push(StateValue::new(
+        "WinHTTP 代理".into(),
+        Some(server.clone().unwrap_or_else(|| "直接连接".into())),
+    ));
+    plan.revert.push(match server {
+        Some(server) => {
+            let mut args = vec![
+                "winhttp".to_string(),
+                "set".into(),
+                "proxy".into(),
+                format!("proxy-server={server}"),
+            ];
+            args.extend(bypass.map(|bypass| format!("bypass-list={bypass}")));
+            FixStep::Command {
+                program: "netsh".into(),
+                args,
+                label: "已恢复 WinHTTP 代理".into(),
+                privileged: false,
+            }
+        }
+        None => FixStep::command(
+            "netsh",
+            &["winhttp", "reset", "proxy"],
+            "已恢复 WinHTTP 直接连接",
+        ),
+    });
+    plan
+}
+
+fn flush_dns_plan(platform: Platform) -> FixPlan {
+    let mut plan = match platform {
+        Platform::MacOs => FixPlan {
+            steps: vec![
+                FixStep::command(
+                    "dscacheutil",
+                    &["-flushcache"],
+                    "已执行 dscacheutil -flushcache",
+                ),
+                FixStep::command(
+                    "killall",
+                    &["-HUP", "mDNSResponder"],
+                    "已通知 mDNSResponder 刷新缓存",
+                ),
+            ],
+            ..FixPlan::default()
+        },
+        Platform::Windows => FixPlan {
+            steps: vec![FixStep::command(
+                "ipconfig",
+                &["/flushdns"],
+                "已刷新 DNS 解析缓存",
+            )],
+            ..FixPlan::default()
+        },
+        Platform::Linux => FixPlan {
+            steps: vec![
+                FixStep::command("resolvectl", &["flush-caches"], "resolvectl 已刷新缓存"),
+                FixStep::command(
+                    "systemd-resolve",
+                    &["--flush-caches"],
+                    "systemd-resolve 已刷新缓存",
+                ),
+                FixStep::command("nscd", &["-i", "hosts"], "nscd hosts 缓存已刷新"),
+            ],
+            mode: StepMode::FirstSuccess,
+            failure_hint: Some("未能自动刷新 DNS 缓存，请手动执行对应命令。".into()),
+            ..FixPlan::default()
+        },
+        Platform::Other => return FixPlan::unsupported("当前系统暂不支持自动刷新 DNS 缓存。"),
+    };
+    plan.notes
+        .push("刷新 DNS 缓存不涉及配置修改，无需撤销。".into());
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::runner::fake::FakeRunner;
+    use super::*;
+
+    #[test]
+    fn plans_macos_proxy_reset_from_networksetup() {
+        let runner = FakeRunner::default()
+            .respond(
+                "networksetup -listallnetworkservices",
+                true,
+                "An asterisk (*) denotes that a network service is disabled.\nWi-Fi\n*Thunderbolt Bridge\n",
+            )
+            .respond(
+                "networksetup -getwebproxy Wi-Fi",
+                true,
+                "Enabled: Yes\nServer: proxy.local\nPort: 8080\nAuthenticated Proxy Enabled: 0\n",
+            )
+            .respond(
+                "networksetup -getsecurewebproxy Wi-Fi",
+                true,
+                "Enabled: No\nServer: \nPort: 0\n",
+            );
+        let plan = build_plan(
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::MacOs,
+            &runner,
+        );
+        assert_eq!(plan.steps.len(), 6);
+        assert_eq!(
+            plan.steps[3].display(),
+            "networksetup -setwebproxystate 'Thunderbolt Bridge' off"
+        );
+        assert_eq!(
+            plan.current_values[0].value.as_deref(),
+            Some("已开启 proxy.local:8080")
+        );
+        assert_eq!(plan.current_values[1].value.as_deref(), Some("已关闭 "));
+        assert_eq!(plan.revert.len(), 1);
+        assert_eq!(
+            plan.revert[0].display(),
+            "networksetup -setwebproxystate Wi-Fi on"
+        );
+
+        let empty = build_plan(
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::MacOs,
+            &FakeRunner::default(),
+        );
+        assert!(empty.steps.is_empty() && empty.failure_hint.is_some());
+    }
+
+    #[test]
+    fn plans_windows_proxy_reset_with_revert() {
+        let runner = FakeRunner::default().respond(
+            "netsh winhttp show proxy",
+            true,
+            "\nCurrent WinHTTP proxy settings:\n\n    Proxy Server(s) :  10.0.0.1:3128\n    Bypass List     :  <local>;*.corp\n",
+        );
+        let plan = build_plan(
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::Windows,
+            &runner,
+        );
+        assert_eq!(
+            plan.current_values[0].value.as_deref(),
+            Some("10.0.0.1:3128")
+        );
+        assert_eq!(
+            plan.revert[0].display(),
+            "netsh winhttp set proxy proxy-server=10.0.0.1:3128 'bypass-list=<local>;*.corp'"
+        );
+
+        let direct = FakeRunner::default().respond(
+            "netsh winhttp show proxy",
+            true,
+            "Current WinHTTP proxy settings:\n\n    Direct access (no proxy server).\n",
+        );
+        let plan = build_plan(
+            NetworkFixAction::ResetSystemProxy,
+            &NetworkFixOptions::default(),
+            Platform::Windows,
+            &direct,
+        );
+        assert_eq!(plan.current_values[0].value.as_deref(), Some("直接连接"));
+        assert_eq!(plan.revert[0].display(), "netsh winhttp reset proxy");
+        assert!(build_plan(
+            NetworkFixAction::FlushDnsCache,
+            &NetworkFixOptions::default(),
+            Platform::Other,
+            &direct
+        )
+        .failure_hint
+        .is_some());
+    }
+}
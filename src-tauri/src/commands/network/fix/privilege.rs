@@ -0,0 +1,212 @@
+use super::{
+    posix_quote,
+    runner::{read_output, CommandRunner},
+    FixPlan, FixStep, Platform,
+};
+
+/// 给需要管理员权限的命令加上提权方式：Linux 用 pkexec，macOS 通过 osascript 弹出授权框。
+/// Windows 无法对单条命令提权，只在未以管理员身份运行时提示。
+pub(super) fn elevate_plan(plan: &mut FixPlan, platform: Platform, runner: &dyn CommandRunner) {
+    let privileged = |step: &FixStep| {
+        matches!(
+            step,
+            FixStep::Command {
+                privileged: true,
+                ..
+            }
+        )
+    };
+    let needs_admin = plan.steps.iter().chain(&plan.revert).any(privileged);
+    if !needs_admin || is_admin(platform, runner) {
+        return;
+    }
+    match platform {
+        Platform::Linux | Platform::MacOs => {
+            for step in plan.steps.iter_mut().chain(plan.revert.iter_mut()) {
+                elevate(step, platform);
+            }
+        }
+        Platform::Windows => plan
+            .notes
+            .push("该操作需要管理员权限，请以管理员身份运行本应用。".into()),
+        Platform::Other => {}
+    }
+}
+
+/// 把有先后依赖的几条提权命令合成一条，只需授权一次，前一条失败时不再执行后面的。
+/// Linux/macOS 交给 `sh -c`，随后由 [`elevate_plan`] 统一提权；Windows 写成一段 PowerShell 脚本。
+pub(super) fn chain_privileged(steps: &[FixStep], label: String, platform: Platform) -> FixStep {
+    let step = match platform {
+        Platform::Windows => {
+            let script = steps
+                .iter()
+                .filter_map(|step| match step {
+                    FixStep::Command { program, args, .. } => Some(
+                        std::iter::once(program)
+                            .chain(args)
+                            .map(|value| powershell_quote(value))
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    FixStep::Env { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join("; if ($LASTEXITCODE) { exit $LASTEXITCODE }; ");
+            FixStep::command(
+                "powershell",
+                &[
+                    "-NoProfile",
+                    "-Command",
+                    &format!("{script}; exit $LASTEXITCODE"),
+                ],
+                label,
+            )
+        }
+        _ => {
+            let script = steps
+                .iter()
+                .map(FixStep::display)
+                .collect::<Vec<_>>()
+                .join(" && ");
+            FixStep::command("sh", &["-c", &script], label)
+        }
+    };
+    step.privileged()
+}
+
+/// PowerShell 单引号字符串里只有 `'` 需要写成两个。
+fn powershell_quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || "-_./:=,@%+".contains(ch));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+fn is_admin(platform: Platform, runner: &dyn CommandRunner) -> bool {
+    match platform {
+        Platform::Linux | Platform::MacOs => {
+            read_output(runner, "id", &["-u"]).is_some_and(|uid| uid.trim() == "0")
+        }
+        // 只有管理员才能查询会话列表
+        Platform::Windows => read_output(runner, "net", &["session"]).is_some(),
+        Platform::Other => false,
+    }
+}
+
+fn elevate(step: &mut FixStep, platform: Platform) {
+    let FixStep::Command {
+        program,
+        args,
+        privileged: true,
+        ..
+    } = step
+    else {
+        return;
+    };
+    match platform {
+        Platform::Linux => args.insert(0, std::mem::replace(program, "pkexec".into())),
+        Platform::MacOs => {
+            let command = std::iter::once(program.as_str())
+                .chain(args.iter().map(String::as_str))
+                .map(posix_quote)
+                .collect::<Vec<_>>()
+                .join(" ");
+            *args = vec![
+                "-e".into(),
+                format!(
+                    "do shell script \"{}\" with administrator privileges",
+                    command.replace('\\', "\\\\").replace('"', "\\\"")
+                ),
+            ];
+            *program = "osascript".into();
+        }
+        Platform::Windows | Platform::Other => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::runner::fake::FakeRunner;
+    use super::*;
+
+    fn plan() -> FixPlan {
+        FixPlan {
+            steps: vec![
+                FixStep::command("ip", &["link", "set", "dev", "eth0", "down"], "down")
+                    .privileged(),
+                FixStep::command("nmcli", &["device", "reapply", "eth0"], "reapply"),
+            ],
+            revert: vec![FixStep::command(
+                "networksetup",
+                &["-setdnsservers", "Wi-Fi 2", "Empty"],
+                "dns",
+            )
+            .privileged()],
+            ..FixPlan::default()
+        }
+    }
+
+    #[test]
+    fn wraps_privileged_commands_per_platform() {
+        let runner = FakeRunner::default().respond("id -u", true, "1000\n");
+        let mut linux = plan();
+        elevate_plan(&mut linux, Platform::Linux, &runner);
+        assert_eq!(linux.steps[0].display(), "pkexec ip link set dev eth0 down");
+        assert_eq!(linux.steps[1].display(), "nmcli device reapply eth0");
+
+        let mut macos = plan();
+        elevate_plan(&mut macos, Platform::MacOs, &runner);
+        let FixStep::Command { program, args, .. } = &macos.revert[0] else {
+            panic!("expected a command step");
+        };
+        assert_eq!(program, "osascript");
+        assert_eq!(
+            args[1],
+            "do shell script \"networksetup -setdnsservers 'Wi-Fi 2' Empty\" with administrator privileges"
+        );
+
+        let root = FakeRunner::default().respond("id -u", true, "0\n");
+        let mut as_root = plan();
+        elevate_plan(&mut as_root, Platform::Linux, &root);
+        assert_eq!(as_root.steps[0].display(), "ip link set dev eth0 down");
+
+        let mut chained = FixPlan {
+            steps: vec![chain_privileged(
+                &[
+                    FixStep::command("ifconfig", &["en0", "down"], "down"),
+                    FixStep::command("ifconfig", &["en0", "up"], "up"),
+                ],
+                "restart".into(),
+                Platform::MacOs,
+            )],
+            ..FixPlan::default()
+        };
+        let mut chained_linux = FixPlan {
+            steps: chained.steps.clone(),
+            ..FixPlan::default()
+        };
+        elevate_plan(&mut chained_linux, Platform::Linux, &runner);
+        assert_eq!(
+            chained_linux.steps[0].display(),
+            "pkexec sh -c 'ifconfig en0 down && ifconfig en0 up'"
+        );
+        elevate_plan(&mut chained, Platform::MacOs, &runner);
+        let FixStep::Command { args, .. } = &chained.steps[0] else {
+            panic!("expected a command step");
+        };
+        assert_eq!(
+            args[1],
+            "do shell script \"sh -c 'ifconfig en0 down && ifconfig en0 up'\" with administrator privileges"
+        );
+
+        let mut windows = plan();
+        elevate_plan(&mut windows, Platform::Windows, &FakeRunner::default());
+        assert_eq!(windows.steps[0].display(), "ip link set dev eth0 down");
+        assert_eq!(windows.notes.len(), 1);
+    }
+}
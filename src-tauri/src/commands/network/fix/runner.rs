@@ -0,0 +1,142 @@
+use std::{env, io, process::Command};
+
+pub(super) struct CommandOutput {
+    pub success: bool,
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// 修复动作对系统的所有读写都经过这里，测试时换成假的实现。
+pub(super) trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput>;
+
+    fn env_var(&self, key: &str) -> Option<String>;
+
+    /// `value` 为空时删除变量。
+    fn set_env_var(&self, key: &str, value: Option<&str>);
+}
+
+pub(super) struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput> {
+        let output = Command::new(program).args(args).output()?;
+        Ok(CommandOutput {
+            success: output.status.success(),
+            code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    fn env_var(&self, key: &str) -> Option<String> {
+        env::var(key).ok()
+    }
+
+    fn set_env_var(&self, key: &str, value: Option<&str>) {
+        match value {
+            Some(value) => env::set_var(key, value),
+            None => env::remove_var(key),
+        }
+    }
+}
+
+impl CommandOutput {
+    /// 命令失败时的简短原因，优先使用 stderr。
+    pub fn failure_reason(&self) -> String {
+        let stderr = self.stderr.trim();
+        if !stderr.is_empty() {
+            return stderr.to_string();
+        }
+        let stdout = self.stdout.trim();
+        if !stdout.is_empty() {
+            return stdout.to_string();
+        }
+        format!("退出码 {:?}", self.code)
+    }
+}
+
+/// 读取类命令的输出，失败时返回 None。
+pub(super) fn read_output(
+    runner: &dyn CommandRunner,
+    program: &str,
+    args: &[&str],
+) -> Option<String> {
+    let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    runner
+        .run(program, &args)
+        .ok()
+        .filter(|output| output.success)
+        .map(|output| output.stdout)
+}
+
+#[cfg(test)]
+pub(super) mod fake {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use super::*;
+
+    /// 按完整命令行返回预设输出，并记录执行过的命令。
+    #[derive(Default)]
+    pub struct FakeRunner {
+        responses: HashMap<String, (bool, String)>,
+        pub calls: Mutex<Vec<String>>,
+        pub env: Mutex<HashMap<String, String>>,
+    }
+
+    impl FakeRunner {
+        pub fn respond(mut self, command: &str, success: bool, stdout: &str) -> Self {
+            self.responses
+                .insert(command.to_string(), (success, stdout.to_string()));
+            self
+        }
+
+        pub fn with_env(self, key: &str, value: &str) -> Self {
+            self.env
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_string());
+            self
+        }
+
+        pub fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl CommandRunner for FakeRunner {
+        fn run(&self, program: &str, args: &[String]) -> io::Result<CommandOutput> {
+            let command = std::iter::once(program.to_string())
+                .chain(args.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.calls.lock().unwrap().push(command.clone());
+            match self.responses.get(&command) {
+                Some((success, stdout)) => Ok(CommandOutput {
+                    success: *success,
+                    code: Some(if *success { 0 } else { 1 }),
+                    stdout: stdout.clone(),
+                    stderr: if *success {
+                        String::new()
+                    } else {
+                        stdout.clone()
+                    },
+                }),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "command not found")),
+            }
+        }
+
+        fn env_var(&self, key: &str) -> Option<String> {
+            self.env.lock().unwrap().get(key).cloned()
+        }
+
+        fn set_env_var(&self, key: &str, value: Option<&str>) {
+            let mut env = self.env.lock().unwrap();
+            match value {
+                Some(value) => env.insert(key.to_string(), value.to_string()),
+                None => env.remove(key),
+            };
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use super::{FixStep, NetworkFixAction, StateValue};
+
+/// 最多保留的快照数量，超出后丢弃最早的。
+const MAX_SNAPSHOTS: usize = 20;
+
+/// 快照文件的读改写需要串行，避免两个修复动作同时执行时互相覆盖。
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 执行修复前保存的系统状态以及恢复所需的步骤。
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixSnapshot {
+    pub id: String,
+    pub action: NetworkFixAction,
+    pub timestamp: u64,
+    pub values: Vec<StateValue>,
+    pub revert: Vec<FixStep>,
+}
+
+pub(super) struct SnapshotStore {
+    path: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// 按时间从新到旧排列。
+    pub fn list(&self) -> Result<Vec<FixSnapshot>, String> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let mut snapshots = self.load()?;
+        snapshots.reverse();
+        Ok(snapshots)
+    }
+
+    pub fn push(&self, snapshot: FixSnapshot) -> Result<(), String> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let mut snapshots = self.load()?;
+        snapshots.push(snapshot);
+        let overflow = snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+        snapshots.drain(..overflow);
+        self.save(&snapshots)
+    }
+
+    /// 取出指定快照；不指定时取最近一次。
+    pub fn take(&self, id: Option<&str>) -> Result<Option<FixSnapshot>, String> {
+        let _guard = STORE_LOCK.lock().unwrap();
+        let mut snapshots = self.load()?;
+        let index = match id {
+            Some(id) => snapshots.iter().position(|snapshot| snapshot.id == id),
+            None => snapshots.len().checked_sub(1),
+        };
+        let Some(index) = index else {
+            return Ok(None);
+        };
+        let snapshot = snapshots.remove(index);
+        self.save(&snapshots)?;
+        Ok(Some(snapshot))
+    }
+
+    fn load(&self) -> Result<Vec<FixSnapshot>, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| format!("修复快照文件已损坏 {}: {err}", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(format!("读取修复快照失败: {err}")),
+        }
+    }
+
+    fn save(&self, snapshots: &[FixSnapshot]) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("创建数据目录失败: {err}"))?;
+        }
+        let content = serde_json::to_string_pretty(snapshots).map_err(|err| err.to_string())?;
+        fs::write(&self.path, content).map_err(|err| format!("保存修复快照失败: {err}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(id: &str) -> FixSnapshot {
+        FixSnapshot {
+            id: id.into(),
+            action: NetworkFixAction::ClearProxyEnv,
+            timestamp: 0,
+            values: Vec::new(),
+            revert: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn keeps_recent_snapshots_and_takes_by_id() {
+        let dir = std::env::temp_dir().join(format!("chef_fix_{}", uuid::Uuid::new_v4()));
+        let store = SnapshotStore::new(dir.join("snapshots.json"));
+        assert!(store.take(None).unwrap().is_none());
+
+        for index in 0..MAX_SNAPSHOTS + 2 {
+            store.push(snapshot(&index.to_string())).unwrap();
+        }
+        let listed = store.list().unwrap();
+        assert_eq!(listed.len(), MAX_SNAPSHOTS);
+        assert_eq!(listed[0].id, (MAX_SNAPSHOTS + 1).to_string());
+        assert!(listed
+            .iter()
+            .all(|snapshot| snapshot.id != "0" && snapshot.id != "1"));
+
+        assert_eq!(store.take(Some("5")).unwrap().unwrap().id, "5");
+        assert!(store.take(Some("5")).unwrap().is_none());
+        assert_eq!(
+            store.take(None).unwrap().unwrap().id,
+            (MAX_SNAPSHOTS + 1).to_string()
+        );
+        assert_eq!(store.list().unwrap().len(), MAX_SNAPSHOTS - 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,736 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE, COOKIE, LOCATION},
+    multipart::{Form, Part},
+    redirect::Policy,
+    Client, Method, RequestBuilder, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+
+use super::{build_reqwest_proxy, probe::elapsed_ms, ProxyEndpoint};
+
+const USER_AGENT: &str = "Chef HTTP Workbench/0.1";
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+const DEFAULT_MAX_REDIRECTS: usize = 10;
+/// 响应体超过该大小时截断，避免一次性传给前端过多数据。
+const MAX_BODY_BYTES: usize = 20 * 1024 * 1024;
+/// application/x-www-form-urlencoded 中保留不转义的字符。
+const FORM_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'*')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_');
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpKeyValue {
+    key: String,
+    value: String,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipartField {
+    name: String,
+    #[serde(default)]
+    value: String,
+    /// 设置后上传该文件，忽略 `value`。
+    file_path: Option<String>,
+    content_type: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum HttpBody {
+    #[default]
+    None,
+    Raw {
+        content: String,
+        content_type: Option<String>,
+    },
+    Json {
+        content: String,
+    },
+    Form {
+        fields: Vec<HttpKeyValue>,
+    },
+    Multipart {
+        fields: Vec<MultipartField>,
+    },
+    File {
+        path: String,
+        content_type: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyLocation {
+    #[default]
+    Header,
+    Query,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum HttpAuth {
+    Basic {
+        username: String,
+        #[serde(default)]
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    ApiKey {
+        key: String,
+        value: String,
+        #[serde(default)]
+        location: ApiKeyLocation,
+    },
+}
+
+#[derive(Default, Deserialize)]
+#[serde(tag = "mode", rename_all = "kebab-case")]
+pub enum HttpProxySelection {
+    /// 沿用环境变量中的代理设置。
+    #[default]
+    System,
+    Direct,
+    Endpoint {
+        endpoint: ProxyEndpoint,
+        username: Option<String>,
+        password: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpRequestSpec {
+    #[serde(default = "default_method")]
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HttpKeyValue>,
+    #[serde(default)]
+    query: Vec<HttpKeyValue>,
+    #[serde(default)]
+    body: HttpBody,
+    auth: Option<HttpAuth>,
+    #[serde(default)]
+    proxy: HttpProxySelection,
+    timeout_ms: Option<u64>,
+    follow_redirects: Option<bool>,
+    max_redirects: Option<usize>,
+    /// 跳过证书校验，用于自签名证书的测试环境。
+    #[serde(default)]
+    insecure: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectHop {
+    url: String,
+    status: u16,
+    location: String,
+    duration_ms: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpTiming {
+    /// 最后一跳从发出请求到收到响应头。
+    headers_ms: f64,
+    download_ms: f64,
+    /// 所有重定向花费的时间。
+    redirect_ms: f64,
+    total_ms: f64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpResponseResult {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    url: String,
+    remote_addr: Option<String>,
+    headers: Vec<HttpHeader>,
+    content_type: Option<String>,
+    /// 原始字节的 Base64，二进制内容也能完整返回。
+    body_base64: String,
+    /// 内容是合法 UTF-8 时附带文本，方便直接展示。
+    body_text: Option<String>,
+    body_size: usize,
+    truncated: bool,
+    redirects: Vec<RedirectHop>,
+    timing: HttpTiming,
+}
+
+/// 读取文件后的请求体，重定向时需要重新构造请求。
+enum PreparedBody {
+    Empty,
+    Bytes {
+        data: Vec<u8>,
+        content_type: Option<String>,
+    },
+    Multipart(Vec<PreparedPart>),
+}
+
+struct PreparedPart {
+    name: String,
+    data: Vec<u8>,
+    file_name: Option<String>,
+    content_type: Option<String>,
+}
+
+/// 发送任意 HTTP 请求并返回完整响应，重定向逐跳处理以便记录跳转链。
+#[tauri::command]
+pub async fn send_http_request(request: HttpRequestSpec) -> Result<HttpResponseResult, String> {
+    let method = Method::from_bytes(request.method.trim().to_ascii_uppercase().as_bytes())
+        .map_err(|_| format!("请求方法无效: {}", request.method))?;
+    let mut url = build_url(&request.url, &request.query, request.auth.as_ref())?;
+    let mut headers = build_headers(&request.headers)?;
+    let timeout_ms = request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS).max(1);
+    let client = build_client(&request.proxy, timeout_ms, request.insecure)?;
+    let body = request.body;
+    let mut body = Some(
+        spawn_blocking(move || prepare_body(body))
+            .await
+            .map_err(|err| err.to_string())??,
+    );
+    let follow = request.follow_redirects.unwrap_or(true);
+    let max_redirects = request.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS);
+
+    let started = Instant::now();
+    let mut method = method;
+    let mut auth = request.auth.as_ref();
+    let mut redirects = Vec::new();
+    let (response, headers_ms) = loop {
+        let hop_started = Instant::now();
+        let builder = client
+            .request(method.clone(), url.clone())
+            .headers(headers.clone());
+        let builder = apply_auth(apply_body(builder, body.as_ref(), &headers), auth);
+        let response = builder
+            .send()
+            .await
+            .map_err(|err| describe_error(&err, timeout_ms))?;
+        let status = response.status();
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|location| url.join(location).ok());
+        let Some(next) = location.filter(|_| follow && status.is_redirection()) else {
+            break (response, elapsed_ms(hop_started));
+        };
+        if redirects.len() >= max_redirects {
+            return Err(format!("重定向次数超过 {max_redirects} 次"));
+        }
+        redirects.push(RedirectHop {
+            url: url.to_string(),
+            status: status.as_u16(),
+            location: next.to_string(),
+            duration_ms: elapsed_ms(hop_started),
+        });
+        if redirect_to_get(status, &method) {
+            method = Method::GET;
+            body = None;
+            headers.remove(CONTENT_TYPE);
+        }
+        // 和浏览器一样，跳到其他站点时不再携带凭据
+        if next.origin() != url.origin() {
+            auth = None;
+            headers.remove(AUTHORIZATION);
+            headers.remove(COOKIE);
+        }
+        url = next;
+    };
+    let redirect_ms = redirects.iter().map(|hop| hop.duration_ms).sum::<f64>();
+
+    let status = response.status();
+    let http_version = format!("{:?}", response.version());
+    let final_url = response.url().to_string();
+    let remote_addr = response.remote_addr().map(|addr| addr.to_string());
+    let response_headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| HttpHeader {
+            name: name.to_string(),
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        })
+        .collect::<Vec<_>>();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let download_started = Instant::now();
+    let (data, truncated) = read_body(response, timeout_ms).await?;
+    let download_ms = elapsed_ms(download_started);
+
+    Ok(HttpResponseResult {
+        status: status.as_u16(),
+        status_text: status.canonical_reason().unwrap_or_default().to_string(),
+        http_version,
+        url: final_url,
+        remote_addr,
+        headers: response_headers,
+        content_type,
+        body_base64: STANDARD.encode(&data),
+        body_text: String::from_utf8(data.clone()).ok(),
+        body_size: data.len(),
+        truncated,
+        redirects,
+        timing: HttpTiming {
+            headers_ms,
+            download_ms,
+            redirect_ms,
+            total_ms: elapsed_ms(started),
+        },
+    })
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn default_method() -> String {
+    "GET".into()
+}
+
+fn build_url(raw: &str, query: &[HttpKeyValue], auth: Option<&HttpAuth>) -> Result<Url, String> {
+    let raw = raw.trim();
+    let text = if raw.contains("://") {
+        raw.to_string()
+    } else {
+        format!("http://{raw}")
+    };
+    let mut url = Url::parse(&text).map_err(|err| format!("请求地址无效 {raw}: {err}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("不支持的协议: {}", url.scheme()));
+    }
+    let mut pairs = query
+        .iter()
+        .filter(|item| item.enabled && !item.key.is_empty())
+        .map(|item| (item.key.as_str(), item.value.as_str()))
+        .collect::<Vec<_>>();
+    if let Some(HttpAuth::ApiKey {
+        key,
+        value,
+        location: ApiKeyLocation::Query,
+    }) = auth
+    {
+        pairs.push((key, value));
+    }
+    if !pairs.is_empty() {
+        url.query_pairs_mut().extend_pairs(pairs);
+    }
+    Ok(url)
+}
+
+fn build_headers(items: &[HttpKeyValue]) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    for item in items.iter().filter(|item| item.enabled) {
+        let key = item.key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let name =
+            HeaderName::from_bytes(key.as_bytes()).map_err(|_| format!("请求头名称无效: {key}"))?;
+        let value = HeaderValue::from_str(item.value.trim())
+            .map_err(|_| format!("请求头 {key} 的值无效"))?;
+        headers.append(name, value);
+    }
+    Ok(headers)
+}
+
+fn build_client(
+    proxy: &HttpProxySelection,
+    timeout_ms: u64,
+    insecure: bool,
+) -> Result<Client, String> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .user_agent(USER_AGENT)
+        .redirect(Policy::none())
+        .danger_accept_invalid_certs(insecure);
+    match proxy {
+        HttpProxySelection::System => {}
+        HttpProxySelection::Direct => builder = builder.no_proxy(),
+        HttpProxySelection::Endpoint {
+            endpoint,
+            username,
+            password,
+        } => {
+            if let Some(mut proxy) = build_reqwest_proxy(endpoint)? {
+                if let Some(username) = username.as_deref().filter(|name| !name.is_empty()) {
+                    proxy = proxy.basic_auth(username, password.as_deref().unwrap_or_default());
+                }
+                builder = builder.proxy(proxy);
+            }
+        }
+    }
+    builder.build().map_err(|err| err.to_string())
+}
+
+fn prepare_body(body: HttpBody) -> Result<PreparedBody, String> {
+    Ok(match body {
+        HttpBody::None => PreparedBody::Empty,
+        HttpBody::Raw {
+            content,
+            content_type,
+        } => PreparedBody::Bytes {
+            data: content.into_bytes(),
+            content_type: content_type.or_else(|| Some("text/plain; charset=utf-8".into())),
+        },
+        HttpBody::Json { content } => {
+            serde_json::from_str::<serde_json::Value>(&content)
+                .map_err(|err| format!("JSON 请求体格式错误: {err}"))?;
+            PreparedBody::Bytes {
+                data: content.into_bytes(),
+                content_type: Some("application/json".into()),
+            }
+        }
+        HttpBody::Form { fields } => {
+            let encode = |value: &str| utf8_percent_encode(value, FORM_ENCODE_SET).to_string();
+            let encoded = fields
+                .iter()
+                .filter(|field| field.enabled && !field.key.is_empty())
+                .map(|field| format!("{}={}", encode(&field.key), encode(&field.value)))
+                .collect::<Vec<_>>()
+                .join("&");
+            PreparedBody::Bytes {
+                data: encoded.into_bytes(),
+                content_type: Some("application/x-www-form-urlencoded".into()),
+            }
+        }
+        HttpBody::Multipart { fields } => PreparedBody::Multipart(
+            fields
+                .into_iter()
+                .filter(|field| field.enabled && !field.name.is_empty())
+                .map(prepare_part)
+                .collect::<Result<_, _>>()?,
+        ),
+        HttpBody::File { path, content_type } => {
+            let path = Path::new(&path);
+            let data = std::fs::read(path)
+                .map_err(|err| format!("读取文件 {} 失败: {err}", path.display()))?;
+            PreparedBody::Bytes {
+                data,
+                content_type: content_type.or_else(|| Some(guess_mime(path))),
+            }
+        }
+    })
+}
+
+fn prepare_part(field: MultipartField) -> Result<PreparedPart, String> {
+    if let Some(content_type) = &field.content_type {
+        content_type
+            .parse::<mime_guess::Mime>()
+            .map_err(|_| format!("字段 {} 的 Content-Type 无效: {content_type}", field.name))?;
+    }
+    let Some(file_path) = field.file_path.filter(|path| !path.is_empty()) else {
+        return Ok(PreparedPart {
+            name: field.name,
+            data: field.value.into_bytes(),
+            file_name: None,
+            content_type: field.content_type,
+        });
+    };
+    let path = Path::new(&file_path);
+    let data =
+        std::fs::read(path).map_err(|err| format!("读取文件 {} 失败: {err}", path.display()))?;
+    Ok(PreparedPart {
+        name: field.name,
+        data,
+        file_name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        content_type: field.content_type.or_else(|| Some(guess_mime(path))),
+    })
+}
+
+fn guess_mime(path: &Path) -> String {
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+/// 用户已经写了 Content-Type 时不覆盖。
+fn apply_body(
+    builder: RequestBuilder,
+    body: Option<&PreparedBody>,
+    headers: &HeaderMap,
+) -> RequestBuilder {
+    match body {
+        None | Some(PreparedBody::Empty) => builder,
+        Some(PreparedBody::Bytes { data, content_type }) => {
+            let builder = builder.body(data.clone());
+            match content_type {
+                Some(content_type) if !headers.contains_key(CONTENT_TYPE) => {
+                    builder.header(CONTENT_TYPE, content_type)
+                }
+                _ => builder,
+            }
+        }
+        Some(PreparedBody::Multipart(parts)) => {
+            let form = parts.iter().fold(Form::new(), |form, part| {
+                let mut body = Part::bytes(part.data.clone());
+                // 类型在 prepare_part 中已校验过
+                if let Some(content_type) = &part.content_type {
+                    body = body
+                        .mime_str(content_type)
+                        .unwrap_or_else(|_| Part::bytes(part.data.clone()));
+                }
+                if let Some(file_name) = &part.file_name {
+                    body = body.file_name(file_name.clone());
+                }
+                form.part(part.name.clone(), body)
+            });
+            builder.multipart(form)
+        }
+    }
+}
+
+fn apply_auth(builder: RequestBuilder, auth: Option<&HttpAuth>) -> RequestBuilder {
+    match auth {
+        Some(HttpAuth::Basic { username, password }) => {
+            builder.basic_auth(username, Some(password))
+        }
+        Some(HttpAuth::Bearer { token }) => builder.bearer_auth(token.trim()),
+        Some(HttpAuth::ApiKey {
+            key,
+            value,
+            location: ApiKeyLocation::Header,
+        }) => builder.header(key.trim(), value.trim()),
+        _ => builder,
+    }
+}
+
+/// 301、302、303 跳转后改用 GET 并丢弃请求体，307、308 保持原样。
+fn redirect_to_get(status: StatusCode, method: &Method) -> bool {
+    match status {
+        StatusCode::SEE_OTHER => *method != Method::HEAD,
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => {
+            *method != Method::GET && *method != Method::HEAD
+        }
+        _ => false,
+    }
+}
+
+async fn read_body(
+    mut response: reqwest::Response,
+    timeout_ms: u64,
+) -> Result<(Vec<u8>, bool), String> {
+    let mut data = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| describe_error(&err, timeout_ms))?
+    {
+        let remaining = MAX_BODY_BYTES - data.len();
+        if chunk.len() > remaining {
+            data.extend_from_slice(&chunk[..remaining]);
+            return Ok((data, true));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((data, false))
+}
+
+/// reqwest 的错误信息只有最外层，把底层原因一并带上。
+fn describe_error(err: &reqwest::Error, timeout_ms: u64) -> String {
+    if err.is_timeout() {
+        return format!("请求超时（{timeout_ms} ms）");
+    }
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Bytes,
+        http::{HeaderMap as AxumHeaders, Uri},
+        response::Redirect,
+        routing::{any, get, post},
+        Router,
+    };
+    use tokio::net::TcpListener;
+
+    async fn spawn_server() -> std::net::SocketAddr {
+        let echo = |method: Method, uri: Uri, headers: AxumHeaders, body: Bytes| async move {
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            serde_json::json!({
+                "method": method.as_str(),
+                "query": uri.query(),
+                "authorization": header("authorization"),
+                "contentType": header("content-type"),
+                "body": String::from_utf8_lossy(&body),
+            })
+            .to_string()
+        };
+        let app = Router::new()
+            .route("/echo", any(echo))
+            .route(
+                "/see-other",
+                post(|| async { Redirect::to("/echo?step=2") }),
+            )
+            .route(
+                "/temporary",
+                post(|| async { Redirect::temporary("/echo") }),
+            )
+            .route("/binary", get(|| async { vec![0u8, 159, 146, 150] }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    fn spec(value: serde_json::Value) -> HttpRequestSpec {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn echoed(result: &HttpResponseResult) -> serde_json::Value {
+        serde_json::from_str(result.body_text.as_deref().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_bodies_auth_and_query() {
+        let addr = spawn_server().await;
+        let result = send_http_request(spec(serde_json::json!({
+            "method": "put",
+            "url": format!("{addr}/echo"),
+            "query": [
+                { "key": "q", "value": "a b" },
+                { "key": "skip", "value": "1", "enabled": false }
+            ],
+            "body": { "type": "json", "content": "{\"x\": 1}" },
+            "auth": { "type": "basic", "username": "user", "password": "pass" },
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        assert_eq!(result.status, 200);
+        assert!(result.redirects.is_empty());
+        let echo = echoed(&result);
+        assert_eq!(echo["method"], "PUT");
+        assert_eq!(echo["query"], "q=a+b");
+        assert_eq!(echo["authorization"], "Basic dXNlcjpwYXNz");
+        assert_eq!(echo["contentType"], "application/json");
+        assert_eq!(echo["body"], "{\"x\": 1}");
+
+        let form = send_http_request(spec(serde_json::json!({
+            "method": "POST",
+            "url": format!("http://{addr}/echo"),
+            "headers": [{ "key": "Content-Type", "value": "text/x-custom" }],
+            "body": { "type": "form", "fields": [{ "key": "name", "value": "张三" }] },
+            "auth": { "type": "api-key", "key": "token", "value": "secret", "location": "query" },
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        let echo = echoed(&form);
+        assert_eq!(echo["contentType"], "text/x-custom");
+        assert_eq!(echo["body"], "name=%E5%BC%A0%E4%B8%89");
+        assert_eq!(echo["query"], "token=secret");
+
+        let invalid = send_http_request(spec(serde_json::json!({
+            "url": format!("http://{addr}/echo"),
+            "body": { "type": "json", "content": "{oops" }
+        })))
+        .await;
+        let Err(err) = invalid else {
+            panic!("invalid JSON body should be rejected");
+        };
+        assert!(err.starts_with("JSON 请求体格式错误"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn records_redirect_chain_and_binary_body() {
+        let addr = spawn_server().await;
+        let see_other = send_http_request(spec(serde_json::json!({
+            "method": "POST",
+            "url": format!("http://{addr}/see-other"),
+            "body": { "type": "raw", "content": "payload" },
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        assert_eq!(see_other.redirects.len(), 1);
+        assert_eq!(see_other.redirects[0].status, 303);
+        assert_eq!(see_other.url, format!("http://{addr}/echo?step=2"));
+        assert_eq!(echoed(&see_other)["method"], "GET");
+        assert_eq!(echoed(&see_other)["body"], "");
+
+        let temporary = send_http_request(spec(serde_json::json!({
+            "method": "POST",
+            "url": format!("http://{addr}/temporary"),
+            "body": { "type": "raw", "content": "payload" },
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        assert_eq!(temporary.redirects[0].status, 307);
+        assert_eq!(echoed(&temporary)["method"], "POST");
+        assert_eq!(echoed(&temporary)["body"], "payload");
+
+        let manual = send_http_request(spec(serde_json::json!({
+            "method": "POST",
+            "url": format!("http://{addr}/see-other"),
+            "followRedirects": false,
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        assert_eq!(manual.status, 303);
+        assert!(manual
+            .headers
+            .iter()
+            .any(|header| header.name == "location" && header.value == "/echo?step=2"));
+
+        let binary = send_http_request(spec(serde_json::json!({
+            "url": format!("http://{addr}/binary"),
+            "proxy": { "mode": "direct" }
+        })))
+        .await
+        .unwrap();
+        assert_eq!(binary.body_size, 4);
+        assert!(binary.body_text.is_none());
+        assert_eq!(binary.body_base64, STANDARD.encode([0u8, 159, 146, 150]));
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+};
+
+use super::{
+    super::dns::parse_resolv_conf, dbm_to_percent, normalize_mac, percent_to_dbm, run_command,
+    wifi_channel, InterfaceDetail, LinkSnapshot, LinkState, RouteEntry, WifiInfo,
+};
+
+const SYS_CLASS_NET: &str = "/sys/class/net";
+const RTF_UP: u32 = 0x0001;
+const IFF_UP: u32 = 0x1;
+
+pub(super) fn collect() -> LinkSnapshot {
+    let mut interfaces = Vec::new();
+    if let Ok(entries) = fs::read_dir(SYS_CLASS_NET) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            interfaces.push(read_interface(&name, &entry.path()));
+        }
+    }
+    LinkSnapshot {
+        interfaces,
+        routes: routes(),
+        dns_servers: dns_servers(),
+    }
+}
+
+pub(super) fn routes() -> Vec<RouteEntry> {
+    let mut routes = fs::read_to_string("/proc/net/route")
+        .map(|content| parse_ipv4_routes(&content))
+        .unwrap_or_default();
+    routes.extend(
+        fs::read_to_string("/proc/net/ipv6_route")
+            .map(|content| parse_ipv6_routes(&content))
+            .unwrap_or_default(),
+    );
+    routes
+}
+
+fn read_interface(name: &str, path: &Path) -> InterfaceDetail {
+    let read = |file: &str| {
+        fs::read_to_string(path.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    let flags = read("flags")
+        .and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok())
+        .unwrap_or_default();
+    let state = match read("operstate").as_deref() {
+        Some("up") => LinkState::Up,
+        Some("down" | "lowerlayerdown" | "notpresent" | "dormant") => LinkState::Down,
+        // 回环和 tun 等虚拟网卡的 operstate 为 unknown，按管理状态判断
+        _ if flags & IFF_UP != 0 => LinkState::Up,
+        Some(_) => LinkState::Down,
+        None => LinkState::Unknown,
+    };
+    let mut detail = InterfaceDetail {
+        name: name.to_string(),
+        state,
+        mtu: read("mtu").and_then(|mtu| mtu.parse().ok()),
+        mac: read("address").and_then(|mac| normalize_mac(&mac)),
+        // 无线网卡和断开的网卡读取 speed 会报错或返回 -1
+        speed_mbps: read("speed")
+            .and_then(|speed| speed.parse::<i64>().ok())
+            .filter(|speed| *speed > 0)
+            .and_then(|speed| u32::try_from(speed).ok()),
+        ..InterfaceDetail::default()
+    };
+    if path.join("wireless").exists() || path.join("phy80211").exists() {
+        let (wifi, bitrate) = wifi_info(name);
+        detail.speed_mbps = detail.speed_mbps.or(bitrate);
+        detail.wifi = Some(wifi);
+    }
+    detail
+}
+
+/// 先用 iw（nl80211）读取，没有 iw 时退回 NetworkManager。
+fn wifi_info(name: &str) -> (WifiInfo, Option<u32>) {
+    if let Some(output) = run_command("iw", &["dev", name, "link"]) {
+        return parse_iw_link(&output);
+    }
+    run_command(
+        "nmcli",
+        &[
+            "-t",
+            "-f",
+            "IN-USE,SSID,BSSID,CHAN,FREQ,SIGNAL,RATE",
+            "device",
+            "wifi",
+            "list",
+            "ifname",
+            name,
+            "--rescan",
+            "no",
+        ],
+    )
+    .map(|output| parse_nmcli_wifi(&output))
+    .unwrap_or_default()
+}
+
+fn parse_iw_link(output: &str) -> (WifiInfo, Option<u32>) {
+    let mut wifi = WifiInfo::default();
+    let mut bitrate = None;
+    for line in output.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("Connected to ") {
+            wifi.bssid = rest.split_whitespace().next().and_then(normalize_mac);
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        let number = || {
+            value
+                .split_whitespace()
+                .next()
+                .and_then(|number| number.parse::<f64>().ok())
+        };
+        match key {
+            "SSID" => wifi.ssid = Some(value.to_string()),
+            "freq" => wifi.frequency_mhz = number().map(|freq| freq as u32),
+            "signal" => wifi.signal_dbm = number().map(|signal| signal as i32),
+            "rx bitrate" => bitrate = number().map(|rate| rate as u32),
+            _ => {}
+        }
+    }
+    wifi.channel = wifi.frequency_mhz.and_then(wifi_channel);
+    wifi.signal_percent = wifi.signal_dbm.map(dbm_to_percent);
+    (wifi, bitrate)
+}
+
+/// `nmcli -t` 用冒号分隔字段，字段内的冒号被转义为 `\:`。
+fn parse_nmcli_wifi(output: &str) -> (WifiInfo, Option<u32>) {
+    let Some(line) = output.lines().find(|line| line.starts_with('*')) else {
+        return (WifiInfo::default(), None);
+    };
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => current.extend(chars.next()),
+            ':' => fields.push(std::mem::take(&mut current)),
+            _ => current.push(ch),
+        }
+    }
+    fields.push(current);
+    let field = |index: usize| fields.get(index).map(String::as_str).unwrap_or_default();
+    let leading_number = |value: &str| {
+        value
+            .split_whitespace()
+            .next()
+            .and_then(|number| number.parse::<u32>().ok())
+    };
+    let signal_percent = field(5).parse::<u8>().ok();
+    let wifi = WifiInfo {
+        ssid: Some(field(1).to_string()).filter(|ssid| !ssid.is_empty()),
+        bssid: normalize_mac(field(2)),
+        channel: field(3).parse().ok(),
+        frequency_mhz: leading_number(field(4)),
+        signal_percent,
+        signal_dbm: signal_percent.map(percent_to_dbm),
+    };
+    (wifi, leading_number(field(6)))
+}
+
+fn parse_ipv4_routes(content: &str) -> Vec<RouteEntry> {
+    let address = |hex: &str| {
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| Ipv4Addr::from(value.to_ne_bytes()))
+    };
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            if columns.len() < 8 {
+                return None;
+            }
+            let flags = u32::from_str_radix(columns[3], 16).ok()?;
+            if flags & RTF_UP == 0 {
+                return None;
+            }
+            let destination = address(columns[1])?;
+            let mask = address(columns[7])?;
+            let gateway = address(columns[2]).filter(|gateway| !gateway.is_unspecified());
+            Some(RouteEntry {
+                destination: format!("{destination}/{}", u32::from(mask).count_ones()),
+                gateway: gateway.map(|gateway| gateway.to_string()),
+                interface: Some(columns[0].to_string()),
+                metric: columns[6].parse().ok(),
+            })
+        })
+        .collect()
+}
+
+fn parse_ipv6_routes(content: &str) -> Vec<RouteEntry> {
+    let address = |hex: &str| {
+        u128::from_str_radix(hex, 16)
+            .ok()
+            .map(|value| Ipv6Addr::from(value.to_be_bytes()))
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            if columns.len() < 10 || columns[9] == "lo" {
+                return None;
+            }
+            let destination = address(columns[0])?;
+            // 跳过组播路由
+            if destination.is_multicast() {
+                return None;
+            }
+            let prefix = u8::from_str_radix(columns[1], 16).ok()?;
+            let gateway = address(columns[4]).filter(|gateway| !gateway.is_unspecified());
+            Some(RouteEntry {
+                destination: format!("{destination}/{prefix}"),
+                gateway: gateway.map(|gateway| gateway.to_string()),
+                interface: Some(columns[9].to_string()),
+                metric: u32::from_str_radix(columns[5], 16).ok(),
+            })
+        })
+        .collect()
+}
+
+/// systemd-resolved 的 resolv.conf 只有 127.0.0.53，这时改读它实际使用的上游服务器。
+fn dns_servers() -> Vec<String> {
+    let read = |path: &str| {
+        fs::read_to_string(path)
+            .map(|content| parse_resolv_conf(&content))
+            .unwrap_or_default()
+    };
+    let mut servers = read("/etc/resolv.conf");
+    if servers.iter().all(IpAddr::is_loopback) {
+        let upstream = read("/run/systemd/resolve/resolv.conf");
+        if !upstream.is_empty() {
+            servers = upstream;
+        }
+    }
+    servers.iter().map(IpAddr::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_proc_route_tables() {
+        let ipv4 =
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0\n\
+docker0\t000011AC\t00000000\t0000\t0\t0\t0\t0000FFFF\t0\t0\t0\n";
+        let routes = parse_ipv4_routes(ipv4);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination, "0.0.0.0/0");
+        assert_eq!(routes[0].gateway.as_deref(), Some("192.168.1.1"));
+        assert_eq!(routes[0].metric, Some(600));
+        assert_eq!(routes[1].destination, "192.168.1.0/24");
+        assert_eq!(routes[1].gateway, None);
+
+        let ipv6 = "00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000064 00000001 00000000 00000003    wlan0\n\
+fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001    wlan0\n\
+ff000000000000000000000000000000 08 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001    wlan0\n\
+00000000000000000000000000000001 80 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000001 00000000 80200001       lo\n";
+        let routes = parse_ipv6_routes(ipv6);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].destination, "::/0");
+        assert_eq!(routes[0].gateway.as_deref(), Some("fe80::1"));
+        assert_eq!(routes[0].metric, Some(100));
+        assert_eq!(routes[1].destination, "fe80::/64");
+    }
+
+    #[test]
+    fn parses_wifi_link_from_iw_and_nmcli() {
+        let iw = "Connected to aa:bb:cc:dd:ee:ff (on wlan0)\n\tSSID: Home Net\n\tfreq: 5180.0\n\tRX: 1234 bytes (10 packets)\n\tsignal: -52 dBm\n\trx bitrate: 866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2\n";
+        let (wifi, bitrate) = parse_iw_link(iw);
+        assert_eq!(
+            wifi,
+            WifiInfo {
+                ssid: Some("Home Net".into()),
+                bssid: Some("aa:bb:cc:dd:ee:ff".into()),
+                signal_dbm: Some(-52),
+                signal_percent: Some(96),
+                channel: Some(36),
+                frequency_mhz: Some(5180),
+            }
+        );
+        assert_eq!(bitrate, Some(866));
+        assert_eq!(parse_iw_link("Not connected.\n").0, WifiInfo::default());
+
+        let nmcli = " :Other:11\\:22\\:33\\:44\\:55\\:66:1:2412 MHz:40:54 Mbit/s\n*:Cafe\\:5G:AA\\:BB\\:CC\\:DD\\:EE\\:FF:149:5745 MHz:70:540 Mbit/s\n";
+        let (wifi, bitrate) = parse_nmcli_wifi(nmcli);
+        assert_eq!(wifi.ssid.as_deref(), Some("Cafe:5G"));
+        assert_eq!(wifi.bssid.as_deref(), Some("aa:bb:cc:dd:ee:ff"));
+        assert_eq!(wifi.channel, Some(149));
+        assert_eq!(wifi.frequency_mhz, Some(5745));
+        assert_eq!(wifi.signal_percent, Some(70));
+        assert_eq!(wifi.signal_dbm, Some(-65));
+        assert_eq!(bitrate, Some(540));
+    }
+}
@@ -0,0 +1,208 @@
+use super::{
+    normalize_mac, run_command, InterfaceDetail, LinkSnapshot, LinkState, RouteEntry, WifiInfo,
+};
+
+pub(super) fn collect() -> LinkSnapshot {
+    let mut interfaces = run_command("ifconfig", &["-a"])
+        .map(|output| parse_ifconfig(&output))
+        .unwrap_or_default();
+    let wifi_devices = run_command("networksetup", &["-listallhardwareports"])
+        .map(|output| parse_wifi_devices(&output))
+        .unwrap_or_default();
+    for detail in &mut interfaces {
+        if wifi_devices.contains(&detail.name) {
+            detail.wifi = Some(WifiInfo {
+                ssid: wifi_ssid(&detail.name),
+                ..WifiInfo::default()
+            });
+        }
+    }
+    LinkSnapshot {
+        interfaces,
+        routes: routes(),
+        dns_servers: run_command("scutil", &["--dns"])
+            .map(|output| parse_scutil_dns(&output))
+            .unwrap_or_default(),
+    }
+}
+
+pub(super) fn routes() -> Vec<RouteEntry> {
+    run_command("netstat", &["-rn"])
+        .map(|output| parse_netstat_routes(&output))
+        .unwrap_or_default()
+}
+
+fn parse_ifconfig(output: &str) -> Vec<InterfaceDetail> {
+    let mut interfaces: Vec<InterfaceDetail> = Vec::new();
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            let Some((name, rest)) = line.split_once(": ") else {
+                continue;
+            };
+            let flags = rest
+                .split_once('<')
+                .and_then(|(_, flags)| flags.split_once('>'))
+                .map(|(flags, _)| flags.split(',').collect::<Vec<_>>())
+                .unwrap_or_default();
+            let up = flags.contains(&"UP") && flags.contains(&"RUNNING");
+            interfaces.push(InterfaceDetail {
+                name: name.to_string(),
+                state: if up { LinkState::Up } else { LinkState::Down },
+                mtu: rest
+                    .split_once(" mtu ")
+                    .and_then(|(_, mtu)| mtu.trim().parse().ok()),
+                ..InterfaceDetail::default()
+            });
+            continue;
+        }
+        let Some(detail) = interfaces.last_mut() else {
+            continue;
+        };
+        let line = line.trim();
+        if let Some(mac) = line.strip_prefix("ether ") {
+            detail.mac = normalize_mac(mac);
+        } else if let Some(status) = line.strip_prefix("status: ") {
+            detail.state = if status == "active" {
+                LinkState::Up
+            } else {
+                LinkState::Down
+            };
+        } else if let Some(media) = line.strip_prefix("media: ") {
+            detail.speed_mbps = media_speed(media);
+        }
+    }
+    interfaces
+}
+
+/// 从 `autoselect (1000baseT <full-duplex>)` 这样的描述中取出速率。
+fn media_speed(media: &str) -> Option<u32> {
+    let (_, active) = media.split_once('(')?;
+    let (speed, _) = active.split_once("base")?;
+    match speed.strip_suffix('G') {
+        Some(gigabits) => gigabits.parse::<u32>().ok().map(|speed| speed * 1000),
+        None => speed.parse().ok(),
+    }
+}
+
+fn parse_wifi_devices(output: &str) -> Vec<String> {
+    let mut devices = Vec::new();
+    let mut wifi_port = false;
+    for line in output.lines().map(str::trim) {
+        if let Some(port) = line.strip_prefix("Hardware Port:") {
+            wifi_port = matches!(port.trim(), "Wi-Fi" | "AirPort");
+        } else if let Some(device) = line.strip_prefix("Device:") {
+            if wifi_port {
+                devices.push(device.trim().to_string());
+            }
+        }
+    }
+    devices
+}
+
+fn wifi_ssid(device: &str) -> Option<String> {
+    if let Some(ssid) =
+        run_command("networksetup", &["-getairportnetwork", device]).and_then(|output| {
+            output
+                .trim()
+                .strip_prefix("Current Wi-Fi Network:")
+                .map(|ssid| ssid.trim().to_string())
+        })
+    {
+        return Some(ssid);
+    }
+    // 新版 macOS 上 networksetup 可能拿不到 SSID，改用 ipconfig 的摘要
+    run_command("ipconfig", &["getsummary", device])?
+        .lines()
+        .find_map(|line| {
+            let (key, value) = line.split_once(" : ")?;
+            (key.trim() == "SSID").then(|| value.trim().to_string())
+        })
+}
+
+fn parse_netstat_routes(output: &str) -> Vec<RouteEntry> {
+    let mut routes = Vec::new();
+    let mut ipv6 = false;
+    for line in output.lines() {
+        match line.trim() {
+            "Internet:" => ipv6 = false,
+            "Internet6:" => ipv6 = true,
+            _ => {}
+        }
+        let columns = line.split_whitespace().collect::<Vec<_>>();
+        if columns.len() < 4 || columns[0] == "Destination" {
+            continue;
+        }
+        let destination = match columns[0] {
+            "default" if ipv6 => "::/0".to_string(),
+            "default" => "0.0.0.0/0".to_string(),
+            other => other.to_string(),
+        };
+        // link#N 和 MAC 地址表示直连，没有下一跳
+        let gateway = columns[1].split('%').next().unwrap_or_default();
+        routes.push(RouteEntry {
+            destination,
+            gateway: gateway
+                .parse::<std::net::IpAddr>()
+                .ok()
+                .map(|gateway| gateway.to_string()),
+            interface: Some(columns[3].to_string()),
+            metric: None,
+        });
+    }
+    routes
+}
+
+fn parse_scutil_dns(output: &str) -> Vec<String> {
+    let mut servers = Vec::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        if key.trim().starts_with("nameserver[") {
+            let server = value.trim().to_string();
+            if !servers.contains(&server) {
+                servers.push(server);
+            }
+        }
+    }
+    servers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_macos_command_output() {
+        let ifconfig = "lo0: flags=8049<UP,LOOPBACK,RUNNING,MULTICAST> mtu 16384\n\toptions=1203<RXCSUM,TXCSUM>\n\tinet 127.0.0.1 netmask 0xff000000\n\
+en0: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 1500\n\tether a4:83:e7:12:34:56\n\tmedia: autoselect\n\tstatus: active\n\
+en7: flags=8863<UP,BROADCAST,SMART,RUNNING,SIMPLEX,MULTICAST> mtu 9000\n\tether 00:e0:4c:68:00:01\n\tmedia: autoselect (10Gbase-T <full-duplex>)\n\tstatus: inactive\n";
+        let interfaces = parse_ifconfig(ifconfig);
+        assert_eq!(interfaces.len(), 3);
+        assert_eq!(interfaces[0].state, LinkState::Up);
+        assert_eq!(interfaces[0].mtu, Some(16384));
+        assert_eq!(interfaces[1].mac.as_deref(), Some("a4:83:e7:12:34:56"));
+        assert_eq!(interfaces[1].speed_mbps, None);
+        assert_eq!(interfaces[2].state, LinkState::Down);
+        assert_eq!(interfaces[2].speed_mbps, Some(10000));
+        assert_eq!(
+            media_speed("autoselect (1000baseT <full-duplex>)"),
+            Some(1000)
+        );
+
+        let ports = "Hardware Port: Ethernet\nDevice: en7\nEthernet Address: 00:e0:4c:68:00:01\n\nHardware Port: Wi-Fi\nDevice: en0\nEthernet Address: a4:83:e7:12:34:56\n";
+        assert_eq!(parse_wifi_devices(ports), vec!["en0"]);
+
+        let netstat = "Routing tables\n\nInternet:\nDestination        Gateway            Flags               Netif Expire\ndefault            192.168.1.1        UGScg                 en0\n192.168.1          link#6             UCS                   en0      !\n\nInternet6:\nDestination                             Gateway                                 Flags               Netif Expire\ndefault                                 fe80::1%en0                             UGcg                  en0\n";
+        let routes = parse_netstat_routes(netstat);
+        assert_eq!(routes.len(), 3);
+        assert_eq!(routes[0].destination, "0.0.0.0/0");
+        assert_eq!(routes[0].gateway.as_deref(), Some("192.168.1.1"));
+        assert_eq!(routes[1].gateway, None);
+        assert_eq!(routes[2].destination, "::/0");
+        assert_eq!(routes[2].gateway.as_deref(), Some("fe80::1"));
+
+        let scutil = "DNS configuration\n\nresolver #1\n  nameserver[0] : 192.168.1.1\n  nameserver[1] : 1.1.1.1\n\nresolver #2\n  domain   : local\n  nameserver[0] : 192.168.1.1\n";
+        assert_eq!(parse_scutil_dns(scutil), vec!["192.168.1.1", "1.1.1.1"]);
+    }
+}
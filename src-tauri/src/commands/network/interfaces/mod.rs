@@ -0,0 +1,298 @@
+mod linux;
+mod macos;
+mod vpn;
+mod windows;
+
+use std::{
+    collections::HashSet,
+    net::{IpAddr, SocketAddr},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::{net::TcpStream, time::timeout};
+
+use super::probe::elapsed_ms;
+
+pub(super) use vpn::{detect_vpns, VpnLink};
+
+/// 探测网关时依次尝试的端口，收到 RST 也说明网关可达。
+const GATEWAY_PORTS: [u16; 3] = [53, 80, 443];
+const GATEWAY_PROBES: usize = 3;
+const GATEWAY_TIMEOUT: Duration = Duration::from_millis(800);
+
+#[derive(Clone, Copy, Default, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum LinkState {
+    Up,
+    Down,
+    #[default]
+    Unknown,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct InterfaceDetail {
+    name: String,
+    state: LinkState,
+    mtu: Option<u32>,
+    mac: Option<String>,
+    /// 协商速率，无线网卡为当前接收速率。
+    speed_mbps: Option<u32>,
+    /// 默认路由经过该网卡。
+    is_default: bool,
+    wifi: Option<WifiInfo>,
+}
+
+#[derive(Clone, Default, Serialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct WifiInfo {
+    ssid: Option<String>,
+    bssid: Option<String>,
+    signal_dbm: Option<i32>,
+    signal_percent: Option<u8>,
+    channel: Option<u32>,
+    frequency_mhz: Option<u32>,
+}
+
+#[derive(Clone, Serialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RouteEntry {
+    /// CIDR 形式，默认路由为 0.0.0.0/0 或 ::/0。
+    destination: String,
+    gateway: Option<String>,
+    interface: Option<String>,
+    metric: Option<u32>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct GatewayStatus {
+    address: String,
+    interface: Option<String>,
+    reachable: bool,
+    latency_ms: Option<f64>,
+    /// 实际应答的探测方式，如 tcp/53。
+    probe: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Default)]
+pub(super) struct LinkSnapshot {
+    pub interfaces: Vec<InterfaceDetail>,
+    pub routes: Vec<RouteEntry>,
+    pub dns_servers: Vec<String>,
+}
+
+/// 读取网卡、路由表和 DNS 配置，会调用系统命令，需在阻塞线程中执行。
+pub(super) fn collect_links() -> LinkSnapshot {
+    let mut snapshot = if cfg!(target_os = "linux") {
+        linux::collect()
+    } else if cfg!(target_os = "macos") {
+        macos::collect()
+    } else if cfg!(target_os = "windows") {
+        windows::collect()
+    } else {
+        LinkSnapshot::default()
+    };
+    let default_interface =
+        default_route(&snapshot.routes).and_then(|route| route.interface.clone());
+    for detail in &mut snapshot.interfaces {
+        detail.is_default = default_interface.as_deref() == Some(detail.name.as_str());
+    }
+    snapshot.interfaces.sort_by(|a, b| {
+        b.is_default
+            .cmp(&a.is_default)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let mut seen = HashSet::new();
+    snapshot
+        .dns_servers
+        .retain(|server| seen.insert(server.clone()));
+    snapshot
+}
+
+/// 只读取路由表，比 [`collect_links`] 轻量，供定时采样使用。
+pub(super) fn collect_routes() -> Vec<RouteEntry> {
+    if cfg!(target_os = "linux") {
+        linux::routes()
+    } else if cfg!(target_os = "macos") {
+        macos::routes()
+    } else if cfg!(target_os = "windows") {
+        windows::routes()
+    } else {
+        Vec::new()
+    }
+}
+
+/// 通过 TCP 连接默认网关测量延迟，不需要 ICMP 权限。
+pub(super) async fn check_gateway(routes: &[RouteEntry]) -> Option<GatewayStatus> {
+    let route = default_route(routes)?;
+    let address = route
+        .gateway
+        .as_deref()?
+        .parse::<IpAddr>()
+        .ok()
+        .filter(|gateway| !gateway.is_unspecified())?;
+    let mut status = GatewayStatus {
+        address: address.to_string(),
+        interface: route.interface.clone(),
+        reachable: false,
+        latency_ms: None,
+        probe: None,
+        error: None,
+    };
+    let mut rtts = Vec::new();
+    let mut ports = GATEWAY_PORTS.to_vec();
+    for _ in 0..GATEWAY_PROBES {
+        let mut answered = None;
+        for (index, port) in ports.iter().enumerate() {
+            let started = Instant::now();
+            match timeout(
+                GATEWAY_TIMEOUT,
+                TcpStream::connect(SocketAddr::new(address, *port)),
+            )
+            .await
+            {
+                Ok(Ok(_)) => answered = Some((index, elapsed_ms(started))),
+                Ok(Err(err)) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+                    answered = Some((index, elapsed_ms(started)))
+                }
+                Ok(Err(err)) => status.error = Some(err.to_string()),
+                Err(_) => {
+                    status.error = Some(format!("连接超时（{} ms）", GATEWAY_TIMEOUT.as_millis()))
+                }
+            }
+            if answered.is_some() {
+                break;
+            }
+        }
+        let Some((index, rtt)) = answered else {
+            break;
+        };
+        // 之后只用有应答的端口
+        ports = vec![ports[index]];
+        status.probe = Some(format!("tcp/{}", ports[0]));
+        rtts.push(rtt);
+    }
+    if !rtts.is_empty() {
+        status.reachable = true;
+        status.error = None;
+        let average = rtts.iter().sum::<f64>() / rtts.len() as f64;
+        status.latency_ms = Some((average * 100.0).round() / 100.0);
+    }
+    Some(status)
+}
+
+/// 默认路由中跃点数最小的一条，IPv4 优先。
+/// `dev wg0` 这类没有网关的默认路由同样算数，只是无法探测网关。
+fn default_route(routes: &[RouteEntry]) -> Option<&RouteEntry> {
+    routes
+        .iter()
+        .filter(|route| matches!(route.destination.as_str(), "0.0.0.0/0" | "::/0"))
+        .min_by_key(|route| {
+            (
+                route.destination.contains(':'),
+                route.metric.unwrap_or(u32::MAX),
+            )
+        })
+}
+
+fn run_command(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 按 IEEE 802.11 的信道划分由频率换算信道号。
+fn wifi_channel(frequency_mhz: u32) -> Option<u32> {
+    match frequency_mhz {
+        2484 => Some(14),
+        2412..=2472 => Some((frequency_mhz - 2407) / 5),
+        5000..=5925 => Some((frequency_mhz - 5000) / 5),
+        5955..=7115 => Some((frequency_mhz - 5950) / 5),
+        _ => None,
+    }
+}
+
+/// 常用的近似换算：-100 dBm 为 0%，-50 dBm 及以上为 100%。
+fn dbm_to_percent(dbm: i32) -> u8 {
+    (2 * (dbm + 100)).clamp(0, 100) as u8
+}
+
+fn percent_to_dbm(percent: u8) -> i32 {
+    i32::from(percent.min(100)) / 2 - 100
+}
+
+fn normalize_mac(raw: &str) -> Option<String> {
+    let mac = raw.trim().replace('-', ":").to_ascii_lowercase();
+    let valid = mac.split(':').count() == 6
+        && mac
+            .split(':')
+            .all(|part| part.len() == 2 && part.chars().all(|ch| ch.is_ascii_hexdigit()));
+    (valid && mac != "00:00:00:00:00:00").then_some(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(destination: &str, gateway: Option<&str>, interface: &str, metric: u32) -> RouteEntry {
+        RouteEntry {
+            destination: destination.into(),
+            gateway: gateway.map(str::to_string),
+            interface: Some(interface.into()),
+            metric: Some(metric),
+        }
+    }
+
+    #[test]
+    fn picks_default_route_and_converts_wifi_units() {
+        let routes = vec![
+            route("::/0", Some("fe80::1"), "wlan0", 100),
+            route("0.0.0.0/0", Some("10.8.0.1"), "tun0", 50),
+            route("0.0.0.0/0", Some("192.168.1.1"), "wlan0", 600),
+            route("0.0.0.0/0", None, "wg0", 10),
+            route("192.168.1.0/24", None, "wlan0", 600),
+        ];
+        assert_eq!(
+            default_route(&routes).unwrap().interface.as_deref(),
+            Some("wg0")
+        );
+        assert_eq!(
+            default_route(&routes[..3]).unwrap().interface.as_deref(),
+            Some("tun0")
+        );
+        assert!(default_route(&routes[4..]).is_none());
+
+        assert_eq!(wifi_channel(2412), Some(1));
+        assert_eq!(wifi_channel(2484), Some(14));
+        assert_eq!(wifi_channel(5180), Some(36));
+        assert_eq!(wifi_channel(5975), Some(5));
+        assert_eq!(dbm_to_percent(-52), 96);
+        assert_eq!(dbm_to_percent(-30), 100);
+        assert_eq!(percent_to_dbm(70), -65);
+        assert_eq!(
+            normalize_mac("AA-BB-CC-DD-EE-0F").as_deref(),
+            Some("aa:bb:cc:dd:ee:0f")
+        );
+        assert_eq!(normalize_mac("00:00:00:00:00:00"), None);
+    }
+
+    #[tokio::test]
+    async fn measures_gateway_latency_over_tcp() {
+        // 本机 53 端口通常拒绝连接，同样算作可达
+        let routes = vec![route("0.0.0.0/0", Some("127.0.0.1"), "lo", 0)];
+        let status = check_gateway(&routes).await.unwrap();
+        assert!(status.reachable, "{:?}", status.error);
+        assert!(status.latency_ms.is_some());
+        assert!(status.probe.unwrap().starts_with("tcp/"));
+        assert!(check_gateway(&[]).await.is_none());
+        // 没有网关的默认路由不做探测
+        let routes = vec![route("0.0.0.0/0", None, "wg0", 0)];
+        assert!(check_gateway(&routes).await.is_none());
+    }
+}
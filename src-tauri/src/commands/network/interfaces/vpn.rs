@@ -0,0 +1,571 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use if_addrs::get_if_addrs;
+use serde::{Deserialize, Serialize};
+
+use super::{run_command, RouteEntry};
+
+const SYS_CLASS_NET: &str = "/sys/class/net";
+/// linux/if_arp.h 中的链路层类型。
+const ARPHRD_PPP: u32 = 512;
+const ARPHRD_NONE: u32 = 65534;
+/// linux/if_tun.h
+const IFF_TAP: u32 = 0x0002;
+
+/// 全局模式的路由：默认路由，或 OpenVPN 等用两条 /1 覆盖默认路由的写法。
+const FULL_TUNNEL_ROUTES: [&str; 8] = [
+    "0.0.0.0/0",
+    "::/0",
+    "0.0.0.0/1",
+    "128.0.0.0/1",
+    "::/1",
+    "8000::/1",
+    "0/1",
+    "128.0/1",
+];
+
+#[derive(Clone, Copy, Serialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub(in super::super) enum VpnKind {
+    WireGuard,
+    OpenVpn,
+    OpenConnect,
+    Ipsec,
+    Ppp,
+    Tailscale,
+    ZeroTier,
+    Tun,
+    Tap,
+    Other,
+}
+
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(in super::super) struct VpnLink {
+    pub name: String,
+    pub kind: VpnKind,
+    /// NetworkManager 中对应的连接名称。
+    pub connection: Option<String>,
+    /// 默认流量是否经过该网卡。
+    pub full_tunnel: bool,
+    /// 经该网卡转发的目的网段，不含链路本地和组播。
+    pub routed_destinations: Vec<String>,
+    /// 判定依据，便于界面上解释为什么认为它是 VPN。
+    pub evidence: Vec<String>,
+}
+
+/// sysfs 中与隧道相关的属性，非 Linux 平台没有。
+#[derive(Default)]
+struct LinkInfo {
+    link_type: Option<u32>,
+    tun_flags: Option<u32>,
+    devtype: Option<String>,
+}
+
+struct NmConnection {
+    name: String,
+    kind: VpnKind,
+}
+
+/// `ip -j route show table all` 的一条记录，只取用得到的字段。
+#[derive(Deserialize)]
+struct IpRoute {
+    dst: String,
+    gateway: Option<String>,
+    dev: Option<String>,
+    metric: Option<u32>,
+    /// 主表的路由没有该字段。
+    table: Option<String>,
+    /// 普通路由没有该字段，local、broadcast 等会标出类型。
+    #[serde(rename = "type")]
+    route_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IpRule {
+    table: Option<String>,
+}
+
+/// 综合网卡类型、NetworkManager 连接和路由表找出 VPN 网卡，会调用系统命令，需在阻塞线程中执行。
+pub(in super::super) fn detect_vpns(routes: &[RouteEntry]) -> Vec<VpnLink> {
+    let mut routes = routes.to_vec();
+    if cfg!(target_os = "linux") {
+        routes.extend(policy_routes());
+    }
+    let mut candidates: HashMap<String, Option<LinkInfo>> = HashMap::new();
+    if cfg!(target_os = "linux") {
+        if let Ok(entries) = fs::read_dir(SYS_CLASS_NET) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                candidates.insert(name, Some(read_link_info(&entry.path())));
+            }
+        }
+    }
+    let names = get_if_addrs()
+        .map(|interfaces| interfaces.into_iter().map(|iface| iface.name))
+        .into_iter()
+        .flatten()
+        .chain(routes.iter().filter_map(|route| route.interface.clone()));
+    for name in names {
+        candidates.entry(name).or_default();
+    }
+    let connections = if cfg!(target_os = "linux") {
+        nm_connections()
+    } else {
+        HashMap::new()
+    };
+    evaluate(candidates, &connections, &routes)
+}
+
+fn evaluate(
+    candidates: HashMap<String, Option<LinkInfo>>,
+    connections: &HashMap<String, NmConnection>,
+    routes: &[RouteEntry],
+) -> Vec<VpnLink> {
+    let mut links = candidates
+        .into_iter()
+        .filter_map(|(name, info)| {
+            let (kind, mut evidence, confirmed) =
+                classify(&name, info.as_ref(), connections.get(&name))?;
+            let mut routed_destinations = routes
+                .iter()
+                .filter(|route| route.interface.as_deref() == Some(name.as_str()))
+                .map(|route| route.destination.clone())
+                .filter(|destination| !is_link_scoped(destination))
+                .collect::<Vec<_>>();
+            routed_destinations.sort();
+            routed_destinations.dedup();
+            // 只凭名称判断时必须有路由经过，macOS 上闲置的 utun 网卡很多
+            if !confirmed && routed_destinations.is_empty() {
+                return None;
+            }
+            let full_tunnel = routed_destinations
+                .iter()
+                .any(|destination| FULL_TUNNEL_ROUTES.contains(&destination.as_str()));
+            if full_tunnel {
+                evidence.push("默认路由经过该网卡".into());
+            } else if !routed_destinations.is_empty() {
+                evidence.push(format!(
+                    "{} 条分流路由经过该网卡",
+                    routed_destinations.len()
+                ));
+            }
+            Some(VpnLink {
+                connection: connections.get(&name).map(|nm| nm.name.clone()),
+                name,
+                kind,
+                full_tunnel,
+                routed_destinations,
+                evidence,
+            })
+        })
+        .collect::<Vec<_>>();
+    links.sort_by(|a, b| a.name.cmp(&b.name));
+    links
+}
+
+/// 返回 (类型, 依据, 是否由网卡属性或连接信息确认)。
+/// 有 sysfs 信息时以它为准，名称只用来细分类型，避免把名为 tunnel 的网桥误判为 VPN。
+fn classify(
+    name: &str,
+    info: Option<&LinkInfo>,
+    connection: Option<&NmConnection>,
+) -> Option<(VpnKind, Vec<String>, bool)> {
+    let mut evidence = Vec::new();
+    let mut kind = info.and_then(|info| link_kind(info, &mut evidence));
+    if let Some(connection) = connection {
+        evidence.push(format!("NetworkManager 连接「{}」", connection.name));
+        if kind.is_none() || connection.kind != VpnKind::Tun {
+            kind = Some(connection.kind);
+        }
+    }
+    let Some(kind) = kind else {
+        if info.is_some() {
+            return None;
+        }
+        let kind = name_hint(name)?;
+        evidence.push("网卡名称符合常见 VPN 命名".into());
+        return Some((kind, evidence, false));
+    };
+    // tun/tap 只说明是隧道，Tailscale、ZeroTier 等要靠名称区分
+    let kind = match (kind, name_hint(name)) {
+        (VpnKind::Tun | VpnKind::Tap, Some(hint)) => hint,
+        _ => kind,
+    };
+    Some((kind, evidence, true))
+}
+
+fn link_kind(info: &LinkInfo, evidence: &mut Vec<String>) -> Option<VpnKind> {
+    match info.devtype.as_deref() {
+        Some("wireguard") => {
+            evidence.push("内核网卡类型为 wireguard".into());
+            return Some(VpnKind::WireGuard);
+        }
+        Some("xfrm") => {
+            evidence.push("内核网卡类型为 xfrm（IPsec）".into());
+            return Some(VpnKind::Ipsec);
+        }
+        _ => {}
+    }
+    if let Some(flags) = info.tun_flags {
+        evidence.push(format!("tun/tap 设备（tun_flags=0x{flags:04x}）"));
+        return Some(if flags & IFF_TAP != 0 {
+            VpnKind::Tap
+        } else {
+            VpnKind::Tun
+        });
+    }
+    match info.link_type {
+        Some(ARPHRD_PPP) => {
+            evidence.push("链路层类型为 PPP".into());
+            Some(VpnKind::Ppp)
+        }
+        Some(ARPHRD_NONE) => {
+            evidence.push("链路层类型为 none（三层隧道）".into());
+            Some(VpnKind::Tun)
+        }
+        _ => None,
+    }
+}
+
+/// 按名称推测类型，只匹配前缀加序号或完整的产品名，不做子串匹配。
+fn name_hint(name: &str) -> Option<VpnKind> {
+    let lower = name.to_ascii_lowercase();
+    for (marker, kind) in [
+        ("wireguard", VpnKind::WireGuard),
+        ("openvpn", VpnKind::OpenVpn),
+        ("tailscale", VpnKind::Tailscale),
+        ("zerotier", VpnKind::ZeroTier),
+        ("wintun", VpnKind::Tun),
+        ("tap-windows", VpnKind::Tap),
+    ] {
+        if lower.contains(marker) {
+            return Some(kind);
+        }
+    }
+    let numbered = |prefix: &str| {
+        lower
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.chars().all(|ch| ch.is_ascii_digit()))
+    };
+    if numbered("utun") || numbered("tun") {
+        Some(VpnKind::Tun)
+    } else if numbered("tap") {
+        Some(VpnKind::Tap)
+    } else if numbered("wg") {
+        Some(VpnKind::WireGuard)
+    } else if numbered("ppp") {
+        Some(VpnKind::Ppp)
+    } else if numbered("ipsec") {
+        Some(VpnKind::Ipsec)
+    } else if lower.len() > 2
+        && lower.starts_with("zt")
+        && lower[2..].chars().all(|ch| ch.is_ascii_alphanumeric())
+    {
+        // ZeroTier 网卡名为 zt 加网络 ID 的哈希
+        Some(VpnKind::ZeroTier)
+    } else {
+        None
+    }
+}
+
+fn is_link_scoped(destination: &str) -> bool {
+    let host = destination.split('/').next().unwrap_or_default();
+    ["fe80", "ff", "169.254", "224.", "255.255.255.255"]
+        .iter()
+        .any(|prefix| host.starts_with(prefix))
+}
+
+fn read_link_info(path: &Path) -> LinkInfo {
+    let read = |file: &str| {
+        fs::read_to_string(path.join(file))
+            .ok()
+            .map(|value| value.trim().to_string())
+    };
+    LinkInfo {
+        link_type: read("type").and_then(|value| value.parse().ok()),
+        tun_flags: read("tun_flags")
+            .and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok()),
+        devtype: read("uevent").and_then(|uevent| {
+            uevent
+                .lines()
+                .find_map(|line| line.strip_prefix("DEVTYPE="))
+                .map(str::to_string)
+        }),
+    }
+}
+
+/// 主表之外的路由。wg-quick（表 51820 + fwmark 规则）和 Tailscale（表 52）的路由都不在主表里，
+/// 这里读出所有路由表，只保留被 `ip rule` 引用到的表。
+fn policy_routes() -> Vec<RouteEntry> {
+    let mut tables = HashSet::new();
+    for family in ["-4", "-6"] {
+        if let Some(output) = run_command("ip", &["-j", family, "rule"]) {
+            tables.extend(parse_ip_rules(&output));
+        }
+    }
+    let mut routes = Vec::new();
+    for (family, ipv6) in [("-4", false), ("-6", true)] {
+        if let Some(output) = run_command("ip", &["-j", family, "route", "show", "table", "all"]) {
+            routes.extend(parse_ip_routes(&output, ipv6, &tables));
+        }
+    }
+    routes
+}
+
+/// 规则引用的路由表，不含主表。
+fn parse_ip_rules(output: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<IpRule>>(output)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|rule| rule.table)
+        .filter(|table| table != "main")
+        .collect()
+}
+
+/// 只保留 `tables` 中普通的单播路由，主表已经由 [`super::collect_routes`] 读过。
+fn parse_ip_routes(output: &str, ipv6: bool, tables: &HashSet<String>) -> Vec<RouteEntry> {
+    serde_json::from_str::<Vec<IpRoute>>(output)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|route| {
+            route
+                .route_type
+                .as_deref()
+                .is_none_or(|kind| kind == "unicast")
+                && route
+                    .table
+                    .as_ref()
+                    .is_some_and(|table| tables.contains(table))
+        })
+        .map(|route| {
+            let destination = match route.dst.as_str() {
+                "default" if ipv6 => "::/0".to_string(),
+                "default" => "0.0.0.0/0".to_string(),
+                dst if dst.contains('/') => dst.to_string(),
+                dst if ipv6 => format!("{dst}/128"),
+                dst => format!("{dst}/32"),
+            };
+            RouteEntry {
+                destination,
+                gateway: route.gateway,
+                interface: route.dev,
+                metric: route.metric,
+            }
+        })
+        .collect()
+}
+
+/// 活动连接中的 VPN、WireGuard 和 tun 连接，按网卡名索引。
+fn nm_connections() -> HashMap<String, NmConnection> {
+    let Some(output) = run_command(
+        "nmcli",
+        &[
+            "-t",
+            "-f",
+            "NAME,TYPE,DEVICE",
+            "connection",
+            "show",
+            "--active",
+        ],
+    ) else {
+        return HashMap::new();
+    };
+    let mut connections = HashMap::new();
+    for (name, connection_type, device) in parse_nm_active(&output) {
+        let (device, kind) = match connection_type.as_str() {
+            "wireguard" => (Some(device), VpnKind::WireGuard),
+            "tun" => (Some(device), VpnKind::Tun),
+            // 插件类 VPN 的 DEVICE 是承载它的物理网卡，实际的隧道网卡要另外查
+            "vpn" => {
+                let field = |field: &str| {
+                    run_command("nmcli", &["-g", field, "connection", "show", "id", &name])
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                };
+                let kind = field("vpn.service-type")
+                    .map(|service| vpn_plugin_kind(&service))
+                    .unwrap_or(VpnKind::Other);
+                (field("GENERAL.IP-IFACE"), kind)
+            }
+            _ => continue,
+        };
+        if let Some(device) = device.filter(|device| !device.is_empty() && device != "--") {
+            connections.insert(device, NmConnection { name, kind });
+        }
+    }
+    connections
+}
+
+/// nmcli 的 terse 输出以冒号分隔，字段内的冒号转义为 `\:`。
+fn parse_nm_active(output: &str) -> Vec<(String, String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = Vec::new();
+            let mut current = String::new();
+            let mut chars = line.chars();
+            while let Some(ch) = chars.next() {
+                match ch {
+                    '\\' => current.extend(chars.next()),
+                    ':' => fields.push(std::mem::take(&mut current)),
+                    _ => current.push(ch),
+                }
+            }
+            fields.push(current);
+            let [name, connection_type, device] = <[String; 3]>::try_from(fields).ok()?;
+            Some((name, connection_type, device))
+        })
+        .collect()
+}
+
+fn vpn_plugin_kind(service: &str) -> VpnKind {
+    let service = service.to_ascii_lowercase();
+    if service.contains("openvpn") {
+        VpnKind::OpenVpn
+    } else if service.contains("openconnect") {
+        VpnKind::OpenConnect
+    } else if ["strongswan", "libreswan", "l2tp", "vpnc"]
+        .iter()
+        .any(|plugin| service.contains(plugin))
+    {
+        VpnKind::Ipsec
+    } else if service.contains("pptp") || service.contains("sstp") {
+        VpnKind::Ppp
+    } else if service.contains("wireguard") {
+        VpnKind::WireGuard
+    } else {
+        VpnKind::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(destination: &str, interface: &str) -> RouteEntry {
+        RouteEntry {
+            destination: destination.into(),
+            gateway: None,
+            interface: Some(interface.into()),
+            metric: None,
+        }
+    }
+
+    fn sysfs(link_type: u32, tun_flags: Option<u32>, devtype: Option<&str>) -> Option<LinkInfo> {
+        Some(LinkInfo {
+            link_type: Some(link_type),
+            tun_flags,
+            devtype: devtype.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn detects_vpns_from_link_type_and_routes() {
+        let candidates = HashMap::from([
+            ("tunnel0".to_string(), sysfs(1, None, Some("bridge"))),
+            ("corp".to_string(), sysfs(ARPHRD_NONE, Some(0x1001), None)),
+            (
+                "wg-home".to_string(),
+                sysfs(ARPHRD_NONE, None, Some("wireguard")),
+            ),
+            (
+                "tailscale0".to_string(),
+                sysfs(ARPHRD_NONE, Some(0x1001), None),
+            ),
+            ("eth0".to_string(), sysfs(1, None, None)),
+            ("utun0".to_string(), None),
+            ("utun3".to_string(), None),
+            ("tun0".to_string(), sysfs(ARPHRD_NONE, Some(0x1001), None)),
+        ]);
+        let connections = HashMap::from([(
+            "tun0".to_string(),
+            NmConnection {
+                name: "Office".into(),
+                kind: VpnKind::OpenVpn,
+            },
+        )]);
+        let mut routes = vec![
+            route("0.0.0.0/0", "eth0"),
+            route("172.17.0.0/16", "tunnel0"),
+            route("10.30.0.0/16", "corp"),
+            route("10.20.0.0/16", "corp"),
+            route("fe80::/64", "utun0"),
+            route("0/1", "utun3"),
+            route("128.0/1", "utun3"),
+            route("0.0.0.0/1", "tun0"),
+            route("10.30.0.0/16", "corp"),
+        ];
+        // wg-quick 和 Tailscale 的路由在独立的路由表里，由 ip rule 引用
+        let tables = parse_ip_rules(
+            r#"[{"priority":0,"src":"all","table":"local"},{"priority":5270,"src":"all","table":"52"},{"priority":32764,"src":"all","suppress_prefixlength":0,"table":"main"},{"priority":32765,"not":null,"src":"all","fwmark":"0xca6c","table":"51820"},{"priority":32766,"src":"all","table":"main"}]"#,
+        )
+        .into_iter()
+        .collect::<HashSet<_>>();
+        routes.extend(parse_ip_routes(
+            r#"[{"dst":"default","dev":"wg-home","table":"51820","scope":"link","flags":[]},{"dst":"100.64.0.0/10","dev":"tailscale0","table":"52","flags":[]},{"dst":"100.100.100.100","dev":"tailscale0","table":"52","flags":[]},{"dst":"10.9.0.0/16","dev":"tun0","table":"100","flags":[]},{"type":"local","dst":"10.0.0.2","dev":"wg-home","table":"local","protocol":"kernel","scope":"host","flags":[]}]"#,
+            false,
+            &tables,
+        ));
+        let links = evaluate(candidates, &connections, &routes);
+        let names = links
+            .iter()
+            .map(|link| link.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["corp", "tailscale0", "tun0", "utun3", "wg-home"]
+        );
+
+        let corp = &links[0];
+        assert_eq!(corp.kind, VpnKind::Tun);
+        assert!(!corp.full_tunnel);
+        assert_eq!(
+            corp.routed_destinations,
+            vec!["10.20.0.0/16", "10.30.0.0/16"]
+        );
+        assert_eq!(links[1].kind, VpnKind::Tailscale);
+        assert_eq!(
+            links[1].routed_destinations,
+            vec!["100.100.100.100/32", "100.64.0.0/10"]
+        );
+        assert_eq!(links[2].kind, VpnKind::OpenVpn);
+        assert_eq!(links[2].connection.as_deref(), Some("Office"));
+        assert!(links[2].full_tunnel);
+        assert_eq!(links[2].routed_destinations, vec!["0.0.0.0/1"]);
+        assert!(links[3].full_tunnel);
+        assert_eq!(links[4].kind, VpnKind::WireGuard);
+        assert!(links[4].full_tunnel);
+        assert_eq!(links[4].routed_destinations, vec!["0.0.0.0/0"]);
+    }
+
+    #[test]
+    fn parses_nmcli_output_and_name_hints() {
+        let active = "Home Wi\\:Fi:802-11-wireless:wlan0\nOffice:vpn:wlan0\nwg0:wireguard:wg0\n";
+        let parsed = parse_nm_active(active);
+        assert_eq!(parsed[0].0, "Home Wi:Fi");
+        assert_eq!(parsed[2], ("wg0".into(), "wireguard".into(), "wg0".into()));
+        assert_eq!(
+            vpn_plugin_kind("org.freedesktop.NetworkManager.openvpn"),
+            VpnKind::OpenVpn
+        );
+        assert_eq!(
+            vpn_plugin_kind("org.freedesktop.NetworkManager.strongswan"),
+            VpnKind::Ipsec
+        );
+
+        assert_eq!(name_hint("utun4"), Some(VpnKind::Tun));
+        assert_eq!(
+            name_hint("OpenVPN Data Channel Offload"),
+            Some(VpnKind::OpenVpn)
+        );
+        assert_eq!(name_hint("zt5u4ab3xy"), Some(VpnKind::ZeroTier));
+        assert_eq!(name_hint("tunnel"), None);
+        assert_eq!(name_hint("shadowsocks"), None);
+        assert_eq!(name_hint("wlan0"), None);
+    }
+}
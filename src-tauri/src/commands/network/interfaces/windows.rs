@@ -0,0 +1,252 @@
+use std::{collections::HashMap, net::IpAddr};
+
+use super::{
+    normalize_mac, percent_to_dbm, run_command, InterfaceDetail, LinkSnapshot, LinkState,
+    RouteEntry, WifiInfo,
+};
+
+pub(super) fn collect() -> LinkSnapshot {
+    let mut interfaces = interface_table();
+    let macs = run_command("getmac", &["/v", "/fo", "csv", "/nh"])
+        .map(|output| parse_getmac(&output))
+        .unwrap_or_default();
+    let wifi = run_command("netsh", &["wlan", "show", "interfaces"])
+        .map(|output| parse_wlan_interfaces(&output))
+        .unwrap_or_default();
+    for (_, detail) in &mut interfaces {
+        detail.mac = macs.get(&detail.name).cloned();
+        if let Some((info, rate)) = wifi.get(&detail.name) {
+            detail.wifi = Some(info.clone());
+            detail.speed_mbps = *rate;
+        }
+    }
+    let routes = routes_for(&interfaces);
+    LinkSnapshot {
+        interfaces: interfaces.into_iter().map(|(_, detail)| detail).collect(),
+        routes,
+        dns_servers: run_command("ipconfig", &["/all"])
+            .map(|output| parse_ipconfig_dns(&output))
+            .unwrap_or_default(),
+    }
+}
+
+pub(super) fn routes() -> Vec<RouteEntry> {
+    routes_for(&interface_table())
+}
+
+fn interface_table() -> Vec<(u32, InterfaceDetail)> {
+    run_command("netsh", &["interface", "ipv4", "show", "interfaces"])
+        .map(|output| parse_netsh_interfaces(&output))
+        .unwrap_or_default()
+}
+
+/// netsh 的路由表只有网卡序号，需要按网卡列表换成名称。
+fn routes_for(interfaces: &[(u32, InterfaceDetail)]) -> Vec<RouteEntry> {
+    let names = interfaces
+        .iter()
+        .map(|(index, detail)| (*index, detail.name.clone()))
+        .collect::<HashMap<_, _>>();
+    let mut routes = Vec::new();
+    for family in ["ipv4", "ipv6"] {
+        if let Some(output) = run_command("netsh", &["interface", family, "show", "route"]) {
+            routes.extend(parse_netsh_routes(&output, &names));
+        }
+    }
+    routes
+}
+
+/// 表格列依次为 Idx、Met、MTU、State、Name，名称中可能有空格。
+fn parse_netsh_interfaces(output: &str) -> Vec<(u32, InterfaceDetail)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            if columns.len() < 5 {
+                return None;
+            }
+            let index = columns[0].parse::<u32>().ok()?;
+            let state = match columns[3] {
+                "connected" => LinkState::Up,
+                "disconnected" => LinkState::Down,
+                _ => LinkState::Unknown,
+            };
+            Some((
+                index,
+                InterfaceDetail {
+                    name: columns[4..].join(" "),
+                    state,
+                    mtu: columns[2].parse().ok(),
+                    ..InterfaceDetail::default()
+                },
+            ))
+        })
+        .collect()
+}
+
+/// 最后一列是网关地址，直连路由则是网卡名称。
+fn parse_netsh_routes(output: &str, names: &HashMap<u32, String>) -> Vec<RouteEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            if columns.len() < 6 || !columns[3].contains('/') {
+                return None;
+            }
+            let index = columns[4].parse::<u32>().ok()?;
+            let target = columns[5..].join(" ");
+            Some(RouteEntry {
+                destination: columns[3].to_string(),
+                gateway: target
+                    .parse::<IpAddr>()
+                    .ok()
+                    .filter(|gateway| !gateway.is_unspecified())
+                    .map(|gateway| gateway.to_string()),
+                interface: names.get(&index).cloned(),
+                metric: columns[2].parse().ok(),
+            })
+        })
+        .collect()
+}
+
+fn parse_getmac(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = line
+                .trim()
+                .trim_matches('"')
+                .split("\",\"")
+                .collect::<Vec<_>>();
+            Some((fields.first()?.to_string(), normalize_mac(fields.get(2)?)?))
+        })
+        .collect()
+}
+
+type WlanInterfaces = HashMap<String, (WifiInfo, Option<u32>)>;
+
+fn parse_wlan_interfaces(output: &str) -> WlanInterfaces {
+    let mut interfaces = WlanInterfaces::new();
+    let mut current: Option<(String, WifiInfo, Option<u32>)> = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Name" => {
+                if let Some((name, info, rate)) = current.take() {
+                    interfaces.insert(name, (info, rate));
+                }
+                current = Some((value.to_string(), WifiInfo::default(), None));
+            }
+            key => {
+                let Some((_, info, rate)) = current.as_mut() else {
+                    continue;
+                };
+                match key {
+                    "SSID" => info.ssid = Some(value.to_string()),
+                    "BSSID" | "AP BSSID" => info.bssid = normalize_mac(value),
+                    "Channel" => info.channel = value.parse().ok(),
+                    "Signal" => {
+                        info.signal_percent = value.trim_end_matches('%').parse().ok();
+                        info.signal_dbm = info.signal_percent.map(percent_to_dbm);
+                    }
+                    "Receive rate (Mbps)" => {
+                        *rate = value.parse::<f64>().ok().map(|rate| rate as u32)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if let Some((name, info, rate)) = current {
+        interfaces.insert(name, (info, rate));
+    }
+    for (info, _) in interfaces.values_mut() {
+        // netsh 只给出信道号，按频段反推中心频率
+        info.frequency_mhz = info.channel.and_then(|channel| match channel {
+            1..=13 => Some(2407 + channel * 5),
+            14 => Some(2484),
+            32..=177 => Some(5000 + channel * 5),
+            _ => None,
+        });
+    }
+    interfaces
+}
+
+/// "DNS Servers" 后的第一个地址和同一缩进下的后续行。
+fn parse_ipconfig_dns(output: &str) -> Vec<String> {
+    let mut servers = Vec::new();
+    let mut in_dns = false;
+    for line in output.lines() {
+        let value = match line.split_once(": ") {
+            Some((key, value)) if key.contains("DNS Servers") || key.contains("DNS 服务器") => {
+                in_dns = true;
+                value
+            }
+            Some(_) => {
+                in_dns = false;
+                continue;
+            }
+            None if in_dns => line,
+            None => continue,
+        };
+        let address = value.trim().split('%').next().unwrap_or_default();
+        match address.parse::<IpAddr>() {
+            Ok(address) => {
+                let address = address.to_string();
+                if !servers.contains(&address) {
+                    servers.push(address);
+                }
+            }
+            Err(_) => in_dns = false,
+        }
+    }
+    servers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows_command_output() {
+        let interfaces = parse_netsh_interfaces(
+            "\nIdx     Met         MTU          State                Name\n---  ----------  ----------  ------------  ---------------------------\n  1          75  4294967295  connected     Loopback Pseudo-Interface 1\n 12          25        1500  connected     Wi-Fi\n 18          35        1500  disconnected  Ethernet 2\n",
+        );
+        assert_eq!(interfaces.len(), 3);
+        assert_eq!(interfaces[0].1.name, "Loopback Pseudo-Interface 1");
+        assert_eq!(interfaces[2].1.state, LinkState::Down);
+        let names = interfaces
+            .iter()
+            .map(|(index, detail)| (*index, detail.name.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let routes = parse_netsh_routes(
+            "\nPublish  Type      Met  Prefix                    Idx  Gateway/Interface Name\n-------  --------  ---  ------------------------  ---  ------------------------\nNo       Manual    0    0.0.0.0/0                  12  192.168.1.1\nNo       System    256  127.0.0.0/8                 1  Loopback Pseudo-Interface 1\n",
+            &names,
+        );
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].gateway.as_deref(), Some("192.168.1.1"));
+        assert_eq!(routes[0].interface.as_deref(), Some("Wi-Fi"));
+        assert_eq!(routes[1].gateway, None);
+
+        let macs = parse_getmac("\"Wi-Fi\",\"Intel(R) Wi-Fi 6 AX201\",\"A4-83-E7-12-34-56\",\"\\Device\\Tcpip_{X}\"\n\"Ethernet 2\",\"Realtek\",\"N/A\",\"Media disconnected\"\n");
+        assert_eq!(
+            macs.get("Wi-Fi").map(String::as_str),
+            Some("a4:83:e7:12:34:56")
+        );
+        assert!(!macs.contains_key("Ethernet 2"));
+
+        let wlan = parse_wlan_interfaces("\nThere is 1 interface on the system:\n\n    Name                   : Wi-Fi\n    State                  : connected\n    SSID                   : Home Net\n    AP BSSID               : 11:22:33:44:55:66\n    Channel                : 36\n    Receive rate (Mbps)    : 866.7\n    Signal                 : 92%\n");
+        let (info, rate) = &wlan["Wi-Fi"];
+        assert_eq!(info.ssid.as_deref(), Some("Home Net"));
+        assert_eq!(info.bssid.as_deref(), Some("11:22:33:44:55:66"));
+        assert_eq!(info.frequency_mhz, Some(5180));
+        assert_eq!(info.signal_percent, Some(92));
+        assert_eq!(*rate, Some(866));
+
+        let dns = parse_ipconfig_dns("   DHCP Enabled. . . . . . . . . . . : Yes\n   DNS Servers . . . . . . . . . . . : fe80::1%12\n                                       192.168.1.1\n   NetBIOS over Tcpip. . . . . . . . : Enabled\n");
+        assert_eq!(dns, vec!["fe80::1", "192.168.1.1"]);
+    }
+}
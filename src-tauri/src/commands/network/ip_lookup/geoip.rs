@@ -0,0 +1,326 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use maxminddb::{MaxMindDBError, Reader};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::super::PublicIpInfo;
+
+type ReaderCache = HashMap<PathBuf, Arc<Reader<Vec<u8>>>>;
+
+/// 打开过的数据库按路径缓存，City 库有几十 MB，不适合每次查询都重新读取。
+static READERS: Lazy<Mutex<ReaderCache>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 兼容 GeoLite2/GeoIP2 的 Country、City 和 ASN 库，缺少的字段保持为空。
+#[derive(Default, Deserialize)]
+struct MmdbRecord {
+    country: Option<MmdbPlace>,
+    registered_country: Option<MmdbPlace>,
+    city: Option<MmdbPlace>,
+    subdivisions: Option<Vec<MmdbPlace>>,
+    location: Option<MmdbLocation>,
+    autonomous_system_number: Option<u32>,
+    autonomous_system_organization: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct MmdbPlace {
+    iso_code: Option<String>,
+    names: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Default, Deserialize)]
+struct MmdbLocation {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    time_zone: Option<String>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeoIpRecord {
+    ip: String,
+    country: Option<String>,
+    country_code: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    asn: Option<String>,
+    organization: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    timezone: Option<String>,
+    /// 命中的数据库类型，如 GeoLite2-ASN。
+    databases: Vec<String>,
+}
+
+impl GeoIpRecord {
+    #[cfg(test)]
+    pub(super) fn is_empty(&self) -> bool {
+        self.databases.is_empty()
+    }
+
+    /// 只补全查询服务没有给出的字段。
+    pub(super) fn fill(&self, info: &mut PublicIpInfo) {
+        fn merge<T: Clone>(target: &mut Option<T>, value: &Option<T>) {
+            if target.is_none() {
+                target.clone_from(value);
+            }
+        }
+        merge(&mut info.country, &self.country);
+        merge(&mut info.country_code, &self.country_code);
+        merge(&mut info.region, &self.region);
+        merge(&mut info.city, &self.city);
+        merge(&mut info.asn, &self.asn);
+        merge(&mut info.organization, &self.organization);
+        merge(&mut info.latitude, &self.latitude);
+        merge(&mut info.longitude, &self.longitude);
+        merge(&mut info.timezone, &self.timezone);
+        if !self.databases.is_empty() {
+            info.source = format!("{}+{}", info.source, self.databases.join("+"));
+        }
+    }
+}
+
+/// 依次查询各个数据库并合并结果，前面的数据库优先。
+pub(super) fn lookup(ip: IpAddr, databases: &[String]) -> Result<GeoIpRecord, String> {
+    let mut record = GeoIpRecord {
+        ip: ip.to_string(),
+        ..GeoIpRecord::default()
+    };
+    for path in databases {
+        let reader = open_reader(Path::new(path))?;
+        let found = match reader.lookup::<MmdbRecord>(ip) {
+            Ok(found) => found,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => continue,
+            Err(err) => return Err(format!("查询 GeoIP 数据库失败 {path}: {err}")),
+        };
+        merge_record(&mut record, found);
+        record.databases.push(reader.metadata.database_type.clone());
+    }
+    Ok(record)
+}
+
+pub(super) fn clear_cache() {
+    READERS.lock().unwrap().clear();
+}
+
+fn open_reader(path: &Path) -> Result<Arc<Reader<Vec<u8>>>, String> {
+    let mut readers = READERS.lock().unwrap();
+    if let Some(reader) = readers.get(path) {
+        return Ok(reader.clone());
+    }
+    let reader = Reader::open_readfile(path)
+        .map_err(|err| format!("打开 GeoIP 数据库失败 {}: {err}", path.display()))?;
+    let reader = Arc::new(reader);
+    readers.insert(path.to_path_buf(), reader.clone());
+    Ok(reader)
+}
+
+fn merge_record(record: &mut GeoIpRecord, found: MmdbRecord) {
+    let country = found.country.or(found.registered_country);
+    let region = found.subdivisions.and_then(|list| list.into_iter().next());
+    let location = found.location.unwrap_or_default();
+    let values = [
+        (&mut record.country, country.as_ref().and_then(place_name)),
+        (
+            &mut record.country_code,
+            country.as_ref().and_then(|place| place.iso_code.clone()),
+        ),
+        (&mut record.region, region.as_ref().and_then(place_name)),
+        (&mut record.city, found.city.as_ref().and_then(place_name)),
+        (
+            &mut record.asn,
+            found.autonomous_system_number.map(|asn| format!("AS{asn}")),
+        ),
+        (
+            &mut record.organization,
+            found.autonomous_system_organization,
+        ),
+        (&mut record.timezone, location.time_zone),
+    ];
+    for (target, value) in values {
+        if target.is_none() {
+            *target = value;
+        }
+    }
+    if record.latitude.is_none() && record.longitude.is_none() {
+        record.latitude = location.latitude;
+        record.longitude = location.longitude;
+    }
+}
+
+/// 优先使用中文名称，没有时退回英文。
+fn place_name(place: &MmdbPlace) -> Option<String> {
+    let names = place.names.as_ref()?;
+    names
+        .get("zh-CN")
+        .or_else(|| names.get("en"))
+        .or_else(|| names.values().next())
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按 MaxMind DB 格式写一个只含 IPv4 网段的小数据库，记录宽度 24 位。
+    fn write_test_mmdb(path: &Path, database_type: &str, networks: &[(&str, u8, Vec<u8>)]) {
+        let mut tree: Vec<[u32; 2]> = vec![[u32::MAX; 2]];
+        let mut data = Vec::new();
+        let mut leaves = Vec::new();
+        for (network, prefix, record) in networks {
+            let bits = u32::from(network.parse::<std::net::Ipv4Addr>().unwrap());
+            let mut node = 0usize;
+            for depth in 0..*prefix {
+                let bit = ((bits >> (31 - depth)) & 1) as usize;
+                if depth + 1 == *prefix {
+                    leaves.push((node, bit, data.len()));
+                    break;
+                }
+                if tree[node][bit] == u32::MAX {
+                    tree.push([u32::MAX; 2]);
+                    tree[node][bit] = (tree.len() - 1) as u32;
+                }
+                node = tree[node][bit] as usize;
+            }
+            data.extend_from_slice(record);
+        }
+        let node_count = tree.len() as u32;
+        for (node, bit, offset) in leaves {
+            tree[node][bit] = node_count + 16 + offset as u32;
+        }
+
+        let mut file = Vec::new();
+        for [left, right] in tree {
+            for record in [left, right] {
+                let record = if record == u32::MAX {
+                    node_count
+                } else {
+                    record
+                };
+                file.extend_from_slice(&record.to_be_bytes()[1..]);
+            }
+        }
+        file.extend_from_slice(&[0; 16]);
+        file.extend_from_slice(&data);
+        file.extend_from_slice(b"\xab\xcd\xefMaxMind.com");
+        file.extend(map(&[
+            ("binary_format_major_version", uint(5, 2)),
+            ("binary_format_minor_version", uint(5, 0)),
+            (
+                "build_epoch",
+                [vec![0x08, 0x02], 1_700_000_000u64.to_be_bytes().to_vec()].concat(),
+            ),
+            ("database_type", string(database_type)),
+            ("description", map(&[("en", string("test"))])),
+            ("ip_version", uint(5, 4)),
+            ("languages", [vec![0x01, 0x04], string("en")].concat()),
+            ("node_count", uint(6, node_count)),
+            ("record_size", uint(5, 24)),
+        ]));
+        std::fs::write(path, file).unwrap();
+    }
+
+    /// 长度 29 及以上时用一个额外字节表示 `长度 - 29`。
+    fn string(value: &str) -> Vec<u8> {
+        let len = value.len();
+        let header = if len < 29 {
+            vec![0x40 | len as u8]
+        } else {
+            vec![0x40 | 29, (len - 29) as u8]
+        };
+        [header, value.as_bytes().to_vec()].concat()
+    }
+
+    /// `kind` 为 5 表示 uint16（2 字节），6 表示 uint32（4 字节）。
+    fn uint(kind: u8, value: u32) -> Vec<u8> {
+        let width = if kind == 5 { 2 } else { 4 };
+        let bytes = value.to_be_bytes();
+        [vec![(kind << 5) | width as u8], bytes[4 - width..].to_vec()].concat()
+    }
+
+    fn map(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut out = vec![0xe0 | entries.len() as u8];
+        for (key, value) in entries {
+            out.extend(string(key));
+            out.extend_from_slice(value);
+        }
+        out
+    }
+
+    #[test]
+    fn looks_up_country_and_asn_databases() {
+        let dir = std::env::temp_dir().join(format!("chef_geoip_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let country_db = dir.join("country.mmdb");
+        let asn_db = dir.join("asn.mmdb");
+        write_test_mmdb(
+            &country_db,
+            "GeoLite2-Country",
+            &[(
+                "203.0.113.0",
+                24,
+                map(&[(
+                    "country",
+                    map(&[
+                        ("iso_code", string("JP")),
+                        ("names", map(&[("en", string("Japan"))])),
+                    ]),
+                )]),
+            )],
+        );
+        write_test_mmdb(
+            &asn_db,
+            "GeoLite2-ASN",
+            &[(
+                "203.0.0.0",
+                8,
+                map(&[
+                    ("autonomous_system_number", uint(6, 64500)),
+                    ("autonomous_system_organization", string("Example Net")),
+                ]),
+            )],
+        );
+        let databases = [country_db, asn_db].map(|path| path.to_string_lossy().into_owned());
+
+        let record = lookup("203.0.113.9".parse().unwrap(), &databases).unwrap();
+        assert_eq!(record.country.as_deref(), Some("Japan"));
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+        assert_eq!(record.asn.as_deref(), Some("AS64500"));
+        assert_eq!(record.organization.as_deref(), Some("Example Net"));
+        assert_eq!(record.databases, vec!["GeoLite2-Country", "GeoLite2-ASN"]);
+
+        let record = lookup("203.5.0.1".parse().unwrap(), &databases).unwrap();
+        assert_eq!(record.country, None);
+        assert_eq!(record.databases, vec!["GeoLite2-ASN"]);
+        assert!(lookup("8.8.8.8".parse().unwrap(), &databases)
+            .unwrap()
+            .is_empty());
+
+        let mut info = PublicIpInfo {
+            ip: "203.0.113.9".into(),
+            country: Some("日本".into()),
+            source: "api.ip.sb".into(),
+            ..PublicIpInfo::default()
+        };
+        lookup("203.0.113.9".parse().unwrap(), &databases)
+            .unwrap()
+            .fill(&mut info);
+        assert_eq!(info.country.as_deref(), Some("日本"));
+        assert_eq!(info.asn.as_deref(), Some("AS64500"));
+        assert_eq!(info.source, "api.ip.sb+GeoLite2-Country+GeoLite2-ASN");
+
+        assert!(lookup(
+            "1.1.1.1".parse().unwrap(),
+            &[dir.join("missing.mmdb").to_string_lossy().into_owned()]
+        )
+        .is_err());
+        clear_cache();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,334 @@
+mod geoip;
+mod providers;
+
+use std::{
+    fs,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::PublicIpInfo;
+pub use geoip::GeoIpRecord;
+use providers::query_provider;
+pub use providers::{CustomProvider, IpProviderConfig, ProviderFieldPaths, ProviderKind};
+
+const SETTINGS_FILE: &str = "ip-lookup.json";
+const DEFAULT_PROVIDER_TIMEOUT_MS: u64 = 5000;
+
+static SETTINGS: Lazy<RwLock<IpLookupSettings>> =
+    Lazy::new(|| RwLock::new(IpLookupSettings::default()));
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct IpLookupSettings {
+    /// 按顺序尝试，直到某个服务返回结果。
+    providers: Vec<IpProviderConfig>,
+    /// MaxMind 格式的离线数据库路径（Country/City/ASN），用于补全国家和 ASN。
+    geoip_databases: Vec<String>,
+}
+
+impl Default for IpLookupSettings {
+    fn default() -> Self {
+        Self {
+            providers: vec![
+                IpProviderConfig::builtin(ProviderKind::Pconline),
+                IpProviderConfig::builtin(ProviderKind::UserAgentInfo),
+                IpProviderConfig::builtin(ProviderKind::IpSb),
+            ],
+            geoip_databases: Vec::new(),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_ip_lookup_settings() -> IpLookupSettings {
+    SETTINGS.read().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn save_ip_lookup_settings(
+    app: AppHandle,
+    settings: IpLookupSettings,
+) -> Result<IpLookupSettings, String> {
+    let settings = validate_settings(settings)?;
+    let path = settings_path(&app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("创建配置目录失败: {err}"))?;
+    }
+    let content = serde_json::to_string_pretty(&settings).map_err(|err| err.to_string())?;
+    fs::write(&path, content).map_err(|err| format!("保存公网 IP 查询配置失败: {err}"))?;
+    geoip::clear_cache();
+    *SETTINGS.write().unwrap() = settings.clone();
+    Ok(settings)
+}
+
+/// 用离线 GeoIP 数据库查询任意 IP，不需要联网。
+#[tauri::command]
+pub async fn lookup_geoip(ip: String) -> Result<GeoIpRecord, String> {
+    let ip = ip
+        .trim()
+        .parse::<IpAddr>()
+        .map_err(|_| format!("IP 地址无效: {ip}"))?;
+    let databases = SETTINGS.read().unwrap().geoip_databases.clone();
+    if databases.is_empty() {
+        return Err("尚未配置 GeoIP 数据库。".into());
+    }
+    tauri::async_runtime::spawn_blocking(move || geoip::lookup(ip, &databases))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+/// 启动时读取保存的配置，文件不存在或损坏时使用默认配置。
+pub fn load_ip_lookup_settings(app: &AppHandle) {
+    let Ok(path) = settings_path(app) else {
+        return;
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+    match parse_saved_settings(&content) {
+        Ok(settings) => *SETTINGS.write().unwrap() = settings,
+        Err(err) => eprintln!("读取公网 IP 查询配置失败 {}: {err}", path.display()),
+    }
+}
+
+/// 保存后被移走的 GeoIP 数据库只忽略该项，不影响其余配置。
+fn parse_saved_settings(content: &str) -> Result<IpLookupSettings, String> {
+    let mut settings =
+        serde_json::from_str::<IpLookupSettings>(content).map_err(|err| err.to_string())?;
+    settings.geoip_databases.retain(|path| {
+        let exists = path.trim().is_empty() || Path::new(path).is_file();
+        if !exists {
+            eprintln!("GeoIP 数据库不存在，已忽略: {path}");
+        }
+        exists
+    });
+    validate_settings(settings)
+}
+
+pub(super) async fn fetch_public_ip_with_client(
+    client: &Client,
+    prefer_global: bool,
+) -> Result<PublicIpInfo, String> {
+    let settings = SETTINGS.read().unwrap().clone();
+    fetch_with_settings(client, &settings, prefer_global).await
+}
+
+async fn fetch_with_settings(
+    client: &Client,
+    settings: &IpLookupSettings,
+    prefer_global: bool,
+) -> Result<PublicIpInfo, String> {
+    let default_timeout = Duration::from_millis(DEFAULT_PROVIDER_TIMEOUT_MS);
+    let mut providers = settings
+        .providers
+        .iter()
+        .filter(|config| config.enabled)
+        .map(|config| config.build(default_timeout))
+        .collect::<Vec<_>>();
+    if prefer_global {
+        providers.sort_by_key(|configured| configured.provider.domestic());
+    }
+
+    let mut errors = Vec::new();
+    for configured in &providers {
+        match query_provider(client, configured).await {
+            Ok(mut info) => {
+                enrich_with_geoip(&mut info, &settings.geoip_databases).await;
+                return Ok(info);
+            }
+            Err(err) => errors.push(format!("{}: {err}", configured.provider.name())),
+        }
+    }
+    if errors.is_empty() {
+        Err("没有启用的公网 IP 查询服务".into())
+    } else {
+        Err(format!("无法获取公网 IP（{}）", errors.join("；")))
+    }
+}
+
+async fn enrich_with_geoip(info: &mut PublicIpInfo, databases: &[String]) {
+    let Ok(ip) = info.ip.parse::<IpAddr>() else {
+        return;
+    };
+    if databases.is_empty() {
+        return;
+    }
+    let databases = databases.to_vec();
+    match tauri::async_runtime::spawn_blocking(move || geoip::lookup(ip, &databases)).await {
+        Ok(Ok(record)) => record.fill(info),
+        Ok(Err(err)) => eprintln!("GeoIP 查询失败: {err}"),
+        Err(err) => eprintln!("GeoIP 查询失败: {err}"),
+    }
+}
+
+fn validate_settings(mut settings: IpLookupSettings) -> Result<IpLookupSettings, String> {
+    for config in &mut settings.providers {
+        if let ProviderKind::Custom(custom) = &mut config.kind {
+            custom.name = custom.name.trim().to_string();
+            if custom.name.is_empty() {
+                return Err("自定义查询服务需要填写名称。".into());
+            }
+            if !custom.url.starts_with("http://") && !custom.url.starts_with("https://") {
+                return Err(format!(
+                    "查询服务 {} 的地址必须以 http:// 或 https:// 开头。",
+                    custom.name
+                ));
+            }
+            if custom.fields.ip.trim().is_empty() {
+                return Err(format!("查询服务 {} 需要指定 IP 字段路径。", custom.name));
+            }
+        }
+        if config.timeout_ms == Some(0) {
+            config.timeout_ms = None;
+        }
+    }
+    settings
+        .geoip_databases
+        .retain(|path| !path.trim().is_empty());
+    for path in &settings.geoip_databases {
+        if !Path::new(path).is_file() {
+            return Err(format!("GeoIP 数据库不存在: {path}"));
+        }
+    }
+    Ok(settings)
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_config_dir()
+        .map_err(|err| format!("无法定位应用配置目录: {err}"))?
+        .join(SETTINGS_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// 按请求路径返回固定响应的本地服务。
+    async fn spawn_canned_server(routes: Vec<(&'static str, u16, &'static str)>) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let routes = routes.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 4096];
+                    let read = stream.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..read]);
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let (status, body) = routes
+                        .iter()
+                        .find(|(route, _, _)| *route == path)
+                        .map(|(_, status, body)| (*status, *body))
+                        .unwrap_or((404, ""));
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        port
+    }
+
+    fn custom(port: u16, name: &str, path: &str, ip_field: &str) -> IpProviderConfig {
+        IpProviderConfig {
+            enabled: true,
+            timeout_ms: Some(2000),
+            kind: ProviderKind::Custom(Box::new(CustomProvider {
+                name: name.into(),
+                url: format!("http://127.0.0.1:{port}{path}"),
+                fields: ProviderFieldPaths {
+                    ip: ip_field.into(),
+                    country: Some("country".into()),
+                    ..ProviderFieldPaths::default()
+                },
+            })),
+        }
+    }
+
+    #[tokio::test]
+    async fn falls_back_through_configured_providers() {
+        let port = spawn_canned_server(vec![
+            ("/broken", 500, "oops"),
+            ("/empty", 200, r#"{"country":"Nowhere"}"#),
+            ("/ok", 200, r#"{"addr":"198.51.100.20","country":"Chile"}"#),
+        ])
+        .await;
+        let client = Client::new();
+        let mut disabled = custom(port, "disabled", "/ok", "addr");
+        disabled.enabled = false;
+        let settings = IpLookupSettings {
+            providers: vec![
+                disabled,
+                custom(port, "broken", "/broken", "addr"),
+                custom(port, "empty", "/empty", "addr"),
+                custom(port, "ok", "/ok", "addr"),
+            ],
+            geoip_databases: Vec::new(),
+        };
+        let info = fetch_with_settings(&client, &settings, false)
+            .await
+            .unwrap();
+        assert_eq!(info.ip, "198.51.100.20");
+        assert_eq!(info.country.as_deref(), Some("Chile"));
+        assert_eq!(info.source, "ok");
+
+        let failing = IpLookupSettings {
+            providers: settings.providers[1..3].to_vec(),
+            geoip_databases: Vec::new(),
+        };
+        let Err(err) = fetch_with_settings(&client, &failing, false).await else {
+            panic!("all providers should fail");
+        };
+        assert!(err.contains("broken") && err.contains("empty"), "{err}");
+        let none = IpLookupSettings {
+            providers: Vec::new(),
+            geoip_databases: Vec::new(),
+        };
+        assert!(fetch_with_settings(&client, &none, true).await.is_err());
+    }
+
+    #[test]
+    fn validates_settings() {
+        let mut settings = IpLookupSettings::default();
+        settings.providers.push(custom(80, " mine ", "/", "ip"));
+        settings.geoip_databases.push("  ".into());
+        let validated = validate_settings(settings.clone()).unwrap();
+        assert!(validated.geoip_databases.is_empty());
+        assert!(
+            matches!(&validated.providers[3].kind, ProviderKind::Custom(custom) if custom.name == "mine")
+        );
+
+        let mut bad = settings.clone();
+        bad.providers.push(custom(80, "x", "/", " "));
+        assert!(validate_settings(bad).is_err());
+        let mut bad = settings;
+        bad.geoip_databases
+            .push("/nonexistent/GeoLite2-ASN.mmdb".into());
+        assert!(validate_settings(bad.clone()).is_err());
+
+        // 读取已保存的配置时只丢掉失效的数据库路径
+        let loaded = parse_saved_settings(&serde_json::to_string(&bad).unwrap()).unwrap();
+        assert!(loaded.geoip_databases.is_empty());
+        assert_eq!(loaded.providers.len(), bad.providers.len());
+    }
+}
@@ -0,0 +1,478 @@
+use std::time::Duration;
+
+use encoding_rs::GBK;
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::super::PublicIpInfo;
+
+/// 公网 IP 查询服务。请求和解析分开，解析部分可以直接用固定响应测试。
+pub(super) trait PublicIpProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn request(&self, client: &Client) -> RequestBuilder;
+
+    fn parse(&self, body: &[u8]) -> Result<PublicIpInfo, String>;
+
+    /// 只能识别国内 IP 的服务，经代理查询时排到最后。
+    fn domestic(&self) -> bool {
+        false
+    }
+}
+
+/// 设置里的一项查询服务，列表顺序即查询顺序。
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IpProviderConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub timeout_ms: Option<u64>,
+    #[serde(flatten)]
+    pub kind: ProviderKind,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ProviderKind {
+    Pconline,
+    UserAgentInfo,
+    IpSb,
+    Custom(Box<CustomProvider>),
+}
+
+/// 用户自定义的服务：请求 `url`，再按字段路径从 JSON 里取值。
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomProvider {
+    pub name: String,
+    pub url: String,
+    pub fields: ProviderFieldPaths,
+}
+
+/// 字段路径形如 `data.geo.country` 或 `$.results[0].ip`；`ip` 为 `$` 时把整个响应当作 IP 文本。
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProviderFieldPaths {
+    pub ip: String,
+    pub location: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub isp: Option<String>,
+    pub organization: Option<String>,
+    pub country_code: Option<String>,
+    pub timezone: Option<String>,
+    pub asn: Option<String>,
+    pub latitude: Option<String>,
+    pub longitude: Option<String>,
+}
+
+pub(super) struct ConfiguredProvider {
+    pub provider: Box<dyn PublicIpProvider>,
+    pub timeout: Duration,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl IpProviderConfig {
+    pub(super) fn builtin(kind: ProviderKind) -> Self {
+        Self {
+            enabled: true,
+            timeout_ms: None,
+            kind,
+        }
+    }
+
+    pub(super) fn build(&self, default_timeout: Duration) -> ConfiguredProvider {
+        let provider: Box<dyn PublicIpProvider> = match &self.kind {
+            ProviderKind::Pconline => Box::new(PconlineProvider),
+            ProviderKind::UserAgentInfo => Box::new(UserAgentInfoProvider),
+            ProviderKind::IpSb => Box::new(IpSbProvider),
+            ProviderKind::Custom(custom) => custom.clone(),
+        };
+        ConfiguredProvider {
+            provider,
+            timeout: self
+                .timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(default_timeout),
+        }
+    }
+}
+
+pub(super) async fn query_provider(
+    client: &Client,
+    configured: &ConfiguredProvider,
+) -> Result<PublicIpInfo, String> {
+    let bytes = configured
+        .provider
+        .request(client)
+        .timeout(configured.timeout)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?
+        .error_for_status()
+        .map_err(|err| err.to_string())?
+        .bytes()
+        .await
+        .map_err(|err| err.to_string())?;
+    configured.provider.parse(&bytes)
+}
+
+struct PconlineProvider;
+
+#[derive(Deserialize)]
+struct PconlineResponse {
+    ip: Option<String>,
+    pro: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    addr: Option<String>,
+    err: Option<String>,
+}
+
+impl PublicIpProvider for PconlineProvider {
+    fn name(&self) -> &str {
+        "whois.pconline.com.cn"
+    }
+
+    fn request(&self, client: &Client) -> RequestBuilder {
+        client
+            .get("https://whois.pconline.com.cn/ipJson.jsp")
+            .query(&[("json", "true")])
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<PublicIpInfo, String> {
+        let (text, _, _) = GBK.decode(body);
+        let data: PconlineResponse = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+        let Some(ip) = data.ip.clone() else {
+            return Err(data.err.unwrap_or_else(|| "pconline 响应缺少 IP".into()));
+        };
+        let mut location = data.addr.clone().filter(|value| !value.trim().is_empty());
+        if location.is_none() {
+            let parts = [&data.pro, &data.city, &data.region]
+                .into_iter()
+                .flatten()
+                .filter(|value| !value.trim().is_empty())
+                .cloned()
+                .collect::<Vec<_>>();
+            if !parts.is_empty() {
+                location = Some(parts.join(" "));
+            }
+        }
+        Ok(PublicIpInfo {
+            ip,
+            country: data.pro.clone(),
+            region: data.region.clone(),
+            city: data.city.clone(),
+            isp: data.addr.clone(),
+            location,
+            source: self.name().into(),
+            ..PublicIpInfo::default()
+        })
+    }
+
+    fn domestic(&self) -> bool {
+        true
+    }
+}
+
+struct UserAgentInfoProvider;
+
+#[derive(Deserialize)]
+struct UserAgentInfoResponse {
+    ip: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    isp: Option<String>,
+    location: Option<String>,
+}
+
+impl PublicIpProvider for UserAgentInfoProvider {
+    fn name(&self) -> &str {
+        "ip.useragentinfo.com"
+    }
+
+    fn request(&self, client: &Client) -> RequestBuilder {
+        client.get("https://ip.useragentinfo.com/json")
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<PublicIpInfo, String> {
+        let data: UserAgentInfoResponse =
+            serde_json::from_slice(body).map_err(|err| err.to_string())?;
+        let ip = data.ip.clone().ok_or("useragentinfo 响应缺少 IP")?;
+        Ok(PublicIpInfo {
+            ip,
+            location: data
+                .location
+                .clone()
+                .or_else(|| join_location(&data.country, &data.region, &data.city)),
+            country: data.country,
+            region: data.region,
+            city: data.city,
+            isp: data.isp,
+            source: self.name().into(),
+            ..PublicIpInfo::default()
+        })
+    }
+}
+
+struct IpSbProvider;
+
+#[derive(Deserialize)]
+struct IpSbResponse {
+    ip: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    isp: Option<String>,
+    organization: Option<String>,
+    country_code: Option<String>,
+    timezone: Option<String>,
+    asn: Option<u64>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+}
+
+impl PublicIpProvider for IpSbProvider {
+    fn name(&self) -> &str {
+        "api.ip.sb"
+    }
+
+    fn request(&self, client: &Client) -> RequestBuilder {
+        client.get("https://api.ip.sb/geoip")
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<PublicIpInfo, String> {
+        let data: IpSbResponse = serde_json::from_slice(body).map_err(|err| err.to_string())?;
+        let ip = data.ip.clone().ok_or("ip.sb 响应缺少 IP")?;
+        Ok(PublicIpInfo {
+            ip,
+            location: join_location(&data.country, &data.region, &data.city),
+            asn: data
+                .asn
+                .map(|asn| format!("AS{asn}"))
+                .or_else(|| data.organization.clone()),
+            country: data.country,
+            region: data.region,
+            city: data.city,
+            isp: data.isp,
+            organization: data.organization,
+            country_code: data.country_code,
+            timezone: data.timezone,
+            latitude: data.latitude,
+            longitude: data.longitude,
+            source: self.name().into(),
+        })
+    }
+}
+
+impl PublicIpProvider for CustomProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn request(&self, client: &Client) -> RequestBuilder {
+        client.get(&self.url)
+    }
+
+    fn parse(&self, body: &[u8]) -> Result<PublicIpInfo, String> {
+        let text = String::from_utf8_lossy(body);
+        let fields = &self.fields;
+        let json = serde_json::from_str::<Value>(text.trim()).ok();
+        let ip = match (json.as_ref(), fields.ip.trim()) {
+            (_, "$") | (None, "") => Some(text.trim().to_string()).filter(|ip| {
+                // 纯文本响应只接受一个 IP 地址
+                ip.parse::<std::net::IpAddr>().is_ok()
+            }),
+            (Some(json), path) => json_path(json, path).and_then(value_text),
+            (None, _) => None,
+        }
+        .ok_or_else(|| format!("{} 响应中找不到 IP 字段", self.name))?;
+        let ip = ip
+            .trim()
+            .parse::<std::net::IpAddr>()
+            .map_err(|_| format!("{} 响应中的 IP 字段不是有效地址: {ip}", self.name))?
+            .to_string();
+
+        let field = |path: &Option<String>| -> Option<String> {
+            json_path(json.as_ref()?, path.as_deref()?).and_then(value_text)
+        };
+        let number = |path: &Option<String>| field(path).and_then(|value| value.parse().ok());
+        let country = field(&fields.country);
+        let region = field(&fields.region);
+        let city = field(&fields.city);
+        Ok(PublicIpInfo {
+            ip,
+            location: field(&fields.location).or_else(|| join_location(&country, &region, &city)),
+            country,
+            region,
+            city,
+            isp: field(&fields.isp),
+            organization: field(&fields.organization),
+            country_code: field(&fields.country_code),
+            timezone: field(&fields.timezone),
+            asn: field(&fields.asn).map(|asn| {
+                if asn.chars().all(|ch| ch.is_ascii_digit()) {
+                    format!("AS{asn}")
+                } else {
+                    asn
+                }
+            }),
+            latitude: number(&fields.latitude),
+            longitude: number(&fields.longitude),
+            source: self.name.clone(),
+        })
+    }
+}
+
+/// 按 `a.b[0].c` 形式的路径取 JSON 值，前缀 `$` 可省略。
+pub(super) fn json_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let mut current = value;
+    for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+        let (key, indexes) = match segment.find('[') {
+            Some(start) => segment.split_at(start),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            current = current.get(key)?;
+        }
+        for index in indexes.split('[').filter(|index| !index.is_empty()) {
+            let index = index.strip_suffix(']')?.trim().parse::<usize>().ok()?;
+            current = current.get(index)?;
+        }
+    }
+    Some(current)
+}
+
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.trim().to_string()).filter(|text| !text.is_empty()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+fn join_location(
+    country: &Option<String>,
+    region: &Option<String>,
+    city: &Option<String>,
+) -> Option<String> {
+    match (country, region, city) {
+        (Some(country), Some(region), Some(city)) => Some(format!("{country} {region} {city}")),
+        (Some(country), Some(region), None) => Some(format!("{country} {region}")),
+        (Some(country), None, None) => Some(country.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom(fields: ProviderFieldPaths) -> CustomProvider {
+        CustomProvider {
+            name: "custom".into(),
+            url: "https://ip.example.com/json".into(),
+            fields,
+        }
+    }
+
+    #[test]
+    fn parses_builtin_provider_responses() {
+        let (gbk, _, _) = GBK.encode(
+            r#"{"ip":"112.10.1.2","pro":"浙江省","pro_code":"330000","city":"杭州市","region":"","addr":"浙江省杭州市 移动","err":""}"#,
+        );
+        let info = PconlineProvider.parse(&gbk).unwrap();
+        assert_eq!(info.ip, "112.10.1.2");
+        assert_eq!(info.location.as_deref(), Some("浙江省杭州市 移动"));
+        assert_eq!(info.city.as_deref(), Some("杭州市"));
+        let result = PconlineProvider.parse(r#"{"err":"noprovince"}"#.as_bytes());
+        assert!(matches!(result, Err(err) if err == "noprovince"));
+
+        let info = IpSbProvider
+            .parse(
+                br#"{"ip":"203.0.113.5","country":"Japan","country_code":"JP","region":"Tokyo","asn":2516,
+                     "organization":"KDDI","timezone":"Asia/Tokyo","latitude":35.6,"longitude":139.7}"#,
+            )
+            .unwrap();
+        assert_eq!(info.asn.as_deref(), Some("AS2516"));
+        assert_eq!(info.location.as_deref(), Some("Japan Tokyo"));
+        assert_eq!(info.country_code.as_deref(), Some("JP"));
+
+        let info = UserAgentInfoProvider
+            .parse(br#"{"ip":"198.51.100.8","country":"Germany","isp":"DTAG"}"#)
+            .unwrap();
+        assert_eq!(info.location.as_deref(), Some("Germany"));
+        assert!(UserAgentInfoProvider.parse(b"{}").is_err());
+        assert!(IpSbProvider.parse(b"<html>").is_err());
+    }
+
+    #[test]
+    fn parses_custom_provider_with_field_paths() {
+        let provider = custom(ProviderFieldPaths {
+            ip: "$.data.query".into(),
+            country: Some("data.geo.country".into()),
+            country_code: Some("data.geo.codes[1]".into()),
+            asn: Some("data.as.number".into()),
+            organization: Some("data.as.name".into()),
+            latitude: Some("data.geo.coords[0]".into()),
+            longitude: Some("data.geo.coords[1]".into()),
+            city: Some("data.geo.missing".into()),
+            ..ProviderFieldPaths::default()
+        });
+        let info = provider
+            .parse(
+                br#"{"data":{"query":"192.0.2.44","geo":{"country":"France","codes":["FRA","FR"],
+                     "coords":[48.85,2.35]},"as":{"number":3215,"name":"Orange"}}}"#,
+            )
+            .unwrap();
+        assert_eq!(info.ip, "192.0.2.44");
+        assert_eq!(info.country_code.as_deref(), Some("FR"));
+        assert_eq!(info.asn.as_deref(), Some("AS3215"));
+        assert_eq!(info.organization.as_deref(), Some("Orange"));
+        assert_eq!(info.latitude, Some(48.85));
+        assert_eq!(info.city, None);
+        assert_eq!(info.location.as_deref(), Some("France"));
+        assert_eq!(info.source, "custom");
+        assert!(provider.parse(br#"{"data":{}}"#).is_err());
+        let Err(err) = provider.parse(br#"{"data":{"query":"<html>"}}"#) else {
+            panic!("non-IP values should be rejected");
+        };
+        assert!(err.contains("不是有效地址"));
+
+        let text = custom(ProviderFieldPaths {
+            ip: "$".into(),
+            ..ProviderFieldPaths::default()
+        });
+        assert_eq!(text.parse(b"2001:db8::7\n").unwrap().ip, "2001:db8::7");
+        assert!(text.parse(b"not an ip").is_err());
+    }
+
+    #[test]
+    fn deserializes_provider_settings() {
+        let providers: Vec<IpProviderConfig> = serde_json::from_str(
+            r#"[{"kind":"ipSb","timeoutMs":1500},
+                {"kind":"pconline","enabled":false},
+                {"kind":"custom","name":"mine","url":"https://ip.example.com","fields":{"ip":"ip"}}]"#,
+        )
+        .unwrap();
+        let built = providers
+            .iter()
+            .map(|config| config.build(Duration::from_secs(5)))
+            .collect::<Vec<_>>();
+        assert_eq!(built[0].provider.name(), "api.ip.sb");
+        assert_eq!(built[0].timeout, Duration::from_millis(1500));
+        assert!(!providers[1].enabled);
+        assert!(built[1].provider.domestic());
+        assert_eq!(built[2].provider.name(), "mine");
+        assert_eq!(built[2].timeout, Duration::from_secs(5));
+    }
+}
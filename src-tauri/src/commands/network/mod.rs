@@ -0,0 +1,563 @@
+mod dns;
+mod fix;
+mod http_request;
+mod interfaces;
+mod ip_lookup;
+mod monitor;
+mod pac;
+mod ports;
+mod probe;
+mod process;
+mod proxy_check;
+mod proxy_config;
+mod trace;
+
+use std::{
+    collections::HashMap,
+    env,
+    net::{Ipv4Addr, Ipv6Addr},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+use if_addrs::{get_if_addrs, IfAddr};
+use reqwest::{Client, Proxy as ReqwestProxy, Url};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+
+use interfaces::{
+    check_gateway, collect_links, detect_vpns, GatewayStatus, InterfaceDetail, RouteEntry, VpnLink,
+};
+use ip_lookup::fetch_public_ip_with_client;
+
+pub use dns::{list_dns_resolvers, query_dns};
+pub use fix::{list_network_fix_snapshots, revert_network_fix, run_network_fix_action};
+pub use http_request::send_http_request;
+pub use ip_lookup::{
+    get_ip_lookup_settings, load_ip_lookup_settings, lookup_geoip, save_ip_lookup_settings,
+};
+pub use monitor::{
+    get_network_monitor_status, query_network_history, start_network_monitor, stop_network_monitor,
+    NetworkMonitor,
+};
+pub use ports::{list_listening_sockets, scan_ports};
+pub use probe::probe_network_targets;
+pub use process::{inspect_port_process, kill_port_process};
+pub use proxy_check::check_proxies;
+pub use proxy_config::{get_system_proxy_config, resolve_proxy_for_url};
+pub use trace::{start_network_trace, stop_network_trace, TraceManager};
+
+#[derive(Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum AddressCategory {
+    Loopback,
+    Private,
+    LinkLocal,
+    Global,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IpAddressInfo {
+    interface: String,
+    address: String,
+    category: AddressCategory,
+    version: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VpnInterfaceInfo {
+    #[serde(flatten)]
+    link: VpnLink,
+    addresses: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VpnStatus {
+    active: bool,
+    interfaces: Vec<VpnInterfaceInfo>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProxyEnvVar {
+    key: String,
+    value: String,
+}
+
+const PROXY_ENV_KEYS: [&str; 6] = [
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkOverview {
+    primary_private_ipv4: Option<String>,
+    primary_ipv6: Option<String>,
+    public_ip: Option<PublicIpInfo>,
+    proxy_public_ip: Option<PublicIpInfo>,
+    ipv4_addresses: Vec<IpAddressInfo>,
+    ipv6_addresses: Vec<IpAddressInfo>,
+    lan_addresses: Vec<IpAddressInfo>,
+    vpn_status: VpnStatus,
+    proxy_env: Vec<ProxyEnvVar>,
+    proxy_detected: bool,
+    proxy_endpoints: Vec<ProxyEndpoint>,
+    interfaces: Vec<InterfaceDetail>,
+    routes: Vec<RouteEntry>,
+    dns_servers: Vec<String>,
+    default_gateway: Option<GatewayStatus>,
+    capture_timestamp: u64,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicIpInfo {
+    ip: String,
+    location: Option<String>,
+    country: Option<String>,
+    region: Option<String>,
+    city: Option<String>,
+    isp: Option<String>,
+    organization: Option<String>,
+    country_code: Option<String>,
+    timezone: Option<String>,
+    asn: Option<String>,
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    source: String,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum ProxyProtocol {
+    Http,
+    Https,
+    Socks5,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProxyEndpoint {
+    protocol: ProxyProtocol,
+    host: String,
+    port: u16,
+    #[serde(default)]
+    source: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDiagnosis {
+    online: bool,
+    dns_ok: bool,
+    latency_ms: Option<u128>,
+    endpoint: String,
+    timestamp: u64,
+    detail_log: Vec<String>,
+    error: Option<String>,
+}
+
+impl ProxyProtocol {
+    fn scheme(self) -> &'static str {
+        match self {
+            ProxyProtocol::Http | ProxyProtocol::Https => "http",
+            ProxyProtocol::Socks5 => "socks5h",
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn diagnose_network_connectivity() -> Result<NetworkDiagnosis, String> {
+    let endpoint = "https://www.gstatic.com/generate_204";
+    let mut detail_log = Vec::new();
+    let mut error_message: Option<String> = None;
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .user_agent("Chef Network Doctor/0.1")
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    let mut latency_ms = None;
+    let mut online = false;
+
+    let start = Instant::now();
+    match client.get(endpoint).send().await {
+        Ok(resp) => {
+            latency_ms = Some(start.elapsed().as_millis());
+            if resp.status().is_success() {
+                detail_log.push(format!("成功访问 {endpoint}，状态码 {}", resp.status()));
+                online = true;
+            } else {
+                let msg = format!("访问 {endpoint} 返回状态码 {}", resp.status());
+                detail_log.push(msg.clone());
+                error_message = Some(msg);
+            }
+        }
+        Err(err) => {
+            let msg = format!("访问 {endpoint} 失败: {err}");
+            detail_log.push(msg.clone());
+            error_message = Some(msg);
+        }
+    }
+
+    let dns_ok = match dns::check_system_dns("example.com").await {
+        Ok(message) => {
+            detail_log.push(message);
+            true
+        }
+        Err(msg) => {
+            detail_log.push(msg.clone());
+            if error_message.is_none() {
+                error_message = Some(msg);
+            }
+            false
+        }
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Ok(NetworkDiagnosis {
+        online,
+        dns_ok,
+        latency_ms,
+        endpoint: endpoint.to_string(),
+        timestamp,
+        detail_log,
+        error: error_message,
+    })
+}
+
+#[tauri::command]
+pub async fn get_network_overview() -> Result<NetworkOverview, String> {
+    let interfaces = get_if_addrs().map_err(|err| format!("获取网络接口失败: {err}"))?;
+
+    let mut ipv4_addresses = Vec::new();
+    let mut ipv6_addresses = Vec::new();
+    let mut lan_addresses = Vec::new();
+    let mut interface_addresses: HashMap<String, Vec<String>> = HashMap::new();
+
+    for iface in interfaces {
+        let interface_name = iface.name;
+        match iface.addr {
+            IfAddr::V4(v4) => {
+                let category = categorize_ipv4(&v4.ip);
+                let info = IpAddressInfo {
+                    interface: interface_name.clone(),
+                    address: v4.ip.to_string(),
+                    category: category.clone(),
+                    version: "IPv4".into(),
+                };
+                if category == AddressCategory::Private {
+                    lan_addresses.push(info.clone());
+                }
+                interface_addresses
+                    .entry(interface_name.clone())
+                    .or_default()
+                    .push(v4.ip.to_string());
+                ipv4_addresses.push(info);
+            }
+            IfAddr::V6(v6) => {
+                let category = categorize_ipv6(&v6.ip);
+                let info = IpAddressInfo {
+                    interface: interface_name.clone(),
+                    address: format_ipv6(v6.ip),
+                    category: category.clone(),
+                    version: "IPv6".into(),
+                };
+                interface_addresses
+                    .entry(interface_name.clone())
+                    .or_default()
+                    .push(info.address.clone());
+                ipv6_addresses.push(info);
+            }
+        }
+    }
+
+    let primary_private_ipv4 = lan_addresses
+        .iter()
+        .find(|entry| entry.category == AddressCategory::Private)
+        .map(|entry| entry.address.clone());
+    let primary_ipv6 = ipv6_addresses
+        .iter()
+        .find(|entry| entry.category != AddressCategory::Loopback)
+        .map(|entry| entry.address.clone());
+
+    let (links, vpn_links) = spawn_blocking(|| {
+        let links = collect_links();
+        let vpn_links = detect_vpns(&links.routes);
+        (links, vpn_links)
+    })
+    .await
+    .map_err(|err| err.to_string())?;
+    let vpn_interfaces = vpn_links
+        .into_iter()
+        .map(|link| {
+            let mut addresses = interface_addresses.remove(&link.name).unwrap_or_default();
+            addresses.sort_unstable();
+            addresses.dedup();
+            VpnInterfaceInfo { link, addresses }
+        })
+        .collect::<Vec<_>>();
+
+    let proxy_env = gather_proxy_env();
+    let proxy_endpoints = detect_proxy_endpoints();
+    let proxy_detected =
+        !proxy_env.is_empty() || !vpn_interfaces.is_empty() || !proxy_endpoints.is_empty();
+
+    let public_ip = match fetch_public_ip_info(None).await {
+        Ok(info) => Some(info),
+        Err(err) => {
+            eprintln!("获取公网 IP 失败: {err}");
+            None
+        }
+    };
+    let proxy_public_ip = resolve_proxy_public_ip(&proxy_endpoints).await;
+    let default_gateway = check_gateway(&links.routes).await;
+
+    let capture_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    Ok(NetworkOverview {
+        primary_private_ipv4,
+        primary_ipv6,
+        public_ip,
+        proxy_public_ip,
+        ipv4_addresses,
+        ipv6_addresses,
+        lan_addresses,
+        vpn_status: VpnStatus {
+            active: !vpn_interfaces.is_empty(),
+            interfaces: vpn_interfaces,
+        },
+        proxy_env,
+        proxy_detected,
+        proxy_endpoints,
+        interfaces: links.interfaces,
+        routes: links.routes,
+        dns_servers: links.dns_servers,
+        default_gateway,
+        capture_timestamp,
+    })
+}
+
+fn categorize_ipv4(addr: &Ipv4Addr) -> AddressCategory {
+    if addr.is_loopback() {
+        return AddressCategory::Loopback;
+    }
+    if addr.is_private() {
+        return AddressCategory::Private;
+    }
+    if addr.is_link_local() {
+        return AddressCategory::LinkLocal;
+    }
+    AddressCategory::Global
+}
+
+fn categorize_ipv6(addr: &Ipv6Addr) -> AddressCategory {
+    if addr.is_loopback() || addr.is_unspecified() {
+        return AddressCategory::Loopback;
+    }
+    if addr.is_unique_local() {
+        return AddressCategory::Private;
+    }
+    if addr.is_unicast_link_local() {
+        return AddressCategory::LinkLocal;
+    }
+    AddressCategory::Global
+}
+
+fn gather_proxy_env() -> Vec<ProxyEnvVar> {
+    PROXY_ENV_KEYS
+        .iter()
+        .filter_map(|key| env::var(key).ok().map(|value| (key, value)))
+        .filter_map(|(key, value)| {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                return None;
+            }
+            Some(ProxyEnvVar {
+                key: (*key).to_string(),
+                value: trimmed.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn format_ipv6(addr: Ipv6Addr) -> String {
+    addr.to_string()
+}
+
+async fn fetch_public_ip_info(proxy: Option<&ProxyEndpoint>) -> Result<PublicIpInfo, String> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .user_agent("Chef Network Inspector/0.1");
+    if let Some(endpoint) = proxy {
+        if let Some(proxy) = build_reqwest_proxy(endpoint)? {
+            builder = builder.proxy(proxy);
+        }
+    }
+    let client = builder.build().map_err(|err| err.to_string())?;
+    fetch_public_ip_with_client(&client, proxy.is_some()).await
+}
+
+async fn resolve_proxy_public_ip(endpoints: &[ProxyEndpoint]) -> Option<PublicIpInfo> {
+    for endpoint in endpoints {
+        match fetch_public_ip_info(Some(endpoint)).await {
+            Ok(info) => return Some(info),
+            Err(err) => eprintln!(
+                "通过代理 {}:{} ({:?}) 获取公网 IP 失败: {err}",
+                endpoint.host, endpoint.port, endpoint.protocol
+            ),
+        }
+    }
+    None
+}
+
+fn detect_proxy_endpoints() -> Vec<ProxyEndpoint> {
+    let mut endpoints = Vec::new();
+    detect_env_proxy_endpoints(&mut endpoints);
+    #[cfg(target_os = "macos")]
+    detect_macos_proxy_endpoints(&mut endpoints);
+    #[cfg(target_os = "linux")]
+    proxy_config::detect_desktop_proxy_endpoints(&mut endpoints);
+    endpoints
+}
+
+fn detect_env_proxy_endpoints(endpoints: &mut Vec<ProxyEndpoint>) {
+    const CANDIDATES: [(&str, ProxyProtocol); 8] = [
+        ("HTTP_PROXY", ProxyProtocol::Http),
+        ("http_proxy", ProxyProtocol::Http),
+        ("HTTPS_PROXY", ProxyProtocol::Https),
+        ("https_proxy", ProxyProtocol::Https),
+        ("ALL_PROXY", ProxyProtocol::Socks5),
+        ("all_proxy", ProxyProtocol::Socks5),
+        ("SOCKS_PROXY", ProxyProtocol::Socks5),
+        ("socks_proxy", ProxyProtocol::Socks5),
+    ];
+    for (key, protocol) in CANDIDATES {
+        if let Ok(value) = env::var(key) {
+            if let Some((host, port)) = parse_proxy_value(&value, protocol) {
+                push_endpoint(endpoints, protocol, host, port, &format!("env:{key}"));
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_macos_proxy_endpoints(endpoints: &mut Vec<ProxyEndpoint>) {
+    if let Ok(output) = Command::new("scutil").arg("--proxy").output() {
+        if !output.status.success() {
+            return;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let map: HashMap<_, _> = stdout
+            .lines()
+            .filter_map(|line| {
+                line.split_once(':')
+                    .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+            })
+            .collect();
+        if map.get("HTTPEnable").map(|v| v == "1").unwrap_or(false) {
+            if let (Some(host), Some(port)) = (map.get("HTTPProxy"), map.get("HTTPPort")) {
+                if let Ok(port) = port.parse::<u16>() {
+                    push_endpoint(
+                        endpoints,
+                        ProxyProtocol::Http,
+                        host.clone(),
+                        port,
+                        "mac:scutil",
+                    );
+                }
+            }
+        }
+        if map.get("HTTPSEnable").map(|v| v == "1").unwrap_or(false) {
+            if let (Some(host), Some(port)) = (map.get("HTTPSProxy"), map.get("HTTPSPort")) {
+                if let Ok(port) = port.parse::<u16>() {
+                    push_endpoint(
+                        endpoints,
+                        ProxyProtocol::Https,
+                        host.clone(),
+                        port,
+                        "mac:scutil",
+                    );
+                }
+            }
+        }
+        if map.get("SOCKSEnable").map(|v| v == "1").unwrap_or(false) {
+            if let (Some(host), Some(port)) = (map.get("SOCKSProxy"), map.get("SOCKSPort")) {
+                if let Ok(port) = port.parse::<u16>() {
+                    push_endpoint(
+                        endpoints,
+                        ProxyProtocol::Socks5,
+                        host.clone(),
+                        port,
+                        "mac:scutil",
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn parse_proxy_value(value: &str, protocol: ProxyProtocol) -> Option<(String, u16)> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let candidate = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else {
+        format!("{}://{}", protocol.scheme(), trimmed)
+    };
+    let parsed = Url::parse(&candidate).ok()?;
+    let host = parsed.host_str()?.to_string();
+    let port = parsed.port_or_known_default()?;
+    Some((host, port))
+}
+
+fn push_endpoint(
+    endpoints: &mut Vec<ProxyEndpoint>,
+    protocol: ProxyProtocol,
+    host: String,
+    port: u16,
+    source: &str,
+) {
+    if endpoints.iter().any(|existing| {
+        existing.protocol == protocol && existing.host == host && existing.port == port
+    }) {
+        return;
+    }
+    endpoints.push(ProxyEndpoint {
+        protocol,
+        host,
+        port,
+        source: source.to_string(),
+    });
+}
+
+fn build_reqwest_proxy(endpoint: &ProxyEndpoint) -> Result<Option<ReqwestProxy>, String> {
+    let url = format!(
+        "{}://{}:{}",
+        endpoint.protocol.scheme(),
+        endpoint.host,
+        endpoint.port
+    );
+    let proxy = ReqwestProxy::all(&url).map_err(|err| err.to_string())?;
+    Ok(Some(proxy))
+}
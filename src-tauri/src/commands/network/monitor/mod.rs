@@ -0,0 +1,671 @@
+mod store;
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex as StdMutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Emitter, Manager};
+use tokio::{net::TcpStream, time::timeout};
+use tokio_util::sync::CancellationToken;
+
+use self::store::{bucket_samples, HistoryPoint, MonitorRecord, MonitorStore};
+use super::{
+    dns, fetch_public_ip_info,
+    interfaces::{collect_routes, detect_vpns},
+    probe::{elapsed_ms, round2, split_host_port},
+};
+use crate::utils::current_timestamp_millis;
+
+pub const MONITOR_EVENT: &str = "network-monitor";
+
+const MONITOR_DATA_DIR: &str = "network-monitor";
+const DEFAULT_HISTORY_SECS: u64 = 24 * 3600;
+const MAX_HISTORY_POINTS: u64 = 300;
+
+/// 后台连通性监控；同一时间只运行一个监控任务。
+#[derive(Default)]
+pub struct NetworkMonitor {
+    inner: StdMutex<Option<MonitorHandle>>,
+}
+
+struct MonitorHandle {
+    cancel: CancellationToken,
+    config: MonitorConfig,
+    started_at: u64,
+    last_sample: Arc<StdMutex<Option<MonitorSample>>>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MonitorConfig {
+    /// 需要探测的 host:port，通过 TCP 建连测量延迟。
+    pub targets: Vec<String>,
+    pub interval_secs: u64,
+    pub probes_per_target: u32,
+    pub timeout_ms: u64,
+    /// 用于检测 DNS 健康的域名，留空则不检测。
+    pub dns_name: Option<String>,
+    /// 公网 IP 检测间隔，0 表示不检测。
+    pub public_ip_interval_secs: u64,
+    pub retention_days: u32,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            targets: vec![
+                "223.5.5.5:53".into(),
+                "1.1.1.1:443".into(),
+                "www.baidu.com:443".into(),
+            ],
+            interval_secs: 30,
+            probes_per_target: 4,
+            timeout_ms: 2000,
+            dns_name: Some("example.com".into()),
+            public_ip_interval_secs: 300,
+            retention_days: 7,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MonitorSample {
+    timestamp: u64,
+    online: bool,
+    targets: Vec<TargetSample>,
+    dns: Option<DnsHealth>,
+    vpn_interfaces: Vec<String>,
+    /// 最近一次成功获取的公网 IP。
+    public_ip: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TargetSample {
+    target: String,
+    sent: u32,
+    received: u32,
+    loss_percent: f64,
+    latency_ms: Option<f64>,
+    /// 相邻两次延迟差值的平均值。
+    jitter_ms: Option<f64>,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DnsHealth {
+    ok: bool,
+    duration_ms: Option<f64>,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MonitorChange {
+    timestamp: u64,
+    #[serde(flatten)]
+    change: StateChange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum StateChange {
+    Online,
+    Offline,
+    VpnUp {
+        interfaces: Vec<String>,
+    },
+    VpnDown {
+        interfaces: Vec<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    PublicIpChanged {
+        previous: String,
+        current: String,
+    },
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MonitorEvent {
+    Sample { sample: MonitorSample },
+    StateChanged { change: MonitorChange },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkMonitorStatus {
+    running: bool,
+    started_at: Option<u64>,
+    config: Option<MonitorConfig>,
+    last_sample: Option<MonitorSample>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHistoryQuery {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub target: Option<String>,
+    /// 图表的时间粒度，不填则按范围自动选择。
+    pub bucket_secs: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkHistory {
+    from: u64,
+    to: u64,
+    bucket_secs: u64,
+    targets: Vec<String>,
+    points: Vec<HistoryPoint>,
+    changes: Vec<MonitorChange>,
+}
+
+/// 启动后台监控；已在运行时按新配置重启。
+#[tauri::command]
+pub async fn start_network_monitor(
+    app: AppHandle,
+    state: tauri::State<'_, NetworkMonitor>,
+    config: Option<MonitorConfig>,
+) -> Result<NetworkMonitorStatus, String> {
+    let config = validate_config(config.unwrap_or_default())?;
+    let store = MonitorStore::new(monitor_dir(&app)?);
+    let cancel = CancellationToken::new();
+    let last_sample = Arc::new(StdMutex::new(None));
+    let handle = MonitorHandle {
+        cancel: cancel.clone(),
+        config: config.clone(),
+        started_at: now_secs(),
+        last_sample: Arc::clone(&last_sample),
+    };
+    if let Some(previous) = state.inner.lock().unwrap().replace(handle) {
+        previous.cancel.cancel();
+    }
+
+    async_runtime::spawn(async move {
+        run_monitor(store, config, cancel, |event| {
+            if let MonitorEvent::Sample { sample } = &event {
+                *last_sample.lock().unwrap() = Some(sample.clone());
+            }
+            if let Err(err) = app.emit(MONITOR_EVENT, event) {
+                eprintln!("推送网络监控事件失败: {err}");
+            }
+        })
+        .await;
+    });
+    Ok(monitor_status(&state))
+}
+
+#[tauri::command]
+pub async fn stop_network_monitor(
+    state: tauri::State<'_, NetworkMonitor>,
+) -> Result<NetworkMonitorStatus, String> {
+    if let Some(handle) = state.inner.lock().unwrap().take() {
+        handle.cancel.cancel();
+    }
+    Ok(monitor_status(&state))
+}
+
+#[tauri::command]
+pub async fn get_network_monitor_status(
+    state: tauri::State<'_, NetworkMonitor>,
+) -> Result<NetworkMonitorStatus, String> {
+    Ok(monitor_status(&state))
+}
+
+/// 查询监控历史，按时间分桶后供前端绘制图表。
+#[tauri::command]
+pub async fn query_network_history(
+    app: AppHandle,
+    query: NetworkHistoryQuery,
+) -> Result<NetworkHistory, String> {
+    let store = MonitorStore::new(monitor_dir(&app)?);
+    async_runtime::spawn_blocking(move || load_history(&store, query, now_secs()))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+fn monitor_status(monitor: &NetworkMonitor) -> NetworkMonitorStatus {
+    let inner = monitor.inner.lock().unwrap();
+    match inner.as_ref() {
+        Some(handle) => NetworkMonitorStatus {
+            running: true,
+            started_at: Some(handle.started_at),
+            config: Some(handle.config.clone()),
+            last_sample: handle.last_sample.lock().unwrap().clone(),
+        },
+        None => NetworkMonitorStatus {
+            running: false,
+            started_at: None,
+            config: None,
+            last_sample: None,
+        },
+    }
+}
+
+fn monitor_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("无法定位应用数据目录: {err}"))?
+        .join(MONITOR_DATA_DIR))
+}
+
+fn validate_config(mut config: MonitorConfig) -> Result<MonitorConfig, String> {
+    config.targets = config
+        .targets
+        .iter()
+        .map(|target| target.trim().to_string())
+        .filter(|target| !target.is_empty())
+        .collect();
+    if config.targets.is_empty() && config.dns_name.is_none() {
+        return Err("请至少配置一个监控目标。".into());
+    }
+    for target in &config.targets {
+        let (_, port) = split_host_port(target)?;
+        if port.is_none() {
+            return Err(format!("监控目标需要包含端口: {target}"));
+        }
+    }
+    config.dns_name = config
+        .dns_name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+    config.interval_secs = config.interval_secs.max(1);
+    config.probes_per_target = config.probes_per_target.clamp(1, 20);
+    config.timeout_ms = config.timeout_ms.clamp(100, 30_000);
+    config.retention_days = config.retention_days.max(1);
+    Ok(config)
+}
+
+fn load_history(
+    store: &MonitorStore,
+    query: NetworkHistoryQuery,
+    now: u64,
+) -> Result<NetworkHistory, String> {
+    let to = query.to.unwrap_or(now);
+    let from = query
+        .from
+        .unwrap_or_else(|| to.saturating_sub(DEFAULT_HISTORY_SECS));
+    if from > to {
+        return Err("开始时间不能晚于结束时间。".into());
+    }
+    let bucket_secs = query
+        .bucket_secs
+        .unwrap_or_else(|| (to - from).div_ceil(MAX_HISTORY_POINTS))
+        .max(1);
+
+    let records = store.read_range(from, to)?;
+    let mut samples = Vec::new();
+    let mut changes = Vec::new();
+    for record in &records {
+        match record {
+            MonitorRecord::Sample(sample) => samples.push(sample),
+            MonitorRecord::Change(change) => changes.push(change.clone()),
+        }
+    }
+    samples.sort_by_key(|sample| sample.timestamp);
+    changes.sort_by_key(|change| change.timestamp);
+
+    let mut targets = samples
+        .iter()
+        .flat_map(|sample| sample.targets.iter().map(|entry| entry.target.clone()))
+        .collect::<Vec<_>>();
+    targets.sort_unstable();
+    targets.dedup();
+
+    Ok(NetworkHistory {
+        from,
+        to,
+        bucket_secs,
+        targets,
+        points: bucket_samples(&samples, from, bucket_secs, query.target.as_deref()),
+        changes,
+    })
+}
+
+async fn run_monitor(
+    store: MonitorStore,
+    config: MonitorConfig,
+    cancel: CancellationToken,
+    mut emit: impl FnMut(MonitorEvent),
+) {
+    let interval = Duration::from_secs(config.interval_secs);
+    let public_ip_interval = Duration::from_secs(config.public_ip_interval_secs);
+    let mut previous: Option<MonitorSample> = None;
+    let mut public_ip: Option<String> = None;
+    let mut public_ip_checked: Option<Instant> = None;
+    let mut pruned_day = None;
+
+    loop {
+        let now = now_secs();
+        if pruned_day != Some(now / 86_400) {
+            pruned_day = Some(now / 86_400);
+            if let Err(err) = store.prune(config.retention_days, now) {
+                eprintln!("{err}");
+            }
+        }
+
+        if !public_ip_interval.is_zero()
+            && public_ip_checked.is_none_or(|checked| checked.elapsed() >= public_ip_interval)
+        {
+            public_ip_checked = Some(Instant::now());
+            let fetched = tokio::select! {
+                _ = cancel.cancelled() => return,
+                fetched = fetch_public_ip_info(None) => fetched,
+            };
+            match fetched {
+                Ok(info) => public_ip = Some(info.ip),
+                Err(err) => eprintln!("获取公网 IP 失败: {err}"),
+            }
+        }
+
+        let sample = tokio::select! {
+            _ = cancel.cancelled() => return,
+            sample = collect_sample(&config, public_ip.clone()) => sample,
+        };
+        let changes = detect_changes(previous.as_ref(), &sample)
+            .into_iter()
+            .map(|change| MonitorChange {
+                timestamp: sample.timestamp,
+                change,
+            })
+            .collect::<Vec<_>>();
+
+        let records = std::iter::once(MonitorRecord::Sample(sample.clone()))
+            .chain(changes.iter().cloned().map(MonitorRecord::Change));
+        for record in records {
+            if let Err(err) = store.append(&record) {
+                eprintln!("{err}");
+            }
+        }
+        emit(MonitorEvent::Sample {
+            sample: sample.clone(),
+        });
+        for change in changes {
+            emit(MonitorEvent::StateChanged { change });
+        }
+        previous = Some(sample);
+
+        tokio::select! {
+            _ = cancel.cancelled() => return,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+}
+
+async fn collect_sample(config: &MonitorConfig, public_ip: Option<String>) -> MonitorSample {
+    let limit = Duration::from_millis(config.timeout_ms);
+    let probes = config
+        .targets
+        .iter()
+        .map(|target| probe_target(target, config.probes_per_target, limit));
+    let dns_check = async {
+        let name = config.dns_name.as_deref()?;
+        let started = Instant::now();
+        Some(match dns::check_system_dns(name).await {
+            Ok(_) => DnsHealth {
+                ok: true,
+                duration_ms: Some(elapsed_ms(started)),
+                error: None,
+            },
+            Err(err) => DnsHealth {
+                ok: false,
+                duration_ms: None,
+                error: Some(err),
+            },
+        })
+    };
+    let (targets, dns) = tokio::join!(join_all(probes), dns_check);
+
+    let online = if targets.is_empty() {
+        dns.as_ref().is_some_and(|health| health.ok)
+    } else {
+        targets.iter().any(|target| target.received > 0)
+    };
+    MonitorSample {
+        timestamp: now_secs(),
+        online,
+        targets,
+        dns,
+        vpn_interfaces: async_runtime::spawn_blocking(vpn_interfaces)
+            .await
+            .unwrap_or_default(),
+        public_ip,
+    }
+}
+
+async fn probe_target(target: &str, probes: u32, limit: Duration) -> TargetSample {
+    let mut rtts = Vec::new();
+    let mut error = None;
+    for _ in 0..probes {
+        let started = Instant::now();
+        match timeout(limit, TcpStream::connect(target)).await {
+            Ok(Ok(_)) => rtts.push(elapsed_ms(started)),
+            Ok(Err(err)) => error = Some(err.to_string()),
+            Err(_) => error = Some(format!("连接超时（{} ms）", limit.as_millis())),
+        }
+    }
+
+    let received = rtts.len() as u32;
+    let latency_ms = (received > 0).then(|| round2(rtts.iter().sum::<f64>() / rtts.len() as f64));
+    let jitter_ms = (rtts.len() > 1).then(|| {
+        let total = rtts
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .sum::<f64>();
+        round2(total / (rtts.len() - 1) as f64)
+    });
+    TargetSample {
+        target: target.to_string(),
+        sent: probes,
+        received,
+        loss_percent: round2(f64::from(probes - received) * 100.0 / f64::from(probes.max(1))),
+        latency_ms,
+        jitter_ms,
+        error: if received == probes { None } else { error },
+    }
+}
+
+fn vpn_interfaces() -> Vec<String> {
+    detect_vpns(&collect_routes())
+        .into_iter()
+        .map(|link| link.name)
+        .collect()
+}
+
+fn detect_changes(previous: Option<&MonitorSample>, current: &MonitorSample) -> Vec<StateChange> {
+    let Some(previous) = previous else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    match (previous.online, current.online) {
+        (true, false) => changes.push(StateChange::Offline),
+        (false, true) => changes.push(StateChange::Online),
+        _ => {}
+    }
+    match (
+        previous.vpn_interfaces.is_empty(),
+        current.vpn_interfaces.is_empty(),
+    ) {
+        (true, false) => changes.push(StateChange::VpnUp {
+            interfaces: current.vpn_interfaces.clone(),
+        }),
+        (false, true) => changes.push(StateChange::VpnDown {
+            interfaces: previous.vpn_interfaces.clone(),
+        }),
+        _ => {}
+    }
+    if let (Some(before), Some(after)) = (&previous.public_ip, &current.public_ip) {
+        if before != after {
+            changes.push(StateChange::PublicIpChanged {
+                previous: before.clone(),
+                current: after.clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn now_secs() -> u64 {
+    (current_timestamp_millis() / 1000) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_sample(online: bool, vpn: &[&str], ip: Option<&str>) -> MonitorSample {
+        MonitorSample {
+            timestamp: 0,
+            online,
+            targets: Vec::new(),
+            dns: None,
+            vpn_interfaces: vpn.iter().map(|name| name.to_string()).collect(),
+            public_ip: ip.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn detects_state_transitions() {
+        let first = bare_sample(true, &[], Some("203.0.113.1"));
+        assert!(detect_changes(None, &first).is_empty());
+        assert!(detect_changes(Some(&first), &first).is_empty());
+
+        let second = bare_sample(false, &["utun3"], Some("198.51.100.7"));
+        assert_eq!(
+            detect_changes(Some(&first), &second),
+            vec![
+                StateChange::Offline,
+                StateChange::VpnUp {
+                    interfaces: vec!["utun3".into()]
+                },
+                StateChange::PublicIpChanged {
+                    previous: "203.0.113.1".into(),
+                    current: "198.51.100.7".into(),
+                },
+            ]
+        );
+
+        let third = bare_sample(true, &[], None);
+        assert_eq!(
+            detect_changes(Some(&second), &third),
+            vec![
+                StateChange::Online,
+                StateChange::VpnDown {
+                    interfaces: vec!["utun3".into()]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_monitor_config() {
+        assert!(validate_config(MonitorConfig {
+            targets: vec!["example.com".into()],
+            ..MonitorConfig::default()
+        })
+        .is_err());
+        assert!(validate_config(MonitorConfig {
+            targets: Vec::new(),
+            dns_name: None,
+            ..MonitorConfig::default()
+        })
+        .is_err());
+        let config = validate_config(MonitorConfig {
+            targets: vec![" 127.0.0.1:80 ".into(), String::new()],
+            interval_secs: 0,
+            dns_name: Some(" ".into()),
+            ..MonitorConfig::default()
+        })
+        .unwrap();
+        assert_eq!(config.targets, vec!["127.0.0.1:80".to_string()]);
+        assert_eq!(config.interval_secs, 1);
+        assert!(config.dns_name.is_none());
+    }
+
+    #[tokio::test]
+    async fn records_samples_and_changes_into_history() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let open = format!("127.0.0.1:{port}");
+        let dir = std::env::temp_dir().join(format!("chef_monitor_{}", uuid::Uuid::new_v4()));
+        let config = MonitorConfig {
+            targets: vec![open.clone()],
+            interval_secs: 1,
+            probes_per_target: 3,
+            timeout_ms: 500,
+            dns_name: None,
+            public_ip_interval_secs: 0,
+            retention_days: 7,
+        };
+
+        let cancel = CancellationToken::new();
+        let stopper = cancel.clone();
+        let mut events = Vec::new();
+        let mut listener = Some(listener);
+        run_monitor(MonitorStore::new(dir.clone()), config, cancel, |event| {
+            if let MonitorEvent::Sample { .. } = event {
+                // 第一次采样后关闭监听，第二次采样应当离线
+                if listener.take().is_none() {
+                    stopper.cancel();
+                }
+            }
+            events.push(event);
+        })
+        .await;
+
+        let samples = events
+            .iter()
+            .filter_map(|event| match event {
+                MonitorEvent::Sample { sample } => Some(sample),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(samples.len(), 2);
+        assert!(samples[0].online);
+        assert_eq!(samples[0].targets[0].loss_percent, 0.0);
+        assert!(samples[0].targets[0].latency_ms.is_some());
+        assert!(!samples[1].online);
+        assert_eq!(samples[1].targets[0].loss_percent, 100.0);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            MonitorEvent::StateChanged {
+                change: MonitorChange {
+                    change: StateChange::Offline,
+                    ..
+                }
+            }
+        )));
+
+        let history = load_history(
+            &MonitorStore::new(dir.clone()),
+            NetworkHistoryQuery {
+                from: None,
+                to: None,
+                target: Some(open.clone()),
+                bucket_secs: Some(3600),
+            },
+            now_secs() + 1,
+        )
+        .unwrap();
+        assert_eq!(history.targets, vec![open]);
+        let points = serde_json::to_value(&history.points).unwrap();
+        let recorded = points
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|point| point["samples"].as_u64().unwrap())
+            .sum::<u64>();
+        assert_eq!(recorded, 2);
+        assert_eq!(history.changes.len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{super::probe::round2, MonitorChange, MonitorSample};
+
+const SECS_PER_DAY: u64 = 86_400;
+
+/// 监控历史按 UTC 日期分文件保存为 JSON Lines，便于追加和按天清理。
+pub(super) struct MonitorStore {
+    dir: PathBuf,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(super) enum MonitorRecord {
+    Sample(MonitorSample),
+    Change(MonitorChange),
+}
+
+/// 图表上的一个时间桶，多个目标的数据取平均。
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPoint {
+    timestamp: u64,
+    samples: u32,
+    online_percent: f64,
+    latency_ms: Option<f64>,
+    jitter_ms: Option<f64>,
+    loss_percent: Option<f64>,
+    dns_ms: Option<f64>,
+    dns_failure_percent: Option<f64>,
+}
+
+impl MonitorRecord {
+    fn timestamp(&self) -> u64 {
+        match self {
+            MonitorRecord::Sample(sample) => sample.timestamp,
+            MonitorRecord::Change(change) => change.timestamp,
+        }
+    }
+}
+
+impl MonitorStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn append(&self, record: &MonitorRecord) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|err| format!("创建监控数据目录失败: {err}"))?;
+        let path = self.day_file(record.timestamp() / SECS_PER_DAY);
+        let mut line = serde_json::to_string(record).map_err(|err| err.to_string())?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|err| format!("写入监控数据失败 {}: {err}", path.display()))
+    }
+
+    /// 读取 [from, to] 时间范围内的记录，损坏的行直接跳过。
+    pub fn read_range(&self, from: u64, to: u64) -> Result<Vec<MonitorRecord>, String> {
+        let mut records = Vec::new();
+        for day in from / SECS_PER_DAY..=to / SECS_PER_DAY {
+            let path = self.day_file(day);
+            let file = match fs::File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(format!("读取监控数据失败 {}: {err}", path.display())),
+            };
+            records.extend(
+                BufReader::new(file)
+                    .lines()
+                    .map_while(Result::ok)
+                    .filter_map(|line| serde_json::from_str::<MonitorRecord>(&line).ok())
+                    .filter(|record| (from..=to).contains(&record.timestamp())),
+            );
+        }
+        Ok(records)
+    }
+
+    /// 删除早于保留天数的文件，返回删除的文件数。
+    pub fn prune(&self, retention_days: u32, now: u64) -> Result<usize, String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(format!("读取监控数据目录失败: {err}")),
+        };
+        // 文件名是 ISO 日期，按字符串比较即可
+        let cutoff = format!(
+            "{}.jsonl",
+            iso_date((now / SECS_PER_DAY).saturating_sub(u64::from(retention_days)))
+        );
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".jsonl") && name.len() == cutoff.len() && name < cutoff {
+                if let Err(err) = fs::remove_file(entry.path()) {
+                    eprintln!("删除过期监控数据失败 {name}: {err}");
+                    continue;
+                }
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn day_file(&self, day: u64) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", iso_date(day)))
+    }
+}
+
+/// 把采样按固定时长分桶，`target` 为空时统计全部目标。
+pub(super) fn bucket_samples(
+    samples: &[&MonitorSample],
+    from: u64,
+    bucket_secs: u64,
+    target: Option<&str>,
+) -> Vec<HistoryPoint> {
+    let bucket_secs = bucket_secs.max(1);
+    let mut buckets: Vec<(u64, Vec<&MonitorSample>)> = Vec::new();
+    for sample in samples {
+        let start = from + (sample.timestamp.saturating_sub(from) / bucket_secs) * bucket_secs;
+        match buckets.last_mut() {
+            Some((bucket, members)) if *bucket == start => members.push(sample),
+            _ => buckets.push((start, vec![sample])),
+        }
+    }
+
+    buckets
+        .into_iter()
+        .map(|(timestamp, members)| {
+            let targets = members
+                .iter()
+                .flat_map(|sample| sample.targets.iter())
+                .filter(|entry| target.is_none_or(|target| entry.target == target))
+                .collect::<Vec<_>>();
+            let dns = members
+                .iter()
+                .filter_map(|sample| sample.dns.as_ref())
+                .collect::<Vec<_>>();
+            let online = members.iter().filter(|sample| sample.online).count();
+            HistoryPoint {
+                timestamp,
+                samples: members.len() as u32,
+                online_percent: round2(online as f64 * 100.0 / members.len() as f64),
+                latency_ms: mean(targets.iter().filter_map(|entry| entry.latency_ms)),
+                jitter_ms: mean(targets.iter().filter_map(|entry| entry.jitter_ms)),
+                loss_percent: mean(targets.iter().map(|entry| entry.loss_percent)),
+                dns_ms: mean(dns.iter().filter_map(|health| health.duration_ms)),
+                dns_failure_percent: mean(
+                    dns.iter().map(|health| if health.ok { 0.0 } else { 100.0 }),
+                ),
+            }
+        })
+        .collect()
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0u32), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| round2(sum / f64::from(count)))
+}
+
+/// 把 Unix 纪元以来的天数转换为 YYYY-MM-DD（公历，UTC）。
+fn iso_date(day: u64) -> String {
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{DnsHealth, StateChange, TargetSample};
+    use super::*;
+
+    fn sample(timestamp: u64, online: bool, latency: Option<f64>) -> MonitorSample {
+        MonitorSample {
+            timestamp,
+            online,
+            targets: vec![TargetSample {
+                target: "1.1.1.1:443".into(),
+                sent: 4,
+                received: if online { 4 } else { 0 },
+                loss_percent: if online { 0.0 } else { 100.0 },
+                latency_ms: latency,
+                jitter_ms: latency.map(|_| 1.0),
+                error: None,
+            }],
+            dns: Some(DnsHealth {
+                ok: online,
+                duration_ms: latency,
+                error: None,
+            }),
+            vpn_interfaces: Vec::new(),
+            public_ip: None,
+        }
+    }
+
+    #[test]
+    fn formats_iso_dates() {
+        assert_eq!(iso_date(0), "1970-01-01");
+        assert_eq!(iso_date(19_782), "2024-02-29");
+        assert_eq!(iso_date(20_744), "2026-10-18");
+    }
+
+    #[test]
+    fn appends_reads_and_prunes_daily_files() {
+        let dir = std::env::temp_dir().join(format!("chef_monitor_{}", uuid::Uuid::new_v4()));
+        let store = MonitorStore::new(dir.clone());
+        let day = 20_000 * SECS_PER_DAY;
+        store
+            .append(&MonitorRecord::Sample(sample(day - 10, true, Some(12.0))))
+            .unwrap();
+        store
+            .append(&MonitorRecord::Sample(sample(day + 10, false, None)))
+            .unwrap();
+        store
+            .append(&MonitorRecord::Change(MonitorChange {
+                timestamp: day + 10,
+                change: StateChange::Offline,
+            }))
+            .unwrap();
+        assert!(dir.join("2024-10-03.jsonl").exists());
+        assert!(dir.join("2024-10-04.jsonl").exists());
+
+        let records = store.read_range(day - 60, day + 60).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(matches!(
+            records[2],
+            MonitorRecord::Change(MonitorChange {
+                change: StateChange::Offline,
+                ..
+            })
+        ));
+        assert_eq!(store.read_range(day, day + 60).unwrap().len(), 2);
+
+        assert_eq!(store.prune(0, day + 10).unwrap(), 1);
+        assert!(!dir.join("2024-10-03.jsonl").exists());
+        assert_eq!(store.read_range(day - 60, day + 60).unwrap().len(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn buckets_samples_for_charting() {
+        let samples = [
+            sample(1000, true, Some(10.0)),
+            sample(1030, true, Some(30.0)),
+            sample(1060, false, None),
+            sample(1200, true, Some(20.0)),
+        ];
+        let refs = samples.iter().collect::<Vec<_>>();
+        let points = bucket_samples(&refs, 1000, 60, None);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].timestamp, 1000);
+        assert_eq!(points[0].samples, 2);
+        assert_eq!(points[0].latency_ms, Some(20.0));
+        assert_eq!(points[0].loss_percent, Some(0.0));
+        assert_eq!(points[1].online_percent, 0.0);
+        assert_eq!(points[1].latency_ms, None);
+        assert_eq!(points[1].dns_failure_percent, Some(100.0));
+        assert_eq!(points[2].timestamp, 1180);
+
+        let filtered = bucket_samples(&refs, 1000, 60, Some("other:80"));
+        assert_eq!(filtered[0].loss_percent, None);
+    }
+}
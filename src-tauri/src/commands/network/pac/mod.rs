@@ -0,0 +1,391 @@
+use std::{
+    fs,
+    net::{IpAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use boa_engine::{js_string, Context, JsArgs, JsResult, JsValue, NativeFunction, Source};
+use reqwest::{Client, Url};
+use serde::Serialize;
+
+use super::{ProxyEndpoint, ProxyProtocol};
+
+const PAC_UTILS: &str = include_str!("pac_utils.js");
+const PAC_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// 防止有问题的 PAC 脚本死循环卡住线程。
+const PAC_LOOP_LIMIT: u64 = 1_000_000;
+const PAC_MAX_SIZE: usize = 1024 * 1024;
+
+/// PAC 返回的一条候选路线，按顺序尝试。
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(super) enum ProxyRoute {
+    Direct,
+    Proxy(ProxyEndpoint),
+}
+
+/// 执行 PAC 脚本中的 `FindProxyForURL(url, host)`，返回原始结果字符串。
+///
+/// boa 的上下文不能跨线程，调用方需要放到阻塞线程里执行。
+pub(super) fn evaluate_pac(script: &str, url: &str, host: &str) -> Result<String, String> {
+    let mut context = Context::default();
+    context
+        .runtime_limits_mut()
+        .set_loop_iteration_limit(PAC_LOOP_LIMIT);
+    register_natives(&mut context).map_err(|err| format!("初始化 PAC 环境失败: {err}"))?;
+    context
+        .eval(Source::from_bytes(PAC_UTILS))
+        .map_err(|err| format!("初始化 PAC 环境失败: {err}"))?;
+    context
+        .eval(Source::from_bytes(script))
+        .map_err(|err| format!("PAC 脚本执行失败: {err}"))?;
+
+    let function = context
+        .global_object()
+        .get(js_string!("FindProxyForURL"), &mut context)
+        .map_err(|err| err.to_string())?;
+    let function = function
+        .as_callable()
+        .ok_or_else(|| "PAC 脚本没有定义 FindProxyForURL 函数。".to_string())?;
+    let result = function
+        .call(
+            &JsValue::undefined(),
+            &[js_string!(url).into(), js_string!(host).into()],
+            &mut context,
+        )
+        .map_err(|err| format!("FindProxyForURL 执行失败: {err}"))?;
+    if result.is_null_or_undefined() {
+        return Ok("DIRECT".into());
+    }
+    result
+        .to_string(&mut context)
+        .map(|value| value.to_std_string_escaped())
+        .map_err(|err| err.to_string())
+}
+
+/// 解析 "PROXY a:8080; SOCKS5 b:1080; DIRECT" 形式的 PAC 结果。
+pub(super) fn parse_pac_result(result: &str, source: &str) -> Vec<ProxyRoute> {
+    let mut routes = Vec::new();
+    for entry in result.split(';') {
+        let mut parts = entry.split_whitespace();
+        let Some(kind) = parts.next() else {
+            continue;
+        };
+        let kind = kind.to_ascii_uppercase();
+        if kind == "DIRECT" {
+            routes.push(ProxyRoute::Direct);
+            continue;
+        }
+        let protocol = match kind.as_str() {
+            "PROXY" | "HTTP" => ProxyProtocol::Http,
+            "HTTPS" => ProxyProtocol::Https,
+            "SOCKS" | "SOCKS4" | "SOCKS5" => ProxyProtocol::Socks5,
+            _ => continue,
+        };
+        let Some((host, port)) = parts.next().and_then(split_proxy_address) else {
+            continue;
+        };
+        routes.push(ProxyRoute::Proxy(ProxyEndpoint {
+            protocol,
+            host,
+            port,
+            source: source.to_string(),
+        }));
+    }
+    if routes.is_empty() {
+        routes.push(ProxyRoute::Direct);
+    }
+    routes
+}
+
+/// 下载 PAC 脚本；支持 file:// 和本地路径。下载时不走代理，避免循环依赖。
+pub(super) async fn fetch_pac_script(location: &str) -> Result<String, String> {
+    let location = location.trim();
+    let local_path = match Url::parse(location) {
+        Ok(url) if url.scheme() == "file" => Some(
+            url.to_file_path()
+                .map_err(|_| format!("PAC 文件路径无效: {location}"))?,
+        ),
+        Ok(_) => None,
+        Err(_) if location.starts_with('/') => Some(location.into()),
+        Err(err) => return Err(format!("PAC 地址无效 {location}: {err}")),
+    };
+    if let Some(path) = local_path {
+        return fs::read_to_string(&path)
+            .map_err(|err| format!("读取 PAC 文件失败 {}: {err}", path.display()));
+    }
+
+    let client = Client::builder()
+        .no_proxy()
+        .timeout(PAC_FETCH_TIMEOUT)
+        .user_agent("Chef Network Doctor/0.1")
+        .build()
+        .map_err(|err| err.to_string())?;
+    let response = client
+        .get(location)
+        .send()
+        .await
+        .map_err(|err| format!("下载 PAC 脚本失败 {location}: {err}"))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "下载 PAC 脚本失败 {location}: 状态码 {}",
+            response.status()
+        ));
+    }
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| format!("下载 PAC 脚本失败 {location}: {err}"))?;
+    if body.len() > PAC_MAX_SIZE {
+        return Err(format!("PAC 脚本过大（{} 字节）。", body.len()));
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// WPAD 自动发现的候选地址：先试 `wpad`，再沿本机域名逐级向上。
+pub(super) fn wpad_candidates(hostname: &str) -> Vec<String> {
+    let mut candidates = vec!["http://wpad/wpad.dat".to_string()];
+    let labels = hostname
+        .trim_end_matches('.')
+        .split('.')
+        .collect::<Vec<_>>();
+    // 至少保留两级域名，避免查询 wpad.com 这类公共域名
+    for start in 1..labels.len().saturating_sub(1) {
+        candidates.push(format!(
+            "http://wpad.{}/wpad.dat",
+            labels[start..].join(".")
+        ));
+    }
+    candidates
+}
+
+fn split_proxy_address(address: &str) -> Option<(String, u16)> {
+    let (host, port) = address.rsplit_once(':')?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_string(), port.parse().ok()?))
+}
+
+fn register_natives(context: &mut Context) -> JsResult<()> {
+    context.register_global_callable(
+        js_string!("dnsResolve"),
+        1,
+        NativeFunction::from_fn_ptr(|_, args, context| {
+            let host = args.get_or_undefined(0).to_string(context)?;
+            Ok(resolve_host(&host.to_std_string_escaped())
+                .into_iter()
+                .find(IpAddr::is_ipv4)
+                .map_or(JsValue::null(), |ip| js_string!(ip.to_string()).into()))
+        }),
+    )?;
+    context.register_global_callable(
+        js_string!("dnsResolveEx"),
+        1,
+        NativeFunction::from_fn_ptr(|_, args, context| {
+            let host = args.get_or_undefined(0).to_string(context)?;
+            let addresses = resolve_host(&host.to_std_string_escaped())
+                .iter()
+                .map(IpAddr::to_string)
+                .collect::<Vec<_>>();
+            Ok(js_string!(addresses.join(";")).into())
+        }),
+    )?;
+    context.register_global_callable(
+        js_string!("myIpAddress"),
+        0,
+        NativeFunction::from_fn_ptr(|_, _, _| {
+            let ip = local_address("8.8.8.8:80").unwrap_or_else(|| "127.0.0.1".into());
+            Ok(js_string!(ip).into())
+        }),
+    )?;
+    context.register_global_callable(
+        js_string!("myIpAddressEx"),
+        0,
+        NativeFunction::from_fn_ptr(|_, _, _| {
+            let addresses = ["8.8.8.8:80", "[2001:4860:4860::8888]:80"]
+                .into_iter()
+                .filter_map(local_address)
+                .collect::<Vec<_>>();
+            Ok(js_string!(addresses.join(";")).into())
+        }),
+    )?;
+    context.register_global_callable(
+        js_string!("alert"),
+        1,
+        NativeFunction::from_fn_ptr(|_, args, context| {
+            let message = args.get_or_undefined(0).to_string(context)?;
+            eprintln!("PAC alert: {}", message.to_std_string_escaped());
+            Ok(JsValue::undefined())
+        }),
+    )?;
+    Ok(())
+}
+
+fn resolve_host(host: &str) -> Vec<IpAddr> {
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return vec![ip];
+    }
+    (host, 0)
+        .to_socket_addrs()
+        .map(|addrs| addrs.map(|addr| addr.ip()).collect())
+        .unwrap_or_default()
+}
+
+/// 通过 UDP connect 让内核选出访问外网时使用的本机地址，不会真正发包。
+fn local_address(remote: &str) -> Option<String> {
+    let bind = if remote.starts_with('[') {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    };
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect(remote).ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORPORATE_PAC: &str = include_str!("fixtures/corporate.pac");
+    const LOOPING_PAC: &str = include_str!("fixtures/looping.pac");
+
+    fn route_summary(routes: &[ProxyRoute]) -> Vec<String> {
+        routes
+            .iter()
+            .map(|route| match route {
+                ProxyRoute::Direct => "DIRECT".to_string(),
+                ProxyRoute::Proxy(endpoint) => {
+                    format!(
+                        "{:?} {}:{}",
+                        endpoint.protocol, endpoint.host, endpoint.port
+                    )
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_corporate_pac_fixture() {
+        let eval = |url: &str, host: &str| evaluate_pac(CORPORATE_PAC, url, host).unwrap();
+        assert_eq!(eval("http://intranet/", "intranet"), "DIRECT");
+        assert_eq!(eval("http://10.20.30.40/", "10.20.30.40"), "DIRECT");
+        assert_eq!(eval("http://localhost:8080/", "localhost"), "DIRECT");
+        assert_eq!(
+            eval("https://git.corp.example.com/", "git.corp.example.com"),
+            "PROXY corp-proxy.example.com:3128"
+        );
+        assert_eq!(
+            eval(
+                "https://downloads.example.org/a.iso",
+                "downloads.example.org"
+            ),
+            "PROXY bulk-proxy:8080; DIRECT"
+        );
+        assert_eq!(
+            eval("https://www.rust-lang.org/", "www.rust-lang.org"),
+            "PROXY proxy.example.com:8080; SOCKS5 127.0.0.1:1080; DIRECT"
+        );
+    }
+
+    #[test]
+    fn provides_standard_pac_helpers() {
+        let script = r#"
+            function FindProxyForURL(url, host) {
+                return [
+                    dnsDomainIs("www.example.com", ".example.com"),
+                    localHostOrDomainIs("www", "www.example.com"),
+                    dnsDomainLevels("a.b.c"),
+                    shExpMatch("http://x.test/a+b", "*.test/a+b"),
+                    shExpMatch("ab", "a?c"),
+                    isInNet("192.168.1.20", "192.168.0.0", "255.255.0.0"),
+                    typeof myIpAddress(),
+                    weekdayRange("SUN", "SAT"),
+                    dateRange(1, 31),
+                    timeRange(0, 0, 0, 23, 59, 59)
+                ].join(",");
+            }
+        "#;
+        assert_eq!(
+            evaluate_pac(script, "http://example.com/", "example.com").unwrap(),
+            "true,true,2,true,false,true,string,true,true,true"
+        );
+    }
+
+    #[test]
+    fn reports_broken_scripts() {
+        let err = evaluate_pac(LOOPING_PAC, "http://a/", "a").unwrap_err();
+        assert!(err.contains("FindProxyForURL"), "{err}");
+        assert!(evaluate_pac("function (", "http://a/", "a").is_err());
+        assert!(evaluate_pac("var x = 1;", "http://a/", "a").is_err());
+    }
+
+    #[test]
+    fn parses_pac_results() {
+        let routes = parse_pac_result(
+            "PROXY a:8080;  socks5 [::1]:1080 ; HTTPS b:443; DIRECT",
+            "pac",
+        );
+        assert_eq!(
+            route_summary(&routes),
+            vec!["Http a:8080", "Socks5 ::1:1080", "Https b:443", "DIRECT"]
+        );
+        assert_eq!(route_summary(&parse_pac_result("", "pac")), vec!["DIRECT"]);
+        assert_eq!(
+            route_summary(&parse_pac_result("PROXY nohost; BOGUS x:1", "pac")),
+            vec!["DIRECT"]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetches_pac_from_file_and_http() {
+        let dir = std::env::temp_dir().join(format!("chef_pac_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proxy.pac");
+        fs::write(&path, CORPORATE_PAC).unwrap();
+        let file_url = Url::from_file_path(&path).unwrap().to_string();
+        assert_eq!(fetch_pac_script(&file_url).await.unwrap(), CORPORATE_PAC);
+        assert_eq!(
+            fetch_pac_script(path.to_str().unwrap()).await.unwrap(),
+            CORPORATE_PAC
+        );
+        fs::remove_dir_all(dir).unwrap();
+
+        let url = serve_once(CORPORATE_PAC).await;
+        assert_eq!(fetch_pac_script(&url).await.unwrap(), CORPORATE_PAC);
+    }
+
+    #[test]
+    fn builds_wpad_candidates_from_hostname() {
+        assert_eq!(
+            wpad_candidates("laptop.eng.corp.example.com"),
+            vec![
+                "http://wpad/wpad.dat",
+                "http://wpad.eng.corp.example.com/wpad.dat",
+                "http://wpad.corp.example.com/wpad.dat",
+                "http://wpad.example.com/wpad.dat",
+            ]
+        );
+        assert_eq!(wpad_candidates("laptop"), vec!["http://wpad/wpad.dat"]);
+    }
+
+    /// 在本地起一个只响应一次的 HTTP 服务，返回 PAC 地址。
+    async fn serve_once(body: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/x-ns-proxy-autoconfig\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{addr}/proxy.pac")
+    }
+}
@@ -0,0 +1,165 @@
+// PAC 规范要求的辅助函数；dnsResolve、myIpAddress 等需要访问系统的函数由 Rust 注入。
+
+var __pacWeekdays = { SUN: 0, MON: 1, TUE: 2, WED: 3, THU: 4, FRI: 5, SAT: 6 };
+var __pacMonths = {
+  JAN: 0, FEB: 1, MAR: 2, APR: 3, MAY: 4, JUN: 5,
+  JUL: 6, AUG: 7, SEP: 8, OCT: 9, NOV: 10, DEC: 11
+};
+
+function isPlainHostName(host) {
+  return host.indexOf('.') == -1;
+}
+
+function dnsDomainIs(host, domain) {
+  return host.length >= domain.length &&
+    host.substring(host.length - domain.length) == domain;
+}
+
+function localHostOrDomainIs(host, hostdom) {
+  return host == hostdom || hostdom.lastIndexOf(host + '.', 0) == 0;
+}
+
+function isResolvable(host) {
+  return dnsResolve(host) != null;
+}
+
+function isResolvableEx(host) {
+  return dnsResolveEx(host) != '';
+}
+
+function dnsDomainLevels(host) {
+  return host.split('.').length - 1;
+}
+
+function convert_addr(ipchars) {
+  var bytes = ipchars.split('.');
+  return ((bytes[0] & 0xff) << 24) | ((bytes[1] & 0xff) << 16) |
+    ((bytes[2] & 0xff) << 8) | (bytes[3] & 0xff);
+}
+
+function isInNet(ipaddr, pattern, maskstr) {
+  if (!/^\d+\.\d+\.\d+\.\d+$/.test(ipaddr)) {
+    ipaddr = dnsResolve(ipaddr);
+    if (ipaddr == null) {
+      return false;
+    }
+  }
+  var mask = convert_addr(maskstr);
+  return (convert_addr(ipaddr) & mask) == (convert_addr(pattern) & mask);
+}
+
+function shExpMatch(str, shexp) {
+  var pattern = shexp
+    .replace(/[.+^${}()|[\]\\]/g, '\\$&')
+    .replace(/\*/g, '.*')
+    .replace(/\?/g, '.');
+  return new RegExp('^' + pattern + '$').test(str);
+}
+
+function __pacArgs(args) {
+  var list = Array.prototype.slice.call(args);
+  var gmt = list.length > 0 && list[list.length - 1] == 'GMT';
+  if (gmt) {
+    list.pop();
+  }
+  return { list: list, gmt: gmt, now: new Date() };
+}
+
+function __pacInRange(from, to, current) {
+  return from <= to ? from <= current && current <= to : current >= from || current <= to;
+}
+
+function weekdayRange() {
+  var args = __pacArgs(arguments);
+  if (args.list.length < 1 || args.list.length > 2) {
+    return false;
+  }
+  var from = __pacWeekdays[args.list[0]];
+  var to = args.list.length == 2 ? __pacWeekdays[args.list[1]] : from;
+  if (from === undefined || to === undefined) {
+    return false;
+  }
+  var today = args.gmt ? args.now.getUTCDay() : args.now.getDay();
+  return __pacInRange(from, to, today);
+}
+
+function dateRange() {
+  var args = __pacArgs(arguments);
+  var now = args.now;
+  var current = {
+    d: args.gmt ? now.getUTCDate() : now.getDate(),
+    m: args.gmt ? now.getUTCMonth() : now.getMonth(),
+    y: args.gmt ? now.getUTCFullYear() : now.getFullYear()
+  };
+  var parts = [];
+  for (var i = 0; i < args.list.length; i++) {
+    var arg = args.list[i];
+    if (typeof arg == 'string' && __pacMonths[arg.toUpperCase()] !== undefined) {
+      parts.push({ m: __pacMonths[arg.toUpperCase()] });
+      continue;
+    }
+    var value = parseInt(arg, 10);
+    if (isNaN(value)) {
+      return false;
+    }
+    parts.push(value < 32 ? { d: value } : { y: value });
+  }
+  if (parts.length == 1) {
+    var only = parts[0];
+    if (only.d !== undefined) return current.d == only.d;
+    if (only.m !== undefined) return current.m == only.m;
+    return current.y == only.y;
+  }
+  if (parts.length == 0 || parts.length % 2 != 0) {
+    return false;
+  }
+
+  function merge(list) {
+    var merged = {};
+    for (var i = 0; i < list.length; i++) {
+      for (var key in list[i]) {
+        merged[key] = list[i][key];
+      }
+    }
+    return merged;
+  }
+  var half = parts.length / 2;
+  var from = merge(parts.slice(0, half));
+  var to = merge(parts.slice(half));
+  // 只比较起始日期里给出的字段，例如 dateRange('JAN', 'MAR') 不看年份和日期
+  function key(date) {
+    return ((from.y !== undefined ? date.y : 0) * 12 + (from.m !== undefined ? date.m : 0)) * 32 +
+      (from.d !== undefined ? date.d : 0);
+  }
+  return __pacInRange(key(from), key(to), key(current));
+}
+
+function timeRange() {
+  var args = __pacArgs(arguments);
+  var now = args.now;
+  var current = args.gmt
+    ? [now.getUTCHours(), now.getUTCMinutes(), now.getUTCSeconds()]
+    : [now.getHours(), now.getMinutes(), now.getSeconds()];
+  var values = args.list.map(Number);
+  if (values.length == 1) {
+    return current[0] == values[0];
+  }
+  if (values.length == 0 || values.length > 6 || values.length % 2 != 0) {
+    return false;
+  }
+  var half = values.length / 2;
+  function seconds(list) {
+    var time = [0, 0, 0];
+    for (var i = 0; i < list.length; i++) {
+      time[i] = list[i];
+    }
+    return time[0] * 3600 + time[1] * 60 + time[2];
+  }
+  var from = seconds(values.slice(0, half));
+  var to = seconds(values.slice(half));
+  // 只给小时时，结束小时整点之后不再匹配
+  if (half == 1) {
+    to -= 1;
+  }
+  return __pacInRange(from, to, seconds(current));
+}
@@ -0,0 +1,644 @@
+#[cfg(any(target_os = "linux", test))]
+use std::net::{Ipv4Addr, Ipv6Addr};
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+use std::process::Command;
+#[cfg(target_os = "linux")]
+use std::{collections::HashMap, fs};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+use tokio::{
+    net::{lookup_host, TcpStream},
+    time::timeout,
+};
+
+use super::probe::elapsed_ms;
+
+const DEFAULT_SCAN_TIMEOUT_MS: u64 = 500;
+const DEFAULT_SCAN_CONCURRENCY: usize = 200;
+const MAX_SCAN_CONCURRENCY: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketProtocol {
+    Tcp,
+    Udp,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListeningSocket {
+    pub(super) protocol: SocketProtocol,
+    pub(super) address: String,
+    pub(super) port: u16,
+    pub(super) pid: Option<u32>,
+    pub(super) process_name: Option<String>,
+    pub(super) command_line: Option<String>,
+    pub(super) uid: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortScanRequest {
+    pub host: String,
+    pub start_port: Option<u16>,
+    pub end_port: Option<u16>,
+    /// 指定端口列表时忽略起止范围。
+    pub ports: Option<Vec<u16>>,
+    pub concurrency: Option<usize>,
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortScanResult {
+    host: String,
+    address: String,
+    scanned: usize,
+    open: Vec<OpenPort>,
+    duration_ms: u128,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OpenPort {
+    port: u16,
+    latency_ms: f64,
+    service: Option<&'static str>,
+    /// 扫描本机时补充监听该端口的进程。
+    pid: Option<u32>,
+    process_name: Option<String>,
+}
+
+const WELL_KNOWN_PORTS: &[(u16, &str)] = &[
+    (21, "ftp"),
+    (22, "ssh"),
+    (23, "telnet"),
+    (25, "smtp"),
+    (53, "dns"),
+    (80, "http"),
+    (110, "pop3"),
+    (143, "imap"),
+    (443, "https"),
+    (445, "smb"),
+    (993, "imaps"),
+    (1080, "socks"),
+    (1433, "mssql"),
+    (1521, "oracle"),
+    (2375, "docker"),
+    (3000, "dev-server"),
+    (3306, "mysql"),
+    (3389, "rdp"),
+    (5173, "vite"),
+    (5432, "postgresql"),
+    (5672, "amqp"),
+    (5900, "vnc"),
+    (6379, "redis"),
+    (7890, "clash"),
+    (8080, "http-alt"),
+    (8443, "https-alt"),
+    (9000, "php-fpm"),
+    (9200, "elasticsearch"),
+    (11211, "memcached"),
+    (27017, "mongodb"),
+];
+
+/// 列出本机正在监听的 TCP/UDP 端口及其所属进程，可按端口过滤。
+#[tauri::command]
+pub async fn list_listening_sockets(port: Option<u16>) -> Result<Vec<ListeningSocket>, String> {
+    let mut sockets = spawn_blocking(collect_listening_sockets)
+        .await
+        .map_err(|err| err.to_string())??;
+    if let Some(port) = port {
+        sockets.retain(|socket| socket.port == port);
+    }
+    sockets.sort_by(|a, b| {
+        (a.port, a.protocol == SocketProtocol::Udp, &a.address).cmp(&(
+            b.port,
+            b.protocol == SocketProtocol::Udp,
+            &b.address,
+        ))
+    });
+    Ok(sockets)
+}
+
+/// 并发探测主机上的 TCP 端口，返回开放端口和连接耗时。
+#[tauri::command]
+pub async fn scan_ports(request: PortScanRequest) -> Result<PortScanResult, String> {
+    let host = request.host.trim().to_string();
+    if host.is_empty() {
+        return Err("请输入要扫描的主机。".into());
+    }
+    let ports = match request.ports {
+        Some(mut ports) if !ports.is_empty() => {
+            ports.sort_unstable();
+            ports.dedup();
+            ports
+        }
+        _ => {
+            let start = request.start_port.unwrap_or(1).max(1);
+            let end = request.end_port.unwrap_or(1024);
+            if start > end {
+                return Err(format!("端口范围无效: {start}-{end}"));
+            }
+            (start..=end).collect()
+        }
+    };
+    if ports.contains(&0) {
+        return Err("端口必须在 1-65535 之间。".into());
+    }
+    let concurrency = request
+        .concurrency
+        .unwrap_or(DEFAULT_SCAN_CONCURRENCY)
+        .clamp(1, MAX_SCAN_CONCURRENCY);
+    let limit = Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_SCAN_TIMEOUT_MS));
+
+    let ip = resolve_scan_host(&host).await?;
+    let started = Instant::now();
+    let mut open = stream::iter(ports.iter().copied())
+        .map(|port| async move {
+            let began = Instant::now();
+            match timeout(limit, TcpStream::connect(SocketAddr::new(ip, port))).await {
+                Ok(Ok(_)) => Some((port, elapsed_ms(began))),
+                _ => None,
+            }
+        })
+        .buffer_unordered(concurrency)
+        .filter_map(|found| async move { found })
+        .map(|(port, latency_ms)| OpenPort {
+            port,
+            latency_ms,
+            service: service_name(port),
+            pid: None,
+            process_name: None,
+        })
+        .collect::<Vec<_>>()
+        .await;
+    open.sort_by_key(|port| port.port);
+    let duration_ms = started.elapsed().as_millis();
+
+    if is_local_address(ip) && !open.is_empty() {
+        if let Ok(Ok(sockets)) = spawn_blocking(collect_listening_sockets).await {
+            for port in open.iter_mut() {
+                if let Some(owner) = sockets.iter().find(|socket| {
+                    socket.protocol == SocketProtocol::Tcp
+                        && socket.port == port.port
+                        && socket.pid.is_some()
+                }) {
+                    port.pid = owner.pid;
+                    port.process_name = owner.process_name.clone();
+                }
+            }
+        }
+    }
+
+    Ok(PortScanResult {
+        host,
+        address: ip.to_string(),
+        scanned: ports.len(),
+        open,
+        duration_ms,
+    })
+}
+
+async fn resolve_scan_host(host: &str) -> Result<IpAddr, String> {
+    if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse() {
+        return Ok(ip);
+    }
+    let addrs = lookup_host((host, 0))
+        .await
+        .map_err(|err| format!("无法解析主机 {host}: {err}"))?
+        .map(|addr| addr.ip())
+        .collect::<Vec<_>>();
+    // 优先 IPv4，和大多数本地开发服务的监听方式一致
+    addrs
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addrs.first())
+        .copied()
+        .ok_or_else(|| format!("主机 {host} 没有可用地址"))
+}
+
+fn is_local_address(ip: IpAddr) -> bool {
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || if_addrs::get_if_addrs()
+            .map(|interfaces| interfaces.iter().any(|interface| interface.ip() == ip))
+            .unwrap_or(false)
+}
+
+fn service_name(port: u16) -> Option<&'static str> {
+    WELL_KNOWN_PORTS
+        .iter()
+        .find(|(known, _)| *known == port)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn collect_listening_sockets() -> Result<Vec<ListeningSocket>, String> {
+    let mut entries = Vec::new();
+    for (file, protocol) in [
+        ("/proc/net/tcp", SocketProtocol::Tcp),
+        ("/proc/net/tcp6", SocketProtocol::Tcp),
+        ("/proc/net/udp", SocketProtocol::Udp),
+        ("/proc/net/udp6", SocketProtocol::Udp),
+    ] {
+        // 没有启用 IPv6 时 tcp6/udp6 不存在
+        if let Ok(content) = fs::read_to_string(file) {
+            entries.extend(parse_proc_net(&content, protocol));
+        }
+    }
+    if entries.is_empty() && fs::metadata("/proc/net/tcp").is_err() {
+        return Err("无法读取 /proc/net/tcp".into());
+    }
+
+    let owners = socket_owners();
+    let mut processes: HashMap<u32, (Option<String>, Option<String>)> = HashMap::new();
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let pid = owners.get(&entry.inode).copied();
+            let (process_name, command_line) = pid
+                .map(|pid| {
+                    processes
+                        .entry(pid)
+                        .or_insert_with(|| process_details(pid))
+                        .clone()
+                })
+                .unwrap_or_default();
+            ListeningSocket {
+                protocol: entry.protocol,
+                address: entry.address.ip().to_string(),
+                port: entry.address.port(),
+                pid,
+                process_name,
+                command_line,
+                uid: Some(entry.uid),
+            }
+        })
+        .collect())
+}
+
+#[cfg(any(target_os = "linux", test))]
+struct ProcNetEntry {
+    protocol: SocketProtocol,
+    address: SocketAddr,
+    uid: u32,
+    inode: u64,
+}
+
+/// 解析 /proc/net/{tcp,udp}[6]，只保留 TCP LISTEN 和未连接的 UDP 套接字。
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_net(content: &str, protocol: SocketProtocol) -> Vec<ProcNetEntry> {
+    const TCP_LISTEN: &str = "0A";
+    const UDP_UNCONNECTED: &str = "07";
+
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() < 10 {
+                return None;
+            }
+            let listening = match protocol {
+                SocketProtocol::Tcp => fields[3] == TCP_LISTEN,
+                SocketProtocol::Udp => fields[3] == UDP_UNCONNECTED && fields[2].ends_with(":0000"),
+            };
+            if !listening {
+                return None;
+            }
+            Some(ProcNetEntry {
+                protocol,
+                address: parse_proc_address(fields[1])?,
+                uid: fields[7].parse().ok()?,
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// 内核按主机字节序输出地址的每个 32 位分组，端口是普通的十六进制数。
+#[cfg(any(target_os = "linux", test))]
+fn parse_proc_address(value: &str) -> Option<SocketAddr> {
+    let (ip, port) = value.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let mut words = Vec::with_capacity(4);
+    for index in (0..ip.len()).step_by(8) {
+        let word = u32::from_str_radix(ip.get(index..index + 8)?, 16).ok()?;
+        words.push(word.to_ne_bytes());
+    }
+    let ip = match words.as_slice() {
+        [word] => IpAddr::V4(Ipv4Addr::from(*word)),
+        [a, b, c, d] => {
+            let mut octets = [0u8; 16];
+            for (chunk, word) in octets.chunks_mut(4).zip([a, b, c, d]) {
+                chunk.copy_from_slice(word);
+            }
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+/// 遍历 /proc/<pid>/fd 建立 socket inode 到进程的映射；无权读取的进程会被跳过。
+#[cfg(target_os = "linux")]
+fn socket_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return owners;
+    };
+    for process in processes.flatten() {
+        let Some(pid) = process
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|inode| inode.parse().ok());
+            if let Some(inode) = inode {
+                owners.entry(inode).or_insert(pid);
+            }
+        }
+    }
+    owners
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn process_details(pid: u32) -> (Option<String>, Option<String>) {
+    let name = fs::read_to_string(format!("/proc/{pid}/comm"))
+        .ok()
+        .map(|name| name.trim().to_string());
+    let command_line = fs::read(format!("/proc/{pid}/cmdline"))
+        .ok()
+        .map(|raw| {
+            raw.split(|byte| *byte == 0)
+                .filter(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|line| !line.is_empty());
+    (name, command_line)
+}
+
+#[cfg(target_os = "macos")]
+pub(super) fn collect_listening_sockets() -> Result<Vec<ListeningSocket>, String> {
+    let output = Command::new("lsof")
+        .args(["-nP", "-iTCP", "-sTCP:LISTEN", "-iUDP", "-FpcPn"])
+        .output()
+        .map_err(|err| format!("执行 lsof 失败: {err}"))?;
+    Ok(parse_lsof(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `lsof -F pcPn` 的字段输出：p 开头为进程，P 为协议，n 为地址。
+#[cfg(any(target_os = "macos", test))]
+fn parse_lsof(output: &str) -> Vec<ListeningSocket> {
+    let mut sockets = Vec::new();
+    let mut pid = None;
+    let mut command = None;
+    let mut protocol = None;
+    for line in output.lines() {
+        let (tag, value) = line.split_at(line.len().min(1));
+        match tag {
+            "p" => {
+                pid = value.parse().ok();
+                command = None;
+            }
+            "c" => command = Some(value.to_string()),
+            "P" => {
+                protocol = match value {
+                    "TCP" => Some(SocketProtocol::Tcp),
+                    "UDP" => Some(SocketProtocol::Udp),
+                    _ => None,
+                }
+            }
+            "n" => {
+                // 已连接的 UDP 会显示为 本地->远端，这里只要本地监听
+                let Some(protocol) = protocol.filter(|_| !value.contains("->")) else {
+                    continue;
+                };
+                let Some((address, port)) = value.rsplit_once(':') else {
+                    continue;
+                };
+                let Ok(port) = port.parse() else {
+                    continue;
+                };
+                let address = match address.trim_start_matches('[').trim_end_matches(']') {
+                    "*" => "0.0.0.0".to_string(),
+                    other => other.to_string(),
+                };
+                sockets.push(ListeningSocket {
+                    protocol,
+                    address,
+                    port,
+                    pid,
+                    process_name: command.clone(),
+                    command_line: None,
+                    uid: None,
+                });
+            }
+            _ => {}
+        }
+    }
+    sockets
+}
+
+#[cfg(target_os = "windows")]
+pub(super) fn collect_listening_sockets() -> Result<Vec<ListeningSocket>, String> {
+    let output = Command::new("netstat")
+        .args(["-ano"])
+        .output()
+        .map_err(|err| format!("执行 netstat 失败: {err}"))?;
+    let mut sockets = parse_netstat(&String::from_utf8_lossy(&output.stdout));
+    if let Ok(output) = Command::new("tasklist")
+        .args(["/FO", "CSV", "/NH"])
+        .output()
+    {
+        let names = parse_tasklist(&String::from_utf8_lossy(&output.stdout));
+        for socket in sockets.iter_mut() {
+            socket.process_name = socket
+                .pid
+                .and_then(|pid| names.iter().find(|(id, _)| *id == pid))
+                .map(|(_, name)| name.clone());
+        }
+    }
+    Ok(sockets)
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn parse_netstat(output: &str) -> Vec<ListeningSocket> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let (protocol, local, pid) = match fields.as_slice() {
+                ["TCP", local, _, "LISTENING", pid] => (SocketProtocol::Tcp, *local, *pid),
+                ["UDP", local, "*:*", pid] => (SocketProtocol::Udp, *local, *pid),
+                _ => return None,
+            };
+            let (address, port) = local.rsplit_once(':')?;
+            Some(ListeningSocket {
+                protocol,
+                address: address
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                port: port.parse().ok()?,
+                pid: pid.parse().ok(),
+                process_name: None,
+                command_line: None,
+                uid: None,
+            })
+        })
+        .collect()
+}
+
+#[cfg(any(target_os = "windows", test))]
+fn parse_tasklist(output: &str) -> Vec<(u32, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split("\",\"");
+            let name = fields.next()?.trim_start_matches('"').to_string();
+            let pid = fields.next()?.parse().ok()?;
+            Some((pid, name))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(super) fn collect_listening_sockets() -> Result<Vec<ListeningSocket>, String> {
+    Err("当前系统暂不支持查看监听端口。".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parses_proc_net_tables() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0\n   1: 0100007F:A1B2 0100007F:1F90 01 00000000:00000000 00:00000000 00000000  1000        0 41300 1 0000000000000000 20 4 30 10 -1\n";
+        let entries = parse_proc_net(tcp, SocketProtocol::Tcp);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].address, "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(entries[0].uid, 1000);
+        assert_eq!(entries[0].inode, 41234);
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n   0: 00000000000000000000000001000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 20001 1 0000000000000000 100 0 0 10 0\n";
+        let entries = parse_proc_net(tcp6, SocketProtocol::Tcp);
+        assert_eq!(entries[0].address, "[::1]:22".parse().unwrap());
+
+        let udp = "   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops\n  100: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   101        0 18000 2 0000000000000000 0\n  101: 0100007F:C000 0100007F:0035 01 00000000:00000000 00:00000000 00000000  1000        0 18001 2 0000000000000000 0\n";
+        let entries = parse_proc_net(udp, SocketProtocol::Udp);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].address, "127.0.0.53:53".parse().unwrap());
+    }
+
+    #[test]
+    fn parses_lsof_and_netstat_output() {
+        let lsof = "p501\ncnode\nPTCP\nn*:3000\nPTCP\nn127.0.0.1:9229\np88\ncmDNSResponder\nPUDP\nn*:5353\nPUDP\nn192.168.1.5:60000->1.1.1.1:53\n";
+        let sockets = parse_lsof(lsof);
+        assert_eq!(sockets.len(), 3);
+        assert_eq!(sockets[0].port, 3000);
+        assert_eq!(sockets[0].address, "0.0.0.0");
+        assert_eq!(sockets[0].pid, Some(501));
+        assert_eq!(sockets[0].process_name.as_deref(), Some("node"));
+        assert_eq!(sockets[2].protocol, SocketProtocol::Udp);
+        assert_eq!(sockets[2].process_name.as_deref(), Some("mDNSResponder"));
+
+        let netstat = "\nActive Connections\n\n  Proto  Local Address          Foreign Address        State           PID\n  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1024\n  TCP    [::]:8080              [::]:0                 LISTENING       4321\n  TCP    10.0.0.2:50000         1.2.3.4:443            ESTABLISHED     999\n  UDP    0.0.0.0:5353           *:*                                    2048\n";
+        let sockets = parse_netstat(netstat);
+        assert_eq!(sockets.len(), 3);
+        assert_eq!(sockets[1].address, "::");
+        assert_eq!(sockets[1].port, 8080);
+        assert_eq!(sockets[2].protocol, SocketProtocol::Udp);
+        assert_eq!(sockets[2].pid, Some(2048));
+
+        let tasklist = "\"System Idle Process\",\"0\",\"Services\",\"0\",\"8 K\"\r\n\"node.exe\",\"4321\",\"Console\",\"1\",\"52,000 K\"\r\n";
+        assert_eq!(
+            parse_tasklist(tasklist),
+            vec![
+                (0, "System Idle Process".to_string()),
+                (4321, "node.exe".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn scans_open_and_closed_ports() {
+        let first = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let second = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open_ports = [
+            first.local_addr().unwrap().port(),
+            second.local_addr().unwrap().port(),
+        ];
+        let closed_port = closed.local_addr().unwrap().port();
+        drop(closed);
+
+        let result = scan_ports(PortScanRequest {
+            host: "localhost".into(),
+            start_port: None,
+            end_port: None,
+            ports: Some(vec![open_ports[1], closed_port, open_ports[0]]),
+            concurrency: Some(2),
+            timeout_ms: Some(500),
+        })
+        .await
+        .unwrap();
+        assert_eq!(result.address, "127.0.0.1");
+        assert_eq!(result.scanned, 3);
+        let mut expected = open_ports.to_vec();
+        expected.sort_unstable();
+        assert_eq!(
+            result.open.iter().map(|port| port.port).collect::<Vec<_>>(),
+            expected
+        );
+
+        let invalid = PortScanRequest {
+            host: "127.0.0.1".into(),
+            start_port: Some(2000),
+            end_port: Some(1000),
+            ports: None,
+            concurrency: None,
+            timeout_ms: None,
+        };
+        assert!(scan_ports(invalid).await.is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn finds_process_listening_on_port() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let sockets = list_listening_sockets(Some(port)).await.unwrap();
+        let socket = sockets
+            .iter()
+            .find(|socket| socket.protocol == SocketProtocol::Tcp)
+            .expect("listener not found");
+        assert_eq!(socket.address, "127.0.0.1");
+        assert_eq!(socket.pid, Some(std::process::id()));
+        assert!(socket.command_line.is_some());
+    }
+}
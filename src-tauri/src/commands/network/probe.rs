@@ -0,0 +1,938 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex as StdMutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures_util::future::join_all;
+use reqwest::Url;
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    crypto::ring,
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, ProtocolVersion, RootCertStore, SignatureScheme,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::{lookup_host, TcpStream},
+    time::timeout,
+};
+use tokio_rustls::TlsConnector;
+use x509_parser::{certificate::X509Certificate, extensions::GeneralName, prelude::FromDer};
+
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const DEFAULT_MAX_ADDRESSES: usize = 8;
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+const USER_AGENT: &str = "Chef Network Doctor/0.1";
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkProbeOptions {
+    /// 每个阶段单独计时的超时时间。
+    pub timeout_ms: Option<u64>,
+    /// host:port 目标是否做 TLS 握手，默认仅 443 端口；URL 目标由协议决定。
+    pub tls: Option<bool>,
+    /// 额外信任的 CA 证书（PEM），用于内网或自签名服务。
+    pub ca_cert_pem: Option<String>,
+    pub max_addresses: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkProbeReport {
+    target: String,
+    host: String,
+    port: u16,
+    dns: DnsPhase,
+    tcp: Vec<TcpAttempt>,
+    connected_address: Option<String>,
+    tls: Option<TlsPhase>,
+    http: Option<HttpPhase>,
+    success: bool,
+    total_ms: f64,
+    error: Option<String>,
+    timestamp: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DnsPhase {
+    duration_ms: f64,
+    addresses: Vec<ResolvedAddress>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolvedAddress {
+    address: String,
+    record_type: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TcpAttempt {
+    address: String,
+    connected: bool,
+    duration_ms: f64,
+    error: Option<String>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TlsPhase {
+    duration_ms: f64,
+    version: Option<String>,
+    cipher_suite: Option<String>,
+    alpn: Option<String>,
+    verified: bool,
+    verify_error: Option<String>,
+    certificates: Vec<CertificateInfo>,
+    error: Option<String>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CertificateInfo {
+    subject: String,
+    issuer: String,
+    serial_number: String,
+    subject_alt_names: Vec<String>,
+    not_before: i64,
+    not_after: i64,
+    days_remaining: i64,
+    expired: bool,
+    fingerprint: String,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpPhase {
+    request: String,
+    ttfb_ms: Option<f64>,
+    duration_ms: f64,
+    version: Option<String>,
+    status: Option<u16>,
+    reason: Option<String>,
+    headers: Vec<HttpHeader>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HttpHeader {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct ProbeTarget {
+    host: String,
+    port: u16,
+    tls: bool,
+    /// 需要发送 HTTP 请求时的路径（含查询串）。
+    path: Option<String>,
+}
+
+/// 逐个阶段诊断目标：系统 DNS、逐地址 TCP 建连、TLS 握手和 HTTP 首字节时间。
+#[tauri::command]
+pub async fn probe_network_targets(
+    targets: Vec<String>,
+    options: Option<NetworkProbeOptions>,
+) -> Result<Vec<NetworkProbeReport>, String> {
+    if targets.is_empty() {
+        return Err("请至少填写一个诊断目标。".into());
+    }
+    let options = options.unwrap_or_default();
+    let extra_roots = match options.ca_cert_pem.as_deref() {
+        Some(pem) => parse_ca_certs(pem)?,
+        None => Vec::new(),
+    };
+    let parsed = targets
+        .iter()
+        .map(|raw| parse_target(raw, options.tls))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let probes = targets
+        .iter()
+        .zip(parsed)
+        .map(|(raw, target)| probe_target(raw.trim(), target, &options, &extra_roots));
+    Ok(join_all(probes).await)
+}
+
+fn parse_target(raw: &str, force_tls: Option<bool>) -> Result<ProbeTarget, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err("诊断目标不能为空。".into());
+    }
+
+    if raw.contains("://") {
+        let url = Url::parse(raw).map_err(|err| format!("无法解析地址 {raw}: {err}"))?;
+        let (tls, http) = match url.scheme() {
+            "http" => (false, true),
+            "https" => (true, true),
+            "tcp" => (false, false),
+            "tls" => (true, false),
+            other => {
+                return Err(format!(
+                    "不支持的协议 {other}，请使用 http、https、tcp 或 tls。"
+                ))
+            }
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| format!("地址缺少主机名: {raw}"))?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| format!("地址缺少端口: {raw}"))?;
+        let path = http.then(|| match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        });
+        return Ok(ProbeTarget {
+            host,
+            port,
+            tls,
+            path,
+        });
+    }
+
+    let (host, port) = split_host_port(raw)?;
+    Ok(match port {
+        Some(port) => ProbeTarget {
+            host,
+            port,
+            tls: force_tls.unwrap_or(port == 443),
+            path: None,
+        },
+        // 只有主机名时按 HTTPS 站点处理
+        None => ProbeTarget {
+            host,
+            port: 443,
+            tls: true,
+            path: Some("/".into()),
+        },
+    })
+}
+
+pub(super) fn split_host_port(raw: &str) -> Result<(String, Option<u16>), String> {
+    if raw.parse::<IpAddr>().is_ok() {
+        return Ok((raw.to_string(), None));
+    }
+    let (host, port) = if let Some(rest) = raw.strip_prefix('[') {
+        let (host, rest) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("IPv6 地址格式无效: {raw}"))?;
+        match rest.strip_prefix(':') {
+            Some(port) => (host, Some(port)),
+            None if rest.is_empty() => (host, None),
+            None => return Err(format!("地址格式无效: {raw}")),
+        }
+    } else {
+        match raw.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (raw, None),
+        }
+    };
+    if host.is_empty() || host.contains('/') {
+        return Err(format!("地址格式无效: {raw}"));
+    }
+    let port = port
+        .map(|port| {
+            port.parse::<u16>()
+                .ok()
+                .filter(|port| *port != 0)
+                .ok_or_else(|| format!("端口无效: {port}"))
+        })
+        .transpose()?;
+    Ok((host.to_string(), port))
+}
+
+fn parse_ca_certs(pem: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = rustls_pemfile::certs(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("无法解析 CA 证书: {err}"))?;
+    if certs.is_empty() {
+        return Err("CA 证书中没有找到证书。".into());
+    }
+    Ok(certs)
+}
+
+async fn probe_target(
+    raw: &str,
+    target: ProbeTarget,
+    options: &NetworkProbeOptions,
+    extra_roots: &[CertificateDer<'static>],
+) -> NetworkProbeReport {
+    let phase_timeout = Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+    let max_addresses = options
+        .max_addresses
+        .unwrap_or(DEFAULT_MAX_ADDRESSES)
+        .max(1);
+    let started = Instant::now();
+
+    let (dns, addrs) = resolve(&target, phase_timeout).await;
+    let mut report = NetworkProbeReport {
+        target: raw.to_string(),
+        host: target.host.clone(),
+        port: target.port,
+        error: dns.error.clone(),
+        dns,
+        tcp: Vec::new(),
+        connected_address: None,
+        tls: None,
+        http: None,
+        success: false,
+        total_ms: 0.0,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+
+    let attempts = addrs
+        .into_iter()
+        .take(max_addresses)
+        .map(|addr| connect(addr, phase_timeout));
+    let mut stream = None;
+    for (attempt, connected) in join_all(attempts).await {
+        if stream.is_none() {
+            if let Some(connected) = connected {
+                report.connected_address = Some(attempt.address.clone());
+                stream = Some(connected);
+            }
+        }
+        report.tcp.push(attempt);
+    }
+
+    if let Some(stream) = stream {
+        let error = if target.tls {
+            run_tls(stream, &target, phase_timeout, extra_roots, &mut report).await
+        } else {
+            match target.path.as_deref() {
+                Some(path) => {
+                    let http = run_http(stream, &target, path, phase_timeout).await;
+                    let error = http.error.clone();
+                    report.http = Some(http);
+                    error
+                }
+                None => None,
+            }
+        };
+        report.success = error.is_none();
+        report.error = error;
+    } else if report.error.is_none() {
+        report.error = Some(match report.tcp.first() {
+            Some(_) => format!("无法连接到 {}:{}", target.host, target.port),
+            None => format!("{} 没有可用的地址", target.host),
+        });
+    }
+
+    report.total_ms = elapsed_ms(started);
+    report
+}
+
+async fn resolve(target: &ProbeTarget, phase_timeout: Duration) -> (DnsPhase, Vec<SocketAddr>) {
+    let started = Instant::now();
+    let result = timeout(
+        phase_timeout,
+        lookup_host((target.host.as_str(), target.port)),
+    )
+    .await;
+    let duration_ms = elapsed_ms(started);
+
+    let mut addrs = Vec::new();
+    let error = match result {
+        Ok(Ok(found)) => {
+            for addr in found {
+                if !addrs.contains(&addr) {
+                    addrs.push(addr);
+                }
+            }
+            addrs
+                .is_empty()
+                .then(|| format!("{} 没有解析到任何地址", target.host))
+        }
+        Ok(Err(err)) => Some(format!("解析 {} 失败: {err}", target.host)),
+        Err(_) => Some(format!("解析 {} 超时", target.host)),
+    };
+    let addresses = addrs
+        .iter()
+        .map(|addr| ResolvedAddress {
+            address: addr.ip().to_string(),
+            record_type: if addr.is_ipv4() { "A" } else { "AAAA" }.into(),
+        })
+        .collect();
+
+    (
+        DnsPhase {
+            duration_ms,
+            addresses,
+            error,
+        },
+        addrs,
+    )
+}
+
+async fn connect(addr: SocketAddr, phase_timeout: Duration) -> (TcpAttempt, Option<TcpStream>) {
+    let started = Instant::now();
+    let result = timeout(phase_timeout, TcpStream::connect(addr)).await;
+    let duration_ms = elapsed_ms(started);
+    let (stream, error) = match result {
+        Ok(Ok(stream)) => (Some(stream), None),
+        Ok(Err(err)) => (None, Some(err.to_string())),
+        Err(_) => (None, Some("连接超时".to_string())),
+    };
+    let attempt = TcpAttempt {
+        address: addr.to_string(),
+        connected: stream.is_some(),
+        duration_ms,
+        error,
+    };
+    (attempt, stream)
+}
+
+/// 完成 TLS 握手并记录证书信息；证书校验失败时仍继续 HTTP 阶段，便于排查。
+async fn run_tls(
+    stream: TcpStream,
+    target: &ProbeTarget,
+    phase_timeout: Duration,
+    extra_roots: &[CertificateDer<'static>],
+    report: &mut NetworkProbeReport,
+) -> Option<String> {
+    let (config, verifier) = match tls_client_config(extra_roots) {
+        Ok(config) => config,
+        Err(err) => {
+            report.tls = Some(TlsPhase {
+                error: Some(err.clone()),
+                ..Default::default()
+            });
+            return Some(err);
+        }
+    };
+    let server_name = match ServerName::try_from(target.host.clone()) {
+        Ok(name) => name,
+        Err(err) => {
+            let err = format!("无效的 TLS 主机名 {}: {err}", target.host);
+            report.tls = Some(TlsPhase {
+                error: Some(err.clone()),
+                ..Default::default()
+            });
+            return Some(err);
+        }
+    };
+
+    let started = Instant::now();
+    let result = timeout(
+        phase_timeout,
+        TlsConnector::from(config).connect(server_name, stream),
+    )
+    .await;
+    let mut tls = TlsPhase {
+        duration_ms: elapsed_ms(started),
+        ..Default::default()
+    };
+    if let Some(outcome) = verifier.outcome.lock().unwrap().take() {
+        tls.verified = outcome.is_ok();
+        tls.verify_error = outcome.err();
+    }
+
+    let stream = match result {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(err)) => {
+            let error = format!("TLS 握手失败: {err}");
+            tls.error = Some(error.clone());
+            report.tls = Some(tls);
+            return Some(error);
+        }
+        Err(_) => {
+            let error = "TLS 握手超时".to_string();
+            tls.error = Some(error.clone());
+            report.tls = Some(tls);
+            return Some(error);
+        }
+    };
+
+    let (_, connection) = stream.get_ref();
+    tls.version = connection.protocol_version().map(describe_version);
+    tls.cipher_suite = connection
+        .negotiated_cipher_suite()
+        .map(|suite| format!("{:?}", suite.suite()));
+    tls.alpn = connection
+        .alpn_protocol()
+        .map(|alpn| String::from_utf8_lossy(alpn).into_owned());
+    let now = UnixTime::now().as_secs() as i64;
+    tls.certificates = connection
+        .peer_certificates()
+        .unwrap_or_default()
+        .iter()
+        .map(|cert| certificate_info(cert, now))
+        .collect();
+
+    let mut error = tls
+        .verify_error
+        .as_ref()
+        .map(|err| format!("证书校验失败: {err}"));
+    report.tls = Some(tls);
+
+    if let Some(path) = target.path.as_deref() {
+        let http = run_http(stream, target, path, phase_timeout).await;
+        if error.is_none() {
+            error = http.error.clone();
+        }
+        report.http = Some(http);
+    }
+    error
+}
+
+pub(super) fn tls_client_config(
+    extra_roots: &[CertificateDer<'static>],
+) -> Result<(Arc<ClientConfig>, Arc<RecordingVerifier>), String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    for cert in extra_roots {
+        roots
+            .add(cert.clone())
+            .map_err(|err| format!("无法添加 CA 证书: {err}"))?;
+    }
+
+    let provider = Arc::new(ring::default_provider());
+    let inner = WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+        .build()
+        .map_err(|err| format!("初始化证书校验失败: {err}"))?;
+    let verifier = Arc::new(RecordingVerifier {
+        inner,
+        outcome: StdMutex::new(None),
+    });
+
+    let mut config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("初始化 TLS 失败: {err}"))?
+        .dangerous()
+        .with_custom_certificate_verifier(verifier.clone())
+        .with_no_client_auth();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok((Arc::new(config), verifier))
+}
+
+/// 记录标准证书校验的结果但不中断握手，这样证书有问题时也能看到证书链。
+#[derive(Debug)]
+pub(super) struct RecordingVerifier {
+    inner: Arc<WebPkiServerVerifier>,
+    outcome: StdMutex<Option<Result<(), String>>>,
+}
+
+impl RecordingVerifier {
+    /// 取出握手期间记录的证书校验错误。
+    pub(super) fn take_error(&self) -> Option<String> {
+        self.outcome.lock().unwrap().take().and_then(Result::err)
+    }
+}
+
+impl ServerCertVerifier for RecordingVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let outcome = self
+            .inner
+            .verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)
+            .map(|_| ())
+            .map_err(|err| err.to_string());
+        *self.outcome.lock().unwrap() = Some(outcome);
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn describe_version(version: ProtocolVersion) -> String {
+    match version {
+        ProtocolVersion::TLSv1_3 => "TLS 1.3".into(),
+        ProtocolVersion::TLSv1_2 => "TLS 1.2".into(),
+        other => format!("{other:?}"),
+    }
+}
+
+fn certificate_info(der: &CertificateDer<'_>, now: i64) -> CertificateInfo {
+    let fingerprint = Sha256::digest(der.as_ref())
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":");
+    let cert = match X509Certificate::from_der(der.as_ref()) {
+        Ok((_, cert)) => cert,
+        Err(err) => {
+            return CertificateInfo {
+                subject: format!("无法解析证书: {err}"),
+                fingerprint,
+                ..Default::default()
+            }
+        }
+    };
+
+    let subject_alt_names = cert
+        .subject_alternative_name()
+        .ok()
+        .flatten()
+        .map(|ext| {
+            ext.value
+                .general_names
+                .iter()
+                .filter_map(|name| match name {
+                    GeneralName::DNSName(dns) => Some(dns.to_string()),
+                    GeneralName::IPAddress(bytes) => match bytes.len() {
+                        4 => Some(IpAddr::from(<[u8; 4]>::try_from(*bytes).ok()?).to_string()),
+                        16 => Some(IpAddr::from(<[u8; 16]>::try_from(*bytes).ok()?).to_string()),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let not_before = cert.validity().not_before.timestamp();
+    let not_after = cert.validity().not_after.timestamp();
+
+    CertificateInfo {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial_number: cert.raw_serial_as_string(),
+        subject_alt_names,
+        not_before,
+        not_after,
+        days_remaining: (not_after - now).div_euclid(86_400),
+        expired: not_after < now,
+        fingerprint,
+    }
+}
+
+/// 在已建立的连接上发出一次 HTTP/1.1 请求，只读取到响应头结束。
+async fn run_http<S>(
+    mut stream: S,
+    target: &ProbeTarget,
+    path: &str,
+    phase_timeout: Duration,
+) -> HttpPhase
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let host = if target.host.contains(':') {
+        format!("[{}]", target.host)
+    } else {
+        target.host.clone()
+    };
+    let default_port = if target.tls { 443 } else { 80 };
+    let authority = if target.port == default_port {
+        host
+    } else {
+        format!("{host}:{}", target.port)
+    };
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {authority}\r\nUser-Agent: {USER_AGENT}\r\nAccept: */*\r\nConnection: close\r\n\r\n"
+    );
+
+    let mut phase = HttpPhase {
+        request: format!("GET {path}"),
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let mut ttfb_ms = None;
+    let exchange = async {
+        stream.write_all(request.as_bytes()).await?;
+        stream.flush().await?;
+        let mut received = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            let read = stream.read(&mut buf).await?;
+            if read == 0 {
+                break;
+            }
+            ttfb_ms.get_or_insert_with(|| elapsed_ms(started));
+            received.extend_from_slice(&buf[..read]);
+            if header_end(&received).is_some() || received.len() >= MAX_HEADER_BYTES {
+                break;
+            }
+        }
+        Ok::<_, std::io::Error>(received)
+    };
+    let result = timeout(phase_timeout, exchange).await;
+    phase.duration_ms = elapsed_ms(started);
+    phase.ttfb_ms = ttfb_ms;
+
+    match result {
+        Ok(Ok(received)) => match parse_response_head(&received) {
+            Some((version, status, reason, headers)) => {
+                phase.version = Some(version);
+                phase.status = Some(status);
+                phase.reason = Some(reason);
+                phase.headers = headers;
+            }
+            None if received.is_empty() => phase.error = Some("服务器未返回任何数据".into()),
+            None => phase.error = Some("无法解析 HTTP 响应头".into()),
+        },
+        Ok(Err(err)) => phase.error = Some(format!("HTTP 请求失败: {err}")),
+        Err(_) => phase.error = Some("等待 HTTP 响应超时".into()),
+    }
+    phase
+}
+
+fn header_end(data: &[u8]) -> Option<usize> {
+    data.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+fn parse_response_head(data: &[u8]) -> Option<(String, u16, String, Vec<HttpHeader>)> {
+    let head = String::from_utf8_lossy(&data[..header_end(data)?]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut status_line = lines.next()?.splitn(3, ' ');
+    let version = status_line.next()?.to_string();
+    if !version.starts_with("HTTP/") {
+        return None;
+    }
+    let status = status_line.next()?.parse().ok()?;
+    let reason = status_line.next().unwrap_or_default().to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| HttpHeader {
+            name: name.trim().to_ascii_lowercase(),
+            value: value.trim().to_string(),
+        })
+        .collect();
+    Some((version, status, reason, headers))
+}
+
+pub(super) fn elapsed_ms(started: Instant) -> f64 {
+    (started.elapsed().as_secs_f64() * 100_000.0).round() / 100.0
+}
+
+pub(super) fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::generate_simple_self_signed;
+    use rustls::{pki_types::PrivateKeyDer, ServerConfig};
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    const RESPONSE: &[u8] =
+        b"HTTP/1.1 204 No Content\r\nServer: stand-in\r\nContent-Length: 0\r\n\r\n";
+
+    async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> String {
+        let mut received = Vec::new();
+        let mut buf = [0u8; 1024];
+        while header_end(&received).is_none() {
+            let read = stream.read(&mut buf).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            received.extend_from_slice(&buf[..read]);
+        }
+        String::from_utf8(received).unwrap()
+    }
+
+    async fn spawn_http_stand_in() -> (u16, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            stream.write_all(RESPONSE).await.unwrap();
+            request
+        });
+        (port, server)
+    }
+
+    /// 本地 TLS 服务，返回端口和 PEM 格式的自签名证书。
+    async fn spawn_tls_stand_in() -> (u16, String) {
+        let certified = generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(certified.key_pair.serialize_der().into());
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![certified.cert.der().clone()], key)
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let acceptor = acceptor.clone();
+                tokio::spawn(async move {
+                    if let Ok(mut stream) = acceptor.accept(stream).await {
+                        read_request(&mut stream).await;
+                        let _ = stream.write_all(RESPONSE).await;
+                        let _ = stream.shutdown().await;
+                    }
+                });
+            }
+        });
+        (port, certified.cert.pem())
+    }
+
+    #[test]
+    fn parses_urls_and_host_port_targets() {
+        assert_eq!(
+            parse_target("https://example.com/status?full=1", None).unwrap(),
+            ProbeTarget {
+                host: "example.com".into(),
+                port: 443,
+                tls: true,
+                path: Some("/status?full=1".into()),
+            }
+        );
+        assert_eq!(
+            parse_target("http://[::1]:8080", None).unwrap(),
+            ProbeTarget {
+                host: "::1".into(),
+                port: 8080,
+                tls: false,
+                path: Some("/".into()),
+            }
+        );
+        assert_eq!(
+            parse_target("db.internal:5432", None).unwrap(),
+            ProbeTarget {
+                host: "db.internal".into(),
+                port: 5432,
+                tls: false,
+                path: None,
+            }
+        );
+        assert!(parse_target("[2001:db8::1]:853", Some(true)).unwrap().tls);
+        assert!(
+            parse_target("tls://mail.example.com:993", None)
+                .unwrap()
+                .tls
+        );
+        assert_eq!(parse_target("example.com", None).unwrap().port, 443);
+        assert!(parse_target("example.com:0", None).is_err());
+        assert!(parse_target("ftp://example.com", None).is_err());
+        assert!(parse_target("  ", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn reports_http_phases_against_local_server() {
+        let (port, server) = spawn_http_stand_in().await;
+        let reports = probe_network_targets(vec![format!("http://127.0.0.1:{port}/ping")], None)
+            .await
+            .unwrap();
+        let report = &reports[0];
+
+        assert!(report.success, "{:?}", report.error);
+        assert_eq!(report.dns.addresses[0].address, "127.0.0.1");
+        assert_eq!(report.dns.addresses[0].record_type, "A");
+        assert!(report.tcp[0].connected);
+        assert_eq!(
+            report.connected_address.as_deref(),
+            Some(report.tcp[0].address.as_str())
+        );
+        assert!(report.tls.is_none());
+
+        let http = report.http.as_ref().unwrap();
+        assert_eq!(http.status, Some(204));
+        assert_eq!(http.version.as_deref(), Some("HTTP/1.1"));
+        assert!(http.ttfb_ms.is_some());
+        assert!(http
+            .headers
+            .iter()
+            .any(|header| header.name == "server" && header.value == "stand-in"));
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /ping HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("Host: 127.0.0.1:{port}\r\n")));
+    }
+
+    #[tokio::test]
+    async fn reports_tls_handshake_and_certificate_chain() {
+        let (port, ca_pem) = spawn_tls_stand_in().await;
+        let target = format!("https://localhost:{port}/health");
+
+        let trusted = NetworkProbeOptions {
+            ca_cert_pem: Some(ca_pem),
+            ..Default::default()
+        };
+        let reports = probe_network_targets(vec![target.clone()], Some(trusted))
+            .await
+            .unwrap();
+        let report = &reports[0];
+        assert!(report.success, "{:?}", report.error);
+        assert_eq!(
+            report.connected_address.as_deref(),
+            Some(&*format!("127.0.0.1:{port}"))
+        );
+
+        let tls = report.tls.as_ref().unwrap();
+        assert!(tls.verified);
+        assert_eq!(tls.version.as_deref(), Some("TLS 1.3"));
+        assert!(tls.cipher_suite.as_deref().unwrap().starts_with("TLS13_"));
+        assert_eq!(tls.certificates.len(), 1);
+        let cert = &tls.certificates[0];
+        assert_eq!(cert.subject_alt_names, vec!["localhost".to_string()]);
+        assert!(!cert.expired && cert.days_remaining > 0);
+        assert_eq!(report.http.as_ref().unwrap().status, Some(204));
+
+        // 不信任自签名证书时仍能拿到证书链和 HTTP 结果，但诊断标记为失败
+        let reports = probe_network_targets(vec![target], None).await.unwrap();
+        let report = &reports[0];
+        let tls = report.tls.as_ref().unwrap();
+        assert!(!report.success);
+        assert!(!tls.verified);
+        assert!(tls.verify_error.is_some());
+        assert_eq!(tls.certificates.len(), 1);
+        assert_eq!(report.http.as_ref().unwrap().status, Some(204));
+    }
+
+    #[tokio::test]
+    async fn reports_refused_tcp_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let reports = probe_network_targets(vec![format!("127.0.0.1:{port}")], None)
+            .await
+            .unwrap();
+        let report = &reports[0];
+        assert!(!report.success);
+        assert!(report.dns.error.is_none());
+        assert!(!report.tcp[0].connected);
+        assert!(report.tcp[0].error.is_some());
+        assert!(report.connected_address.is_none());
+        assert!(report.tls.is_none() && report.http.is_none());
+    }
+}
@@ -0,0 +1,634 @@
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::{
+    collections::{HashMap, HashSet},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+
+use super::ports::{collect_listening_sockets, ListeningSocket};
+
+const DEFAULT_GRACE_MS: u64 = 3000;
+const FORCE_WAIT: Duration = Duration::from_millis(1000);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const MAX_TREE_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProcessEntry {
+    pid: u32,
+    ppid: u32,
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortProcessDetails {
+    port: u16,
+    pid: u32,
+    process_name: Option<String>,
+    command_line: Option<String>,
+    executable: Option<String>,
+    cwd: Option<String>,
+    /// 无法读取（其他用户的进程或不支持的平台）时为 None。
+    environment: Option<Vec<EnvironmentEntry>>,
+    ancestors: Vec<ProcessSummary>,
+    tree: ProcessNode,
+    sockets: Vec<ListeningSocket>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentEntry {
+    key: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessSummary {
+    pid: u32,
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProcessNode {
+    pid: u32,
+    name: String,
+    children: Vec<ProcessNode>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillPortRequest {
+    pub port: u16,
+    /// 界面上确认过的进程；端口期间换了主人时拒绝操作。
+    pub pid: Option<u32>,
+    pub grace_ms: Option<u64>,
+    /// 宽限期结束后进程仍在运行时是否强制结束。
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillPortResult {
+    port: u16,
+    pid: u32,
+    process_name: Option<String>,
+    signals: Vec<String>,
+    terminated: bool,
+    forced: bool,
+    /// 宽限期后仍在运行，等待用户确认是否强制结束。
+    still_running: bool,
+    messages: Vec<String>,
+}
+
+/// 查看占用端口的进程：命令行、工作目录、环境变量和进程树。
+#[tauri::command]
+pub async fn inspect_port_process(port: u16) -> Result<Vec<PortProcessDetails>, String> {
+    spawn_blocking(move || {
+        let sockets = collect_listening_sockets()?;
+        let pids = port_owners(&sockets, port)?;
+        let table = process_table();
+        Ok(pids
+            .into_iter()
+            .map(|pid| process_details(port, pid, &sockets, &table))
+            .collect())
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// 结束占用端口的进程：先发 SIGTERM，宽限期后按需升级为 SIGKILL。
+#[tauri::command]
+pub async fn kill_port_process(request: KillPortRequest) -> Result<Vec<KillPortResult>, String> {
+    let port = request.port;
+    let sockets = spawn_blocking(collect_listening_sockets)
+        .await
+        .map_err(|err| err.to_string())??;
+    let mut pids = port_owners(&sockets, port)?;
+    if let Some(expected) = request.pid {
+        if !pids.contains(&expected) {
+            return Err(format!(
+                "端口 {port} 当前不属于进程 {expected}，请刷新后再试。"
+            ));
+        }
+        pids = vec![expected];
+    }
+
+    let grace = Duration::from_millis(request.grace_ms.unwrap_or(DEFAULT_GRACE_MS));
+    let mut results = Vec::new();
+    for pid in pids {
+        let process_name = sockets
+            .iter()
+            .find(|socket| socket.pid == Some(pid))
+            .and_then(|socket| socket.process_name.clone());
+        let mut result = KillPortResult::new(port, pid, process_name);
+        // 某个进程失败时记下原因，继续处理其余进程
+        if let Err(err) = terminate_process(&mut result, grace, request.force).await {
+            result.messages.push(err);
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+impl KillPortResult {
+    fn new(port: u16, pid: u32, process_name: Option<String>) -> Self {
+        Self {
+            port,
+            pid,
+            process_name,
+            signals: Vec::new(),
+            terminated: false,
+            forced: false,
+            still_running: false,
+            messages: Vec::new(),
+        }
+    }
+}
+
+fn port_owners(sockets: &[ListeningSocket], port: u16) -> Result<Vec<u32>, String> {
+    let on_port = sockets
+        .iter()
+        .filter(|socket| socket.port == port)
+        .collect::<Vec<_>>();
+    if on_port.is_empty() {
+        return Err(format!("没有进程在监听端口 {port}。"));
+    }
+    let mut pids = Vec::new();
+    for pid in on_port.iter().filter_map(|socket| socket.pid) {
+        if !pids.contains(&pid) {
+            pids.push(pid);
+        }
+    }
+    if pids.is_empty() {
+        return Err(format!(
+            "端口 {port} 已被占用，但没有权限查看所属进程（可能属于其他用户）。"
+        ));
+    }
+    Ok(pids)
+}
+
+async fn terminate_process(
+    result: &mut KillPortResult,
+    grace: Duration,
+    force: bool,
+) -> Result<(), String> {
+    let pid = result.pid;
+    if pid == std::process::id() {
+        return Err("不能结束 Chef 自身。".into());
+    }
+
+    result.signals.push(signal_name(false).into());
+    let exited = match send_signal(pid, false) {
+        Ok(()) => wait_for_exit(pid, grace).await,
+        // 不带 /F 的 taskkill 对控制台程序无效，视为仍在运行，交给强制结束处理
+        Err(err) if cfg!(windows) => {
+            result.messages.push(err);
+            false
+        }
+        Err(err) => return Err(err),
+    };
+    if exited {
+        result.terminated = true;
+        result.messages.push(format!(
+            "进程 {pid} 已在收到 {} 后退出。",
+            signal_name(false)
+        ));
+        return Ok(());
+    }
+
+    if !force {
+        result.still_running = true;
+        result.messages.push(format!(
+            "进程 {pid} 在 {} ms 内未退出，确认后可强制结束。",
+            grace.as_millis()
+        ));
+        return Ok(());
+    }
+
+    send_signal(pid, true)?;
+    result.signals.push(signal_name(true).into());
+    result.forced = true;
+    result.terminated = wait_for_exit(pid, FORCE_WAIT).await;
+    result.still_running = !result.terminated;
+    result.messages.push(if result.terminated {
+        format!("进程 {pid} 已被强制结束。")
+    } else {
+        format!("已发送 {}，但进程 {pid} 仍未退出。", signal_name(true))
+    });
+    Ok(())
+}
+
+async fn wait_for_exit(pid: u32, limit: Duration) -> bool {
+    let started = Instant::now();
+    loop {
+        if !process_alive(pid) {
+            return true;
+        }
+        if started.elapsed() >= limit {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn signal_name(force: bool) -> &'static str {
+    match (cfg!(windows), force) {
+        (true, false) => "taskkill",
+        (true, true) => "taskkill /F",
+        (false, false) => "SIGTERM",
+        (false, true) => "SIGKILL",
+    }
+}
+
+fn send_signal(pid: u32, force: bool) -> Result<(), String> {
+    let pid_arg = pid.to_string();
+    let output = if cfg!(windows) {
+        let mut command = Command::new("taskkill");
+        if force {
+            command.arg("/F");
+        }
+        command.args(["/PID", &pid_arg]).output()
+    } else {
+        let signal = if force { "KILL" } else { "TERM" };
+        Command::new("kill").args(["-s", signal, &pid_arg]).output()
+    }
+    .map_err(|err| format!("无法结束进程 {pid}: {err}"))?;
+
+    if output.status.success() || !process_alive(pid) {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Err(format!("结束进程 {pid} 失败: {stderr}"))
+}
+
+#[cfg(target_os = "linux")]
+fn process_alive(pid: u32) -> bool {
+    // 僵尸进程已经退出，只是还没被父进程回收
+    fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| parse_stat(&stat))
+        .map(|(_, state, _)| state != 'Z' && state != 'X')
+        .unwrap_or(false)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&format!("\"{pid}\"")))
+        .unwrap_or(false)
+}
+
+fn process_details(
+    port: u16,
+    pid: u32,
+    sockets: &[ListeningSocket],
+    table: &[ProcessEntry],
+) -> PortProcessDetails {
+    let owned = sockets
+        .iter()
+        .filter(|socket| socket.pid == Some(pid))
+        .cloned()
+        .collect::<Vec<_>>();
+    let process_name = owned
+        .first()
+        .and_then(|socket| socket.process_name.clone())
+        .or_else(|| {
+            table
+                .iter()
+                .find(|entry| entry.pid == pid)
+                .map(|entry| entry.name.clone())
+        });
+    let command_line = owned.first().and_then(|socket| socket.command_line.clone());
+    let (executable, cwd, environment) = process_context(pid);
+
+    PortProcessDetails {
+        port,
+        pid,
+        command_line: command_line.or_else(|| command_line_of(pid)),
+        executable,
+        cwd,
+        environment: environment.map(|mut entries| {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            entries
+                .into_iter()
+                .map(|(key, value)| EnvironmentEntry { key, value })
+                .collect()
+        }),
+        ancestors: ancestors(pid, table),
+        tree: build_tree(pid, process_name.clone().unwrap_or_default(), table),
+        process_name,
+        sockets: owned,
+    }
+}
+
+fn ancestors(pid: u32, table: &[ProcessEntry]) -> Vec<ProcessSummary> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::from([pid]);
+    let mut current = table.iter().find(|entry| entry.pid == pid);
+    while let Some(entry) = current {
+        if entry.ppid == 0 || !seen.insert(entry.ppid) {
+            break;
+        }
+        current = table.iter().find(|parent| parent.pid == entry.ppid);
+        if let Some(parent) = current {
+            chain.push(ProcessSummary {
+                pid: parent.pid,
+                name: parent.name.clone(),
+            });
+        }
+    }
+    chain
+}
+
+fn build_tree(pid: u32, name: String, table: &[ProcessEntry]) -> ProcessNode {
+    let mut children: HashMap<u32, Vec<&ProcessEntry>> = HashMap::new();
+    for entry in table {
+        children.entry(entry.ppid).or_default().push(entry);
+    }
+
+    fn walk(
+        pid: u32,
+        name: String,
+        children: &HashMap<u32, Vec<&ProcessEntry>>,
+        depth: usize,
+    ) -> ProcessNode {
+        let nodes = match children.get(&pid) {
+            Some(entries) if depth < MAX_TREE_DEPTH => entries
+                .iter()
+                .filter(|entry| entry.pid != pid)
+                .map(|entry| walk(entry.pid, entry.name.clone(), children, depth + 1))
+                .collect(),
+            _ => Vec::new(),
+        };
+        ProcessNode {
+            pid,
+            name,
+            children: nodes,
+        }
+    }
+    walk(pid, name, &children, 0)
+}
+
+/// 解析 /proc/<pid>/stat，返回 (进程名, 状态, 父进程)；进程名可能包含空格和括号。
+#[cfg(any(target_os = "linux", test))]
+fn parse_stat(stat: &str) -> Option<(String, char, u32)> {
+    let open = stat.find('(')?;
+    let close = stat.rfind(')')?;
+    let name = stat.get(open + 1..close)?.to_string();
+    let mut rest = stat.get(close + 1..)?.split_whitespace();
+    let state = rest.next()?.chars().next()?;
+    let ppid = rest.next()?.parse().ok()?;
+    Some((name, state, ppid))
+}
+
+#[cfg(target_os = "linux")]
+fn process_table() -> Vec<ProcessEntry> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            let stat = fs::read_to_string(entry.path().join("stat")).ok()?;
+            let (name, _, ppid) = parse_stat(&stat)?;
+            Some(ProcessEntry { pid, ppid, name })
+        })
+        .collect()
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_table() -> Vec<ProcessEntry> {
+    Command::new("ps")
+        .args(["-axo", "pid=,ppid=,comm="])
+        .output()
+        .map(|output| parse_ps(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+#[cfg(windows)]
+fn process_table() -> Vec<ProcessEntry> {
+    Vec::new()
+}
+
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_ps(output: &str) -> Vec<ProcessEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pid = parts.next()?.parse().ok()?;
+            let ppid = parts.next()?.parse().ok()?;
+            let name = parts.collect::<Vec<_>>().join(" ");
+            // macOS 的 comm 是完整路径，只保留可执行文件名
+            let name = name.rsplit('/').next().unwrap_or(&name).to_string();
+            Some(ProcessEntry { pid, ppid, name })
+        })
+        .collect()
+}
+
+type ProcessContext = (
+    Option<String>,
+    Option<String>,
+    Option<Vec<(String, String)>>,
+);
+
+#[cfg(target_os = "linux")]
+fn process_context(pid: u32) -> ProcessContext {
+    let link = |name: &str| {
+        fs::read_link(format!("/proc/{pid}/{name}"))
+            .ok()
+            .map(|path| path.display().to_string())
+    };
+    let environment = fs::read(format!("/proc/{pid}/environ"))
+        .ok()
+        .map(|raw| parse_environ(&raw));
+    (link("exe"), link("cwd"), environment)
+}
+
+#[cfg(target_os = "macos")]
+fn process_context(pid: u32) -> ProcessContext {
+    let cwd = Command::new("lsof")
+        .args(["-a", "-p", &pid.to_string(), "-d", "cwd", "-Fn"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix('n').map(str::to_string))
+        });
+    (None, cwd, None)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn process_context(_pid: u32) -> ProcessContext {
+    (None, None, None)
+}
+
+#[cfg(any(target_os = "linux", test))]
+fn parse_environ(raw: &[u8]) -> Vec<(String, String)> {
+    raw.split(|byte| *byte == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            (!key.is_empty()).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn command_line_of(pid: u32) -> Option<String> {
+    super::ports::process_details(pid).1
+}
+
+#[cfg(not(target_os = "linux"))]
+fn command_line_of(pid: u32) -> Option<String> {
+    if cfg!(windows) {
+        return None;
+    }
+    Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .output()
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pid: u32, ppid: u32, name: &str) -> ProcessEntry {
+        ProcessEntry {
+            pid,
+            ppid,
+            name: name.into(),
+        }
+    }
+
+    #[test]
+    fn parses_process_metadata() {
+        assert_eq!(
+            parse_stat("4242 (node (dev) server) S 4200 4242 4200 0 -1 4194560"),
+            Some(("node (dev) server".to_string(), 'S', 4200))
+        );
+        assert_eq!(
+            parse_ps("    1     0 /sbin/launchd\n  501     1 /usr/local/bin/node\n"),
+            vec![entry(1, 0, "launchd"), entry(501, 1, "node")]
+        );
+        assert_eq!(
+            parse_environ(b"PORT=3000\0NODE_ENV=development\0=broken\0EMPTY=\0"),
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("NODE_ENV".to_string(), "development".to_string()),
+                ("EMPTY".to_string(), String::new())
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_process_tree_and_ancestors() {
+        let table = vec![
+            entry(1, 0, "systemd"),
+            entry(100, 1, "zsh"),
+            entry(200, 100, "npm"),
+            entry(300, 200, "node"),
+            entry(301, 300, "esbuild"),
+            entry(302, 300, "esbuild"),
+        ];
+        let chain = ancestors(300, &table);
+        assert_eq!(
+            chain.iter().map(|item| item.pid).collect::<Vec<_>>(),
+            vec![200, 100, 1]
+        );
+
+        let tree = build_tree(200, "npm".into(), &table);
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].pid, 300);
+        assert_eq!(tree.children[0].children.len(), 2);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn inspects_own_listening_process() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let details = inspect_port_process(port).await.unwrap();
+        assert_eq!(details.len(), 1);
+        let details = &details[0];
+        assert_eq!(details.pid, std::process::id());
+        assert_eq!(
+            details.cwd.as_deref(),
+            std::env::current_dir()
+                .ok()
+                .as_deref()
+                .and_then(|dir| dir.to_str())
+        );
+        assert!(details
+            .environment
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|entry| entry.key == "PATH"));
+        assert!(!details.ancestors.is_empty());
+        assert!(details.sockets.iter().any(|socket| socket.port == port));
+
+        // 不允许结束自身，原因记录在结果里
+        let request = KillPortRequest {
+            port,
+            pid: None,
+            grace_ms: Some(100),
+            force: true,
+        };
+        let results = kill_port_process(request).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].port, port);
+        assert!(!results[0].terminated && results[0].signals.is_empty());
+        assert!(results[0].messages[0].contains("自身"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn escalates_to_sigkill_only_when_forced() {
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; sleep 5"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let mut pending = KillPortResult::new(8080, pid, None);
+        terminate_process(&mut pending, Duration::from_millis(300), false)
+            .await
+            .unwrap();
+        assert!(pending.still_running && !pending.terminated);
+        assert_eq!(pending.signals, vec!["SIGTERM"]);
+
+        let mut forced = KillPortResult::new(8080, pid, None);
+        terminate_process(&mut forced, Duration::from_millis(300), true)
+            .await
+            .unwrap();
+        assert!(forced.terminated && forced.forced);
+        assert_eq!(forced.signals, vec!["SIGTERM", "SIGKILL"]);
+        child.wait().unwrap();
+    }
+}
@@ -0,0 +1,870 @@
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+use std::{
+    env,
+    net::{IpAddr, Ipv4Addr},
+};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use tauri::async_runtime::spawn_blocking;
+
+use super::{
+    detect_env_proxy_endpoints,
+    pac::{evaluate_pac, fetch_pac_script, parse_pac_result, wpad_candidates, ProxyRoute},
+    ProxyEndpoint, ProxyProtocol,
+};
+#[cfg(any(target_os = "linux", target_os = "macos", test))]
+use super::{parse_proxy_value, push_endpoint};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum ProxyMode {
+    None,
+    Manual,
+    /// 使用指定地址的 PAC 脚本。
+    Pac,
+    /// 通过 WPAD 自动发现 PAC 脚本。
+    Wpad,
+}
+
+/// 一个来源（环境变量、GNOME、KDE、macOS）中的代理配置。
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyConfigSource {
+    source: String,
+    mode: ProxyMode,
+    pac_url: Option<String>,
+    endpoints: Vec<ProxyEndpoint>,
+    no_proxy: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyLookupRequest {
+    pub url: String,
+    /// 指定 PAC 地址或脚本内容时不读取系统配置，便于调试 PAC。
+    pub pac_url: Option<String>,
+    pub pac_script: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyLookupResult {
+    url: String,
+    source: String,
+    mode: ProxyMode,
+    pac_url: Option<String>,
+    /// FindProxyForURL 的原始返回值。
+    pac_result: Option<String>,
+    /// 命中的 no_proxy / 忽略主机规则。
+    bypass_rule: Option<String>,
+    routes: Vec<ProxyRoute>,
+    notes: Vec<String>,
+}
+
+/// 列出检测到的所有代理配置来源，包括 PAC、WPAD 和 no_proxy 规则。
+#[tauri::command]
+pub async fn get_system_proxy_config() -> Result<Vec<ProxyConfigSource>, String> {
+    spawn_blocking(detect_proxy_sources)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// 判断访问某个 URL 时会使用哪个代理。
+#[tauri::command]
+pub async fn resolve_proxy_for_url(
+    request: ProxyLookupRequest,
+) -> Result<ProxyLookupResult, String> {
+    let url = parse_target_url(&request.url)?;
+    if request.pac_script.is_some() || request.pac_url.is_some() {
+        let (pac_url, script) = match request.pac_script {
+            Some(script) => (request.pac_url, script),
+            None => {
+                let location = request.pac_url.unwrap_or_default();
+                let script = fetch_pac_script(&location).await?;
+                (Some(location), script)
+            }
+        };
+        let mut result = ProxyLookupResult::direct(&url);
+        result.source = "request".into();
+        result.mode = ProxyMode::Pac;
+        apply_pac(&mut result, &url, pac_url, script).await?;
+        return Ok(result);
+    }
+
+    let sources = spawn_blocking(detect_proxy_sources)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(lookup_with_sources(&url, sources).await)
+}
+
+/// 把桌面环境里手动配置的代理补充到代理列表。
+#[cfg(target_os = "linux")]
+pub(super) fn detect_desktop_proxy_endpoints(endpoints: &mut Vec<ProxyEndpoint>) {
+    for source in [gnome_proxy_source(), kde_proxy_source()]
+        .into_iter()
+        .flatten()
+    {
+        if source.mode != ProxyMode::Manual {
+            continue;
+        }
+        for endpoint in source.endpoints {
+            push_endpoint(
+                endpoints,
+                endpoint.protocol,
+                endpoint.host,
+                endpoint.port,
+                &endpoint.source,
+            );
+        }
+    }
+}
+
+/// 按优先级排列：本进程的环境变量最先生效，其次是桌面/系统设置。
+fn detect_proxy_sources() -> Vec<ProxyConfigSource> {
+    let mut sources = Vec::new();
+    sources.extend(env_proxy_source());
+    #[cfg(target_os = "linux")]
+    {
+        sources.extend(gnome_proxy_source());
+        sources.extend(kde_proxy_source());
+    }
+    #[cfg(target_os = "macos")]
+    if let Ok(output) = Command::new("scutil").arg("--proxy").output() {
+        if output.status.success() {
+            sources.push(parse_scutil_proxy(&String::from_utf8_lossy(&output.stdout)));
+        }
+    }
+    sources
+}
+
+async fn lookup_with_sources(url: &Url, sources: Vec<ProxyConfigSource>) -> ProxyLookupResult {
+    let mut result = ProxyLookupResult::direct(url);
+    let host = url_host(url);
+    for source in sources {
+        match source.mode {
+            ProxyMode::None => continue,
+            ProxyMode::Manual => {
+                if let Some(rule) =
+                    no_proxy_match(&source.no_proxy, &host, url.port_or_known_default())
+                {
+                    result.source = source.source;
+                    result.mode = source.mode;
+                    result.bypass_rule = Some(rule.to_string());
+                    return result;
+                }
+                match select_endpoint(&source.endpoints, url.scheme()) {
+                    Some(endpoint) => {
+                        result.source = source.source;
+                        result.mode = source.mode;
+                        result.routes = vec![ProxyRoute::Proxy(endpoint.clone())];
+                        return result;
+                    }
+                    None => result.notes.push(format!(
+                        "{} 没有适用于 {} 的代理。",
+                        source.source,
+                        url.scheme()
+                    )),
+                }
+            }
+            ProxyMode::Pac | ProxyMode::Wpad => {
+                let script = match (&source.mode, &source.pac_url) {
+                    (ProxyMode::Pac, Some(pac_url)) => fetch_pac_script(pac_url)
+                        .await
+                        .map(|script| (pac_url.clone(), script)),
+                    (ProxyMode::Pac, None) => Err(format!("{} 没有配置 PAC 地址。", source.source)),
+                    _ => discover_wpad().await,
+                };
+                let (pac_url, script) = match script {
+                    Ok(found) => found,
+                    Err(err) => {
+                        result.notes.push(err);
+                        continue;
+                    }
+                };
+                let mut attempt = ProxyLookupResult::direct(url);
+                attempt.notes = std::mem::take(&mut result.notes);
+                attempt.source = source.source;
+                attempt.mode = source.mode;
+                match apply_pac(&mut attempt, url, Some(pac_url), script).await {
+                    Ok(()) => return attempt,
+                    Err(err) => {
+                        result.notes = attempt.notes;
+                        result.notes.push(err);
+                    }
+                }
+            }
+        }
+    }
+    result
+}
+
+async fn apply_pac(
+    result: &mut ProxyLookupResult,
+    url: &Url,
+    pac_url: Option<String>,
+    script: String,
+) -> Result<(), String> {
+    let target = url.to_string();
+    let host = url_host(url);
+    let pac_result = spawn_blocking(move || evaluate_pac(&script, &target, &host))
+        .await
+        .map_err(|err| err.to_string())??;
+    let label = match &pac_url {
+        Some(pac_url) => format!("pac:{pac_url}"),
+        None => "pac".to_string(),
+    };
+    result.routes = parse_pac_result(&pac_result, &label);
+    result.pac_url = pac_url;
+    result.pac_result = Some(pac_result);
+    Ok(())
+}
+
+async fn discover_wpad() -> Result<(String, String), String> {
+    let hostname = gethostname::gethostname().to_string_lossy().into_owned();
+    for candidate in wpad_candidates(&hostname) {
+        if let Ok(script) = fetch_pac_script(&candidate).await {
+            return Ok((candidate, script));
+        }
+    }
+    Err("未能通过 WPAD 找到 PAC 脚本。".into())
+}
+
+impl ProxyLookupResult {
+    fn direct(url: &Url) -> Self {
+        Self {
+            url: url.to_string(),
+            source: "default".into(),
+            mode: ProxyMode::None,
+            pac_url: None,
+            pac_result: None,
+            bypass_rule: None,
+            routes: vec![ProxyRoute::Direct],
+            notes: Vec::new(),
+        }
+    }
+}
+
+fn parse_target_url(raw: &str) -> Result<Url, String> {
+    let raw = raw.trim();
+    let candidate = if raw.contains("://") {
+        raw.to_string()
+    } else {
+        format!("http://{raw}")
+    };
+    let url = Url::parse(&candidate).map_err(|err| format!("URL 无效 {raw}: {err}"))?;
+    if url.host_str().is_none() {
+        return Err(format!("URL 缺少主机名: {raw}"));
+    }
+    Ok(url)
+}
+
+fn url_host(url: &Url) -> String {
+    url.host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_ascii_lowercase()
+}
+
+/// https 优先使用 HTTPS 代理，其余使用 HTTP 代理，都没有时退回 SOCKS。
+fn select_endpoint<'a>(endpoints: &'a [ProxyEndpoint], scheme: &str) -> Option<&'a ProxyEndpoint> {
+    let preferred = if scheme == "https" {
+        ProxyProtocol::Https
+    } else {
+        ProxyProtocol::Http
+    };
+    endpoints
+        .iter()
+        .find(|endpoint| endpoint.protocol == preferred)
+        .or_else(|| {
+            endpoints
+                .iter()
+                .find(|endpoint| endpoint.protocol == ProxyProtocol::Socks5)
+        })
+}
+
+fn env_proxy_source() -> Option<ProxyConfigSource> {
+    let mut endpoints = Vec::new();
+    detect_env_proxy_endpoints(&mut endpoints);
+    if endpoints.is_empty() {
+        return None;
+    }
+    let no_proxy = ["no_proxy", "NO_PROXY"]
+        .iter()
+        .find_map(|key| env::var(key).ok())
+        .map(|value| split_list(&value, ','))
+        .unwrap_or_default();
+    Some(ProxyConfigSource {
+        source: "env".into(),
+        mode: ProxyMode::Manual,
+        pac_url: None,
+        endpoints,
+        no_proxy,
+    })
+}
+
+/// 判断主机是否命中 no_proxy 规则，支持域名后缀、通配符、CIDR 和 `<local>`。
+fn no_proxy_match<'a>(rules: &'a [String], host: &str, port: Option<u16>) -> Option<&'a str> {
+    let ip = host.parse::<IpAddr>().ok();
+    rules.iter().map(|rule| rule.trim()).find(|rule| {
+        if rule.is_empty() {
+            return false;
+        }
+        if *rule == "*" {
+            return true;
+        }
+        if rule.eq_ignore_ascii_case("<local>") {
+            return ip.is_none() && !host.contains('.');
+        }
+        if let Some((network, bits)) = rule.split_once('/') {
+            return match (ip, parse_network(network), bits.parse::<u32>()) {
+                (Some(ip), Some(network), Ok(bits)) => in_network(ip, network, bits),
+                _ => false,
+            };
+        }
+
+        let (pattern, rule_port) = split_rule_port(rule);
+        if rule_port.is_some() && rule_port != port {
+            return false;
+        }
+        let pattern = pattern
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_ascii_lowercase();
+        if let Some(domain) = pattern.strip_prefix("*.").or(pattern.strip_prefix('.')) {
+            return host == domain || host.ends_with(&format!(".{domain}"));
+        }
+        if pattern.contains('*') {
+            return wildcard_match(&pattern, host);
+        }
+        host == pattern || (ip.is_none() && host.ends_with(&format!(".{pattern}")))
+    })
+}
+
+fn split_rule_port(rule: &str) -> (&str, Option<u16>) {
+    // 不带方括号的 IPv6 地址里也有冒号，不能当成端口
+    if rule.parse::<IpAddr>().is_ok() {
+        return (rule, None);
+    }
+    match rule.rsplit_once(':') {
+        Some((pattern, port)) if !pattern.is_empty() => match port.parse::<u16>() {
+            Ok(port) => (pattern, Some(port)),
+            Err(_) => (rule, None),
+        },
+        _ => (rule, None),
+    }
+}
+
+/// 解析网段地址，兼容 macOS 例外列表里 `169.254` 这种省略写法。
+fn parse_network(network: &str) -> Option<IpAddr> {
+    if let Ok(ip) = network.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    let octets = network
+        .split('.')
+        .map(|part| part.parse::<u8>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if octets.is_empty() || octets.len() > 4 {
+        return None;
+    }
+    let mut padded = [0u8; 4];
+    padded[..octets.len()].copy_from_slice(&octets);
+    Some(Ipv4Addr::from(padded).into())
+}
+
+fn in_network(ip: IpAddr, network: IpAddr, bits: u32) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if bits <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if bits <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts = pattern.split('*').collect::<Vec<_>>();
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    if !text.ends_with(last) {
+        return false;
+    }
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn split_list(value: &str, separator: char) -> Vec<String> {
+    value
+        .split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn gnome_proxy_source() -> Option<ProxyConfigSource> {
+    gnome_proxy_from(|schema, key| {
+        let output = Command::new("gsettings")
+            .args(["get", schema, key])
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    })
+}
+
+/// 根据 `gsettings get` 的输出组装 GNOME 代理配置。
+#[cfg(any(target_os = "linux", test))]
+fn gnome_proxy_from(get: impl Fn(&str, &str) -> Option<String>) -> Option<ProxyConfigSource> {
+    const SCHEMA: &str = "org.gnome.system.proxy";
+    let mode = gvariant_strings(&get(SCHEMA, "mode")?).into_iter().next()?;
+    let mut source = ProxyConfigSource {
+        source: "gnome".into(),
+        mode: ProxyMode::None,
+        pac_url: None,
+        endpoints: Vec::new(),
+        no_proxy: get(SCHEMA, "ignore-hosts")
+            .map(|value| gvariant_strings(&value))
+            .unwrap_or_default(),
+    };
+    match mode.as_str() {
+        "manual" => {
+            source.mode = ProxyMode::Manual;
+            for (suffix, protocol) in [
+                ("http", ProxyProtocol::Http),
+                ("https", ProxyProtocol::Https),
+                ("socks", ProxyProtocol::Socks5),
+            ] {
+                let schema = format!("{SCHEMA}.{suffix}");
+                let host = get(&schema, "host")
+                    .and_then(|value| gvariant_strings(&value).into_iter().next())
+                    .unwrap_or_default();
+                let port = get(&schema, "port")
+                    .and_then(|value| value.trim().parse::<u16>().ok())
+                    .unwrap_or(0);
+                if !host.is_empty() && port != 0 {
+                    push_endpoint(
+                        &mut source.endpoints,
+                        protocol,
+                        host,
+                        port,
+                        "gnome:gsettings",
+                    );
+                }
+            }
+        }
+        "auto" => {
+            // 自动模式下没有填写 PAC 地址时 GNOME 会走 WPAD
+            source.pac_url = get(SCHEMA, "autoconfig-url")
+                .and_then(|value| gvariant_strings(&value).into_iter().next())
+                .filter(|url| !url.is_empty());
+            source.mode = if source.pac_url.is_some() {
+                ProxyMode::Pac
+            } else {
+                ProxyMode::Wpad
+            };
+        }
+        _ => {}
+    }
+    Some(source)
+}
+
+/// 提取 GVariant 文本里的字符串，例如 `'auto'` 或 `['localhost', '::1']`。
+#[cfg(any(target_os = "linux", test))]
+fn gvariant_strings(value: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut chars = value.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\'' && ch != '"' {
+            continue;
+        }
+        let mut current = String::new();
+        while let Some(next) = chars.next() {
+            match next {
+                '\\' => current.extend(chars.next()),
+                quote if quote == ch => break,
+                other => current.push(other),
+            }
+        }
+        strings.push(current);
+    }
+    strings
+}
+
+#[cfg(target_os = "linux")]
+fn kde_proxy_source() -> Option<ProxyConfigSource> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    let content = std::fs::read_to_string(config_dir.join("kioslaverc")).ok()?;
+    parse_kioslaverc(&content)
+}
+
+/// 解析 KDE 的 kioslaverc。ProxyType：0 不使用，1 手动，2 PAC，3 WPAD，4 环境变量。
+#[cfg(any(target_os = "linux", test))]
+fn parse_kioslaverc(content: &str) -> Option<ProxyConfigSource> {
+    let mut in_section = false;
+    let mut values = std::collections::HashMap::new();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') && line.ends_with(']') {
+            in_section = line == "[Proxy Settings]";
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            // 去掉 `httpProxy[$e]` 这类 KConfig 修饰
+            let key = key.split('[').next().unwrap_or(key).trim();
+            values.insert(key.to_string(), value.trim().to_string());
+        }
+    }
+    let proxy_type = values.get("ProxyType")?.parse::<u8>().ok()?;
+
+    let mut source = ProxyConfigSource {
+        source: "kde".into(),
+        mode: ProxyMode::None,
+        pac_url: None,
+        endpoints: Vec::new(),
+        no_proxy: values
+            .get("NoProxyFor")
+            .map(|value| split_list(value, ','))
+            .unwrap_or_default(),
+    };
+    match proxy_type {
+        1 => {
+            source.mode = ProxyMode::Manual;
+            for (key, protocol) in [
+                ("httpProxy", ProxyProtocol::Http),
+                ("httpsProxy", ProxyProtocol::Https),
+                ("socksProxy", ProxyProtocol::Socks5),
+            ] {
+                // 新版写作 http://host:port，旧版写作 "http://host port"
+                let Some(value) = values.get(key).map(|value| value.replacen(' ', ":", 1)) else {
+                    continue;
+                };
+                if let Some((host, port)) = parse_proxy_value(&value, protocol) {
+                    push_endpoint(
+                        &mut source.endpoints,
+                        protocol,
+                        host,
+                        port,
+                        "kde:kioslaverc",
+                    );
+                }
+            }
+        }
+        2 => {
+            source.mode = ProxyMode::Pac;
+            source.pac_url = values
+                .get("Proxy Config Script")
+                .filter(|url| !url.is_empty())
+                .cloned();
+        }
+        3 => source.mode = ProxyMode::Wpad,
+        // 4 表示沿用环境变量，已由 env 来源覆盖
+        4 => return None,
+        _ => {}
+    }
+    Some(source)
+}
+
+/// 解析 `scutil --proxy` 的输出，包括 PAC、自动发现和例外列表。
+#[cfg(any(target_os = "macos", test))]
+fn parse_scutil_proxy(output: &str) -> ProxyConfigSource {
+    let mut values = std::collections::HashMap::new();
+    let mut exceptions = Vec::new();
+    let mut in_exceptions = false;
+    for line in output.lines().map(str::trim) {
+        if in_exceptions {
+            if line == "}" {
+                in_exceptions = false;
+            } else if let Some((_, value)) = line.split_once(" : ") {
+                exceptions.push(value.trim().to_string());
+            }
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(" : ") {
+            if key == "ExceptionsList" {
+                in_exceptions = true;
+                continue;
+            }
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    let enabled = |key: &str| values.get(key).is_some_and(|value| value == "1");
+
+    let mut endpoints = Vec::new();
+    for (prefix, protocol) in [
+        ("HTTP", ProxyProtocol::Http),
+        ("HTTPS", ProxyProtocol::Https),
+        ("SOCKS", ProxyProtocol::Socks5),
+    ] {
+        if !enabled(&format!("{prefix}Enable")) {
+            continue;
+        }
+        let host = values.get(&format!("{prefix}Proxy"));
+        let port = values
+            .get(&format!("{prefix}Port"))
+            .and_then(|port| port.parse::<u16>().ok());
+        if let (Some(host), Some(port)) = (host, port) {
+            push_endpoint(&mut endpoints, protocol, host.clone(), port, "mac:scutil");
+        }
+    }
+
+    let pac_url = values
+        .get("ProxyAutoConfigURLString")
+        .filter(|_| enabled("ProxyAutoConfigEnable"))
+        .cloned();
+    let mode = if pac_url.is_some() {
+        ProxyMode::Pac
+    } else if enabled("ProxyAutoDiscoveryEnable") {
+        ProxyMode::Wpad
+    } else if !endpoints.is_empty() {
+        ProxyMode::Manual
+    } else {
+        ProxyMode::None
+    };
+    ProxyConfigSource {
+        source: "macos".into(),
+        mode,
+        pac_url,
+        endpoints,
+        no_proxy: exceptions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    const CORPORATE_PAC: &str = include_str!("pac/fixtures/corporate.pac");
+
+    fn endpoints(source: &ProxyConfigSource) -> Vec<String> {
+        source
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                format!(
+                    "{:?} {}:{}",
+                    endpoint.protocol, endpoint.host, endpoint.port
+                )
+            })
+            .collect()
+    }
+
+    fn route_summary(result: &ProxyLookupResult) -> Vec<String> {
+        result
+            .routes
+            .iter()
+            .map(|route| match route {
+                ProxyRoute::Direct => "DIRECT".to_string(),
+                ProxyRoute::Proxy(endpoint) => format!("{}:{}", endpoint.host, endpoint.port),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_gnome_proxy_settings() {
+        let manual = HashMap::from([
+            ("org.gnome.system.proxy/mode", "'manual'"),
+            (
+                "org.gnome.system.proxy/ignore-hosts",
+                "['localhost', '127.0.0.0/8', '*.corp.example.com']",
+            ),
+            ("org.gnome.system.proxy.http/host", "'proxy.example.com'"),
+            ("org.gnome.system.proxy.http/port", "3128"),
+            ("org.gnome.system.proxy.https/host", "''"),
+            ("org.gnome.system.proxy.https/port", "0"),
+            ("org.gnome.system.proxy.socks/host", "'127.0.0.1'"),
+            ("org.gnome.system.proxy.socks/port", "1080"),
+        ]);
+        let source = gnome_proxy_from(|schema, key| {
+            manual
+                .get(format!("{schema}/{key}").as_str())
+                .map(|v| v.to_string())
+        })
+        .unwrap();
+        assert_eq!(source.mode, ProxyMode::Manual);
+        assert_eq!(
+            endpoints(&source),
+            vec!["Http proxy.example.com:3128", "Socks5 127.0.0.1:1080"]
+        );
+        assert_eq!(source.no_proxy.len(), 3);
+
+        let auto = HashMap::from([
+            ("org.gnome.system.proxy/mode", "'auto'"),
+            (
+                "org.gnome.system.proxy/autoconfig-url",
+                "'http://pac.example.com/proxy.pac'",
+            ),
+        ]);
+        let source = gnome_proxy_from(|schema, key| {
+            auto.get(format!("{schema}/{key}").as_str())
+                .map(|v| v.to_string())
+        })
+        .unwrap();
+        assert_eq!(source.mode, ProxyMode::Pac);
+        assert_eq!(
+            source.pac_url.as_deref(),
+            Some("http://pac.example.com/proxy.pac")
+        );
+        assert!(gnome_proxy_from(|_, _| None).is_none());
+    }
+
+    #[test]
+    fn reads_kde_and_macos_proxy_settings() {
+        let kde = "[Proxy Settings]\nNoProxyFor=localhost,.lan\nProxyType=1\n\
+                   httpProxy=http://proxy.example.com 8080\nhttpsProxy[$e]=http://secure.example.com:8443\n\
+                   socksProxy=\n\n[Other]\nProxyType=2\n";
+        let source = parse_kioslaverc(kde).unwrap();
+        assert_eq!(source.mode, ProxyMode::Manual);
+        assert_eq!(
+            endpoints(&source),
+            vec![
+                "Http proxy.example.com:8080",
+                "Https secure.example.com:8443"
+            ]
+        );
+        assert_eq!(source.no_proxy, vec!["localhost", ".lan"]);
+        let pac = parse_kioslaverc(
+            "[Proxy Settings]\nProxyType=2\nProxy Config Script=file:///etc/proxy.pac\n",
+        )
+        .unwrap();
+        assert_eq!(pac.pac_url.as_deref(), Some("file:///etc/proxy.pac"));
+        assert!(parse_kioslaverc("[Proxy Settings]\nProxyType=4\n").is_none());
+
+        let scutil = "<dictionary> {\n  ExceptionsList : <array> {\n    0 : *.local\n    1 : 169.254/16\n  }\n  \
+                      HTTPEnable : 1\n  HTTPPort : 8080\n  HTTPProxy : proxy.example.com\n  \
+                      ProxyAutoConfigEnable : 1\n  ProxyAutoConfigURLString : http://pac.example.com/proxy.pac\n}\n";
+        let source = parse_scutil_proxy(scutil);
+        assert_eq!(source.mode, ProxyMode::Pac);
+        assert_eq!(source.no_proxy, vec!["*.local", "169.254/16"]);
+        assert_eq!(endpoints(&source), vec!["Http proxy.example.com:8080"]);
+    }
+
+    #[test]
+    fn matches_no_proxy_rules() {
+        let rules = [
+            ".corp.example.com",
+            "*.internal",
+            "localhost",
+            "10.0.0.0/8",
+            "169.254/16",
+            "fd00::/8",
+            "api.example.org:8443",
+            "<local>",
+            "build-*.example.net",
+        ]
+        .map(String::from);
+        let matched = |host: &str, port: u16| no_proxy_match(&rules, host, Some(port));
+        assert_eq!(
+            matched("git.corp.example.com", 443),
+            Some(".corp.example.com")
+        );
+        assert_eq!(matched("corp.example.com", 443), Some(".corp.example.com"));
+        assert_eq!(matched("db.internal", 5432), Some("*.internal"));
+        assert_eq!(matched("10.1.2.3", 80), Some("10.0.0.0/8"));
+        assert_eq!(matched("169.254.10.1", 80), Some("169.254/16"));
+        assert_eq!(matched("fd12::1", 80), Some("fd00::/8"));
+        assert_eq!(
+            matched("api.example.org", 8443),
+            Some("api.example.org:8443")
+        );
+        assert_eq!(matched("api.example.org", 443), None);
+        assert_eq!(matched("printer", 631), Some("<local>"));
+        assert_eq!(
+            matched("build-42.example.net", 443),
+            Some("build-*.example.net")
+        );
+        assert_eq!(matched("www.example.com", 443), None);
+        assert_eq!(matched("11.0.0.1", 80), None);
+        assert!(no_proxy_match(&["*".to_string()], "anything", None).is_some());
+    }
+
+    #[tokio::test]
+    async fn resolves_proxy_through_configured_sources() {
+        let dir = std::env::temp_dir().join(format!("chef_proxy_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pac_path = dir.join("corporate.pac");
+        std::fs::write(&pac_path, CORPORATE_PAC).unwrap();
+        let pac_url = Url::from_file_path(&pac_path).unwrap().to_string();
+
+        let manual = ProxyConfigSource {
+            source: "env".into(),
+            mode: ProxyMode::Manual,
+            pac_url: None,
+            endpoints: vec![ProxyEndpoint {
+                protocol: ProxyProtocol::Http,
+                host: "env-proxy".into(),
+                port: 3128,
+                source: "env:HTTP_PROXY".into(),
+            }],
+            no_proxy: vec!["localhost".into(), ".lan".into()],
+        };
+        let missing_pac = ProxyConfigSource {
+            source: "kde".into(),
+            mode: ProxyMode::Pac,
+            pac_url: Some(dir.join("missing.pac").to_string_lossy().into_owned()),
+            endpoints: Vec::new(),
+            no_proxy: Vec::new(),
+        };
+        let pac = ProxyConfigSource {
+            source: "gnome".into(),
+            mode: ProxyMode::Pac,
+            pac_url: Some(pac_url.clone()),
+            endpoints: Vec::new(),
+            no_proxy: Vec::new(),
+        };
+
+        let url = parse_target_url("http://nas.lan/share").unwrap();
+        let result = lookup_with_sources(&url, vec![manual.clone()]).await;
+        assert_eq!(result.bypass_rule.as_deref(), Some(".lan"));
+        assert_eq!(route_summary(&result), vec!["DIRECT"]);
+
+        // https 没有对应代理时交给下一个来源；缺失的 PAC 记录原因后继续
+        let url = parse_target_url("https://git.corp.example.com/repo").unwrap();
+        let result = lookup_with_sources(&url, vec![manual, missing_pac, pac.clone()]).await;
+        assert_eq!(result.source, "gnome");
+        assert_eq!(result.pac_url.as_deref(), Some(pac_url.as_str()));
+        assert_eq!(
+            result.pac_result.as_deref(),
+            Some("PROXY corp-proxy.example.com:3128")
+        );
+        assert_eq!(route_summary(&result), vec!["corp-proxy.example.com:3128"]);
+        assert_eq!(result.notes.len(), 2, "{:?}", result.notes);
+
+        let url = parse_target_url("example.org").unwrap();
+        let result = lookup_with_sources(&url, vec![pac]).await;
+        assert_eq!(
+            route_summary(&result),
+            vec!["proxy.example.com:8080", "127.0.0.1:1080", "DIRECT"]
+        );
+
+        let result = lookup_with_sources(&url, Vec::new()).await;
+        assert_eq!(result.source, "default");
+        assert_eq!(route_summary(&result), vec!["DIRECT"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    io,
+    mem::{self, MaybeUninit},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    os::fd::{AsRawFd, RawFd},
+    ptr,
+    time::Instant,
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use super::{
+    packet::echo_request,
+    raw::{poll_until, pollfd, reached, set_hop_limit, timeout_reply},
+    ProbeReply, ProbeTarget, Prober, ReplyKind, TraceProtocol,
+};
+
+/// 不需要特权的探测：每个探测包使用独立套接字，开启 IP_RECVERR 后
+/// 从错误队列读取内核转交的 ICMP 差错。
+pub(super) struct ErrQueueProber {
+    target: ProbeTarget,
+}
+
+/// 错误队列里的一条记录（struct sock_extended_err 及其后的来源地址）。
+struct QueuedError {
+    origin: u8,
+    icmp_type: u8,
+    code: u8,
+    offender: Option<IpAddr>,
+}
+
+impl ErrQueueProber {
+    pub fn new(target: ProbeTarget) -> Result<Self, String> {
+        // 先确认当前用户可以创建所需的套接字，ICMP 依赖 ping_group_range
+        open_socket(target).map_err(|err| match err.raw_os_error() {
+            Some(libc::EACCES) | Some(libc::EPERM) if target.protocol == TraceProtocol::Icmp => {
+                "当前用户不允许创建 ICMP 套接字，请调整 net.ipv4.ping_group_range 或改用 UDP/TCP。"
+                    .to_string()
+            }
+            _ => format!("创建探测套接字失败: {err}"),
+        })?;
+        Ok(Self { target })
+    }
+}
+
+impl Prober for ErrQueueProber {
+    fn probe(&mut self, ttl: u8, seq: u16) -> Result<ProbeReply, String> {
+        let target = self.target;
+        let socket = open_socket(target).map_err(|err| format!("创建探测套接字失败: {err}"))?;
+        set_hop_limit(&socket, target.address, ttl).map_err(|err| err.to_string())?;
+        let started = Instant::now();
+        send_probe(&socket, target, seq).map_err(|err| format!("发送探测包失败: {err}"))?;
+        let deadline = started + target.timeout;
+        let fd = socket.as_raw_fd();
+
+        loop {
+            let events = match target.protocol {
+                TraceProtocol::Tcp => libc::POLLOUT,
+                _ => libc::POLLIN,
+            };
+            let mut fds = [pollfd(fd, events)];
+            if !poll_until(&mut fds, deadline).map_err(|err| err.to_string())? {
+                return Ok(timeout_reply());
+            }
+
+            if let Some(error) = read_error_queue(fd).map_err(|err| err.to_string())? {
+                if let Some(reply) = classify(&error, target, started) {
+                    return Ok(reply);
+                }
+                continue;
+            }
+            match target.protocol {
+                TraceProtocol::Tcp => match socket.take_error().map_err(|err| err.to_string())? {
+                    None => return Ok(reached(target.address, started)),
+                    Some(err) if err.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                        return Ok(reached(target.address, started));
+                    }
+                    Some(err) => return Err(format!("连接失败: {err}")),
+                },
+                TraceProtocol::Icmp => {
+                    let mut buffer = [MaybeUninit::<u8>::uninit(); 512];
+                    match socket.recv(&mut buffer) {
+                        // ping 套接字收到的是不带 IP 头的回显应答，内核会改写标识
+                        Ok(len) if len >= 8 => {
+                            let data = unsafe {
+                                std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len)
+                            };
+                            let reply_seq = u16::from_be_bytes([data[6], data[7]]);
+                            if matches!(data[0], 0 | 129) && reply_seq == seq {
+                                return Ok(reached(target.address, started));
+                            }
+                        }
+                        Ok(_) => {}
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                        Err(err) => return Err(format!("接收回显应答失败: {err}")),
+                    }
+                }
+                TraceProtocol::Udp => {
+                    // 目标上恰好有服务监听并回了数据，同样算作到达
+                    let mut buffer = [MaybeUninit::<u8>::uninit(); 512];
+                    match socket.recv(&mut buffer) {
+                        Ok(_) => return Ok(reached(target.address, started)),
+                        Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                        Err(err) if err.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                            return Ok(reached(target.address, started));
+                        }
+                        Err(err) => return Err(format!("接收 UDP 回应失败: {err}")),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn open_socket(target: ProbeTarget) -> io::Result<Socket> {
+    let domain = Domain::for_address(SocketAddr::new(target.address, 0));
+    let (kind, protocol) = match (target.protocol, target.address) {
+        (TraceProtocol::Udp, _) => (Type::DGRAM, Protocol::UDP),
+        (TraceProtocol::Tcp, _) => (Type::STREAM, Protocol::TCP),
+        (TraceProtocol::Icmp, IpAddr::V4(_)) => (Type::DGRAM, Protocol::ICMPV4),
+        (TraceProtocol::Icmp, IpAddr::V6(_)) => (Type::DGRAM, Protocol::ICMPV6),
+    };
+    let socket = Socket::new(domain, kind, Some(protocol))?;
+    socket.set_nonblocking(true)?;
+    let (level, name) = match target.address {
+        IpAddr::V4(_) => (libc::SOL_IP, libc::IP_RECVERR),
+        IpAddr::V6(_) => (libc::SOL_IPV6, libc::IPV6_RECVERR),
+    };
+    let enable: libc::c_int = 1;
+    let status = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            ptr::addr_of!(enable).cast(),
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if status != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(socket)
+}
+
+fn send_probe(socket: &Socket, target: ProbeTarget, seq: u16) -> io::Result<()> {
+    match target.protocol {
+        TraceProtocol::Udp => {
+            let port = target.port.wrapping_add(seq % 512).max(1);
+            socket.connect(&SocketAddr::new(target.address, port).into())?;
+            socket.send(&[0; 32])?;
+        }
+        TraceProtocol::Tcp => {
+            match socket.connect(&SocketAddr::new(target.address, target.port).into()) {
+                Ok(()) => {}
+                Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        TraceProtocol::Icmp => {
+            socket.connect(&SocketAddr::new(target.address, 0).into())?;
+            socket.send(&echo_request(0, seq, target.address.is_ipv6()))?;
+        }
+    }
+    Ok(())
+}
+
+fn classify(error: &QueuedError, target: ProbeTarget, started: Instant) -> Option<ProbeReply> {
+    let v6 = match error.origin {
+        libc::SO_EE_ORIGIN_ICMP => false,
+        libc::SO_EE_ORIGIN_ICMP6 => true,
+        // 本地产生的错误（如无路由）没有对应的跳
+        _ => {
+            return Some(ProbeReply {
+                kind: ReplyKind::Unreachable,
+                responder: None,
+                rtt: Some(started.elapsed()),
+            });
+        }
+    };
+    let kind = match (v6, error.icmp_type, error.code) {
+        (false, 11, _) | (true, 3, _) => ReplyKind::TimeExceeded,
+        (false, 3, 3) | (true, 1, 4) => ReplyKind::Reached,
+        (false, 3, _) | (true, 1, _) if error.offender == Some(target.address) => {
+            ReplyKind::Reached
+        }
+        (false, 3, _) | (true, 1, _) => ReplyKind::Unreachable,
+        _ => return None,
+    };
+    Some(ProbeReply {
+        kind,
+        responder: error.offender,
+        rtt: Some(started.elapsed()),
+    })
+}
+
+/// 用 MSG_ERRQUEUE 读取一条错误记录，队列为空时返回 None。
+fn read_error_queue(fd: RawFd) -> io::Result<Option<QueuedError>> {
+    let mut data = [0u8; 512];
+    let mut control = [0u64; 64];
+    let mut name = MaybeUninit::<libc::sockaddr_storage>::zeroed();
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr().cast(),
+        iov_len: data.len(),
+    };
+    let mut message: libc::msghdr = unsafe { mem::zeroed() };
+    message.msg_name = name.as_mut_ptr().cast();
+    message.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr().cast();
+    message.msg_controllen = mem::size_of_val(&control) as _;
+
+    let received = unsafe { libc::recvmsg(fd, &mut message, libc::MSG_ERRQUEUE) };
+    if received < 0 {
+        let err = io::Error::last_os_error();
+        return match err.kind() {
+            io::ErrorKind::WouldBlock => Ok(None),
+            _ => Err(err),
+        };
+    }
+
+    // 控制消息由内核按 cmsghdr 格式写入，只在 msg_controllen 范围内遍历
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+        while !header.is_null() {
+            let cmsg = &*header;
+            let is_recverr = (cmsg.cmsg_level == libc::SOL_IP
+                && cmsg.cmsg_type == libc::IP_RECVERR)
+                || (cmsg.cmsg_level == libc::SOL_IPV6 && cmsg.cmsg_type == libc::IPV6_RECVERR);
+            if is_recverr {
+                let payload = libc::CMSG_DATA(header);
+                let extended = ptr::read_unaligned(payload.cast::<libc::sock_extended_err>());
+                let offender = payload.add(mem::size_of::<libc::sock_extended_err>());
+                return Ok(Some(QueuedError {
+                    origin: extended.ee_origin,
+                    icmp_type: extended.ee_type,
+                    code: extended.ee_code,
+                    offender: read_offender(offender),
+                }));
+            }
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+    Ok(None)
+}
+
+/// 读取 SO_EE_OFFENDER 指向的地址，即发出 ICMP 差错的路由器。
+unsafe fn read_offender(address: *const u8) -> Option<IpAddr> {
+    let family = ptr::read_unaligned(address.cast::<libc::sa_family_t>());
+    match i32::from(family) {
+        libc::AF_INET => {
+            let address = ptr::read_unaligned(address.cast::<libc::sockaddr_in>());
+            Some(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr)).into())
+        }
+        libc::AF_INET6 => {
+            let address = ptr::read_unaligned(address.cast::<libc::sockaddr_in6>());
+            Some(Ipv6Addr::from(address.sin6_addr.s6_addr).into())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,815 @@
+#[cfg(target_os = "linux")]
+mod errqueue;
+#[cfg(any(unix, test))]
+mod packet;
+#[cfg(unix)]
+mod raw;
+
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque},
+    net::IpAddr,
+    sync::{Arc, Mutex as StdMutex},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{async_runtime, AppHandle, Emitter};
+use tokio::{net::lookup_host, sync::mpsc};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::dns::reverse_lookup;
+
+pub const TRACE_EVENT: &str = "network-trace";
+
+const DEFAULT_MAX_HOPS: u8 = 30;
+const DEFAULT_PROBES_PER_HOP: u8 = 3;
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_INTERVAL_MS: u64 = 1000;
+const DEFAULT_UDP_PORT: u16 = 33434;
+const DEFAULT_TCP_PORT: u16 = 80;
+const RECENT_SAMPLES: usize = 10;
+
+/// 正在运行的路由追踪，停止时通过取消令牌通知探测线程。
+#[derive(Default)]
+pub struct TraceManager {
+    inner: Arc<StdMutex<HashMap<String, CancellationToken>>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceProtocol {
+    #[default]
+    Udp,
+    Tcp,
+    Icmp,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceMode {
+    #[default]
+    Traceroute,
+    /// 持续逐跳探测并累计统计，类似 mtr。
+    Mtr,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceRequest {
+    pub target: String,
+    #[serde(default)]
+    pub protocol: TraceProtocol,
+    /// TCP 的目标端口，或 UDP 的起始端口。
+    pub port: Option<u16>,
+    pub max_hops: Option<u8>,
+    pub probes_per_hop: Option<u8>,
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub mode: TraceMode,
+    pub interval_ms: Option<u64>,
+    /// MTR 模式的轮数，不填则一直运行到手动停止。
+    pub cycles: Option<u32>,
+    pub resolve_hostnames: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(super) enum ReplyKind {
+    TimeExceeded,
+    /// 目标主机回应：端口不可达、回显应答、SYN-ACK 或 RST。
+    Reached,
+    /// 中途路由器回报不可达，探测到此为止。
+    Unreachable,
+    Timeout,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ProbeReply {
+    kind: ReplyKind,
+    responder: Option<IpAddr>,
+    rtt: Option<Duration>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ProbeTarget {
+    protocol: TraceProtocol,
+    address: IpAddr,
+    port: u16,
+    timeout: Duration,
+}
+
+trait Prober: Send {
+    fn probe(&mut self, ttl: u8, seq: u16) -> Result<ProbeReply, String>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProbeMethod {
+    Auto,
+    #[cfg(test)]
+    Raw,
+    #[cfg(all(test, target_os = "linux"))]
+    Unprivileged,
+}
+
+struct TraceConfig {
+    destination: String,
+    target: ProbeTarget,
+    max_hops: u8,
+    probes_per_hop: u8,
+    mode: TraceMode,
+    interval: Duration,
+    cycles: Option<u32>,
+    resolve_hostnames: bool,
+}
+
+enum EngineMessage {
+    Started(&'static str),
+    Probe(u8, ProbeReply),
+    HopDone(u8),
+    RoundDone(u32),
+    Failed(String),
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HopReport {
+    ttl: u8,
+    address: Option<String>,
+    hostname: Option<String>,
+    /// 同一跳上出现的其他地址（负载均衡路径）。
+    alternates: Vec<String>,
+    status: ReplyKind,
+    sent: u32,
+    received: u32,
+    loss_percent: f64,
+    last_ms: Option<f64>,
+    avg_ms: Option<f64>,
+    best_ms: Option<f64>,
+    worst_ms: Option<f64>,
+    stdev_ms: Option<f64>,
+    samples: Vec<Option<f64>>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TraceEvent {
+    #[serde(rename_all = "camelCase")]
+    Started {
+        trace_id: String,
+        destination: String,
+        address: String,
+        protocol: TraceProtocol,
+        method: &'static str,
+    },
+    #[serde(rename_all = "camelCase")]
+    Hop { trace_id: String, hop: HopReport },
+    #[serde(rename_all = "camelCase")]
+    Round { trace_id: String, round: u32 },
+    #[serde(rename_all = "camelCase")]
+    Finished {
+        trace_id: String,
+        reached: bool,
+        hops: Vec<HopReport>,
+        error: Option<String>,
+    },
+}
+
+struct HopStats {
+    ttl: u8,
+    responders: Vec<IpAddr>,
+    status: ReplyKind,
+    sent: u32,
+    received: u32,
+    last: Option<f64>,
+    best: Option<f64>,
+    worst: Option<f64>,
+    sum: f64,
+    sum_squares: f64,
+    samples: VecDeque<Option<f64>>,
+}
+
+/// 开始路由追踪，结果通过 `network-trace` 事件逐跳推送，返回追踪 ID。
+#[tauri::command]
+pub async fn start_network_trace(
+    app: AppHandle,
+    state: tauri::State<'_, TraceManager>,
+    request: TraceRequest,
+) -> Result<String, String> {
+    let config = prepare_trace(request).await?;
+    let trace_id = Uuid::new_v4().to_string();
+    let cancel = CancellationToken::new();
+    state
+        .inner
+        .lock()
+        .unwrap()
+        .insert(trace_id.clone(), cancel.clone());
+
+    let traces = Arc::clone(&state.inner);
+    let id = trace_id.clone();
+    async_runtime::spawn(async move {
+        run_trace(&id, config, ProbeMethod::Auto, cancel, |event| {
+            if let Err(err) = app.emit(TRACE_EVENT, event) {
+                eprintln!("推送路由追踪事件失败: {err}");
+            }
+        })
+        .await;
+        traces.lock().unwrap().remove(&id);
+    });
+    Ok(trace_id)
+}
+
+#[tauri::command]
+pub async fn stop_network_trace(
+    state: tauri::State<'_, TraceManager>,
+    trace_id: String,
+) -> Result<(), String> {
+    match state.inner.lock().unwrap().remove(&trace_id) {
+        Some(cancel) => {
+            cancel.cancel();
+            Ok(())
+        }
+        None => Err("路由追踪不存在或已结束。".into()),
+    }
+}
+
+async fn prepare_trace(request: TraceRequest) -> Result<TraceConfig, String> {
+    let destination = request.target.trim().to_string();
+    if destination.is_empty() {
+        return Err("请输入要追踪的目标地址。".into());
+    }
+    let host = destination.trim_start_matches('[').trim_end_matches(']');
+    let address = match host.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => {
+            let addrs = lookup_host((host, 0))
+                .await
+                .map_err(|err| format!("无法解析 {host}: {err}"))?
+                .map(|addr| addr.ip())
+                .collect::<Vec<_>>();
+            addrs
+                .iter()
+                .find(|ip| ip.is_ipv4())
+                .or_else(|| addrs.first())
+                .copied()
+                .ok_or_else(|| format!("{host} 没有可用地址"))?
+        }
+    };
+
+    let port = request.port.unwrap_or(match request.protocol {
+        TraceProtocol::Tcp => DEFAULT_TCP_PORT,
+        _ => DEFAULT_UDP_PORT,
+    });
+    if port == 0 && request.protocol != TraceProtocol::Icmp {
+        return Err("端口必须在 1-65535 之间。".into());
+    }
+    Ok(TraceConfig {
+        destination,
+        target: ProbeTarget {
+            protocol: request.protocol,
+            address,
+            port,
+            timeout: Duration::from_millis(request.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS)),
+        },
+        max_hops: request.max_hops.unwrap_or(DEFAULT_MAX_HOPS).clamp(1, 64),
+        probes_per_hop: request
+            .probes_per_hop
+            .unwrap_or(DEFAULT_PROBES_PER_HOP)
+            .clamp(1, 10),
+        mode: request.mode,
+        interval: Duration::from_millis(request.interval_ms.unwrap_or(DEFAULT_INTERVAL_MS)),
+        cycles: request.cycles.filter(|cycles| *cycles > 0),
+        resolve_hostnames: request.resolve_hostnames.unwrap_or(true),
+    })
+}
+
+async fn run_trace(
+    trace_id: &str,
+    config: TraceConfig,
+    method: ProbeMethod,
+    cancel: CancellationToken,
+    mut emit: impl FnMut(TraceEvent),
+) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let engine_config = TraceEngineConfig::from(&config);
+    let engine_cancel = cancel.clone();
+    let engine = async_runtime::spawn_blocking(move || {
+        drive_probes(&engine_config, method, &engine_cancel, &tx);
+    });
+
+    let mut hops: BTreeMap<u8, HopStats> = BTreeMap::new();
+    let mut hostnames: HashMap<IpAddr, Option<String>> = HashMap::new();
+    // 反向解析在后台并发进行，不阻塞逐跳推送；解析到名称后再补发一次对应的跳。
+    let (names_tx, mut names_rx) = mpsc::unbounded_channel::<(IpAddr, Option<String>)>();
+    let mut error = None;
+    let mut round_ttls = Vec::new();
+    loop {
+        let message = tokio::select! {
+            message = rx.recv() => match message {
+                Some(message) => message,
+                None => break,
+            },
+            Some((ip, name)) = names_rx.recv() => {
+                let resolved = name.is_some();
+                hostnames.insert(ip, name);
+                if resolved {
+                    for stats in hops.values().filter(|stats| stats.responders.contains(&ip)) {
+                        emit(TraceEvent::Hop {
+                            trace_id: trace_id.to_string(),
+                            hop: stats.report(&hostnames),
+                        });
+                    }
+                }
+                continue;
+            }
+        };
+        match message {
+            EngineMessage::Started(method) => emit(TraceEvent::Started {
+                trace_id: trace_id.to_string(),
+                destination: config.destination.clone(),
+                address: config.target.address.to_string(),
+                protocol: config.target.protocol,
+                method,
+            }),
+            EngineMessage::Probe(ttl, reply) => {
+                if config.resolve_hostnames {
+                    if let Some(ip) = reply.responder {
+                        if let Entry::Vacant(entry) = hostnames.entry(ip) {
+                            entry.insert(None);
+                            let names_tx = names_tx.clone();
+                            async_runtime::spawn(async move {
+                                let _ = names_tx.send((ip, reverse_lookup(ip).await));
+                            });
+                        }
+                    }
+                }
+                hops.entry(ttl)
+                    .or_insert_with(|| HopStats::new(ttl))
+                    .record(&reply);
+                if !round_ttls.contains(&ttl) {
+                    round_ttls.push(ttl);
+                }
+            }
+            EngineMessage::HopDone(ttl) => {
+                if let Some(stats) = hops.get(&ttl) {
+                    emit(TraceEvent::Hop {
+                        trace_id: trace_id.to_string(),
+                        hop: stats.report(&hostnames),
+                    });
+                }
+                round_ttls.clear();
+            }
+            EngineMessage::RoundDone(round) => {
+                for ttl in round_ttls.drain(..) {
+                    if let Some(stats) = hops.get(&ttl) {
+                        emit(TraceEvent::Hop {
+                            trace_id: trace_id.to_string(),
+                            hop: stats.report(&hostnames),
+                        });
+                    }
+                }
+                emit(TraceEvent::Round {
+                    trace_id: trace_id.to_string(),
+                    round,
+                });
+            }
+            EngineMessage::Failed(err) => error = Some(err),
+        }
+    }
+    let _ = engine.await;
+    drop(names_tx);
+    while let Some((ip, name)) = names_rx.recv().await {
+        hostnames.insert(ip, name);
+    }
+
+    let hops = hops
+        .values()
+        .map(|stats| stats.report(&hostnames))
+        .collect::<Vec<_>>();
+    emit(TraceEvent::Finished {
+        trace_id: trace_id.to_string(),
+        reached: hops.iter().any(|hop| hop.status == ReplyKind::Reached),
+        hops,
+        error,
+    });
+}
+
+/// 探测线程只需要的配置，避免把目标名称等字段带进阻塞线程。
+#[derive(Clone, Copy)]
+struct TraceEngineConfig {
+    target: ProbeTarget,
+    max_hops: u8,
+    probes_per_hop: u8,
+    mode: TraceMode,
+    interval: Duration,
+    cycles: Option<u32>,
+}
+
+impl From<&TraceConfig> for TraceEngineConfig {
+    fn from(config: &TraceConfig) -> Self {
+        Self {
+            target: config.target,
+            max_hops: config.max_hops,
+            probes_per_hop: config.probes_per_hop,
+            mode: config.mode,
+            interval: config.interval,
+            cycles: config.cycles,
+        }
+    }
+}
+
+fn drive_probes(
+    config: &TraceEngineConfig,
+    method: ProbeMethod,
+    cancel: &CancellationToken,
+    tx: &mpsc::UnboundedSender<EngineMessage>,
+) {
+    let (mut prober, name) = match open_prober(config.target, method) {
+        Ok(opened) => opened,
+        Err(err) => {
+            let _ = tx.send(EngineMessage::Failed(err));
+            return;
+        }
+    };
+    let _ = tx.send(EngineMessage::Started(name));
+
+    let mut seq = 0u16;
+    let mut probe = |ttl: u8| -> Option<ProbeReply> {
+        seq = seq.wrapping_add(1);
+        match prober.probe(ttl, seq) {
+            Ok(reply) => {
+                let _ = tx.send(EngineMessage::Probe(ttl, reply));
+                Some(reply)
+            }
+            Err(err) => {
+                let _ = tx.send(EngineMessage::Failed(err));
+                None
+            }
+        }
+    };
+    let is_terminal =
+        |reply: &ProbeReply| matches!(reply.kind, ReplyKind::Reached | ReplyKind::Unreachable);
+
+    match config.mode {
+        TraceMode::Traceroute => {
+            for ttl in 1..=config.max_hops {
+                let mut terminal = false;
+                for _ in 0..config.probes_per_hop {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    let Some(reply) = probe(ttl) else {
+                        return;
+                    };
+                    terminal |= is_terminal(&reply);
+                }
+                let _ = tx.send(EngineMessage::HopDone(ttl));
+                if terminal {
+                    return;
+                }
+            }
+        }
+        TraceMode::Mtr => {
+            let mut last_ttl = config.max_hops;
+            let mut round = 0;
+            loop {
+                for ttl in 1..=last_ttl {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    let Some(reply) = probe(ttl) else {
+                        return;
+                    };
+                    if is_terminal(&reply) {
+                        last_ttl = ttl;
+                        break;
+                    }
+                }
+                round += 1;
+                let _ = tx.send(EngineMessage::RoundDone(round));
+                if config.cycles.is_some_and(|cycles| round >= cycles) {
+                    return;
+                }
+                let wake = std::time::Instant::now() + config.interval;
+                while std::time::Instant::now() < wake {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(50).min(config.interval));
+                }
+            }
+        }
+    }
+}
+
+/// 优先使用原始套接字；Linux 上没有权限时退回基于 IP_RECVERR 的非特权探测。
+fn open_prober(
+    target: ProbeTarget,
+    method: ProbeMethod,
+) -> Result<(Box<dyn Prober>, &'static str), String> {
+    #[cfg(target_os = "linux")]
+    {
+        #[cfg(test)]
+        if method == ProbeMethod::Unprivileged {
+            return errqueue::ErrQueueProber::new(target)
+                .map(|prober| (Box::new(prober) as Box<dyn Prober>, "unprivileged"));
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        match raw::RawProber::new(target) {
+            Ok(prober) => return Ok((Box::new(prober), "raw")),
+            Err(err)
+                if method == ProbeMethod::Auto
+                    && err.kind() == std::io::ErrorKind::PermissionDenied => {}
+            Err(err) => return Err(format!("无法创建原始套接字: {err}")),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        errqueue::ErrQueueProber::new(target)
+            .map(|prober| (Box::new(prober) as Box<dyn Prober>, "unprivileged"))
+    }
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        let _ = method;
+        Err("路由追踪需要管理员权限。".into())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (target, method);
+        Err("当前系统暂不支持路由追踪。".into())
+    }
+}
+
+impl HopStats {
+    fn new(ttl: u8) -> Self {
+        Self {
+            ttl,
+            responders: Vec::new(),
+            status: ReplyKind::Timeout,
+            sent: 0,
+            received: 0,
+            last: None,
+            best: None,
+            worst: None,
+            sum: 0.0,
+            sum_squares: 0.0,
+            samples: VecDeque::with_capacity(RECENT_SAMPLES),
+        }
+    }
+
+    fn record(&mut self, reply: &ProbeReply) {
+        self.sent += 1;
+        let rtt = reply
+            .rtt
+            .filter(|_| reply.kind != ReplyKind::Timeout)
+            .map(|rtt| (rtt.as_secs_f64() * 100_000.0).round() / 100.0);
+        if let Some(rtt) = rtt {
+            self.received += 1;
+            self.last = Some(rtt);
+            self.best = Some(self.best.map_or(rtt, |best| best.min(rtt)));
+            self.worst = Some(self.worst.map_or(rtt, |worst| worst.max(rtt)));
+            self.sum += rtt;
+            self.sum_squares += rtt * rtt;
+            self.status = reply.kind;
+        } else if self.received == 0 {
+            self.status = ReplyKind::Timeout;
+        }
+        if let Some(ip) = reply.responder {
+            if !self.responders.contains(&ip) {
+                self.responders.push(ip);
+            }
+        }
+        if self.samples.len() == RECENT_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+    }
+
+    fn report(&self, hostnames: &HashMap<IpAddr, Option<String>>) -> HopReport {
+        let average = (self.received > 0).then(|| self.sum / f64::from(self.received));
+        let stdev = average.map(|average| {
+            let variance = self.sum_squares / f64::from(self.received) - average * average;
+            (variance.max(0.0).sqrt() * 100.0).round() / 100.0
+        });
+        let address = self.responders.first();
+        HopReport {
+            ttl: self.ttl,
+            address: address.map(IpAddr::to_string),
+            hostname: address.and_then(|ip| hostnames.get(ip).cloned().flatten()),
+            alternates: self
+                .responders
+                .iter()
+                .skip(1)
+                .map(IpAddr::to_string)
+                .collect(),
+            status: self.status,
+            sent: self.sent,
+            received: self.received,
+            loss_percent: if self.sent == 0 {
+                0.0
+            } else {
+                (f64::from(self.sent - self.received) * 1000.0 / f64::from(self.sent)).round()
+                    / 10.0
+            },
+            last_ms: self.last,
+            avg_ms: average.map(|average| (average * 100.0).round() / 100.0),
+            best_ms: self.best,
+            worst_ms: self.worst,
+            stdev_ms: stdev,
+            samples: self.samples.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(target: &str, protocol: TraceProtocol, port: Option<u16>) -> TraceRequest {
+        TraceRequest {
+            target: target.into(),
+            protocol,
+            port,
+            max_hops: Some(5),
+            probes_per_hop: Some(2),
+            timeout_ms: Some(500),
+            mode: TraceMode::Traceroute,
+            interval_ms: Some(10),
+            cycles: None,
+            resolve_hostnames: Some(false),
+        }
+    }
+
+    async fn trace(request: TraceRequest, method: ProbeMethod) -> Vec<TraceEvent> {
+        let config = prepare_trace(request).await.unwrap();
+        let mut events = Vec::new();
+        run_trace("test", config, method, CancellationToken::new(), |event| {
+            events.push(event)
+        })
+        .await;
+        events
+    }
+
+    fn finished(events: &[TraceEvent]) -> (bool, &[HopReport], Option<&str>) {
+        match events.last() {
+            Some(TraceEvent::Finished {
+                reached,
+                hops,
+                error,
+                ..
+            }) => (*reached, hops, error.as_deref()),
+            _ => panic!("trace did not finish"),
+        }
+    }
+
+    fn methods() -> Vec<ProbeMethod> {
+        let mut methods = Vec::new();
+        // 原始套接字需要 root 或 CAP_NET_RAW
+        if raw_socket_allowed() {
+            methods.push(ProbeMethod::Raw);
+        }
+        #[cfg(target_os = "linux")]
+        methods.push(ProbeMethod::Unprivileged);
+        methods
+    }
+
+    fn raw_socket_allowed() -> bool {
+        socket2::Socket::new(
+            socket2::Domain::IPV4,
+            socket2::Type::RAW,
+            Some(socket2::Protocol::ICMPV4),
+        )
+        .is_ok()
+    }
+
+    #[test]
+    fn aggregates_hop_statistics() {
+        let mut stats = HopStats::new(3);
+        let hop: IpAddr = "10.0.0.1".parse().unwrap();
+        for rtt in [Some(10.0), None, Some(30.0), Some(20.0)] {
+            stats.record(&ProbeReply {
+                kind: if rtt.is_some() {
+                    ReplyKind::TimeExceeded
+                } else {
+                    ReplyKind::Timeout
+                },
+                responder: rtt.map(|_| hop),
+                rtt: rtt.map(|ms| Duration::from_secs_f64(ms / 1000.0)),
+            });
+        }
+        let report = stats.report(&HashMap::from([(hop, Some("gw.lan".to_string()))]));
+        assert_eq!(report.sent, 4);
+        assert_eq!(report.received, 3);
+        assert_eq!(report.loss_percent, 25.0);
+        assert_eq!(report.best_ms, Some(10.0));
+        assert_eq!(report.worst_ms, Some(30.0));
+        assert_eq!(report.avg_ms, Some(20.0));
+        assert_eq!(report.stdev_ms, Some(8.16));
+        assert_eq!(report.last_ms, Some(20.0));
+        assert_eq!(report.hostname.as_deref(), Some("gw.lan"));
+        assert_eq!(report.status, ReplyKind::TimeExceeded);
+    }
+
+    #[tokio::test]
+    async fn traces_loopback_with_udp_and_tcp() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open_port = listener.local_addr().unwrap().port();
+
+        for method in methods() {
+            let events = trace(request("127.0.0.1", TraceProtocol::Udp, None), method).await;
+            assert!(matches!(events[0], TraceEvent::Started { .. }));
+            let (reached, hops, error) = finished(&events);
+            assert!(reached, "{method:?}: {error:?}");
+            assert_eq!(hops.len(), 1);
+            assert_eq!(hops[0].address.as_deref(), Some("127.0.0.1"));
+            assert_eq!(hops[0].received, 2);
+
+            // 反向解析在后台完成，逐跳事件照常推送，结束事件带上已解析的名称
+            let mut resolving = request("127.0.0.1", TraceProtocol::Udp, None);
+            resolving.resolve_hostnames = Some(true);
+            let events = trace(resolving, method).await;
+            assert!(events
+                .iter()
+                .any(|event| matches!(event, TraceEvent::Hop { .. })));
+            let (reached, hops, _) = finished(&events);
+            assert!(reached);
+            if let Some(name) = hops[0].hostname.as_deref() {
+                assert!(!name.is_empty());
+            }
+
+            let events = trace(
+                request("127.0.0.1", TraceProtocol::Tcp, Some(open_port)),
+                method,
+            )
+            .await;
+            let (reached, hops, error) = finished(&events);
+            assert!(reached, "{method:?}: {error:?}");
+            assert_eq!(hops[0].status, ReplyKind::Reached);
+        }
+    }
+
+    #[tokio::test]
+    async fn runs_mtr_rounds_until_cycle_limit() {
+        let Some(method) = methods().into_iter().next() else {
+            return;
+        };
+        let mut request = request("127.0.0.1", TraceProtocol::Udp, None);
+        request.mode = TraceMode::Mtr;
+        request.cycles = Some(3);
+        let events = trace(request, method).await;
+
+        let rounds = events
+            .iter()
+            .filter(|event| matches!(event, TraceEvent::Round { .. }))
+            .count();
+        assert_eq!(rounds, 3);
+        let (reached, hops, _) = finished(&events);
+        assert!(reached);
+        assert_eq!((hops[0].sent, hops[0].received), (3, 3));
+        assert_eq!(hops[0].loss_percent, 0.0);
+        assert_eq!(hops[0].samples.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn stops_when_cancelled() {
+        let Some(method) = methods().into_iter().next() else {
+            return;
+        };
+        let mut request = request("127.0.0.1", TraceProtocol::Udp, None);
+        request.mode = TraceMode::Mtr;
+        request.interval_ms = Some(60_000);
+        let config = prepare_trace(request).await.unwrap();
+        let cancel = CancellationToken::new();
+        let stopper = cancel.clone();
+
+        let mut rounds = 0;
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            run_trace("test", config, method, cancel, |event| {
+                if matches!(event, TraceEvent::Round { .. }) {
+                    rounds += 1;
+                    stopper.cancel();
+                }
+            }),
+        )
+        .await
+        .expect("trace did not stop after cancel");
+        assert_eq!(rounds, 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_targets() {
+        assert!(prepare_trace(request(" ", TraceProtocol::Udp, None))
+            .await
+            .is_err());
+        assert!(
+            prepare_trace(request("127.0.0.1", TraceProtocol::Tcp, Some(0)))
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+pub(super) const PROTO_ICMP: u8 = 1;
+pub(super) const PROTO_TCP: u8 = 6;
+pub(super) const PROTO_UDP: u8 = 17;
+pub(super) const PROTO_ICMPV6: u8 = 58;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum IcmpKind {
+    TimeExceeded,
+    Unreachable { code: u8 },
+    EchoReply { id: u16, seq: u16 },
+}
+
+/// ICMP 差错报文里引用的原始报文：目的地址和传输层头部的前 8 字节。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct QuotedPacket {
+    pub protocol: u8,
+    pub destination: IpAddr,
+    pub header: [u8; 8],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct IcmpMessage {
+    pub kind: IcmpKind,
+    pub quoted: Option<QuotedPacket>,
+}
+
+impl QuotedPacket {
+    /// UDP/TCP 的源端口和目的端口。
+    pub fn ports(&self) -> (u16, u16) {
+        (
+            u16::from_be_bytes([self.header[0], self.header[1]]),
+            u16::from_be_bytes([self.header[2], self.header[3]]),
+        )
+    }
+
+    /// 被引用的 ICMP 回显请求的标识和序号。
+    pub fn echo(&self) -> (u16, u16) {
+        (
+            u16::from_be_bytes([self.header[4], self.header[5]]),
+            u16::from_be_bytes([self.header[6], self.header[7]]),
+        )
+    }
+}
+
+impl IcmpKind {
+    /// 端口不可达说明探测包已经到达目标主机。
+    pub fn is_port_unreachable(self, v6: bool) -> bool {
+        matches!(self, IcmpKind::Unreachable { code } if code == if v6 { 4 } else { 3 })
+    }
+}
+
+/// IPv4 原始套接字收到的报文带 IP 头，返回发送方地址和 ICMP 内容。
+pub(super) fn parse_icmpv4_packet(packet: &[u8]) -> Option<(Ipv4Addr, IcmpMessage)> {
+    if packet.len() < 20 || packet[0] >> 4 != 4 {
+        return None;
+    }
+    let header_len = usize::from(packet[0] & 0x0f) * 4;
+    let source = Ipv4Addr::new(packet[12], packet[13], packet[14], packet[15]);
+    let message = parse_icmp(packet.get(header_len..)?, false)?;
+    Some((source, message))
+}
+
+/// 解析 ICMP/ICMPv6 报文（不含外层 IP 头）。
+pub(super) fn parse_icmp(icmp: &[u8], v6: bool) -> Option<IcmpMessage> {
+    if icmp.len() < 8 {
+        return None;
+    }
+    let (kind_type, code) = (icmp[0], icmp[1]);
+    let kind = match (v6, kind_type) {
+        (false, 11) | (true, 3) => IcmpKind::TimeExceeded,
+        (false, 3) | (true, 1) => IcmpKind::Unreachable { code },
+        (false, 0) | (true, 129) => {
+            return Some(IcmpMessage {
+                kind: IcmpKind::EchoReply {
+                    id: u16::from_be_bytes([icmp[4], icmp[5]]),
+                    seq: u16::from_be_bytes([icmp[6], icmp[7]]),
+                },
+                quoted: None,
+            });
+        }
+        _ => return None,
+    };
+    Some(IcmpMessage {
+        kind,
+        quoted: parse_quoted(&icmp[8..], v6),
+    })
+}
+
+fn parse_quoted(quoted: &[u8], v6: bool) -> Option<QuotedPacket> {
+    let (protocol, destination, offset) = if v6 {
+        if quoted.len() < 40 || quoted[0] >> 4 != 6 {
+            return None;
+        }
+        let octets: [u8; 16] = quoted[24..40].try_into().ok()?;
+        (quoted[6], IpAddr::V6(Ipv6Addr::from(octets)), 40)
+    } else {
+        if quoted.len() < 20 || quoted[0] >> 4 != 4 {
+            return None;
+        }
+        let destination = Ipv4Addr::new(quoted[16], quoted[17], quoted[18], quoted[19]);
+        (
+            quoted[9],
+            IpAddr::V4(destination),
+            usize::from(quoted[0] & 0x0f) * 4,
+        )
+    };
+    Some(QuotedPacket {
+        protocol,
+        destination,
+        header: quoted.get(offset..offset + 8)?.try_into().ok()?,
+    })
+}
+
+/// 构造 ICMP 回显请求；IPv6 的校验和由内核填写。
+pub(super) fn echo_request(id: u16, seq: u16, v6: bool) -> Vec<u8> {
+    let mut packet = vec![if v6 { 128 } else { 8 }, 0, 0, 0];
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(b"chef-network-trace--------------");
+    if !v6 {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum = data
+        .chunks(2)
+        .map(|chunk| u32::from(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)])))
+        .sum::<u32>();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ipv4_header(protocol: u8, source: [u8; 4], destination: [u8; 4]) -> Vec<u8> {
+        let mut header = vec![0x45, 0, 0, 0, 0, 0, 0, 0, 64, protocol, 0, 0];
+        header.extend_from_slice(&source);
+        header.extend_from_slice(&destination);
+        header
+    }
+
+    #[test]
+    fn parses_time_exceeded_quoting_udp_probe() {
+        let mut quoted = ipv4_header(PROTO_UDP, [192, 168, 1, 5], [93, 184, 216, 34]);
+        quoted.extend_from_slice(&[0xc3, 0x50, 0x82, 0x9b, 0, 40, 0, 0]);
+        let mut packet = ipv4_header(PROTO_ICMP, [10, 0, 0, 1], [192, 168, 1, 5]);
+        packet.extend_from_slice(&[11, 0, 0, 0, 0, 0, 0, 0]);
+        packet.extend_from_slice(&quoted);
+
+        let (source, message) = parse_icmpv4_packet(&packet).unwrap();
+        assert_eq!(source, Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(message.kind, IcmpKind::TimeExceeded);
+        let quoted = message.quoted.unwrap();
+        assert_eq!(quoted.protocol, PROTO_UDP);
+        assert_eq!(quoted.destination, IpAddr::from([93, 184, 216, 34]));
+        assert_eq!(quoted.ports(), (50000, 33435));
+    }
+
+    #[test]
+    fn parses_icmpv6_unreachable_and_echo_reply() {
+        let destination: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let mut quoted = vec![0x60, 0, 0, 0, 0, 8, PROTO_ICMPV6, 1];
+        quoted.extend_from_slice(&"2001:db8::2".parse::<Ipv6Addr>().unwrap().octets());
+        quoted.extend_from_slice(&destination.octets());
+        quoted.extend_from_slice(&[128, 0, 0, 0, 0x12, 0x34, 0, 7]);
+        let mut icmp = vec![1, 4, 0, 0, 0, 0, 0, 0];
+        icmp.extend_from_slice(&quoted);
+
+        let message = parse_icmp(&icmp, true).unwrap();
+        assert!(message.kind.is_port_unreachable(true));
+        let quoted = message.quoted.unwrap();
+        assert_eq!(quoted.destination, IpAddr::V6(destination));
+        assert_eq!(quoted.echo(), (0x1234, 7));
+
+        let reply = parse_icmp(&[129, 0, 0, 0, 0x12, 0x34, 0, 9], true).unwrap();
+        assert_eq!(reply.kind, IcmpKind::EchoReply { id: 0x1234, seq: 9 });
+        assert!(parse_icmp(&[128, 0, 0, 0, 0, 0, 0, 0], true).is_none());
+    }
+
+    #[test]
+    fn builds_echo_request_with_valid_checksum() {
+        let packet = echo_request(0xbeef, 3, false);
+        assert_eq!(packet[0], 8);
+        assert_eq!(checksum(&packet), 0);
+        assert_eq!(&packet[4..8], &[0xbe, 0xef, 0, 3]);
+        assert_eq!(echo_request(1, 1, true)[2..4], [0, 0]);
+    }
+}
@@ -0,0 +1,283 @@
+use std::{
+    io,
+    mem::MaybeUninit,
+    net::{IpAddr, SocketAddr},
+    os::fd::{AsRawFd, RawFd},
+    sync::atomic::{AtomicU16, Ordering},
+    time::Instant,
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use super::{
+    packet::{
+        echo_request, parse_icmp, parse_icmpv4_packet, IcmpKind, IcmpMessage, PROTO_ICMP,
+        PROTO_ICMPV6, PROTO_TCP, PROTO_UDP,
+    },
+    ProbeReply, ProbeTarget, Prober, ReplyKind, TraceProtocol,
+};
+
+/// UDP 探测的目的端口在起始端口之后轮换，便于区分各个探测包。
+const UDP_PORT_SPAN: u16 = 512;
+
+static NEXT_IDENT: AtomicU16 = AtomicU16::new(0);
+
+/// 使用原始 ICMP 套接字接收差错报文，需要 root 或 CAP_NET_RAW。
+pub(super) struct RawProber {
+    target: ProbeTarget,
+    icmp: Socket,
+    udp: Option<Socket>,
+    ident: u16,
+}
+
+/// 等待的探测包：用于从原始套接字收到的报文里挑出属于自己的那一个。
+enum Pending {
+    Udp { source_port: u16, port: u16 },
+    Tcp { source_port: u16, socket: Socket },
+    Echo { seq: u16 },
+}
+
+impl RawProber {
+    pub fn new(target: ProbeTarget) -> io::Result<Self> {
+        let domain = Domain::for_address(SocketAddr::new(target.address, 0));
+        let protocol = if target.address.is_ipv4() {
+            Protocol::ICMPV4
+        } else {
+            Protocol::ICMPV6
+        };
+        let icmp = Socket::new(domain, Type::RAW, Some(protocol))?;
+        icmp.set_nonblocking(true)?;
+        let udp = match target.protocol {
+            TraceProtocol::Udp => {
+                let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+                let any = SocketAddr::new(unspecified(target.address), 0);
+                socket.bind(&any.into())?;
+                Some(socket)
+            }
+            _ => None,
+        };
+        let ident = (std::process::id() as u16).wrapping_add(
+            NEXT_IDENT
+                .fetch_add(1, Ordering::Relaxed)
+                .wrapping_mul(7919),
+        );
+        Ok(Self {
+            target,
+            icmp,
+            udp,
+            ident,
+        })
+    }
+
+    fn send(&self, ttl: u8, seq: u16) -> io::Result<Pending> {
+        let address = self.target.address;
+        match self.target.protocol {
+            TraceProtocol::Udp => {
+                let socket = self.udp.as_ref().expect("udp socket");
+                set_hop_limit(socket, address, ttl)?;
+                let port = self.target.port.wrapping_add(seq % UDP_PORT_SPAN).max(1);
+                socket.send_to(&[0; 32], &SocketAddr::new(address, port).into())?;
+                Ok(Pending::Udp {
+                    source_port: local_port(socket)?,
+                    port,
+                })
+            }
+            TraceProtocol::Tcp => {
+                let socket = Socket::new(
+                    Domain::for_address(SocketAddr::new(address, 0)),
+                    Type::STREAM,
+                    Some(Protocol::TCP),
+                )?;
+                socket.set_nonblocking(true)?;
+                set_hop_limit(&socket, address, ttl)?;
+                match socket.connect(&SocketAddr::new(address, self.target.port).into()) {
+                    Ok(()) => {}
+                    Err(err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+                    Err(err) => return Err(err),
+                }
+                Ok(Pending::Tcp {
+                    source_port: local_port(&socket)?,
+                    socket,
+                })
+            }
+            TraceProtocol::Icmp => {
+                set_hop_limit(&self.icmp, address, ttl)?;
+                let packet = echo_request(self.ident, seq, address.is_ipv6());
+                self.icmp
+                    .send_to(&packet, &SocketAddr::new(address, 0).into())?;
+                Ok(Pending::Echo { seq })
+            }
+        }
+    }
+
+    /// 判断收到的 ICMP 报文是否是对当前探测的回应。
+    fn matches(&self, pending: &Pending, message: &IcmpMessage) -> bool {
+        if let (IcmpKind::EchoReply { id, seq }, Pending::Echo { seq: sent }) =
+            (message.kind, pending)
+        {
+            return id == self.ident && seq == *sent;
+        }
+        let Some(quoted) = message.quoted else {
+            return false;
+        };
+        if quoted.destination != self.target.address {
+            return false;
+        }
+        match pending {
+            Pending::Udp { source_port, port } => {
+                quoted.protocol == PROTO_UDP && quoted.ports() == (*source_port, *port)
+            }
+            Pending::Tcp { source_port, .. } => {
+                quoted.protocol == PROTO_TCP && quoted.ports() == (*source_port, self.target.port)
+            }
+            Pending::Echo { seq } => {
+                matches!(quoted.protocol, PROTO_ICMP | PROTO_ICMPV6)
+                    && quoted.echo() == (self.ident, *seq)
+            }
+        }
+    }
+
+    fn receive(&self) -> io::Result<Option<(IpAddr, IcmpMessage)>> {
+        let mut buffer = [MaybeUninit::<u8>::uninit(); 1500];
+        let (len, from) = self.icmp.recv_from(&mut buffer)?;
+        // recv_from 已经写入了前 len 个字节
+        let data = unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len) };
+        if self.target.address.is_ipv4() {
+            return Ok(parse_icmpv4_packet(data).map(|(source, message)| (source.into(), message)));
+        }
+        let source = from.as_socket().map(|addr| addr.ip());
+        Ok(source.zip(parse_icmp(data, true)))
+    }
+}
+
+impl Prober for RawProber {
+    fn probe(&mut self, ttl: u8, seq: u16) -> Result<ProbeReply, String> {
+        let pending = self
+            .send(ttl, seq)
+            .map_err(|err| format!("发送探测包失败: {err}"))?;
+        let started = Instant::now();
+        let deadline = started + self.target.timeout;
+        let v6 = self.target.address.is_ipv6();
+        let mut watch_connect = matches!(pending, Pending::Tcp { .. });
+
+        loop {
+            let mut fds = vec![pollfd(self.icmp.as_raw_fd(), libc::POLLIN)];
+            if let (Pending::Tcp { socket, .. }, true) = (&pending, watch_connect) {
+                fds.push(pollfd(socket.as_raw_fd(), libc::POLLOUT));
+            }
+            if !poll_until(&mut fds, deadline).map_err(|err| err.to_string())? {
+                return Ok(timeout_reply());
+            }
+
+            if let (Pending::Tcp { socket, .. }, Some(fd)) = (&pending, fds.get(1)) {
+                if fd.revents != 0 {
+                    // 连接建立或被 RST 拒绝都说明已经到达目标
+                    match socket.take_error().map_err(|err| err.to_string())? {
+                        None => return Ok(reached(self.target.address, started)),
+                        Some(err) if err.raw_os_error() == Some(libc::ECONNREFUSED) => {
+                            return Ok(reached(self.target.address, started));
+                        }
+                        // 其他错误由中途路由器的 ICMP 报文说明，继续等原始套接字
+                        Some(_) => watch_connect = false,
+                    }
+                }
+            }
+
+            if fds[0].revents & libc::POLLIN == 0 {
+                continue;
+            }
+            loop {
+                let (source, message) = match self.receive() {
+                    Ok(Some(received)) => received,
+                    Ok(None) => continue,
+                    Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(err) => return Err(format!("接收 ICMP 报文失败: {err}")),
+                };
+                if !self.matches(&pending, &message) {
+                    continue;
+                }
+                let kind = match message.kind {
+                    IcmpKind::TimeExceeded => ReplyKind::TimeExceeded,
+                    IcmpKind::EchoReply { .. } => ReplyKind::Reached,
+                    kind if kind.is_port_unreachable(v6) || source == self.target.address => {
+                        ReplyKind::Reached
+                    }
+                    IcmpKind::Unreachable { .. } => ReplyKind::Unreachable,
+                };
+                return Ok(ProbeReply {
+                    kind,
+                    responder: Some(source),
+                    rtt: Some(started.elapsed()),
+                });
+            }
+        }
+    }
+}
+
+pub(super) fn pollfd(fd: RawFd, events: libc::c_short) -> libc::pollfd {
+    libc::pollfd {
+        fd,
+        events,
+        revents: 0,
+    }
+}
+
+/// 等到任一描述符就绪或超过截止时间，超时返回 false。
+pub(super) fn poll_until(fds: &mut [libc::pollfd], deadline: Instant) -> io::Result<bool> {
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        let millis = remaining.as_millis().clamp(1, i32::MAX as u128) as libc::c_int;
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, millis) };
+        match ready {
+            -1 => {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::Interrupted {
+                    return Err(err);
+                }
+            }
+            0 => continue,
+            _ => return Ok(true),
+        }
+    }
+}
+
+pub(super) fn set_hop_limit(socket: &Socket, address: IpAddr, ttl: u8) -> io::Result<()> {
+    match address {
+        IpAddr::V4(_) => socket.set_ttl(u32::from(ttl)),
+        IpAddr::V6(_) => socket.set_unicast_hops_v6(u32::from(ttl)),
+    }
+}
+
+pub(super) fn reached(address: IpAddr, started: Instant) -> ProbeReply {
+    ProbeReply {
+        kind: ReplyKind::Reached,
+        responder: Some(address),
+        rtt: Some(started.elapsed()),
+    }
+}
+
+pub(super) fn timeout_reply() -> ProbeReply {
+    ProbeReply {
+        kind: ReplyKind::Timeout,
+        responder: None,
+        rtt: None,
+    }
+}
+
+fn local_port(socket: &Socket) -> io::Result<u16> {
+    Ok(socket
+        .local_addr()?
+        .as_socket()
+        .map(|addr| addr.port())
+        .unwrap_or_default())
+}
+
+fn unspecified(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V4(_) => IpAddr::from([0u8; 4]),
+        IpAddr::V6(_) => IpAddr::from([0u8; 16]),
+    }
+}
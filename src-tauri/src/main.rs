@@ -6,17 +6,32 @@ mod utils;
 mod windowing;
 
 use commands::{
-    cancel_region_capture, capture_region, diagnose_network_connectivity, finalize_region_capture,
-    get_file_share_status, get_network_overview, list_window_snap_targets, pick_screen_color,
-    pick_search_directories, pick_share_directories, pick_share_files, read_environment_sources,
-    read_hosts_file, run_network_fix_action, save_capture_image, search_files,
-    set_current_window_always_on_top, show_region_capture_overlay, start_file_share, stop_file_share,
-    FileShareManager,
+    add_file_share_items, cancel_region_capture, capture_region, check_proxies,
+    diagnose_network_connectivity, discover_file_shares, finalize_region_capture,
+    get_file_share_qr_codes, get_file_share_status, get_ip_lookup_settings,
+    get_network_monitor_status, get_network_overview, get_system_proxy_config,
+    inspect_port_process, kill_port_process, list_dns_resolvers, list_file_shares,
+    list_listening_sockets, list_network_fix_snapshots, list_share_bind_addresses,
+    list_share_templates, list_window_snap_targets, load_ip_lookup_settings, lookup_geoip,
+    pick_screen_color, pick_search_directories, pick_share_directories, pick_share_files,
+    probe_network_targets, push_share_clipboard, push_share_text, query_dns, query_network_history,
+    read_environment_sources, read_hosts_file, remove_file_share_items, remove_share_text,
+    resolve_proxy_for_url, revert_network_fix, run_network_fix_action, save_capture_image,
+    save_ip_lookup_settings, scan_ports, search_files, send_http_request,
+    set_current_window_always_on_top, show_region_capture_overlay, start_file_share,
+    start_network_monitor, start_network_trace, stop_file_share, stop_network_monitor,
+    stop_network_trace, update_file_share_limits, FileShareManager, NetworkMonitor, TraceManager,
 };
 
 fn main() {
     tauri::Builder::default()
         .manage(FileShareManager::default())
+        .manage(TraceManager::default())
+        .manage(NetworkMonitor::default())
+        .setup(|app| {
+            load_ip_lookup_settings(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             show_region_capture_overlay,
             cancel_region_capture,
@@ -27,6 +42,17 @@ fn main() {
             start_file_share,
             stop_file_share,
             get_file_share_status,
+            list_file_shares,
+            add_file_share_items,
+            remove_file_share_items,
+            list_share_bind_addresses,
+            list_share_templates,
+            get_file_share_qr_codes,
+            update_file_share_limits,
+            push_share_text,
+            push_share_clipboard,
+            remove_share_text,
+            discover_file_shares,
             pick_share_files,
             pick_share_directories,
             pick_search_directories,
@@ -34,7 +60,29 @@ fn main() {
             pick_screen_color,
             get_network_overview,
             diagnose_network_connectivity,
+            probe_network_targets,
+            query_dns,
+            list_dns_resolvers,
+            list_listening_sockets,
+            scan_ports,
+            inspect_port_process,
+            kill_port_process,
+            start_network_trace,
+            stop_network_trace,
+            start_network_monitor,
+            stop_network_monitor,
+            get_network_monitor_status,
+            query_network_history,
+            get_system_proxy_config,
+            resolve_proxy_for_url,
+            check_proxies,
+            get_ip_lookup_settings,
+            save_ip_lookup_settings,
+            lookup_geoip,
             run_network_fix_action,
+            send_http_request,
+            revert_network_fix,
+            list_network_fix_snapshots,
             read_environment_sources,
             read_hosts_file,
             save_capture_image,